@@ -4,21 +4,13 @@ use std::{
     io::{BufReader, BufWriter, Write},
     net::{TcpStream, ToSocketAddrs},
 };
-use xbf_rs::{XbfMetadata, XbfPrimitive, XbfStruct, XbfType};
+use xbf_rs::{XbfMetadata, XbfStruct, XbfType};
 
 fn find_galbatorix(value: &XbfType) -> Option<&XbfStruct> {
-    if let XbfType::Vec(v) = value {
-        for value in v {
-            if let XbfType::Struct(s) = value {
-                if let Some(XbfType::Primitive(XbfPrimitive::String(name))) = s.get("name") {
-                    if name == "Galbatorix" {
-                        return Some(s);
-                    }
-                }
-            }
-        }
-    }
-    None
+    value.as_vec().ok()?.iter().find_map(|value| {
+        let s = value.as_struct().ok()?;
+        (s.get("name")?.as_str().ok()? == "Galbatorix").then_some(s)
+    })
 }
 
 fn get_connection(
@@ -6,8 +6,8 @@ use tokio::{
     net::{TcpListener, TcpStream},
 };
 use xbf_rs::{
-    NativeToXbfPrimitive, XbfPrimitiveMetadata, XbfStruct, XbfStructMetadata, XbfTypeUpcast,
-    XbfVec, XbfVecMetadata,
+    NativeToXbfPrimitive, XbfDate, XbfDecimal, XbfPrimitiveMetadata, XbfStruct, XbfStructMetadata,
+    XbfTypeUpcast, XbfVec, XbfVecMetadata,
 };
 
 #[derive(Serialize, Deserialize)]
@@ -68,48 +68,60 @@ fn to_xml<T: Serialize>(records: &[T]) -> Result<Vec<u8>, quick_xml::de::DeError
     Ok(quick_xml::se::to_string_with_root("root", records)?.into_bytes())
 }
 
-fn create_xbf_vec(records: &[StockRecord]) -> XbfVec {
+fn create_xbf_vec(records: &[StockRecord]) -> anyhow::Result<XbfVec> {
     let sr_xbf_metadata = XbfStructMetadata::new(
         "StockRecord",
         indexmap::indexmap! {
-            "Date" => XbfPrimitiveMetadata::String.into(),
-            "Open" => XbfPrimitiveMetadata::F64.into(),
-            "High" => XbfPrimitiveMetadata::F64.into(),
-            "Low" => XbfPrimitiveMetadata::F64.into(),
-            "Close" => XbfPrimitiveMetadata::F64.into(),
-            "AdjClose" => XbfPrimitiveMetadata::F64.into(),
+            "Date" => XbfPrimitiveMetadata::Date.into(),
+            "Open" => XbfPrimitiveMetadata::Decimal.into(),
+            "High" => XbfPrimitiveMetadata::Decimal.into(),
+            "Low" => XbfPrimitiveMetadata::Decimal.into(),
+            "Close" => XbfPrimitiveMetadata::Decimal.into(),
+            "AdjClose" => XbfPrimitiveMetadata::Decimal.into(),
             "Volume" => XbfPrimitiveMetadata::U64.into(),
         },
     );
 
-    XbfVec::new_unchecked(
-        XbfVecMetadata::new(sr_xbf_metadata.clone()),
-        records.iter().map(|record| {
-            XbfStruct::new_unchecked(
+    // prices are exact in the csv, so send them as the shortest decimal that reads back the same
+    let price = |x: f64| XbfDecimal::try_from(x).map(|x| x.into_xbf_primitive().into_base_type());
+
+    let structs = records
+        .iter()
+        .map(|record| {
+            anyhow::Ok(XbfStruct::new_unchecked(
                 sr_xbf_metadata.clone(),
                 [
-                    record.date.to_xbf_primitive().into_base_type(),
-                    record.open.to_xbf_primitive().into_base_type(),
-                    record.high.to_xbf_primitive().into_base_type(),
-                    record.low.to_xbf_primitive().into_base_type(),
-                    record.close.to_xbf_primitive().into_base_type(),
-                    record.adj_close.to_xbf_primitive().into_base_type(),
+                    record
+                        .date
+                        .parse::<XbfDate>()?
+                        .into_xbf_primitive()
+                        .into_base_type(),
+                    price(record.open)?,
+                    price(record.high)?,
+                    price(record.low)?,
+                    price(record.close)?,
+                    price(record.adj_close)?,
                 ],
-            )
-        }),
-    )
+            ))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(XbfVec::new_unchecked(
+        XbfVecMetadata::new(sr_xbf_metadata),
+        structs,
+    ))
 }
 
-fn to_xbf(records: &[StockRecord]) -> Result<Vec<u8>, std::io::Error> {
-    let vec = create_xbf_vec(records);
+fn to_xbf(records: &[StockRecord]) -> anyhow::Result<Vec<u8>> {
+    let vec = create_xbf_vec(records)?;
     let mut bytes = vec![];
     vec.serialize_vec_type(&mut bytes)?;
 
     Ok(bytes)
 }
 
-fn to_xbf_data_only(records: &[StockRecord]) -> Result<Vec<u8>, std::io::Error> {
-    let vec = create_xbf_vec(records);
+fn to_xbf_data_only(records: &[StockRecord]) -> anyhow::Result<Vec<u8>> {
+    let vec = create_xbf_vec(records)?;
     let mut bytes = vec![];
     vec.get_metadata().serialize_vec_metadata(&mut bytes)?;
     vec.serialize_vec_type(&mut bytes)?;
@@ -173,10 +185,7 @@ async fn handle_stock_request(
 async fn main() -> anyhow::Result<()> {
     let stock_data = {
         let csv_data = get_yahoo_data().await?;
-        println!(
-            "original stock csv data size: {}",
-            csv_data.as_bytes().len()
-        );
+        println!("original stock csv data size: {}", csv_data.len());
         let native_data = get_native_vec_from_csv(&csv_data)?;
 
         let size_of_floats = std::mem::size_of::<f64>() * 5;
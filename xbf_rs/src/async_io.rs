@@ -0,0 +1,1186 @@
+//! Serializing and deserializing XBF data over tokio's asynchronous IO traits.
+
+use crate::{
+    reader::XbfReader, xbf_tensor::element_count, DeserializeOptions, XbfEnumMetadata, XbfError,
+    XbfMap, XbfMetadata, XbfPrimitive, XbfPrimitiveBuffer, XbfPrimitiveMetadata, XbfStructMetadata,
+    XbfType, ARRAY_METADATA_DISCRIMINANT, ENUM_METADATA_DISCRIMINANT, MAP_METADATA_DISCRIMINANT,
+    OPTION_METADATA_DISCRIMINANT, STRUCT_METADATA_DISCRIMINANT, TENSOR_METADATA_DISCRIMINANT,
+    VEC_METADATA_DISCRIMINANT,
+};
+use indexmap::map::Values;
+use std::{io, slice};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// The number of bytes buffered while serializing to an asynchronous writer before they are
+/// written out.
+const WRITE_BUFFER_SIZE: usize = 8 * 1024;
+
+impl XbfMetadata {
+    /// Serialize metadata to an asynchronous writer, as with
+    /// [`XbfMetadata::serialize_base_metadata`].
+    ///
+    /// The metadata is written a few kilobytes at a time as it is serialized, so it is never
+    /// held in memory all at once.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::Error`] if writing to the writer fails.
+    pub async fn serialize_base_metadata_async(
+        &self,
+        writer: &mut (impl AsyncWrite + Unpin),
+    ) -> io::Result<()> {
+        let mut spill = Spill::new(writer);
+        spill.metadata(self).await?;
+        spill.flush().await
+    }
+
+    /// Deserialize metadata from an asynchronous reader, as with
+    /// [`XbfMetadata::deserialize_base_metadata`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails or the metadata is invalid.
+    pub async fn deserialize_base_metadata_async(
+        reader: &mut (impl AsyncRead + Unpin),
+    ) -> Result<XbfMetadata, XbfError> {
+        XbfAsyncReader::new(reader).read_metadata().await
+    }
+}
+
+impl XbfType {
+    /// Serialize a value to an asynchronous writer, as with [`XbfType::serialize_base_type`].
+    ///
+    /// The value is written a few kilobytes at a time as it is serialized, so it is never held
+    /// in memory all at once. Large strings and bytes are written straight from the value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::Error`] if writing to the writer fails.
+    pub async fn serialize_base_type_async(
+        &self,
+        writer: &mut (impl AsyncWrite + Unpin),
+    ) -> io::Result<()> {
+        let mut spill = Spill::new(writer);
+        spill.value(self).await?;
+        spill.flush().await
+    }
+
+    /// Deserialize a value from an asynchronous reader, as with
+    /// [`XbfType::deserialize_base_type`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails or the data is invalid.
+    pub async fn deserialize_base_type_async(
+        metadata: &XbfMetadata,
+        reader: &mut (impl AsyncRead + Unpin),
+    ) -> Result<XbfType, XbfError> {
+        XbfAsyncReader::new(reader).read_value(metadata).await
+    }
+}
+
+/// An asynchronous counterpart of [`XbfReader`], which deserializes XBF metadata and values from
+/// a tokio [`AsyncRead`] according to a set of [`DeserializeOptions`].
+///
+/// Each metadata or value is read by first reading exactly the bytes it takes up, walking its
+/// structure and length prefixes, and then deserializing those bytes. Nothing past the end of
+/// the metadata or value is read, so the reader can be used again for whatever follows it. The
+/// limits in the options are checked while reading, before any memory is allocated for a
+/// length which exceeds them.
+///
+/// As the bytes of each metadata or value are held in memory until it has been read in full, and
+/// are then deserialized into the value, reading a value takes memory proportional to its
+/// serialized size on top of the value itself.
+///
+/// As many small reads are made, wrapping an unbuffered reader such as a socket in a
+/// [`tokio::io::BufReader`] is recommended.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::prelude::*;
+/// use xbf_rs::{DeserializeOptions, XbfAsyncReader, XbfMetadata};
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let value = "Saphira".to_string().into_xbf_primitive().into_base_type();
+///
+/// let mut data = vec![];
+/// XbfMetadata::from(&value)
+///     .serialize_base_metadata_async(&mut data)
+///     .await
+///     .unwrap();
+/// value.serialize_base_type_async(&mut data).await.unwrap();
+///
+/// let options = DeserializeOptions::new().max_bytes_len(1024);
+/// let mut reader = XbfAsyncReader::with_options(data.as_slice(), options);
+///
+/// let metadata = reader.read_metadata().await.unwrap();
+/// assert_eq!(reader.read_value(&metadata).await.unwrap(), value);
+/// # });
+/// ```
+#[derive(Debug)]
+pub struct XbfAsyncReader<R> {
+    inner: R,
+    options: DeserializeOptions,
+    position: u64,
+    charged: u64,
+}
+
+impl<R: AsyncRead + Unpin> XbfAsyncReader<R> {
+    /// Creates a new reader with no limits.
+    pub fn new(inner: R) -> Self {
+        Self::with_options(inner, DeserializeOptions::default())
+    }
+
+    /// Creates a new reader which enforces the given options.
+    pub fn with_options(inner: R, options: DeserializeOptions) -> Self {
+        Self {
+            inner,
+            options,
+            position: 0,
+            charged: 0,
+        }
+    }
+
+    /// Returns the options this reader enforces.
+    pub fn options(&self) -> &DeserializeOptions {
+        &self.options
+    }
+
+    /// Returns the number of bytes read so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Consumes this reader, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Reads metadata, as with [`XbfReader::read_metadata`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails, the metadata is invalid, or a limit is
+    /// exceeded.
+    pub async fn read_metadata(&mut self) -> Result<XbfMetadata, XbfError> {
+        let mut gather = Gather::new(&mut self.inner, self.options, self.position, self.charged);
+        let result = gather.metadata().await;
+        let buf = Self::gathered(gather, result).inspect_err(|e| self.position = e.offset())?;
+        self.finish_read(&buf, |reader| reader.read_metadata())
+    }
+
+    /// Reads a value described by `metadata`, as with [`XbfReader::read_value`].
+    ///
+    /// The serialized bytes of the value are held in memory while it is read.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails, the data is invalid, or a limit is exceeded.
+    pub async fn read_value(&mut self, metadata: &XbfMetadata) -> Result<XbfType, XbfError> {
+        let mut gather = Gather::new(&mut self.inner, self.options, self.position, self.charged);
+        let result = gather.value(metadata).await;
+        let buf = Self::gathered(gather, result).inspect_err(|e| self.position = e.offset())?;
+        self.finish_read(&buf, |reader| reader.read_value(metadata))
+    }
+
+    /// Returns the gathered bytes, unless gathering them failed with an IO error.
+    fn gathered(gather: Gather<R>, result: Result<(), Stop>) -> Result<Vec<u8>, XbfError> {
+        match result {
+            Err(Stop::Io(e)) => Err(XbfError::from_io(e, gather.position())),
+            Ok(()) | Err(Stop::Early) => Ok(gather.buf),
+        }
+    }
+
+    /// Deserializes the gathered bytes with a synchronous reader. If gathering stopped early,
+    /// this reports the error exactly as it would have been reported reading directly from the
+    /// input.
+    fn finish_read<T>(
+        &mut self,
+        buf: &[u8],
+        f: impl FnOnce(&mut XbfReader<&[u8]>) -> Result<T, XbfError>,
+    ) -> Result<T, XbfError> {
+        let mut reader = XbfReader::at_position(buf, self.options, self.position, self.charged);
+        let result = f(&mut reader);
+        self.position += buf.len() as u64;
+        self.charged = reader.charged();
+        result
+    }
+}
+
+/// Why gathering the bytes of a metadata or value stopped before reaching its end.
+enum Stop {
+    /// The input ended, or the bytes read so far are invalid or exceed a limit. Deserializing
+    /// the bytes read so far will fail with the appropriate error.
+    Early,
+    /// The underlying reader failed.
+    Io(io::Error),
+}
+
+enum Frame<'m> {
+    Vec {
+        inner_type: &'m XbfMetadata,
+        remaining: u64,
+    },
+    Struct(Values<'m, Box<str>, XbfMetadata>),
+    Map {
+        key_type: &'m XbfMetadata,
+        value_type: &'m XbfMetadata,
+        remaining: u64,
+        value_next: bool,
+    },
+}
+
+/// Reads exactly the bytes of one metadata or value into memory, following the same steps and
+/// checking the same limits as [`XbfReader`] does while deserializing them.
+struct Gather<'r, R> {
+    reader: &'r mut R,
+    options: DeserializeOptions,
+    start: u64,
+    charged: u64,
+    buf: Vec<u8>,
+}
+
+impl<'r, R: AsyncRead + Unpin> Gather<'r, R> {
+    fn new(reader: &'r mut R, options: DeserializeOptions, start: u64, charged: u64) -> Self {
+        Self {
+            reader,
+            options,
+            start,
+            charged,
+            buf: vec![],
+        }
+    }
+
+    fn position(&self) -> u64 {
+        self.start + self.buf.len() as u64
+    }
+
+    fn consumed(&self) -> u64 {
+        self.position().saturating_add(self.charged)
+    }
+
+    async fn metadata(&mut self) -> Result<(), Stop> {
+        // the number of fields left in each enclosing vec or struct, and whether they are named
+        let mut stack: Vec<(u16, bool)> = vec![];
+        let mut started = false;
+        loop {
+            while let Some((0, _)) = stack.last() {
+                stack.pop();
+            }
+            if started && stack.is_empty() {
+                return Ok(());
+            }
+            started = true;
+            if let Some((remaining, named)) = stack.last_mut() {
+                *remaining -= 1;
+                if *named {
+                    self.bytes().await?;
+                }
+            }
+
+            let discriminant = self.read::<1>().await?[0];
+            if discriminant == VEC_METADATA_DISCRIMINANT
+                || discriminant == OPTION_METADATA_DISCRIMINANT
+            {
+                self.enter(stack.len())?;
+                stack.push((1, false));
+            } else if discriminant == MAP_METADATA_DISCRIMINANT {
+                self.enter(stack.len())?;
+                stack.push((2, false));
+            } else if discriminant == ARRAY_METADATA_DISCRIMINANT {
+                self.enter(stack.len())?;
+                let len = u64::from_le_bytes(self.read().await?);
+                Self::check(self.options.max_vec_len, len)?;
+                stack.push((1, false));
+            } else if discriminant == STRUCT_METADATA_DISCRIMINANT
+                || discriminant == ENUM_METADATA_DISCRIMINANT
+            {
+                self.enter(stack.len())?;
+                self.bytes().await?;
+                let len = u16::from_le_bytes(self.read().await?);
+                let max = self.options.max_struct_fields.map(u64::from);
+                Self::check(max, len.into())?;
+                stack.push((len, true));
+            } else if discriminant == TENSOR_METADATA_DISCRIMINANT {
+                let element_type = XbfPrimitiveMetadata::try_from(self.read::<1>().await?[0]);
+                if !element_type.is_ok_and(|x| x.fixed_size().is_some()) {
+                    return Err(Stop::Early);
+                }
+                let [rank, fixed] = self.read().await?;
+                if self.options.strict && fixed > 1 {
+                    return Err(Stop::Early);
+                }
+                if fixed != 0 {
+                    self.shape(rank).await?;
+                }
+            } else if XbfPrimitiveMetadata::try_from(discriminant).is_err() {
+                return Err(Stop::Early);
+            }
+        }
+    }
+
+    async fn value(&mut self, metadata: &XbfMetadata) -> Result<(), Stop> {
+        let mut stack = vec![];
+        let mut next = Some(metadata);
+        loop {
+            let metadata = match next.take() {
+                Some(x) => x,
+                None => match stack.last_mut() {
+                    None => return Ok(()),
+                    Some(Frame::Vec { remaining: 0, .. }) => {
+                        stack.pop();
+                        continue;
+                    }
+                    Some(Frame::Vec {
+                        inner_type,
+                        remaining,
+                    }) => {
+                        *remaining -= 1;
+                        *inner_type
+                    }
+                    Some(Frame::Struct(fields)) => match fields.next() {
+                        Some(x) => x,
+                        None => {
+                            stack.pop();
+                            continue;
+                        }
+                    },
+                    Some(Frame::Map {
+                        remaining: 0,
+                        value_next: false,
+                        ..
+                    }) => {
+                        stack.pop();
+                        continue;
+                    }
+                    Some(Frame::Map {
+                        key_type,
+                        value_type,
+                        remaining,
+                        value_next,
+                    }) => {
+                        if *value_next {
+                            *remaining -= 1;
+                        }
+                        *value_next = !*value_next;
+                        if *value_next {
+                            *key_type
+                        } else {
+                            *value_type
+                        }
+                    }
+                },
+            };
+
+            match metadata {
+                XbfMetadata::Primitive(x) => match x.fixed_size() {
+                    Some(size) => self.take(size as u64).await?,
+                    None => self.bytes().await?,
+                },
+                XbfMetadata::Vec(x) => {
+                    self.enter(stack.len())?;
+                    let len = u64::from_le_bytes(self.read().await?);
+                    Self::check(self.options.max_vec_len, len)?;
+                    self.elements(&x.inner_type, len, &mut stack).await?;
+                }
+                XbfMetadata::Array(x) => {
+                    self.enter(stack.len())?;
+                    self.elements(&x.inner_type, x.len, &mut stack).await?;
+                }
+                XbfMetadata::Struct(x) => {
+                    self.enter(stack.len())?;
+                    stack.push(Frame::Struct(x.field_types()));
+                }
+                XbfMetadata::Option(x) => {
+                    self.enter(stack.len())?;
+                    let present = self.read::<1>().await?[0];
+                    if self.options.strict && present > 1 {
+                        return Err(Stop::Early);
+                    }
+                    // an option is a vec of at most one element as far as gathering goes
+                    stack.push(Frame::Vec {
+                        inner_type: &x.inner_type,
+                        remaining: u64::from(present != 0),
+                    });
+                }
+                XbfMetadata::Enum(x) => {
+                    self.enter(stack.len())?;
+                    let index = u16::from_le_bytes(self.read().await?);
+                    let Some((_, variant_type)) = x.get_variant(index) else {
+                        return Err(Stop::Early);
+                    };
+                    stack.push(Frame::Vec {
+                        inner_type: variant_type,
+                        remaining: 1,
+                    });
+                }
+                XbfMetadata::Map(x) => {
+                    self.enter(stack.len())?;
+                    let len = u64::from_le_bytes(self.read().await?);
+                    Self::check(self.options.max_vec_len, len)?;
+                    let entry_size = x.key_type.fixed_size().zip(x.value_type.fixed_size());
+                    self.charge(entry_size.map(|(k, v)| k + v), len)?;
+                    // duplicate keys are left for the sync reader to reject in strict mode
+                    stack.push(Frame::Map {
+                        key_type: &x.key_type,
+                        value_type: &x.value_type,
+                        remaining: len,
+                        value_next: false,
+                    });
+                }
+                XbfMetadata::Tensor(x) => {
+                    let len = match &x.shape {
+                        Some(shape) => element_count(shape),
+                        None => self.shape(x.rank).await?,
+                    };
+                    let size = x.element_type.fixed_size().unwrap_or_default();
+                    self.take(len.saturating_mul(size as u64)).await?;
+                }
+            }
+        }
+    }
+
+    /// Reads `len` elements of a vec or array, pushing a frame for them unless they can be read
+    /// in one go.
+    async fn elements<'m>(
+        &mut self,
+        inner_type: &'m XbfMetadata,
+        len: u64,
+        stack: &mut Vec<Frame<'m>>,
+    ) -> Result<(), Stop> {
+        self.charge(inner_type.fixed_size(), len)?;
+        // only primitives are read in one go, as the elements of a vec of structs are each subject
+        // to the depth limit
+        let fixed_size = match inner_type {
+            XbfMetadata::Primitive(inner_type) => inner_type.fixed_size(),
+            _ => None,
+        };
+        match fixed_size {
+            Some(size) => self.take(len.saturating_mul(size as u64)).await,
+            None => {
+                stack.push(Frame::Vec {
+                    inner_type,
+                    remaining: len,
+                });
+                Ok(())
+            }
+        }
+    }
+
+    /// Charges elements which encode to no bytes at all against the total bytes limit, as
+    /// [`XbfReader`] does.
+    fn charge(&mut self, element_size: Option<usize>, len: u64) -> Result<(), Stop> {
+        if element_size == Some(0) {
+            let total = self.consumed().saturating_add(len);
+            Self::check(self.options.max_total_bytes, total)?;
+            self.charged = self.charged.saturating_add(len);
+        }
+        Ok(())
+    }
+
+    /// Reads the shape of a tensor, returning its number of elements.
+    async fn shape(&mut self, rank: u8) -> Result<u64, Stop> {
+        let mut shape = Vec::with_capacity(rank.into());
+        for _ in 0..rank {
+            shape.push(u64::from_le_bytes(self.read().await?));
+        }
+        let len = element_count(&shape);
+        Self::check(self.options.max_vec_len, len)?;
+        Ok(len)
+    }
+
+    /// Checks the depth limit when entering a vec or struct inside `depth` others.
+    fn enter(&self, depth: usize) -> Result<(), Stop> {
+        let max = self.options.max_depth.map(u64::from);
+        Self::check(max, depth as u64 + 1)
+    }
+
+    fn check(max: Option<u64>, value: u64) -> Result<(), Stop> {
+        match max {
+            Some(max) if value > max => Err(Stop::Early),
+            _ => Ok(()),
+        }
+    }
+
+    /// Reads a length prefixed string or bytes.
+    async fn bytes(&mut self) -> Result<(), Stop> {
+        let len = u64::from_le_bytes(self.read().await?);
+        Self::check(self.options.max_bytes_len, len)?;
+        let total = self.consumed().saturating_add(len);
+        Self::check(self.options.max_total_bytes, total)?;
+        self.take(len).await
+    }
+
+    async fn read<const N: usize>(&mut self) -> Result<[u8; N], Stop> {
+        self.take(N as u64).await?;
+        let bytes = &self.buf[self.buf.len() - N..];
+        Ok(bytes.try_into().expect("exactly N bytes were read"))
+    }
+
+    /// Reads `len` bytes, or as many as the input and total bytes limit allow.
+    async fn take(&mut self, len: u64) -> Result<(), Stop> {
+        let allowed = match self.options.max_total_bytes {
+            Some(max) => len.min(max.saturating_sub(self.consumed())),
+            None => len,
+        };
+        let read = (&mut *self.reader)
+            .take(allowed)
+            .read_to_end(&mut self.buf)
+            .await
+            .map_err(Stop::Io)?;
+        if (read as u64) < len {
+            return Err(Stop::Early);
+        }
+        Ok(())
+    }
+}
+
+/// What is left to serialize of the metadata enclosing the one being serialized.
+enum MetadataFrame<'m> {
+    Fields(&'m XbfStructMetadata, u16),
+    Variants(&'m XbfEnumMetadata, u16),
+    Then(&'m XbfMetadata),
+}
+
+/// What is left to serialize of the values enclosing the one being serialized.
+enum ValueFrame<'v> {
+    Values(slice::Iter<'v, XbfType>),
+    Entries(&'v XbfMap, usize),
+}
+
+/// Serializes metadata and values to an asynchronous writer through a buffer of about
+/// [`WRITE_BUFFER_SIZE`] bytes, writing the same bytes as serializing them synchronously does.
+struct Spill<'w, W> {
+    writer: &'w mut W,
+    buf: Vec<u8>,
+}
+
+impl<'w, W: AsyncWrite + Unpin> Spill<'w, W> {
+    fn new(writer: &'w mut W) -> Self {
+        Self {
+            writer,
+            buf: Vec::with_capacity(WRITE_BUFFER_SIZE),
+        }
+    }
+
+    async fn metadata(&mut self, metadata: &XbfMetadata) -> io::Result<()> {
+        let mut stack = vec![];
+        let mut next = Some(metadata);
+        loop {
+            let metadata = match next.take() {
+                Some(metadata) => metadata,
+                None => {
+                    let named = match stack.last_mut() {
+                        None => return Ok(()),
+                        Some(MetadataFrame::Then(metadata)) => {
+                            let metadata = *metadata;
+                            stack.pop();
+                            next = Some(metadata);
+                            continue;
+                        }
+                        Some(MetadataFrame::Fields(x, index)) => {
+                            let named = x.get_field(*index);
+                            *index = index.saturating_add(1);
+                            named
+                        }
+                        Some(MetadataFrame::Variants(x, index)) => {
+                            let named = x.get_variant(*index);
+                            *index = index.saturating_add(1);
+                            named
+                        }
+                    };
+                    let Some((name, metadata)) = named else {
+                        stack.pop();
+                        continue;
+                    };
+                    self.bytes(name.as_bytes()).await?;
+                    metadata
+                }
+            };
+
+            match metadata {
+                XbfMetadata::Vec(x) => {
+                    self.put(&[VEC_METADATA_DISCRIMINANT]).await?;
+                    next = Some(&x.inner_type);
+                }
+                XbfMetadata::Option(x) => {
+                    self.put(&[OPTION_METADATA_DISCRIMINANT]).await?;
+                    next = Some(&x.inner_type);
+                }
+                XbfMetadata::Map(x) => {
+                    self.put(&[MAP_METADATA_DISCRIMINANT]).await?;
+                    next = Some(&x.key_type);
+                    stack.push(MetadataFrame::Then(&x.value_type));
+                }
+                XbfMetadata::Array(x) => {
+                    self.put(&[ARRAY_METADATA_DISCRIMINANT]).await?;
+                    self.put(&x.len.to_le_bytes()).await?;
+                    next = Some(&x.inner_type);
+                }
+                XbfMetadata::Struct(x) => {
+                    self.put(&[STRUCT_METADATA_DISCRIMINANT]).await?;
+                    self.bytes(x.name().as_bytes()).await?;
+                    let len = x.fields().len() as u16;
+                    self.put(&len.to_le_bytes()).await?;
+                    stack.push(MetadataFrame::Fields(x, 0));
+                }
+                XbfMetadata::Enum(x) => {
+                    self.put(&[ENUM_METADATA_DISCRIMINANT]).await?;
+                    self.bytes(x.name().as_bytes()).await?;
+                    let len = x.variants().len() as u16;
+                    self.put(&len.to_le_bytes()).await?;
+                    stack.push(MetadataFrame::Variants(x, 0));
+                }
+                XbfMetadata::Primitive(_) | XbfMetadata::Tensor(_) => {
+                    self.put_with(|buf| metadata.serialize_base_metadata(buf))
+                        .await?;
+                }
+            }
+        }
+    }
+
+    async fn value(&mut self, value: &XbfType) -> io::Result<()> {
+        let mut stack = vec![];
+        let mut next = Some(value);
+        loop {
+            let value = match next.take() {
+                Some(value) => value,
+                None => match stack.last_mut() {
+                    None => return Ok(()),
+                    Some(ValueFrame::Values(values)) => match values.next() {
+                        Some(value) => value,
+                        None => {
+                            stack.pop();
+                            continue;
+                        }
+                    },
+                    Some(ValueFrame::Entries(map, index)) => {
+                        let Some((key, value)) = map.get_serialized_entry(*index) else {
+                            stack.pop();
+                            continue;
+                        };
+                        *index += 1;
+                        self.raw(key).await?;
+                        value
+                    }
+                },
+            };
+
+            match value {
+                XbfType::Primitive(XbfPrimitive::Bytes(x)) => self.bytes(x).await?,
+                XbfType::Primitive(XbfPrimitive::String(x)) => self.bytes(x.as_bytes()).await?,
+                XbfType::Primitive(x) => {
+                    self.put_with(|buf| x.serialize_primitive_type(buf)).await?
+                }
+                XbfType::Vec(x) => {
+                    let len = x.len() as u64;
+                    self.put(&len.to_le_bytes()).await?;
+                    // a packed vector is written from its buffer, so that it is not unpacked
+                    match x.as_primitive_buffer() {
+                        Some(buffer) => self.elements(buffer).await?,
+                        None => stack.push(ValueFrame::Values(x.iter())),
+                    }
+                }
+                XbfType::Struct(x) => stack.push(ValueFrame::Values(x.fields.iter())),
+                XbfType::Option(x) => {
+                    let present = x.value();
+                    self.put(&[present.is_some().into()]).await?;
+                    next = present;
+                }
+                XbfType::Enum(x) => {
+                    let variant = x.variant_index();
+                    self.put(&variant.to_le_bytes()).await?;
+                    next = Some(x.value());
+                }
+                XbfType::Map(x) => {
+                    let len = x.len() as u64;
+                    self.put(&len.to_le_bytes()).await?;
+                    stack.push(ValueFrame::Entries(x, 0));
+                }
+                XbfType::Array(x) => stack.push(ValueFrame::Values(x.iter())),
+                XbfType::Tensor(x) => {
+                    if x.metadata.shape.is_none() {
+                        for len in x.shape() {
+                            self.put(&len.to_le_bytes()).await?;
+                        }
+                    }
+                    self.elements(x.elements()).await?;
+                }
+            }
+        }
+    }
+
+    /// Serializes the elements of a buffer a chunk at a time.
+    async fn elements(&mut self, buffer: &XbfPrimitiveBuffer) -> io::Result<()> {
+        let size = buffer.element_type().fixed_size();
+        let chunk = WRITE_BUFFER_SIZE / size.expect("buffers hold fixed size primitives");
+        for start in (0..buffer.len()).step_by(chunk) {
+            let range = start..buffer.len().min(start + chunk);
+            self.put_with(|buf| buffer.serialize_range(range, buf))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Serializes length prefixed bytes.
+    async fn bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let len = bytes.len() as u64;
+        self.put(&len.to_le_bytes()).await?;
+        self.raw(bytes).await
+    }
+
+    /// Writes bytes which are already serialized, straight to the writer rather than copying
+    /// them into the buffer if they would not fit.
+    async fn raw(&mut self, bytes: &[u8]) -> io::Result<()> {
+        if self.buf.len() + bytes.len() <= WRITE_BUFFER_SIZE {
+            self.put(bytes).await
+        } else {
+            self.flush().await?;
+            self.writer.write_all(bytes).await
+        }
+    }
+
+    /// Copies bytes into the buffer, writing the buffer out once it is full.
+    async fn put(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.put_with(|buf| {
+            buf.extend_from_slice(bytes);
+            Ok(())
+        })
+        .await
+    }
+
+    /// Serializes something into the buffer, writing the buffer out once it is full.
+    async fn put_with(&mut self, f: impl FnOnce(&mut Vec<u8>) -> io::Result<()>) -> io::Result<()> {
+        f(&mut self.buf)?;
+        if self.buf.len() >= WRITE_BUFFER_SIZE {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Writes out everything in the buffer.
+    async fn flush(&mut self) -> io::Result<()> {
+        self.writer.write_all(&self.buf).await?;
+        self.buf.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        XbfArray, XbfArrayMetadata, XbfEnum, XbfEnumMetadata, XbfErrorKind, XbfLimit, XbfMap,
+        XbfMapMetadata, XbfOption, XbfPrimitive, XbfStruct, XbfStructMetadata, XbfTensor,
+        XbfTensorMetadata, XbfTypeUpcast, XbfVec, XbfVecMetadata,
+    };
+    use indexmap::{indexmap, IndexMap};
+    use std::{
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    fn block_on<T>(future: impl std::future::Future<Output = T>) -> T {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    fn riders() -> XbfType {
+        let rider_metadata = XbfStructMetadata::new(
+            "DragonRider",
+            indexmap! {
+                "name" => XbfPrimitiveMetadata::String.into(),
+                "age" => XbfPrimitiveMetadata::U16.into(),
+                "scores" => XbfVecMetadata::new(XbfPrimitiveMetadata::I32).into(),
+                "alive" => XbfPrimitiveMetadata::Bool.into(),
+            },
+        );
+        XbfVec::new(
+            XbfVecMetadata::new(rider_metadata.clone()),
+            [("Eragon", 16), ("Arya", 103)].map(|(name, age)| {
+                XbfStruct::new(
+                    rider_metadata.clone(),
+                    [
+                        XbfPrimitive::String(name.to_string()).into(),
+                        XbfPrimitive::U16(age).into(),
+                        XbfVec::new(
+                            XbfVecMetadata::new(XbfPrimitiveMetadata::I32),
+                            [XbfPrimitive::I32(1), XbfPrimitive::I32(2)],
+                        )
+                        .unwrap()
+                        .into(),
+                        XbfPrimitive::Bool(true).into(),
+                    ],
+                )
+                .unwrap()
+            }),
+        )
+        .unwrap()
+        .into_base_type()
+    }
+
+    fn serialize(value: &XbfType) -> Vec<u8> {
+        let mut data = vec![];
+        XbfMetadata::from(value)
+            .serialize_base_metadata(&mut data)
+            .unwrap();
+        value.serialize_base_type(&mut data).unwrap();
+        data
+    }
+
+    fn read_sync(data: &[u8], options: DeserializeOptions) -> Result<XbfType, XbfError> {
+        let mut reader = XbfReader::with_options(data, options);
+        let metadata = reader.read_metadata()?;
+        reader.read_value(&metadata)
+    }
+
+    fn read_async(data: &[u8], options: DeserializeOptions) -> Result<XbfType, XbfError> {
+        block_on(async {
+            let mut reader = XbfAsyncReader::with_options(data, options);
+            let metadata = reader.read_metadata().await?;
+            reader.read_value(&metadata).await
+        })
+    }
+
+    #[test]
+    fn round_trip_works() {
+        let value = riders();
+        let mut data = vec![];
+        block_on(async {
+            XbfMetadata::from(&value)
+                .serialize_base_metadata_async(&mut data)
+                .await
+                .unwrap();
+            value.serialize_base_type_async(&mut data).await.unwrap();
+        });
+        assert_eq!(data, serialize(&value));
+        data.push(42);
+
+        let mut reader = data.as_slice();
+        let (metadata, deserialized) = block_on(async {
+            let metadata = XbfMetadata::deserialize_base_metadata_async(&mut reader)
+                .await
+                .unwrap();
+            let value = XbfType::deserialize_base_type_async(&metadata, &mut reader)
+                .await
+                .unwrap();
+            (metadata, value)
+        });
+
+        assert_eq!(metadata, XbfMetadata::from(&value));
+        assert_eq!(deserialized, value);
+        assert_eq!(reader, [42]);
+    }
+
+    /// A writer which records the size of every write made to it.
+    #[derive(Default)]
+    struct RecordingWriter {
+        data: Vec<u8>,
+        writes: Vec<usize>,
+    }
+
+    impl AsyncWrite for RecordingWriter {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            self.data.extend_from_slice(buf);
+            self.writes.push(buf.len());
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[test]
+    fn serialize_writes_in_bounded_chunks() {
+        let name = "Saphira".repeat(3000);
+        let event = XbfEnumMetadata::new(
+            "Event",
+            indexmap! {
+                "Login" => XbfPrimitiveMetadata::String.into(),
+                "Logout" => XbfPrimitiveMetadata::U64.into(),
+            },
+        );
+        let tensor = XbfTensorMetadata::new(XbfPrimitiveMetadata::I64, 2).unwrap();
+        let fields = [
+            XbfPrimitive::String(name.clone()).into(),
+            XbfVec::from((0..10_000u64).collect::<Vec<_>>()).into(),
+            riders(),
+            XbfEnum::new(event, "Logout", XbfPrimitive::U64(42))
+                .unwrap()
+                .into(),
+            XbfOption::some(XbfPrimitive::U32(1)).into(),
+            XbfMap::new(
+                XbfMapMetadata::new(XbfPrimitiveMetadata::String, XbfPrimitiveMetadata::U16),
+                ["Eragon", "Murtagh"]
+                    .map(|x| (XbfPrimitive::String(x.to_string()), XbfPrimitive::U16(1))),
+            )
+            .unwrap()
+            .into(),
+            XbfArray::new(
+                XbfArrayMetadata::new(XbfPrimitiveMetadata::U8, 2),
+                [XbfPrimitive::U8(1), XbfPrimitive::U8(2)],
+            )
+            .unwrap()
+            .into(),
+            XbfTensor::new(tensor, [3, 1], vec![-1i64, 0, 1])
+                .unwrap()
+                .into(),
+        ];
+        let metadata = XbfStructMetadata::new(
+            "Hoard",
+            fields
+                .iter()
+                .enumerate()
+                .map(|(i, x)| (format!("field{i}"), XbfMetadata::from(x)))
+                .collect(),
+        );
+        let value = XbfStruct::new(metadata, fields).unwrap().into_base_type();
+
+        let mut writer = RecordingWriter::default();
+        block_on(async {
+            XbfMetadata::from(&value)
+                .serialize_base_metadata_async(&mut writer)
+                .await
+                .unwrap();
+            value.serialize_base_type_async(&mut writer).await.unwrap();
+        });
+        assert_eq!(writer.data, serialize(&value));
+
+        // the long name is written straight from the value, and nothing else is held at once
+        assert!(writer.writes.contains(&name.len()));
+        assert!(writer
+            .writes
+            .iter()
+            .all(|x| *x == name.len() || *x < 2 * WRITE_BUFFER_SIZE));
+        assert!(writer.writes.len() > 10);
+    }
+
+    #[test]
+    fn errors_match_sync_reader() {
+        let data = serialize(&riders());
+        let option_sets = [
+            DeserializeOptions::new(),
+            DeserializeOptions::new().max_depth(1),
+            DeserializeOptions::new().max_depth(2),
+            DeserializeOptions::new().max_bytes_len(4),
+            DeserializeOptions::new().max_vec_len(1),
+            DeserializeOptions::new().max_struct_fields(3),
+            DeserializeOptions::new().max_total_bytes(100),
+            DeserializeOptions::new().max_total_bytes(160),
+        ];
+
+        for options in option_sets {
+            for len in 0..=data.len() {
+                let expected = read_sync(&data[..len], options);
+                let actual = read_async(&data[..len], options);
+                match (expected, actual) {
+                    (Ok(expected), Ok(actual)) => assert_eq!(expected, actual),
+                    (Err(expected), Err(actual)) => {
+                        assert_eq!(expected.to_string(), actual.to_string())
+                    }
+                    (expected, actual) => {
+                        panic!("{options:?} {len}: expected {expected:?}, got {actual:?}")
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn enum_errors_match_sync_reader() {
+        let metadata = XbfEnumMetadata::new(
+            "Event",
+            indexmap! {
+                "Login" => XbfPrimitiveMetadata::String.into(),
+                "Logout" => XbfPrimitiveMetadata::U64.into(),
+            },
+        );
+        let value = XbfEnum::new(
+            metadata,
+            "Login",
+            XbfPrimitive::String("Eragon".to_string()),
+        )
+        .unwrap()
+        .into_base_type();
+        let mut data = serialize(&value);
+
+        for len in 0..=data.len() {
+            let expected = read_sync(&data[..len], DeserializeOptions::new());
+            let actual = read_async(&data[..len], DeserializeOptions::new());
+            assert_eq!(format!("{expected:?}"), format!("{actual:?}"));
+        }
+
+        // an unknown variant index
+        let index = data.len() - 16;
+        data[index] = 2;
+        let expected = read_sync(&data, DeserializeOptions::new()).unwrap_err();
+        let actual = read_async(&data, DeserializeOptions::new()).unwrap_err();
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn map_errors_match_sync_reader() {
+        let metadata = XbfMapMetadata::new(XbfPrimitiveMetadata::String, XbfPrimitiveMetadata::U16);
+        let value = XbfMap::new(
+            metadata,
+            ["Eragon", "Murtag"]
+                .map(|x| (XbfPrimitive::String(x.to_string()), XbfPrimitive::U16(1))),
+        )
+        .unwrap()
+        .into_base_type();
+        let data = serialize(&value);
+
+        for len in 0..=data.len() {
+            let expected = read_sync(&data[..len], DeserializeOptions::new());
+            let actual = read_async(&data[..len], DeserializeOptions::new());
+            assert_eq!(format!("{expected:?}"), format!("{actual:?}"));
+        }
+
+        // the same key twice, which only strict mode rejects
+        let entry = &data[data.len() - 16..];
+        let mut data = data[..data.len() - 32].to_vec();
+        data.extend_from_slice(entry);
+        data.extend_from_slice(entry);
+        for options in [
+            DeserializeOptions::new(),
+            DeserializeOptions::new().strict(true),
+        ] {
+            let expected = read_sync(&data, options);
+            let actual = read_async(&data, options);
+            assert_eq!(format!("{expected:?}"), format!("{actual:?}"));
+        }
+    }
+
+    #[test]
+    fn array_errors_match_sync_reader() {
+        let metadata =
+            XbfArrayMetadata::new(XbfArrayMetadata::new(XbfPrimitiveMetadata::String, 2), 2);
+        let value = XbfArray::new(
+            metadata,
+            [["Eragon", "Saphira"], ["Murtagh", "Thorn"]].map(|pair| {
+                XbfArray::new(
+                    XbfArrayMetadata::new(XbfPrimitiveMetadata::String, 2),
+                    pair.map(|x| XbfPrimitive::String(x.to_string())),
+                )
+                .unwrap()
+            }),
+        )
+        .unwrap()
+        .into_base_type();
+        let data = serialize(&value);
+
+        for options in [
+            DeserializeOptions::new(),
+            DeserializeOptions::new().max_depth(1),
+            DeserializeOptions::new().max_vec_len(1),
+        ] {
+            for len in 0..=data.len() {
+                let expected = read_sync(&data[..len], options);
+                let actual = read_async(&data[..len], options);
+                assert_eq!(format!("{expected:?}"), format!("{actual:?}"));
+            }
+        }
+    }
+
+    #[test]
+    fn tensor_errors_match_sync_reader() {
+        let fixed = XbfTensorMetadata::with_shape(XbfPrimitiveMetadata::U16, [2, 2]).unwrap();
+        let dynamic = XbfTensorMetadata::new(XbfPrimitiveMetadata::I64, 2).unwrap();
+        let metadata = XbfStructMetadata::new(
+            "Egg",
+            indexmap! {
+                "scales" => fixed.clone().into(),
+                "heat" => dynamic.clone().into(),
+            },
+        );
+        let value = XbfStruct::new(
+            metadata,
+            [
+                XbfTensor::new(fixed, [2, 2], vec![1u16, 2, 3, 4])
+                    .unwrap()
+                    .into(),
+                XbfTensor::new(dynamic, [3, 1], vec![-1i64, 0, 1])
+                    .unwrap()
+                    .into(),
+            ],
+        )
+        .unwrap()
+        .into_base_type();
+        let data = serialize(&value);
+        assert_eq!(read_async(&data, DeserializeOptions::new()).unwrap(), value);
+
+        for options in [
+            DeserializeOptions::new(),
+            DeserializeOptions::new().max_vec_len(3),
+            DeserializeOptions::new().max_total_bytes(60),
+        ] {
+            for len in 0..=data.len() {
+                let expected = read_sync(&data[..len], options);
+                let actual = read_async(&data[..len], options);
+                assert_eq!(format!("{expected:?}"), format!("{actual:?}"));
+            }
+        }
+    }
+
+    #[test]
+    fn strict_errors_match_sync_reader() {
+        let mut data = serialize(&riders());
+        *data.last_mut().unwrap() = 2;
+        let options = DeserializeOptions::new().strict(true);
+
+        let expected = read_sync(&data, options).unwrap_err();
+        let actual = read_async(&data, options).unwrap_err();
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn zero_size_elements_errors_match_sync_reader() {
+        let empty = XbfStructMetadata::new("Empty", IndexMap::<&str, _>::new());
+        let options = DeserializeOptions::new().max_total_bytes(64);
+
+        let mut data = vec![];
+        XbfMetadata::from(XbfVecMetadata::new(empty.clone()))
+            .serialize_base_metadata(&mut data)
+            .unwrap();
+        data.extend_from_slice(&u64::MAX.to_le_bytes());
+        let expected = read_sync(&data, options).unwrap_err();
+        let actual = read_async(&data, options).unwrap_err();
+        assert!(matches!(
+            actual.kind(),
+            XbfErrorKind::LimitExceeded {
+                limit: XbfLimit::TotalBytes,
+                ..
+            }
+        ));
+        assert_eq!(expected.to_string(), actual.to_string());
+
+        let metadata = XbfArrayMetadata::new(empty, u64::MAX).into();
+        let expected = XbfReader::with_options([].as_slice(), options)
+            .read_value(&metadata)
+            .unwrap_err();
+        let actual = block_on(async {
+            XbfAsyncReader::with_options([].as_slice(), options)
+                .read_value(&metadata)
+                .await
+                .unwrap_err()
+        });
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+}
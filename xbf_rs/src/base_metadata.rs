@@ -1,10 +1,15 @@
 use crate::{
+    reader::XbfReader,
+    xbf_array::{XbfArrayMetadata, ARRAY_METADATA_DISCRIMINANT},
+    xbf_enum::{XbfEnumMetadata, ENUM_METADATA_DISCRIMINANT},
+    xbf_map::{XbfMapMetadata, MAP_METADATA_DISCRIMINANT},
+    xbf_option::{XbfOptionMetadata, OPTION_METADATA_DISCRIMINANT},
     xbf_primitive::XbfPrimitiveMetadata,
     xbf_struct::{XbfStructMetadata, STRUCT_METADATA_DISCRIMINANT},
+    xbf_tensor::{XbfTensorMetadata, TENSOR_METADATA_DISCRIMINANT},
     xbf_vec::{XbfVecMetadata, VEC_METADATA_DISCRIMINANT},
-    XbfType,
+    XbfError, XbfErrorKind, XbfType,
 };
-use byteorder::ReadBytesExt;
 use std::io::{self, Read, Write};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -12,6 +17,11 @@ pub enum XbfMetadata {
     Primitive(XbfPrimitiveMetadata),
     Vec(XbfVecMetadata),
     Struct(XbfStructMetadata),
+    Option(XbfOptionMetadata),
+    Enum(XbfEnumMetadata),
+    Map(XbfMapMetadata),
+    Array(XbfArrayMetadata),
+    Tensor(XbfTensorMetadata),
 }
 
 impl XbfMetadata {
@@ -20,21 +30,105 @@ impl XbfMetadata {
             XbfMetadata::Primitive(x) => x.serialize_primitive_metadata(writer),
             XbfMetadata::Vec(x) => x.serialize_vec_metadata(writer),
             XbfMetadata::Struct(x) => x.serialize_struct_metadata(writer),
+            XbfMetadata::Option(x) => x.serialize_option_metadata(writer),
+            XbfMetadata::Enum(x) => x.serialize_enum_metadata(writer),
+            XbfMetadata::Map(x) => x.serialize_map_metadata(writer),
+            XbfMetadata::Array(x) => x.serialize_array_metadata(writer),
+            XbfMetadata::Tensor(x) => x.serialize_tensor_metadata(writer),
         }
     }
 
-    pub fn deserialize_base_metadata(reader: &mut impl Read) -> io::Result<XbfMetadata> {
+    /// Deserialize metadata as defined by the XBF specification.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails or the metadata is invalid, such as when it
+    /// contains an unknown discriminant.
+    pub fn deserialize_base_metadata(reader: &mut impl Read) -> Result<XbfMetadata, XbfError> {
+        XbfReader::new(reader).read_metadata()
+    }
+
+    /// Returns the number of bytes every serialized value of this type takes up, or `None` if it
+    /// depends on the value.
+    ///
+    /// Primitives other than bytes and strings have a fixed size, as do structs whose fields all
+    /// have a fixed size, arrays whose elements have a fixed size, tensors whose shape is part of
+    /// their metadata, and enums whose variants all have the same fixed size. Vectors, options and
+    /// maps never do, as their length or presence is part of the value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use xbf_rs::{XbfMetadata, XbfPrimitiveMetadata, XbfStructMetadata, XbfVecMetadata};
+    /// use indexmap::indexmap;
+    ///
+    /// let point: XbfMetadata = XbfStructMetadata::new(
+    ///     "Point",
+    ///     indexmap! {
+    ///         "x" => XbfPrimitiveMetadata::F32.into(),
+    ///         "y" => XbfPrimitiveMetadata::F32.into(),
+    ///     },
+    /// )
+    /// .into();
+    ///
+    /// assert_eq!(point.fixed_size(), Some(8));
+    /// assert_eq!(XbfMetadata::from(XbfVecMetadata::new(point)).fixed_size(), None);
+    /// ```
+    pub fn fixed_size(&self) -> Option<usize> {
+        match self {
+            XbfMetadata::Primitive(x) => x.fixed_size(),
+            XbfMetadata::Vec(_) | XbfMetadata::Option(_) | XbfMetadata::Map(_) => None,
+            XbfMetadata::Struct(x) => x
+                .fields()
+                .try_fold(0usize, |acc, (_, x)| acc.checked_add(x.fixed_size()?)),
+            XbfMetadata::Array(x) => x
+                .inner_type
+                .fixed_size()?
+                .checked_mul(x.len.try_into().ok()?),
+            XbfMetadata::Tensor(x) => x
+                .shape
+                .as_ref()?
+                .iter()
+                .try_fold(x.element_type.fixed_size()?, |acc, x| {
+                    acc.checked_mul((*x).try_into().ok()?)
+                }),
+            XbfMetadata::Enum(x) => {
+                let mut sizes = x.variants().map(|(_, x)| x.fixed_size());
+                let size = sizes.next()??;
+                if sizes.all(|x| x == Some(size)) {
+                    size.checked_add(2)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    pub(crate) fn read_base_metadata(
+        reader: &mut XbfReader<impl Read>,
+    ) -> Result<XbfMetadata, XbfError> {
+        let offset = reader.position();
         let discriminant = reader.read_u8()?;
         if let Ok(x) = XbfPrimitiveMetadata::try_from(discriminant) {
             Ok(XbfMetadata::Primitive(x))
         } else if discriminant == VEC_METADATA_DISCRIMINANT {
-            Ok(XbfVecMetadata::deserialize_vec_metadata(reader)?.to_base_metadata())
+            Ok(XbfVecMetadata::read_vec_metadata(reader)?.to_base_metadata())
         } else if discriminant == STRUCT_METADATA_DISCRIMINANT {
-            Ok(XbfStructMetadata::deserialize_struct_metadata(reader)?.to_base_metadata())
+            Ok(XbfStructMetadata::read_struct_metadata(reader)?.to_base_metadata())
+        } else if discriminant == OPTION_METADATA_DISCRIMINANT {
+            Ok(XbfOptionMetadata::read_option_metadata(reader)?.to_base_metadata())
+        } else if discriminant == ENUM_METADATA_DISCRIMINANT {
+            Ok(XbfEnumMetadata::read_enum_metadata(reader)?.to_base_metadata())
+        } else if discriminant == MAP_METADATA_DISCRIMINANT {
+            Ok(XbfMapMetadata::read_map_metadata(reader)?.to_base_metadata())
+        } else if discriminant == ARRAY_METADATA_DISCRIMINANT {
+            Ok(XbfArrayMetadata::read_array_metadata(reader)?.to_base_metadata())
+        } else if discriminant == TENSOR_METADATA_DISCRIMINANT {
+            Ok(XbfTensorMetadata::read_tensor_metadata(reader)?.to_base_metadata())
         } else {
-            Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!("Unknown metadata discriminant {}", discriminant),
+            Err(XbfError::new(
+                XbfErrorKind::UnknownDiscriminant(discriminant),
+                offset,
             ))
         }
     }
@@ -76,12 +170,77 @@ impl From<&XbfStructMetadata> for XbfMetadata {
     }
 }
 
+impl From<XbfOptionMetadata> for XbfMetadata {
+    fn from(value: XbfOptionMetadata) -> Self {
+        value.into_base_metadata()
+    }
+}
+
+impl From<&XbfOptionMetadata> for XbfMetadata {
+    fn from(value: &XbfOptionMetadata) -> Self {
+        value.to_base_metadata()
+    }
+}
+
+impl From<XbfEnumMetadata> for XbfMetadata {
+    fn from(value: XbfEnumMetadata) -> Self {
+        value.into_base_metadata()
+    }
+}
+
+impl From<&XbfEnumMetadata> for XbfMetadata {
+    fn from(value: &XbfEnumMetadata) -> Self {
+        value.to_base_metadata()
+    }
+}
+
+impl From<XbfMapMetadata> for XbfMetadata {
+    fn from(value: XbfMapMetadata) -> Self {
+        value.into_base_metadata()
+    }
+}
+
+impl From<&XbfMapMetadata> for XbfMetadata {
+    fn from(value: &XbfMapMetadata) -> Self {
+        value.to_base_metadata()
+    }
+}
+
+impl From<XbfArrayMetadata> for XbfMetadata {
+    fn from(value: XbfArrayMetadata) -> Self {
+        value.into_base_metadata()
+    }
+}
+
+impl From<&XbfArrayMetadata> for XbfMetadata {
+    fn from(value: &XbfArrayMetadata) -> Self {
+        value.to_base_metadata()
+    }
+}
+
+impl From<XbfTensorMetadata> for XbfMetadata {
+    fn from(value: XbfTensorMetadata) -> Self {
+        value.into_base_metadata()
+    }
+}
+
+impl From<&XbfTensorMetadata> for XbfMetadata {
+    fn from(value: &XbfTensorMetadata) -> Self {
+        value.to_base_metadata()
+    }
+}
+
 impl From<&XbfType> for XbfMetadata {
     fn from(value: &XbfType) -> Self {
         match value {
             XbfType::Primitive(v) => XbfPrimitiveMetadata::from(v).into(),
             XbfType::Vec(v) => XbfVecMetadata::from(v).into(),
             XbfType::Struct(v) => XbfStructMetadata::from(v).into(),
+            XbfType::Option(v) => XbfOptionMetadata::from(v).into(),
+            XbfType::Enum(v) => XbfEnumMetadata::from(v).into(),
+            XbfType::Map(v) => XbfMapMetadata::from(v).into(),
+            XbfType::Array(v) => XbfArrayMetadata::from(v).into(),
+            XbfType::Tensor(v) => XbfTensorMetadata::from(v).into(),
         }
     }
 }
@@ -92,13 +251,21 @@ pub trait XbfMetadataUpcast: private::Sealed {
 }
 
 mod private {
-    use crate::{XbfPrimitiveMetadata, XbfStructMetadata, XbfVecMetadata};
+    use crate::{
+        XbfArrayMetadata, XbfEnumMetadata, XbfMapMetadata, XbfOptionMetadata, XbfPrimitiveMetadata,
+        XbfStructMetadata, XbfTensorMetadata, XbfVecMetadata,
+    };
 
     pub trait Sealed {}
 
     impl Sealed for XbfPrimitiveMetadata {}
     impl Sealed for XbfVecMetadata {}
     impl Sealed for XbfStructMetadata {}
+    impl Sealed for XbfOptionMetadata {}
+    impl Sealed for XbfEnumMetadata {}
+    impl Sealed for XbfMapMetadata {}
+    impl Sealed for XbfArrayMetadata {}
+    impl Sealed for XbfTensorMetadata {}
 }
 
 #[cfg(test)]
@@ -107,6 +274,21 @@ mod tests {
     use indexmap::indexmap;
     use std::io::Cursor;
 
+    #[test]
+    fn discriminants_are_stable() {
+        // changing any of these breaks every payload and fingerprint that uses them
+        assert_eq!(XbfPrimitiveMetadata::String as u8, 16);
+        assert_eq!(VEC_METADATA_DISCRIMINANT, 17);
+        assert_eq!(STRUCT_METADATA_DISCRIMINANT, 18);
+        assert_eq!(OPTION_METADATA_DISCRIMINANT, 19);
+        assert_eq!(ENUM_METADATA_DISCRIMINANT, 20);
+        assert_eq!(MAP_METADATA_DISCRIMINANT, 21);
+        assert_eq!(ARRAY_METADATA_DISCRIMINANT, 22);
+        assert_eq!(XbfPrimitiveMetadata::Timestamp as u8, 23);
+        assert_eq!(XbfPrimitiveMetadata::BF16 as u8, 32);
+        assert_eq!(TENSOR_METADATA_DISCRIMINANT, 33);
+    }
+
     #[test]
     fn deserialize_unknown_discriminant_works() {
         let bad_discriminant = 69u8;
@@ -115,9 +297,14 @@ mod tests {
         let should_be_err = XbfMetadata::deserialize_base_metadata(&mut reader)
             .expect_err("should have failed deserialization");
 
+        assert!(matches!(
+            should_be_err.kind(),
+            XbfErrorKind::UnknownDiscriminant(x) if *x == bad_discriminant
+        ));
+        assert_eq!(should_be_err.offset(), 0);
         assert_eq!(
             should_be_err.to_string(),
-            format!("Unknown metadata discriminant {bad_discriminant}")
+            format!("Unknown metadata discriminant {bad_discriminant} at offset 0")
         )
     }
 
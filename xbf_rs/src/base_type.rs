@@ -1,4 +1,9 @@
-use crate::{xbf_primitive::XbfPrimitive, xbf_struct::XbfStruct, xbf_vec::XbfVec, XbfMetadata};
+use crate::{
+    reader::XbfReader, xbf_array::XbfArray, xbf_enum::XbfEnum, xbf_map::XbfMap,
+    xbf_option::XbfOption, xbf_primitive::XbfPrimitive, xbf_struct::XbfStruct,
+    xbf_tensor::XbfTensor, xbf_vec::XbfVec, FromXbfError, XbfDate, XbfDecimal, XbfDuration,
+    XbfError, XbfI256, XbfMetadata, XbfProjection, XbfTime, XbfTimestamp, XbfTimestampTz, XbfU256,
+};
 use std::io::{self, Read, Write};
 
 #[derive(Debug, Clone, PartialEq)]
@@ -6,6 +11,11 @@ pub enum XbfType {
     Primitive(XbfPrimitive),
     Vec(XbfVec),
     Struct(XbfStruct),
+    Option(XbfOption),
+    Enum(XbfEnum),
+    Map(XbfMap),
+    Array(XbfArray),
+    Tensor(XbfTensor),
 }
 
 impl XbfType {
@@ -35,25 +45,346 @@ impl XbfType {
             XbfType::Primitive(x) => x.serialize_primitive_type(writer),
             XbfType::Vec(x) => x.serialize_vec_type(writer),
             XbfType::Struct(x) => x.serialize_struct_type(writer),
+            XbfType::Option(x) => x.serialize_option_type(writer),
+            XbfType::Enum(x) => x.serialize_enum_type(writer),
+            XbfType::Map(x) => x.serialize_map_type(writer),
+            XbfType::Array(x) => x.serialize_array_type(writer),
+            XbfType::Tensor(x) => x.serialize_tensor_type(writer),
         }
     }
 
+    /// Deserialize an [`XbfType`] as defined by the XBF specification.
+    ///
+    /// This function **does not** read the metadata of the type from the reader. It is expected
+    /// that to call this function the metadata for a type is already known, be that from reading
+    /// it from the reader with [`XbfMetadata::deserialize_base_metadata`] or having it in some
+    /// other manner.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails or the data is invalid. The error records the
+    /// offset and path of the value which failed to deserialize.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use xbf_rs::{XbfErrorKind, XbfPrimitive, XbfPrimitiveMetadata, XbfType};
+    ///
+    /// let metadata = XbfPrimitiveMetadata::I32.into();
+    ///
+    /// let value = XbfType::deserialize_base_type(&metadata, &mut &42i32.to_le_bytes()[..]);
+    /// assert_eq!(value.unwrap(), XbfPrimitive::I32(42).into());
+    ///
+    /// let err = XbfType::deserialize_base_type(&metadata, &mut &[0u8, 0][..]).unwrap_err();
+    /// assert!(matches!(err.kind(), XbfErrorKind::UnexpectedEof));
+    /// ```
     pub fn deserialize_base_type(
         metadata: &XbfMetadata,
         reader: &mut impl Read,
-    ) -> io::Result<XbfType> {
+    ) -> Result<XbfType, XbfError> {
+        XbfReader::new(reader).read_value(metadata)
+    }
+
+    pub(crate) fn read_base_type(
+        metadata: &XbfMetadata,
+        reader: &mut XbfReader<impl Read>,
+    ) -> Result<XbfType, XbfError> {
         match metadata {
             XbfMetadata::Primitive(x) => {
-                XbfPrimitive::deserialize_primitive_type(x, reader).map(|x| x.into())
+                XbfPrimitive::read_primitive_type(x, reader).map(|x| x.into())
+            }
+            XbfMetadata::Vec(x) => XbfVec::read_vec_type(x, reader).map(|x| x.into()),
+            XbfMetadata::Struct(x) => XbfStruct::read_struct_type(x, reader).map(|x| x.into()),
+            XbfMetadata::Option(x) => XbfOption::read_option_type(x, reader).map(|x| x.into()),
+            XbfMetadata::Enum(x) => XbfEnum::read_enum_type(x, reader).map(|x| x.into()),
+            XbfMetadata::Map(x) => XbfMap::read_map_type(x, reader).map(|x| x.into()),
+            XbfMetadata::Array(x) => XbfArray::read_array_type(x, reader).map(|x| x.into()),
+            XbfMetadata::Tensor(x) => XbfTensor::read_tensor_type(x, reader).map(|x| x.into()),
+        }
+    }
+
+    /// Deserialize only the parts of an [`XbfType`] selected by a projection, skipping over the
+    /// rest.
+    ///
+    /// The deserialized value has metadata containing only the selected fields. See
+    /// [`XbfProjection`] for how fields are selected.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails or the data is invalid, or with
+    /// [`XbfErrorKind::UnknownField`](crate::XbfErrorKind::UnknownField) before reading anything
+    /// if the projection selects a field which does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use xbf_rs::{XbfPrimitiveMetadata, XbfProjection, XbfStructMetadata, XbfType};
+    /// use indexmap::indexmap;
+    ///
+    /// let metadata = XbfStructMetadata::new(
+    ///     "Point",
+    ///     indexmap! {
+    ///         "x" => XbfPrimitiveMetadata::I32.into(),
+    ///         "y" => XbfPrimitiveMetadata::I32.into(),
+    ///     },
+    /// )
+    /// .into();
+    ///
+    /// let mut data = vec![];
+    /// data.extend_from_slice(&1i32.to_le_bytes());
+    /// data.extend_from_slice(&2i32.to_le_bytes());
+    ///
+    /// let projection = XbfProjection::new(["y"]);
+    /// let point =
+    ///     XbfType::deserialize_base_type_projected(&metadata, &projection, &mut data.as_slice())
+    ///         .unwrap();
+    ///
+    /// let point = point.as_struct().unwrap();
+    /// assert_eq!(point.get("x"), None);
+    /// assert_eq!(point.get("y").unwrap().as_i32().unwrap(), 2);
+    /// ```
+    pub fn deserialize_base_type_projected(
+        metadata: &XbfMetadata,
+        projection: &XbfProjection,
+        reader: &mut impl Read,
+    ) -> Result<XbfType, XbfError> {
+        XbfReader::new(reader).read_value_projected(metadata, projection)
+    }
+
+    /// Reads a value described by `metadata` into the already projected metadata `projected`.
+    pub(crate) fn read_projected(
+        metadata: &XbfMetadata,
+        projected: &XbfMetadata,
+        projection: &XbfProjection,
+        reader: &mut XbfReader<impl Read>,
+    ) -> Result<XbfType, XbfError> {
+        if projection.is_all() {
+            return Self::read_base_type(metadata, reader);
+        }
+        match (metadata, projected) {
+            (XbfMetadata::Vec(x), XbfMetadata::Vec(projected)) => {
+                XbfVec::read_vec_type_projected(x, projected, projection, reader).map(|x| x.into())
+            }
+            (XbfMetadata::Struct(x), XbfMetadata::Struct(projected)) => {
+                XbfStruct::read_struct_type_projected(x, projected, projection, reader)
+                    .map(|x| x.into())
+            }
+            (XbfMetadata::Option(x), XbfMetadata::Option(projected)) => {
+                XbfOption::read_option_type_projected(x, projected, projection, reader)
+                    .map(|x| x.into())
             }
-            XbfMetadata::Vec(x) => XbfVec::deserialize_vec_type(x, reader).map(|x| x.into()),
-            XbfMetadata::Struct(x) => {
-                XbfStruct::deserialize_struct_type(x, reader).map(|x| x.into())
+            (XbfMetadata::Array(x), XbfMetadata::Array(projected)) => {
+                XbfArray::read_array_type_projected(x, projected, projection, reader)
+                    .map(|x| x.into())
             }
+            _ => unreachable!("projected metadata has the same shape as the original"),
+        }
+    }
+
+    /// Returns a reference to the inner [`XbfPrimitive`] if this is a primitive.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FromXbfError::TypeMismatch`] if this is not a primitive.
+    pub fn as_primitive(&self) -> Result<&XbfPrimitive, FromXbfError> {
+        match self {
+            XbfType::Primitive(x) => Ok(x),
+            other => Err(FromXbfError::type_mismatch("Primitive", other)),
+        }
+    }
+
+    /// Returns a reference to the inner [`XbfVec`] if this is a vec.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FromXbfError::TypeMismatch`] if this is not a vec.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use xbf_rs::prelude::*;
+    /// use xbf_rs::{XbfPrimitive, XbfPrimitiveMetadata, XbfVec, XbfVecMetadata};
+    ///
+    /// let vec = XbfVec::new(
+    ///     XbfVecMetadata::new(XbfPrimitiveMetadata::I32),
+    ///     [XbfPrimitive::I32(42)],
+    /// )
+    /// .unwrap()
+    /// .into_base_type();
+    ///
+    /// assert_eq!(vec.as_vec().unwrap()[0].as_i32(), Ok(42));
+    /// assert!(vec.as_struct().is_err());
+    /// ```
+    pub fn as_vec(&self) -> Result<&XbfVec, FromXbfError> {
+        match self {
+            XbfType::Vec(x) => Ok(x),
+            other => Err(FromXbfError::type_mismatch("Vec", other)),
+        }
+    }
+
+    /// Returns a reference to the inner [`XbfStruct`] if this is a struct.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FromXbfError::TypeMismatch`] if this is not a struct.
+    pub fn as_struct(&self) -> Result<&XbfStruct, FromXbfError> {
+        match self {
+            XbfType::Struct(x) => Ok(x),
+            other => Err(FromXbfError::type_mismatch("Struct", other)),
+        }
+    }
+
+    /// Returns a reference to the inner [`XbfOption`] if this is an option.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FromXbfError::TypeMismatch`] if this is not an option.
+    pub fn as_option(&self) -> Result<&XbfOption, FromXbfError> {
+        match self {
+            XbfType::Option(x) => Ok(x),
+            other => Err(FromXbfError::type_mismatch("Option", other)),
+        }
+    }
+
+    /// Returns a reference to the inner [`XbfEnum`] if this is an enum.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FromXbfError::TypeMismatch`] if this is not an enum.
+    pub fn as_enum(&self) -> Result<&XbfEnum, FromXbfError> {
+        match self {
+            XbfType::Enum(x) => Ok(x),
+            other => Err(FromXbfError::type_mismatch("Enum", other)),
+        }
+    }
+
+    /// Returns a reference to the inner [`XbfArray`] if this is an array.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FromXbfError::TypeMismatch`] if this is not an array.
+    pub fn as_array(&self) -> Result<&XbfArray, FromXbfError> {
+        match self {
+            XbfType::Array(x) => Ok(x),
+            other => Err(FromXbfError::type_mismatch("Array", other)),
+        }
+    }
+
+    /// Returns a reference to the inner [`XbfTensor`] if this is a tensor.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FromXbfError::TypeMismatch`] if this is not a tensor.
+    pub fn as_tensor(&self) -> Result<&XbfTensor, FromXbfError> {
+        match self {
+            XbfType::Tensor(x) => Ok(x),
+            other => Err(FromXbfError::type_mismatch("Tensor", other)),
+        }
+    }
+
+    /// Returns a reference to the inner [`XbfMap`] if this is a map.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FromXbfError::TypeMismatch`] if this is not a map.
+    pub fn as_map(&self) -> Result<&XbfMap, FromXbfError> {
+        match self {
+            XbfType::Map(x) => Ok(x),
+            other => Err(FromXbfError::type_mismatch("Map", other)),
+        }
+    }
+
+    /// Returns the inner string slice if this is a [`XbfPrimitive::String`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FromXbfError::TypeMismatch`] if this is not a string.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use xbf_rs::{FromXbfError, XbfPrimitive, XbfPrimitiveMetadata, XbfType};
+    ///
+    /// let name = XbfType::from(XbfPrimitive::String("Saphira".to_string()));
+    /// assert_eq!(name.as_str(), Ok("Saphira"));
+    ///
+    /// let age = XbfType::from(XbfPrimitive::U16(16));
+    /// assert_eq!(
+    ///     age.as_str(),
+    ///     Err(FromXbfError::TypeMismatch {
+    ///         expected: "String".into(),
+    ///         actual: XbfPrimitiveMetadata::U16.into(),
+    ///     })
+    /// );
+    /// ```
+    pub fn as_str(&self) -> Result<&str, FromXbfError> {
+        match self {
+            XbfType::Primitive(XbfPrimitive::String(x)) => Ok(x),
+            other => Err(FromXbfError::type_mismatch("String", other)),
+        }
+    }
+
+    /// Returns the inner byte slice if this is a [`XbfPrimitive::Bytes`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FromXbfError::TypeMismatch`] if this is not bytes.
+    pub fn as_bytes(&self) -> Result<&[u8], FromXbfError> {
+        match self {
+            XbfType::Primitive(XbfPrimitive::Bytes(x)) => Ok(x),
+            other => Err(FromXbfError::type_mismatch("Bytes", other)),
         }
     }
 }
 
+macro_rules! impl_primitive_accessors {
+    ($($fn_name:ident, $xbf_type:tt, $ty:ty);+ $(;)?) => {
+        impl XbfType {
+            $(
+                #[doc = concat!("Returns the inner value if this is a [`XbfPrimitive::", stringify!($xbf_type), "`].")]
+                ///
+                /// # Errors
+                ///
+                #[doc = concat!("Returns [`FromXbfError::TypeMismatch`] if this is not a ", stringify!($xbf_type), ".")]
+                pub fn $fn_name(&self) -> Result<$ty, FromXbfError> {
+                    match self {
+                        XbfType::Primitive(XbfPrimitive::$xbf_type(x)) => Ok(*x),
+                        other => Err(FromXbfError::type_mismatch(stringify!($xbf_type), other)),
+                    }
+                }
+            )+
+        }
+    };
+}
+
+impl_primitive_accessors! {
+    as_bool, Bool, bool;
+    as_u8, U8, u8;
+    as_u16, U16, u16;
+    as_u32, U32, u32;
+    as_u64, U64, u64;
+    as_u128, U128, u128;
+    as_u256, U256, XbfU256;
+    as_i8, I8, i8;
+    as_i16, I16, i16;
+    as_i32, I32, i32;
+    as_i64, I64, i64;
+    as_i128, I128, i128;
+    as_i256, I256, XbfI256;
+    as_f32, F32, f32;
+    as_f64, F64, f64;
+    as_timestamp, Timestamp, XbfTimestamp;
+    as_timestamp_tz, TimestampTz, XbfTimestampTz;
+    as_date, Date, XbfDate;
+    as_time, Time, XbfTime;
+    as_duration, Duration, XbfDuration;
+    as_decimal, Decimal, XbfDecimal;
+    as_uuid, Uuid, [u8; 16];
+    as_char, Char, char;
+    as_f16, F16, u16;
+    as_bf16, BF16, u16;
+}
+
 impl From<XbfPrimitive> for XbfType {
     fn from(value: XbfPrimitive) -> Self {
         value.into_base_type()
@@ -90,17 +421,82 @@ impl From<&XbfStruct> for XbfType {
     }
 }
 
+impl From<XbfOption> for XbfType {
+    fn from(value: XbfOption) -> Self {
+        value.into_base_type()
+    }
+}
+
+impl From<&XbfOption> for XbfType {
+    fn from(value: &XbfOption) -> Self {
+        value.to_base_type()
+    }
+}
+
+impl From<XbfEnum> for XbfType {
+    fn from(value: XbfEnum) -> Self {
+        value.into_base_type()
+    }
+}
+
+impl From<&XbfEnum> for XbfType {
+    fn from(value: &XbfEnum) -> Self {
+        value.to_base_type()
+    }
+}
+
+impl From<XbfMap> for XbfType {
+    fn from(value: XbfMap) -> Self {
+        value.into_base_type()
+    }
+}
+
+impl From<&XbfMap> for XbfType {
+    fn from(value: &XbfMap) -> Self {
+        value.to_base_type()
+    }
+}
+
+impl From<XbfArray> for XbfType {
+    fn from(value: XbfArray) -> Self {
+        value.into_base_type()
+    }
+}
+
+impl From<&XbfArray> for XbfType {
+    fn from(value: &XbfArray) -> Self {
+        value.to_base_type()
+    }
+}
+
+impl From<XbfTensor> for XbfType {
+    fn from(value: XbfTensor) -> Self {
+        value.into_base_type()
+    }
+}
+
+impl From<&XbfTensor> for XbfType {
+    fn from(value: &XbfTensor) -> Self {
+        value.to_base_type()
+    }
+}
+
 pub trait XbfTypeUpcast: private::Sealed {
     fn into_base_type(self) -> XbfType;
     fn to_base_type(&self) -> XbfType;
 }
 
 mod private {
-    use crate::{XbfPrimitive, XbfStruct, XbfVec};
+    use crate::{XbfArray, XbfEnum, XbfMap, XbfOption, XbfPrimitive, XbfStruct, XbfTensor, XbfVec};
 
     pub trait Sealed {}
 
     impl Sealed for XbfPrimitive {}
     impl Sealed for XbfVec {}
     impl Sealed for XbfStruct {}
+    impl Sealed for XbfOption {}
+    impl Sealed for XbfEnum {}
+    impl Sealed for XbfMap {}
+    impl Sealed for XbfArray {}
+    impl Sealed for XbfTensor {}
 }
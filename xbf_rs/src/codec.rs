@@ -0,0 +1,384 @@
+//! A [`tokio_util::codec`] codec for sending framed XBF messages over a byte stream.
+
+use crate::{DeserializeOptions, XbfError, XbfErrorKind, XbfMetadata, XbfReader, XbfType};
+use bytes::{Buf, BufMut, BytesMut};
+use std::{error::Error, fmt::Display, io};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// The size of the length prefix of each frame.
+const LEN_SIZE: usize = std::mem::size_of::<u64>();
+
+/// A codec which frames XBF values for use with [`tokio_util::codec::Framed`].
+///
+/// Each frame is the length in bytes of the message as a little endian `u64`, followed by the
+/// message. By default a message is the metadata of a value followed by the value itself, so
+/// every frame is self describing. When both sides have agreed on the metadata up front, a codec
+/// created with [`XbfCodec::with_metadata`] sends only the values.
+///
+/// Frames longer than [`max_frame_len`](XbfCodec::max_frame_len) are rejected before any of
+/// their contents are buffered, and the contents of each frame are deserialized according to a
+/// set of [`DeserializeOptions`].
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::prelude::*;
+/// use xbf_rs::{XbfCodec, XbfType};
+/// use bytes::BytesMut;
+/// use tokio_util::codec::{Decoder, Encoder};
+///
+/// let mut codec = XbfCodec::new();
+/// let value = 42u32.into_xbf_primitive().into_base_type();
+///
+/// let mut buf = BytesMut::new();
+/// codec.encode(&value, &mut buf).unwrap();
+///
+/// // a partial frame isn't decoded until the rest of it arrives
+/// let mut partial = buf.split_to(buf.len() - 1);
+/// assert_eq!(codec.decode(&mut partial).unwrap(), None);
+///
+/// partial.unsplit(buf);
+/// assert_eq!(codec.decode(&mut partial).unwrap(), Some(value));
+/// assert!(partial.is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct XbfCodec {
+    metadata: Option<XbfMetadata>,
+    options: DeserializeOptions,
+    max_frame_len: u64,
+}
+
+impl XbfCodec {
+    /// The default maximum length of a frame, 8 MiB.
+    pub const DEFAULT_MAX_FRAME_LEN: u64 = 8 * 1024 * 1024;
+
+    /// Creates a codec which sends the metadata of each value alongside it.
+    pub fn new() -> Self {
+        Self {
+            metadata: None,
+            options: DeserializeOptions::default(),
+            max_frame_len: Self::DEFAULT_MAX_FRAME_LEN,
+        }
+    }
+
+    /// Creates a codec which sends only values described by `metadata`, which both sides must
+    /// have agreed on beforehand.
+    pub fn with_metadata(metadata: XbfMetadata) -> Self {
+        Self {
+            metadata: Some(metadata),
+            ..Self::new()
+        }
+    }
+
+    /// Sets the options used to deserialize the contents of each frame.
+    pub fn options(mut self, options: DeserializeOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sets the maximum length of a frame, not including its length prefix.
+    pub fn max_frame_len(mut self, max: u64) -> Self {
+        self.max_frame_len = max;
+        self
+    }
+
+    /// Returns the agreed metadata of the values this codec sends, if it was created with
+    /// [`XbfCodec::with_metadata`].
+    pub fn metadata(&self) -> Option<&XbfMetadata> {
+        self.metadata.as_ref()
+    }
+
+    fn check_frame_len(&self, len: u64) -> Result<(), XbfCodecError> {
+        if len > self.max_frame_len {
+            return Err(XbfCodecError::FrameTooLong {
+                len,
+                max: self.max_frame_len,
+            });
+        }
+        Ok(())
+    }
+
+    fn decode_frame(&self, frame: &[u8]) -> Result<XbfType, XbfError> {
+        let mut reader = XbfReader::with_options(frame, self.options);
+        let value = match &self.metadata {
+            Some(metadata) => reader.read_value(metadata)?,
+            None => {
+                let metadata = reader.read_metadata()?;
+                reader.read_value(&metadata)?
+            }
+        };
+        if !reader.get_ref().is_empty() {
+            return Err(XbfError::new(
+                XbfErrorKind::TrailingBytes,
+                reader.position(),
+            ));
+        }
+        Ok(value)
+    }
+}
+
+impl Default for XbfCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Decoder for XbfCodec {
+    type Item = XbfType;
+
+    type Error = XbfCodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let Some(len) = src.get(..LEN_SIZE) else {
+            src.reserve(LEN_SIZE - src.len());
+            return Ok(None);
+        };
+        let len = u64::from_le_bytes(len.try_into().expect("exactly LEN_SIZE bytes"));
+        self.check_frame_len(len)?;
+
+        // a frame no longer than the maximum fits in memory, or the maximum is unreasonable
+        let frame_len = LEN_SIZE + len as usize;
+        if src.len() < frame_len {
+            src.reserve(frame_len - src.len());
+            return Ok(None);
+        }
+
+        src.advance(LEN_SIZE);
+        let frame = src.split_to(len as usize);
+        Ok(Some(self.decode_frame(&frame)?))
+    }
+}
+
+impl Encoder<&XbfType> for XbfCodec {
+    type Error = XbfCodecError;
+
+    fn encode(&mut self, item: &XbfType, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let start = dst.len();
+        dst.put_u64_le(0);
+        let mut writer = dst.writer();
+        match &self.metadata {
+            Some(metadata) => {
+                let actual = XbfMetadata::from(item);
+                if actual != *metadata {
+                    writer.into_inner().truncate(start);
+                    return Err(XbfCodecError::MetadataMismatch {
+                        expected: metadata.clone(),
+                        actual,
+                    });
+                }
+            }
+            None => XbfMetadata::from(item).serialize_base_metadata(&mut writer)?,
+        }
+        item.serialize_base_type(&mut writer)?;
+
+        let len = (dst.len() - start - LEN_SIZE) as u64;
+        if let Err(e) = self.check_frame_len(len) {
+            dst.truncate(start);
+            return Err(e);
+        }
+        dst[start..start + LEN_SIZE].copy_from_slice(&len.to_le_bytes());
+        Ok(())
+    }
+}
+
+impl Encoder<XbfType> for XbfCodec {
+    type Error = XbfCodecError;
+
+    fn encode(&mut self, item: XbfType, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        self.encode(&item, dst)
+    }
+}
+
+/// Error type for [`XbfCodec`].
+#[derive(Debug)]
+pub enum XbfCodecError {
+    /// A frame was longer than the codec's maximum frame length.
+    FrameTooLong { len: u64, max: u64 },
+    /// A value being encoded did not match the codec's agreed metadata.
+    MetadataMismatch {
+        expected: XbfMetadata,
+        actual: XbfMetadata,
+    },
+    /// The contents of a frame could not be deserialized.
+    Xbf(XbfError),
+    /// Reading from or writing to the underlying stream failed.
+    Io(io::Error),
+}
+
+impl Display for XbfCodecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            XbfCodecError::FrameTooLong { len, max } => {
+                write!(f, "Frame of length {len} exceeds the maximum of {max}")
+            }
+            XbfCodecError::MetadataMismatch { expected, actual } => {
+                write!(f, "Value is of type {actual:?}, expected {expected:?}")
+            }
+            XbfCodecError::Xbf(e) => write!(f, "{e}"),
+            XbfCodecError::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Error for XbfCodecError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            XbfCodecError::Xbf(e) => Some(e),
+            XbfCodecError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<XbfError> for XbfCodecError {
+    fn from(value: XbfError) -> Self {
+        XbfCodecError::Xbf(value)
+    }
+}
+
+impl From<io::Error> for XbfCodecError {
+    fn from(value: io::Error) -> Self {
+        XbfCodecError::Io(value)
+    }
+}
+
+impl From<XbfCodecError> for io::Error {
+    fn from(value: XbfCodecError) -> Self {
+        match value {
+            XbfCodecError::Xbf(e) => e.into(),
+            XbfCodecError::Io(e) => e,
+            // the metadata in a mismatch is not `Send` without the `xbf_threadsafe` feature
+            other => io::Error::new(io::ErrorKind::InvalidData, other.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        XbfLimit, XbfPrimitive, XbfPrimitiveMetadata, XbfTypeUpcast, XbfVec, XbfVecMetadata,
+    };
+    use futures::{SinkExt, StreamExt};
+    use tokio_util::codec::{FramedRead, FramedWrite};
+
+    fn names() -> XbfType {
+        XbfVec::new(
+            XbfVecMetadata::new(XbfPrimitiveMetadata::String),
+            ["Eragon", "Saphira"].map(|x| XbfPrimitive::String(x.to_string())),
+        )
+        .unwrap()
+        .into_base_type()
+    }
+
+    #[test]
+    fn decode_byte_by_byte_works() {
+        let mut codec = XbfCodec::new();
+        let mut encoded = BytesMut::new();
+        codec.encode(&names(), &mut encoded).unwrap();
+        codec
+            .encode(&XbfPrimitive::U8(42).into_base_type(), &mut encoded)
+            .unwrap();
+
+        let mut src = BytesMut::new();
+        let mut decoded = vec![];
+        for byte in encoded {
+            src.put_u8(byte);
+            decoded.extend(codec.decode(&mut src).unwrap());
+        }
+
+        assert_eq!(decoded, [names(), XbfPrimitive::U8(42).into_base_type()]);
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn agreed_metadata_works() {
+        let metadata = XbfMetadata::from(&names());
+        let mut codec = XbfCodec::with_metadata(metadata);
+
+        let mut buf = BytesMut::new();
+        codec.encode(&names(), &mut buf).unwrap();
+        assert_eq!(codec.decode(&mut buf).unwrap(), Some(names()));
+
+        let err = codec
+            .encode(&XbfPrimitive::U8(42).into_base_type(), &mut buf)
+            .unwrap_err();
+        assert!(matches!(err, XbfCodecError::MetadataMismatch { .. }));
+        assert!(buf.is_empty());
+
+        let message = err.to_string();
+        let err = io::Error::from(err);
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), message);
+    }
+
+    #[test]
+    fn limits_work() {
+        let mut codec = XbfCodec::new().max_frame_len(16);
+        let mut buf = BytesMut::new();
+        let err = codec.encode(&names(), &mut buf).unwrap_err();
+        assert!(matches!(err, XbfCodecError::FrameTooLong { max: 16, .. }));
+        assert!(buf.is_empty());
+
+        // the length is checked before waiting for the rest of the frame
+        buf.put_u64_le(u64::MAX);
+        let err = codec.decode(&mut buf).unwrap_err();
+        assert!(matches!(err, XbfCodecError::FrameTooLong { .. }));
+
+        let options = DeserializeOptions::new().max_bytes_len(6);
+        let mut codec = XbfCodec::new().options(options);
+        let mut buf = BytesMut::new();
+        codec.encode(&names(), &mut buf).unwrap();
+        let err = codec.decode(&mut buf).unwrap_err();
+        assert!(matches!(
+            err,
+            XbfCodecError::Xbf(e) if matches!(
+                e.kind(),
+                XbfErrorKind::LimitExceeded { limit: XbfLimit::BytesLength, .. }
+            )
+        ));
+    }
+
+    #[test]
+    fn trailing_bytes_in_frame_fail() {
+        let mut codec = XbfCodec::with_metadata(XbfPrimitiveMetadata::U8.into());
+        let mut buf = BytesMut::new();
+        buf.put_u64_le(2);
+        buf.put_slice(&[1, 2]);
+
+        let err = codec.decode(&mut buf).unwrap_err();
+        assert!(matches!(
+            err,
+            XbfCodecError::Xbf(e) if matches!(e.kind(), XbfErrorKind::TrailingBytes)
+        ));
+    }
+
+    #[test]
+    fn framed_works() {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(async {
+                let (client, server) = tokio::io::duplex(8);
+                let mut sink = FramedWrite::new(client, XbfCodec::new());
+                let mut stream = FramedRead::new(server, XbfCodec::new());
+
+                let send = async {
+                    for _ in 0..3 {
+                        sink.send(names()).await.unwrap();
+                    }
+                    SinkExt::<XbfType>::close(&mut sink).await.unwrap();
+                };
+                let receive = async {
+                    let mut received = vec![];
+                    while let Some(value) = stream.next().await {
+                        received.push(value.unwrap());
+                    }
+                    received
+                };
+
+                let ((), received) = tokio::join!(send, receive);
+                assert_eq!(received, [names(), names(), names()]);
+            });
+    }
+}
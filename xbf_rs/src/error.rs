@@ -0,0 +1,342 @@
+//! The error type returned when deserializing XBF data.
+
+use crate::{XbfFingerprint, XbfMetadata, XbfPrimitiveMetadata};
+use std::{
+    error::Error,
+    fmt::{self, Display, Write},
+    io,
+};
+
+/// An error that occurred while deserializing XBF data.
+///
+/// Alongside the [`kind`](XbfError::kind) of error, this records the byte offset into the input
+/// at which the failing read started, and the path through the value being deserialized that
+/// leads to the failure, such as `DragonRider[2].name`.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::{XbfErrorKind, XbfPrimitiveMetadata, XbfType, XbfVecMetadata};
+///
+/// let metadata = XbfVecMetadata::new(XbfPrimitiveMetadata::String).into();
+///
+/// let mut data = vec![];
+/// data.extend_from_slice(&1u64.to_le_bytes());
+/// data.extend_from_slice(&2u64.to_le_bytes());
+/// data.extend_from_slice(&[0xff, 0xff]);
+///
+/// let err = XbfType::deserialize_base_type(&metadata, &mut data.as_slice()).unwrap_err();
+///
+/// assert!(matches!(err.kind(), XbfErrorKind::InvalidUtf8));
+/// assert_eq!(err.offset(), 8);
+/// assert_eq!(err.path(), "String[0]");
+/// ```
+#[derive(Debug)]
+pub struct XbfError {
+    kind: XbfErrorKind,
+    offset: u64,
+    root: Option<Box<str>>,
+    // stored innermost first, as segments are added while the error propagates outwards
+    segments: Vec<PathSegment>,
+}
+
+/// The kind of an [`XbfError`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum XbfErrorKind {
+    /// The input ended before the value was fully read.
+    UnexpectedEof,
+    /// A string was not valid UTF-8.
+    InvalidUtf8,
+    /// A metadata discriminant did not correspond to any known type.
+    UnknownDiscriminant(u8),
+    /// In strict mode, struct metadata contained the same field name more than once.
+    DuplicateField(Box<str>),
+    /// Enum metadata contained the same variant name more than once.
+    DuplicateVariant(Box<str>),
+    /// An enum value had a variant index which its metadata does not define.
+    UnknownVariant(u16),
+    /// In strict mode, a map contained the same key more than once.
+    DuplicateKey,
+    /// In strict mode, a boolean had a value other than 0 or 1.
+    InvalidBool(u8),
+    /// A char was not a valid Unicode scalar value.
+    InvalidChar(u32),
+    /// In strict mode, a time of day was 86,401 seconds or more, the length of a day ending in a
+    /// leap second.
+    InvalidTime(u64),
+    /// Tensor metadata had an element type without a fixed size.
+    InvalidTensorElement(XbfPrimitiveMetadata),
+    /// In strict mode, a struct name was empty or contained control characters.
+    InvalidStructName(Box<str>),
+    /// In strict mode, input remained after the top level value was read.
+    TrailingBytes,
+    /// A struct being seeked into has no field with the given name.
+    UnknownField(Box<str>),
+    /// A vector being seeked into has fewer elements than the given index.
+    IndexOutOfBounds {
+        /// The index being seeked to.
+        index: u64,
+        /// The length of the vector.
+        len: u64,
+    },
+    /// A record in a session or handshake had an unknown tag.
+    UnknownRecordTag(u8),
+    /// A session value referenced a schema ID which has not been defined.
+    UnknownSchema(u32),
+    /// A session defined the same schema ID more than once.
+    DuplicateSchema(u32),
+    /// A value referenced metadata by a fingerprint which is not cached.
+    UnknownFingerprint(XbfFingerprint),
+    /// One of the limits set in [`DeserializeOptions`](crate::DeserializeOptions) was exceeded.
+    LimitExceeded {
+        /// The limit which was exceeded.
+        limit: XbfLimit,
+        /// The value which exceeded the limit.
+        actual: u64,
+        /// The maximum the limit allows.
+        max: u64,
+    },
+    /// Any other error returned by the underlying reader.
+    Io(io::Error),
+}
+
+/// A limit which can be set in [`DeserializeOptions`](crate::DeserializeOptions).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum XbfLimit {
+    /// The length of a string or bytes.
+    BytesLength,
+    /// The number of elements in a vector or array, or entries in a map.
+    VecLength,
+    /// The nesting depth of vectors and structs.
+    Depth,
+    /// The number of fields in struct metadata, or variants in enum metadata.
+    StructFields,
+    /// The total number of bytes read.
+    TotalBytes,
+}
+
+#[derive(Debug)]
+enum PathSegment {
+    Field(Box<str>),
+    Index(u64),
+}
+
+impl XbfError {
+    pub(crate) fn new(kind: XbfErrorKind, offset: u64) -> Self {
+        Self {
+            kind,
+            offset,
+            root: None,
+            segments: vec![],
+        }
+    }
+
+    pub(crate) fn from_io(error: io::Error, offset: u64) -> Self {
+        let kind = match error.kind() {
+            io::ErrorKind::UnexpectedEof => XbfErrorKind::UnexpectedEof,
+            _ => XbfErrorKind::Io(error),
+        };
+        Self::new(kind, offset)
+    }
+
+    /// Records that this error occurred within the given field of a struct.
+    pub(crate) fn in_field(mut self, name: &str) -> Self {
+        self.segments.push(PathSegment::Field(name.into()));
+        self
+    }
+
+    /// Records that this error occurred within the given element of a vector.
+    pub(crate) fn at_index(mut self, index: u64) -> Self {
+        self.segments.push(PathSegment::Index(index));
+        self
+    }
+
+    /// Records the type of the outermost value being deserialized when this error occurred.
+    ///
+    /// Vectors, arrays, options and maps are labelled by the type they contain, so that the path of an error in
+    /// a vector of structs reads like `DragonRider[2].name`.
+    pub(crate) fn in_root(mut self, metadata: &XbfMetadata) -> Self {
+        let mut metadata = metadata;
+        loop {
+            metadata = match metadata {
+                XbfMetadata::Vec(x) => &x.inner_type,
+                XbfMetadata::Option(x) => &x.inner_type,
+                XbfMetadata::Map(x) => &x.value_type,
+                XbfMetadata::Array(x) => &x.inner_type,
+                _ => break,
+            };
+        }
+        self.root = Some(match metadata {
+            XbfMetadata::Primitive(x) => format!("{x:?}").into(),
+            XbfMetadata::Tensor(x) => format!("{:?}", x.element_type).into(),
+            XbfMetadata::Struct(x) => x.name().into(),
+            XbfMetadata::Enum(x) => x.name().into(),
+            XbfMetadata::Vec(_)
+            | XbfMetadata::Option(_)
+            | XbfMetadata::Map(_)
+            | XbfMetadata::Array(_) => {
+                unreachable!("vectors, options, maps and arrays were unwrapped above")
+            }
+        });
+        self
+    }
+
+    /// Returns the kind of error that occurred.
+    pub fn kind(&self) -> &XbfErrorKind {
+        &self.kind
+    }
+
+    /// Consumes the error, returning its kind.
+    pub fn into_kind(self) -> XbfErrorKind {
+        self.kind
+    }
+
+    /// Returns the byte offset into the input at which the failing read started.
+    ///
+    /// The offset is relative to the position of the reader when deserialization began.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the path to the value which failed to deserialize, such as
+    /// `DragonRider[2].name`.
+    ///
+    /// The path is empty if the error occurred in the outermost value of metadata or a primitive.
+    pub fn path(&self) -> String {
+        let mut path = self.root.as_deref().unwrap_or_default().to_string();
+        for segment in self.segments.iter().rev() {
+            match segment {
+                PathSegment::Field(name) if path.is_empty() => path.push_str(name),
+                PathSegment::Field(name) => write!(path, ".{name}").expect("infallible"),
+                PathSegment::Index(i) => write!(path, "[{i}]").expect("infallible"),
+            }
+        }
+        path
+    }
+}
+
+impl Display for XbfErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XbfErrorKind::UnexpectedEof => write!(f, "Unexpected end of input"),
+            XbfErrorKind::InvalidUtf8 => write!(f, "Invalid utf8"),
+            XbfErrorKind::UnknownDiscriminant(x) => {
+                write!(f, "Unknown metadata discriminant {x}")
+            }
+            XbfErrorKind::DuplicateField(name) => write!(f, "Duplicate field {name}"),
+            XbfErrorKind::DuplicateVariant(name) => write!(f, "Duplicate variant {name}"),
+            XbfErrorKind::UnknownVariant(x) => write!(f, "Unknown variant index {x}"),
+            XbfErrorKind::DuplicateKey => write!(f, "Duplicate map key"),
+            XbfErrorKind::InvalidBool(x) => write!(f, "Invalid bool {x}"),
+            XbfErrorKind::InvalidChar(x) => write!(f, "Invalid char {x:#x}"),
+            XbfErrorKind::InvalidTime(x) => write!(f, "Invalid time {x}ns since midnight"),
+            XbfErrorKind::InvalidTensorElement(x) => write!(f, "Invalid tensor element type {x:?}"),
+            XbfErrorKind::InvalidStructName(name) => write!(f, "Invalid struct name {name:?}"),
+            XbfErrorKind::TrailingBytes => write!(f, "Trailing bytes after value"),
+            XbfErrorKind::UnknownField(name) => write!(f, "Unknown field {name}"),
+            XbfErrorKind::IndexOutOfBounds { index, len } => {
+                write!(f, "Index {index} is out of bounds for length {len}")
+            }
+            XbfErrorKind::UnknownRecordTag(x) => write!(f, "Unknown record tag {x}"),
+            XbfErrorKind::UnknownSchema(x) => write!(f, "Unknown schema ID {x}"),
+            XbfErrorKind::DuplicateSchema(x) => write!(f, "Duplicate schema ID {x}"),
+            XbfErrorKind::UnknownFingerprint(x) => write!(f, "Unknown metadata fingerprint {x}"),
+            XbfErrorKind::LimitExceeded { limit, actual, max } => {
+                write!(f, "{limit} of {actual} exceeds the maximum of {max}")
+            }
+            XbfErrorKind::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Display for XbfLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XbfLimit::BytesLength => write!(f, "Length"),
+            XbfLimit::VecLength => write!(f, "Vec length"),
+            XbfLimit::Depth => write!(f, "Nesting depth"),
+            XbfLimit::StructFields => write!(f, "Struct field count"),
+            XbfLimit::TotalBytes => write!(f, "Total bytes read"),
+        }
+    }
+}
+
+impl Display for XbfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.kind, self.offset)?;
+        let path = self.path();
+        if !path.is_empty() {
+            write!(f, " in {path}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for XbfError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            XbfErrorKind::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<XbfError> for io::Error {
+    fn from(value: XbfError) -> Self {
+        let kind = match value.kind() {
+            XbfErrorKind::UnexpectedEof => io::ErrorKind::UnexpectedEof,
+            XbfErrorKind::Io(e) => e.kind(),
+            _ => io::ErrorKind::InvalidData,
+        };
+        io::Error::new(kind, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{XbfPrimitiveMetadata, XbfStructMetadata, XbfVecMetadata};
+    use indexmap::indexmap;
+
+    #[test]
+    fn path_works() {
+        let metadata = XbfVecMetadata::new(XbfStructMetadata::new(
+            "DragonRider",
+            indexmap! {"name" => XbfPrimitiveMetadata::String.into()},
+        ));
+        let err = XbfError::new(XbfErrorKind::InvalidUtf8, 42)
+            .in_field("name")
+            .at_index(2)
+            .in_root(&metadata.into());
+
+        assert_eq!(err.path(), "DragonRider[2].name");
+        assert_eq!(
+            err.to_string(),
+            "Invalid utf8 at offset 42 in DragonRider[2].name"
+        );
+    }
+
+    #[test]
+    fn path_without_root_works() {
+        let err = XbfError::new(XbfErrorKind::UnexpectedEof, 0)
+            .in_field("b")
+            .in_field("a");
+
+        assert_eq!(err.path(), "a.b");
+        assert_eq!(
+            XbfError::new(XbfErrorKind::UnexpectedEof, 0).to_string(),
+            "Unexpected end of input at offset 0"
+        );
+    }
+
+    #[test]
+    fn into_io_error_works() {
+        let err = io::Error::from(XbfError::new(XbfErrorKind::UnexpectedEof, 0));
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        let err = io::Error::from(XbfError::new(XbfErrorKind::InvalidUtf8, 0));
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
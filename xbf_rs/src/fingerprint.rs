@@ -0,0 +1,206 @@
+//! Stable fingerprints of metadata, and a handshake for omitting metadata the receiver already
+//! knows.
+
+mod handshake;
+
+pub use handshake::*;
+
+use crate::{
+    XbfArrayMetadata, XbfEnumMetadata, XbfMapMetadata, XbfMetadata, XbfOptionMetadata,
+    XbfStructMetadata, XbfTensorMetadata, XbfVecMetadata,
+};
+use std::{
+    fmt::{self, Display},
+    io::{self, Write},
+};
+
+/// A 128-bit fingerprint identifying a piece of metadata.
+///
+/// The fingerprint is the 128-bit FNV-1a hash of the metadata serialized as defined by the XBF
+/// specification, so it is the same across platforms, versions of this crate and
+/// implementations of XBF in other languages. Two pieces of metadata have the same fingerprint
+/// exactly when they serialize to the same bytes, barring hash collisions.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::{XbfMetadata, XbfPrimitiveMetadata, XbfVecMetadata};
+///
+/// let metadata = XbfVecMetadata::new(XbfPrimitiveMetadata::String);
+///
+/// assert_eq!(
+///     metadata.fingerprint(),
+///     XbfMetadata::from(metadata.clone()).fingerprint()
+/// );
+/// assert_ne!(
+///     metadata.fingerprint(),
+///     XbfVecMetadata::new(XbfPrimitiveMetadata::Bytes).fingerprint()
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct XbfFingerprint(u128);
+
+impl XbfFingerprint {
+    /// Creates a fingerprint from its integer representation.
+    pub const fn from_u128(value: u128) -> Self {
+        Self(value)
+    }
+
+    /// Returns the integer representation of this fingerprint.
+    pub const fn as_u128(self) -> u128 {
+        self.0
+    }
+}
+
+impl Display for XbfFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:032x}", self.0)
+    }
+}
+
+/// A writer which computes the 128-bit FNV-1a hash of everything written to it.
+struct Fnv1a(u128);
+
+impl Fnv1a {
+    const OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+}
+
+impl Write for Fnv1a {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for &byte in buf {
+            self.0 ^= u128::from(byte);
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn fingerprint_of(serialize: impl FnOnce(&mut Fnv1a) -> io::Result<()>) -> XbfFingerprint {
+    let mut hasher = Fnv1a::new();
+    serialize(&mut hasher).expect("hashing never fails");
+    XbfFingerprint(hasher.0)
+}
+
+impl XbfMetadata {
+    /// Returns the [`XbfFingerprint`] of this metadata.
+    pub fn fingerprint(&self) -> XbfFingerprint {
+        fingerprint_of(|hasher| self.serialize_base_metadata(hasher))
+    }
+}
+
+impl XbfVecMetadata {
+    /// Returns the [`XbfFingerprint`] of this metadata, which is the same as that of the
+    /// equivalent [`XbfMetadata`].
+    pub fn fingerprint(&self) -> XbfFingerprint {
+        fingerprint_of(|hasher| self.serialize_vec_metadata(hasher))
+    }
+}
+
+impl XbfStructMetadata {
+    /// Returns the [`XbfFingerprint`] of this metadata, which is the same as that of the
+    /// equivalent [`XbfMetadata`].
+    pub fn fingerprint(&self) -> XbfFingerprint {
+        fingerprint_of(|hasher| self.serialize_struct_metadata(hasher))
+    }
+}
+
+impl XbfOptionMetadata {
+    /// Returns the [`XbfFingerprint`] of this metadata, which is the same as that of the
+    /// equivalent [`XbfMetadata`].
+    pub fn fingerprint(&self) -> XbfFingerprint {
+        fingerprint_of(|hasher| self.serialize_option_metadata(hasher))
+    }
+}
+
+impl XbfEnumMetadata {
+    /// Returns the [`XbfFingerprint`] of this metadata, which is the same as that of the
+    /// equivalent [`XbfMetadata`].
+    pub fn fingerprint(&self) -> XbfFingerprint {
+        fingerprint_of(|hasher| self.serialize_enum_metadata(hasher))
+    }
+}
+
+impl XbfMapMetadata {
+    /// Returns the [`XbfFingerprint`] of this metadata, which is the same as that of the
+    /// equivalent [`XbfMetadata`].
+    pub fn fingerprint(&self) -> XbfFingerprint {
+        fingerprint_of(|hasher| self.serialize_map_metadata(hasher))
+    }
+}
+
+impl XbfArrayMetadata {
+    /// Returns the [`XbfFingerprint`] of this metadata, which is the same as that of the
+    /// equivalent [`XbfMetadata`].
+    pub fn fingerprint(&self) -> XbfFingerprint {
+        fingerprint_of(|hasher| self.serialize_array_metadata(hasher))
+    }
+}
+
+impl XbfTensorMetadata {
+    /// Returns the [`XbfFingerprint`] of this metadata, which is the same as that of the
+    /// equivalent [`XbfMetadata`].
+    pub fn fingerprint(&self) -> XbfFingerprint {
+        fingerprint_of(|hasher| self.serialize_tensor_metadata(hasher))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::XbfPrimitiveMetadata;
+    use indexmap::indexmap;
+
+    #[test]
+    fn fnv1a_matches_reference() {
+        // reference values for the 128-bit FNV-1a hash
+        assert_eq!(fingerprint_of(|_| Ok(())).as_u128(), Fnv1a::OFFSET_BASIS);
+        assert_eq!(
+            fingerprint_of(|x| x.write_all(b"a")).as_u128(),
+            0xd228cb696f1a8caf78912b704e4a8964
+        );
+    }
+
+    #[test]
+    fn fingerprint_is_stable() {
+        // a vector of strings serializes as [17, 16], as it has since the first version of XBF
+        let metadata = XbfVecMetadata::new(XbfPrimitiveMetadata::String);
+        assert_eq!(
+            metadata.fingerprint().as_u128(),
+            0x0880948029ab1be95aa07330553dda8c
+        );
+    }
+
+    #[test]
+    fn fingerprint_depends_on_field_order() {
+        let metadata = XbfStructMetadata::new(
+            "DragonRider",
+            indexmap! {
+                "name" => XbfPrimitiveMetadata::String.into(),
+                "age" => XbfPrimitiveMetadata::U16.into(),
+            },
+        );
+        let reordered = XbfStructMetadata::new(
+            "DragonRider",
+            indexmap! {
+                "age" => XbfPrimitiveMetadata::U16.into(),
+                "name" => XbfPrimitiveMetadata::String.into(),
+            },
+        );
+
+        assert_eq!(metadata.fingerprint(), metadata.clone().fingerprint());
+        assert_eq!(
+            metadata.fingerprint(),
+            XbfMetadata::from(metadata.clone()).fingerprint()
+        );
+        assert_ne!(metadata.fingerprint(), reordered.fingerprint());
+    }
+}
@@ -0,0 +1,250 @@
+use crate::{XbfError, XbfErrorKind, XbfFingerprint, XbfMetadata, XbfReader, XbfType};
+use byteorder::{LittleEndian, WriteBytesExt};
+use std::{
+    collections::{HashMap, HashSet},
+    io::{self, Read, Write},
+};
+
+/// The tag of a value preceded by its metadata.
+const METADATA_TAG: u8 = 0;
+/// The tag of a value preceded by the fingerprint of metadata the receiver already knows.
+const FINGERPRINT_TAG: u8 = 1;
+
+/// The receiving side of a fingerprint handshake, which caches metadata by fingerprint.
+///
+/// The receiver begins by sending the fingerprints of all the metadata it already knows with
+/// [`XbfSchemaCache::write_advertisement`]. The sender reads these into an
+/// [`XbfSchemaAdvertisement`], and from then on sends only the fingerprint of any metadata the
+/// receiver knows in place of the metadata itself. Metadata the receiver did not know is cached
+/// as it is received, so it can be advertised in later handshakes.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::prelude::*;
+/// use xbf_rs::{XbfReader, XbfSchemaAdvertisement, XbfSchemaCache};
+///
+/// let value = 42u32.into_xbf_primitive().into_base_type();
+///
+/// // the receiver already knows the metadata of the value
+/// let mut cache = XbfSchemaCache::new();
+/// cache.insert((&value).into());
+/// let mut advertisement = vec![];
+/// cache.write_advertisement(&mut advertisement).unwrap();
+///
+/// // so the sender omits it
+/// let mut sender =
+///     XbfSchemaAdvertisement::read(&mut XbfReader::new(advertisement.as_slice())).unwrap();
+/// let mut data = vec![];
+/// sender.write_value(&value, &mut data).unwrap();
+/// assert_eq!(data.len(), 1 + 16 + 4);
+///
+/// let received = cache.read_value(&mut XbfReader::new(data.as_slice())).unwrap();
+/// assert_eq!(received, value);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct XbfSchemaCache {
+    schemas: HashMap<XbfFingerprint, XbfMetadata>,
+}
+
+impl XbfSchemaCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds metadata to the cache, returning its fingerprint.
+    pub fn insert(&mut self, metadata: XbfMetadata) -> XbfFingerprint {
+        let fingerprint = metadata.fingerprint();
+        self.schemas.insert(fingerprint, metadata);
+        fingerprint
+    }
+
+    /// Returns the metadata with the given fingerprint, if it is cached.
+    pub fn get(&self, fingerprint: XbfFingerprint) -> Option<&XbfMetadata> {
+        self.schemas.get(&fingerprint)
+    }
+
+    /// Returns the number of cached schemas.
+    pub fn len(&self) -> usize {
+        self.schemas.len()
+    }
+
+    /// Returns `true` if no schemas are cached.
+    pub fn is_empty(&self) -> bool {
+        self.schemas.is_empty()
+    }
+
+    /// Returns an iterator over the fingerprints of every cached schema.
+    pub fn fingerprints(&self) -> impl ExactSizeIterator<Item = XbfFingerprint> + '_ {
+        self.schemas.keys().copied()
+    }
+
+    /// Writes the fingerprint of every cached schema, to be read by the sender as an
+    /// [`XbfSchemaAdvertisement`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::Error`] if writing to the underlying writer fails.
+    pub fn write_advertisement(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_u64::<LittleEndian>(self.schemas.len() as u64)?;
+        for fingerprint in self.fingerprints() {
+            writer.write_u128::<LittleEndian>(fingerprint.as_u128())?;
+        }
+        Ok(())
+    }
+
+    /// Reads a value written by [`XbfSchemaAdvertisement::write_value`], caching its metadata
+    /// if it was sent.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails, the data is invalid, the value references a
+    /// fingerprint which is not cached, or a limit is exceeded.
+    pub fn read_value<R: Read>(&mut self, reader: &mut XbfReader<R>) -> Result<XbfType, XbfError> {
+        let offset = reader.position();
+        match reader.read_u8()? {
+            METADATA_TAG => {
+                let metadata = reader.read_metadata()?;
+                let value = reader.read_value(&metadata)?;
+                self.insert(metadata);
+                Ok(value)
+            }
+            FINGERPRINT_TAG => {
+                let fingerprint = XbfFingerprint::from_u128(reader.read_u128()?);
+                let Some(metadata) = self.get(fingerprint) else {
+                    return Err(XbfError::new(
+                        XbfErrorKind::UnknownFingerprint(fingerprint),
+                        offset,
+                    ));
+                };
+                reader.read_value(metadata)
+            }
+            tag => Err(XbfError::new(XbfErrorKind::UnknownRecordTag(tag), offset)),
+        }
+    }
+}
+
+/// The sending side of a fingerprint handshake, which tracks the metadata the receiver knows.
+///
+/// See [`XbfSchemaCache`] for a description of the handshake.
+#[derive(Debug, Clone, Default)]
+pub struct XbfSchemaAdvertisement {
+    known: HashSet<XbfFingerprint>,
+}
+
+impl XbfSchemaAdvertisement {
+    /// Creates an advertisement of the given fingerprints, for when the receiver's schemas are
+    /// known without a handshake.
+    pub fn new(known: impl IntoIterator<Item = XbfFingerprint>) -> Self {
+        Self {
+            known: known.into_iter().collect(),
+        }
+    }
+
+    /// Reads an advertisement written by [`XbfSchemaCache::write_advertisement`].
+    ///
+    /// The number of fingerprints is limited by the reader's
+    /// [`max_vec_len`](crate::DeserializeOptions::max_vec_len).
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails or a limit is exceeded.
+    pub fn read<R: Read>(reader: &mut XbfReader<R>) -> Result<Self, XbfError> {
+        let len = reader.read_vec_len()?;
+        let mut known = HashSet::new();
+        for _ in 0..len {
+            known.insert(XbfFingerprint::from_u128(reader.read_u128()?));
+        }
+        Ok(Self { known })
+    }
+
+    /// Returns `true` if the receiver knows the metadata with the given fingerprint.
+    pub fn contains(&self, fingerprint: XbfFingerprint) -> bool {
+        self.known.contains(&fingerprint)
+    }
+
+    /// Writes a value, sending only the fingerprint of its metadata if the receiver knows it.
+    ///
+    /// Once metadata has been sent, the receiver is assumed to have cached it, so later values
+    /// with the same metadata are sent with only its fingerprint.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::Error`] if writing to the underlying writer fails.
+    pub fn write_value(&mut self, value: &XbfType, writer: &mut impl Write) -> io::Result<()> {
+        let metadata = XbfMetadata::from(value);
+        let fingerprint = metadata.fingerprint();
+        if self.contains(fingerprint) {
+            writer.write_u8(FINGERPRINT_TAG)?;
+            writer.write_u128::<LittleEndian>(fingerprint.as_u128())?;
+        } else {
+            writer.write_u8(METADATA_TAG)?;
+            metadata.serialize_base_metadata(writer)?;
+            self.known.insert(fingerprint);
+        }
+        value.serialize_base_type(writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DeserializeOptions, XbfLimit, XbfPrimitive, XbfTypeUpcast};
+
+    #[test]
+    fn handshake_caches_new_metadata() {
+        let value = XbfPrimitive::String("Saphira".to_string()).into_base_type();
+
+        let mut cache = XbfSchemaCache::new();
+        let mut advertisement = vec![];
+        cache.write_advertisement(&mut advertisement).unwrap();
+        let mut sender =
+            XbfSchemaAdvertisement::read(&mut XbfReader::new(advertisement.as_slice())).unwrap();
+
+        let mut data = vec![];
+        sender.write_value(&value, &mut data).unwrap();
+        sender.write_value(&value, &mut data).unwrap();
+        assert_eq!(data[0], METADATA_TAG);
+
+        let mut reader = XbfReader::new(data.as_slice());
+        assert_eq!(cache.read_value(&mut reader).unwrap(), value);
+        assert_eq!(cache.read_value(&mut reader).unwrap(), value);
+        assert!(reader.into_inner().is_empty());
+
+        let fingerprint = XbfMetadata::from(&value).fingerprint();
+        assert_eq!(cache.fingerprints().collect::<Vec<_>>(), [fingerprint]);
+    }
+
+    #[test]
+    fn unknown_fingerprint_fails() {
+        let value = XbfPrimitive::U8(42).into_base_type();
+        let mut sender = XbfSchemaAdvertisement::new([XbfMetadata::from(&value).fingerprint()]);
+        let mut data = vec![];
+        sender.write_value(&value, &mut data).unwrap();
+
+        let err = XbfSchemaCache::new()
+            .read_value(&mut XbfReader::new(data.as_slice()))
+            .unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::UnknownFingerprint(_)));
+        assert_eq!(err.offset(), 0);
+    }
+
+    #[test]
+    fn advertisement_respects_limits() {
+        let mut data = vec![];
+        data.extend_from_slice(&u64::MAX.to_le_bytes());
+        let options = DeserializeOptions::new().max_vec_len(16);
+
+        let err =
+            XbfSchemaAdvertisement::read(&mut XbfReader::with_options(data.as_slice(), options))
+                .unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            XbfErrorKind::LimitExceeded {
+                limit: XbfLimit::VecLength,
+                ..
+            }
+        ));
+    }
+}
@@ -2,22 +2,70 @@
 //!
 //! The format itself is described [here](https://github.com/XtensibleBinaryFormat/XBF/blob/main/docs/specification.md)
 
+#[cfg(feature = "async")]
+mod async_io;
 mod base_metadata;
 mod base_type;
+#[cfg(feature = "codec")]
+mod codec;
+mod error;
+mod fingerprint;
+mod native_type;
+mod projection;
+mod reader;
+mod session;
 mod util;
+mod value_ref;
+mod xbf_array;
+mod xbf_enum;
+mod xbf_map;
+mod xbf_option;
 mod xbf_primitive;
 mod xbf_struct;
+mod xbf_tensor;
 mod xbf_vec;
 
 pub mod prelude;
+#[cfg(feature = "serde")]
+pub mod serde;
 
+#[cfg(feature = "async")]
+pub use async_io::*;
 pub use base_metadata::*;
 pub use base_type::*;
+#[cfg(feature = "codec")]
+pub use codec::*;
+pub use error::*;
+pub use fingerprint::*;
+pub use native_type::*;
+pub use projection::*;
+pub use reader::*;
+pub use session::*;
+pub use value_ref::*;
+pub use xbf_array::*;
+pub use xbf_enum::*;
+pub use xbf_map::*;
+pub use xbf_option::*;
 pub use xbf_primitive::*;
 pub use xbf_struct::*;
+pub use xbf_tensor::*;
 pub use xbf_vec::*;
 
+#[cfg(feature = "derive")]
+pub use xbf_rs_derive::Xbf;
+
+/// Items used by code generated by `#[derive(Xbf)]`, not public API.
+#[doc(hidden)]
+pub mod __private {
+    pub use indexmap::IndexMap;
+}
+
 #[cfg(not(feature = "xbf_threadsafe"))]
 use std::rc::Rc as RcType;
 #[cfg(feature = "xbf_threadsafe")]
 use std::sync::Arc as RcType;
+
+#[cfg(not(feature = "xbf_threadsafe"))]
+use std::cell::OnceCell as OnceType;
+#[cfg(feature = "xbf_threadsafe")]
+use std::sync::OnceLock as OnceType;
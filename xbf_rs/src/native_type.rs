@@ -0,0 +1,696 @@
+//! Conversions between native Rust types and XBF types whose metadata is known statically.
+//!
+//! These traits are what `#[derive(Xbf)]` (enabled with the `derive` feature) implements for
+//! structs, and are implemented here for the native primitives, `Vec<T>`, `[T; N]`, `Option<T>`,
+//! `HashMap<K, V>` and `IndexMap<K, V>`.
+//! [`FromXbf`] is additionally implemented for tuples, and every native type implementing it also
+//! implements `TryFrom<&XbfType>`.
+
+use crate::{
+    NativeToXbfPrimitive, XbfArray, XbfArrayMetadata, XbfDate, XbfDecimal, XbfDuration, XbfI256,
+    XbfMap, XbfMapMetadata, XbfMetadata, XbfOption, XbfOptionMetadata, XbfPrimitive,
+    XbfPrimitiveMetadata, XbfStruct, XbfTime, XbfTimestamp, XbfTimestampTz, XbfType, XbfTypeUpcast,
+    XbfU256, XbfVec, XbfVecMetadata,
+};
+use indexmap::IndexMap;
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::Display,
+    hash::{BuildHasher, Hash},
+};
+
+/// A trait for native Rust types which have a statically known XBF representation.
+///
+/// Unlike [`NativeToXbfPrimitive`], this trait can be implemented outside of this crate, and is
+/// implemented for structs by `#[derive(Xbf)]`.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::prelude::*;
+/// use xbf_rs::{XbfMetadata, XbfPrimitive, XbfPrimitiveMetadata, XbfVecMetadata};
+///
+/// assert_eq!(
+///     Vec::<i32>::xbf_metadata(),
+///     XbfVecMetadata::new(XbfPrimitiveMetadata::I32).into(),
+/// );
+///
+/// let value = vec![1i32, 2, 3].into_xbf_type();
+/// assert_eq!(XbfMetadata::from(&value), Vec::<i32>::xbf_metadata());
+/// ```
+pub trait NativeToXbfType {
+    /// Returns the metadata every value of this type converts to.
+    fn xbf_metadata() -> XbfMetadata;
+
+    /// Convert a native Rust type to an [`XbfType`], consuming `self`.
+    fn into_xbf_type(self) -> XbfType;
+
+    /// Convert a native Rust type to an [`XbfType`].
+    fn to_xbf_type(&self) -> XbfType;
+}
+
+/// A trait for native Rust types which can be extracted from an [`XbfType`].
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::prelude::*;
+/// use xbf_rs::XbfPrimitive;
+///
+/// let value = XbfPrimitive::I32(42).into_base_type();
+///
+/// assert_eq!(i32::from_xbf(&value), Ok(42));
+/// assert!(u64::from_xbf(&value).is_err());
+/// ```
+pub trait FromXbf: Sized {
+    /// Extract a native value from an [`XbfType`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`FromXbfError`] if the value is not of the type expected by `Self`.
+    fn from_xbf(value: &XbfType) -> Result<Self, FromXbfError>;
+
+    /// Extract a native value from the field of an [`XbfStruct`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FromXbfError::MissingField`] if the struct does not have the field, or any error
+    /// [`from_xbf`](FromXbf::from_xbf) returns.
+    fn from_xbf_field(value: &XbfStruct, field_name: &str) -> Result<Self, FromXbfError> {
+        match value.get(field_name) {
+            Some(field) => Self::from_xbf(field),
+            None => Err(FromXbfError::MissingField {
+                struct_name: value.metadata.name().into(),
+                field_name: field_name.into(),
+            }),
+        }
+    }
+}
+
+/// A marker trait for native types which are stored as an [`XbfVec`] when contained in a `Vec`.
+///
+/// This is implemented for every type implementing [`NativeToXbfType`] except for `u8`, as a
+/// `Vec<u8>` is represented as [`XbfPrimitive::Bytes`] instead.
+pub trait XbfVecElement {}
+
+macro_rules! impl_NativeToXbfType {
+    ($ty:ty, $xbf_type:tt) => {
+        impl_NativeToXbfType!($ty, $xbf_type, |x| x.clone());
+    };
+    ($ty:ty, $xbf_type:tt, |$x:ident| $from_xbf:expr) => {
+        impl NativeToXbfType for $ty {
+            fn xbf_metadata() -> XbfMetadata {
+                XbfMetadata::Primitive(XbfPrimitiveMetadata::$xbf_type)
+            }
+
+            fn into_xbf_type(self) -> XbfType {
+                self.into_xbf_primitive().into_base_type()
+            }
+
+            fn to_xbf_type(&self) -> XbfType {
+                self.to_xbf_primitive().into_base_type()
+            }
+        }
+
+        impl FromXbf for $ty {
+            fn from_xbf(value: &XbfType) -> Result<Self, FromXbfError> {
+                match value {
+                    XbfType::Primitive(XbfPrimitive::$xbf_type($x)) => Ok($from_xbf),
+                    other => Err(FromXbfError::type_mismatch(stringify!($xbf_type), other)),
+                }
+            }
+        }
+
+        impl_TryFromXbfType!($ty);
+    };
+}
+
+macro_rules! impl_TryFromXbfType {
+    ($ty:ty $(, $generic:ident)*) => {
+        impl<$($generic: FromXbf),*> TryFrom<&XbfType> for $ty {
+            type Error = FromXbfError;
+
+            fn try_from(value: &XbfType) -> Result<Self, Self::Error> {
+                <$ty>::from_xbf(value)
+            }
+        }
+    };
+}
+
+impl_NativeToXbfType!(bool, Bool);
+impl_NativeToXbfType!(u8, U8);
+impl_NativeToXbfType!(u16, U16);
+impl_NativeToXbfType!(u32, U32);
+impl_NativeToXbfType!(u64, U64);
+impl_NativeToXbfType!(u128, U128);
+impl_NativeToXbfType!(XbfU256, U256);
+impl_NativeToXbfType!(i8, I8);
+impl_NativeToXbfType!(i16, I16);
+impl_NativeToXbfType!(i32, I32);
+impl_NativeToXbfType!(i64, I64);
+impl_NativeToXbfType!(i128, I128);
+impl_NativeToXbfType!(XbfI256, I256);
+impl_NativeToXbfType!(f32, F32);
+impl_NativeToXbfType!(f64, F64);
+impl_NativeToXbfType!(char, Char);
+impl_NativeToXbfType!(Vec<u8>, Bytes);
+impl_NativeToXbfType!(XbfTimestamp, Timestamp);
+impl_NativeToXbfType!(XbfTimestampTz, TimestampTz);
+impl_NativeToXbfType!(XbfDate, Date);
+impl_NativeToXbfType!(XbfTime, Time);
+impl_NativeToXbfType!(XbfDuration, Duration);
+impl_NativeToXbfType!(XbfDecimal, Decimal);
+#[cfg(feature = "uuid")]
+impl_NativeToXbfType!(uuid::Uuid, Uuid, |x| uuid::Uuid::from_bytes(*x));
+#[cfg(feature = "half")]
+impl_NativeToXbfType!(half::f16, F16, |x| half::f16::from_bits(*x));
+#[cfg(feature = "half")]
+impl_NativeToXbfType!(half::bf16, BF16, |x| half::bf16::from_bits(*x));
+#[cfg(feature = "primitive-types")]
+impl_NativeToXbfType!(primitive_types::U256, U256, |x| (*x).into());
+#[cfg(feature = "ethnum")]
+impl_NativeToXbfType!(ethnum::U256, U256, |x| (*x).into());
+#[cfg(feature = "ethnum")]
+impl_NativeToXbfType!(ethnum::I256, I256, |x| (*x).into());
+impl_NativeToXbfType!(String, String);
+
+impl XbfVecElement for bool {}
+impl XbfVecElement for u16 {}
+impl XbfVecElement for u32 {}
+impl XbfVecElement for u64 {}
+impl XbfVecElement for u128 {}
+impl XbfVecElement for XbfU256 {}
+impl XbfVecElement for i8 {}
+impl XbfVecElement for i16 {}
+impl XbfVecElement for i32 {}
+impl XbfVecElement for i64 {}
+impl XbfVecElement for i128 {}
+impl XbfVecElement for XbfI256 {}
+impl XbfVecElement for f32 {}
+impl XbfVecElement for f64 {}
+impl XbfVecElement for char {}
+impl XbfVecElement for String {}
+impl XbfVecElement for Vec<u8> {}
+impl XbfVecElement for XbfTimestamp {}
+impl XbfVecElement for XbfTimestampTz {}
+impl XbfVecElement for XbfDate {}
+impl XbfVecElement for XbfTime {}
+impl XbfVecElement for XbfDuration {}
+impl XbfVecElement for XbfDecimal {}
+#[cfg(feature = "uuid")]
+impl XbfVecElement for uuid::Uuid {}
+#[cfg(feature = "half")]
+impl XbfVecElement for half::f16 {}
+#[cfg(feature = "half")]
+impl XbfVecElement for half::bf16 {}
+#[cfg(feature = "primitive-types")]
+impl XbfVecElement for primitive_types::U256 {}
+#[cfg(feature = "ethnum")]
+impl XbfVecElement for ethnum::U256 {}
+#[cfg(feature = "ethnum")]
+impl XbfVecElement for ethnum::I256 {}
+impl<T: NativeToXbfType + XbfVecElement> XbfVecElement for Vec<T> {}
+impl<T: NativeToXbfType> XbfVecElement for Option<T> {}
+
+impl<T: NativeToXbfType + XbfVecElement> NativeToXbfType for Vec<T> {
+    fn xbf_metadata() -> XbfMetadata {
+        XbfVecMetadata::new(T::xbf_metadata()).into()
+    }
+
+    fn into_xbf_type(self) -> XbfType {
+        let metadata = XbfVecMetadata::new(T::xbf_metadata());
+        XbfVec::new_unchecked(metadata, self.into_iter().map(T::into_xbf_type)).into_base_type()
+    }
+
+    fn to_xbf_type(&self) -> XbfType {
+        let metadata = XbfVecMetadata::new(T::xbf_metadata());
+        XbfVec::new_unchecked(metadata, self.iter().map(T::to_xbf_type)).into_base_type()
+    }
+}
+
+impl<T: FromXbf + XbfVecElement> FromXbf for Vec<T> {
+    fn from_xbf(value: &XbfType) -> Result<Self, FromXbfError> {
+        match value {
+            XbfType::Vec(x) => x.values().map(|x| T::from_xbf(&x)).collect(),
+            other => Err(FromXbfError::type_mismatch("Vec", other)),
+        }
+    }
+}
+
+impl<T: FromXbf + XbfVecElement> TryFrom<&XbfType> for Vec<T> {
+    type Error = FromXbfError;
+
+    fn try_from(value: &XbfType) -> Result<Self, Self::Error> {
+        Self::from_xbf(value)
+    }
+}
+
+impl<T: NativeToXbfType, const N: usize> XbfVecElement for [T; N] {}
+
+/// Fixed size arrays are stored as an [`XbfArray`], including arrays of `u8`.
+impl<T: NativeToXbfType, const N: usize> NativeToXbfType for [T; N] {
+    fn xbf_metadata() -> XbfMetadata {
+        XbfArrayMetadata::new(T::xbf_metadata(), N as u64).into()
+    }
+
+    fn into_xbf_type(self) -> XbfType {
+        let metadata = XbfArrayMetadata::new(T::xbf_metadata(), N as u64);
+        XbfArray::new_unchecked(metadata, self.into_iter().map(T::into_xbf_type)).into_base_type()
+    }
+
+    fn to_xbf_type(&self) -> XbfType {
+        let metadata = XbfArrayMetadata::new(T::xbf_metadata(), N as u64);
+        XbfArray::new_unchecked(metadata, self.iter().map(T::to_xbf_type)).into_base_type()
+    }
+}
+
+impl<T: FromXbf, const N: usize> FromXbf for [T; N] {
+    fn from_xbf(value: &XbfType) -> Result<Self, FromXbfError> {
+        match value {
+            XbfType::Array(x) if x.len() == N => {
+                let elements = x.iter().map(T::from_xbf).collect::<Result<Vec<_>, _>>()?;
+                Ok(elements
+                    .try_into()
+                    .unwrap_or_else(|_| unreachable!("length was checked")))
+            }
+            other => Err(FromXbfError::type_mismatch(
+                &format!("array of {N} elements"),
+                other,
+            )),
+        }
+    }
+}
+
+impl<T: FromXbf, const N: usize> TryFrom<&XbfType> for [T; N] {
+    type Error = FromXbfError;
+
+    fn try_from(value: &XbfType) -> Result<Self, Self::Error> {
+        Self::from_xbf(value)
+    }
+}
+
+impl<T: NativeToXbfType> NativeToXbfType for Option<T> {
+    fn xbf_metadata() -> XbfMetadata {
+        XbfOptionMetadata::new(T::xbf_metadata()).into()
+    }
+
+    fn into_xbf_type(self) -> XbfType {
+        XbfOption::from(self).into_base_type()
+    }
+
+    fn to_xbf_type(&self) -> XbfType {
+        match self {
+            Some(x) => XbfOption::some(x.to_xbf_type()),
+            None => XbfOption::none(T::xbf_metadata()),
+        }
+        .into_base_type()
+    }
+}
+
+/// An `Option<T>` is extracted from an [`XbfOption`], or as `Some` from any other value. It is
+/// also `None` when extracted from a struct field that does not exist.
+impl<T: FromXbf> FromXbf for Option<T> {
+    fn from_xbf(value: &XbfType) -> Result<Self, FromXbfError> {
+        match value {
+            XbfType::Option(x) => x.value().map(T::from_xbf).transpose(),
+            other => T::from_xbf(other).map(Some),
+        }
+    }
+
+    fn from_xbf_field(value: &XbfStruct, field_name: &str) -> Result<Self, FromXbfError> {
+        value
+            .get(field_name)
+            .map(Self::from_xbf)
+            .transpose()
+            .map(Option::flatten)
+    }
+}
+
+impl_TryFromXbfType!(Option<T>, T);
+
+/// Maps are stored as an [`XbfMap`], in the order they iterate in.
+macro_rules! impl_NativeToXbfType_map {
+    ($map:ident) => {
+        impl<K: NativeToXbfType, V: NativeToXbfType, S> NativeToXbfType for $map<K, V, S> {
+            fn xbf_metadata() -> XbfMetadata {
+                XbfMapMetadata::new(K::xbf_metadata(), V::xbf_metadata()).into()
+            }
+
+            fn into_xbf_type(self) -> XbfType {
+                let metadata = XbfMapMetadata::new(K::xbf_metadata(), V::xbf_metadata());
+                let entries = self
+                    .into_iter()
+                    .map(|(k, v)| (k.into_xbf_type(), v.into_xbf_type()));
+                XbfMap::new_unchecked(metadata, entries).into_base_type()
+            }
+
+            fn to_xbf_type(&self) -> XbfType {
+                let metadata = XbfMapMetadata::new(K::xbf_metadata(), V::xbf_metadata());
+                let entries = self.iter().map(|(k, v)| (k.to_xbf_type(), v.to_xbf_type()));
+                XbfMap::new_unchecked(metadata, entries).into_base_type()
+            }
+        }
+
+        impl<K, V, S> FromXbf for $map<K, V, S>
+        where
+            K: FromXbf + Hash + Eq,
+            V: FromXbf,
+            S: BuildHasher + Default,
+        {
+            fn from_xbf(value: &XbfType) -> Result<Self, FromXbfError> {
+                match value {
+                    XbfType::Map(x) => x
+                        .iter()
+                        .map(|(k, v)| Ok((K::from_xbf(k)?, V::from_xbf(v)?)))
+                        .collect(),
+                    other => Err(FromXbfError::type_mismatch("Map", other)),
+                }
+            }
+        }
+
+        impl<K, V, S> TryFrom<&XbfType> for $map<K, V, S>
+        where
+            K: FromXbf + Hash + Eq,
+            V: FromXbf,
+            S: BuildHasher + Default,
+        {
+            type Error = FromXbfError;
+
+            fn try_from(value: &XbfType) -> Result<Self, Self::Error> {
+                Self::from_xbf(value)
+            }
+        }
+
+        impl<K: NativeToXbfType, V: NativeToXbfType, S> XbfVecElement for $map<K, V, S> {}
+    };
+}
+
+impl_NativeToXbfType_map!(HashMap);
+impl_NativeToXbfType_map!(IndexMap);
+
+/// Tuples are extracted from a struct with exactly as many fields as the tuple has elements, in
+/// order. This matches how tuples are written by the `serde` feature, whose fields are named `0`,
+/// `1`, etc, but the names of the fields are not checked.
+macro_rules! impl_FromXbf_tuple {
+    ($len:literal => $($name:ident),+) => {
+        impl<$($name: FromXbf),+> FromXbf for ($($name,)+) {
+            fn from_xbf(value: &XbfType) -> Result<Self, FromXbfError> {
+                match value {
+                    XbfType::Struct(x) if x.fields.len() == $len => {
+                        let mut fields = x.fields.iter();
+                        Ok(($($name::from_xbf(fields.next().expect("length was checked"))?,)+))
+                    }
+                    other => Err(FromXbfError::type_mismatch(
+                        concat!("struct with ", $len, " fields"),
+                        other,
+                    )),
+                }
+            }
+        }
+
+        impl_TryFromXbfType!(($($name,)+) $(, $name)+);
+    };
+}
+
+impl_FromXbf_tuple!(1 => A);
+impl_FromXbf_tuple!(2 => A, B);
+impl_FromXbf_tuple!(3 => A, B, C);
+impl_FromXbf_tuple!(4 => A, B, C, D);
+impl_FromXbf_tuple!(5 => A, B, C, D, E);
+impl_FromXbf_tuple!(6 => A, B, C, D, E, F);
+impl_FromXbf_tuple!(7 => A, B, C, D, E, F, G);
+impl_FromXbf_tuple!(8 => A, B, C, D, E, F, G, H);
+
+/// Error type for [`FromXbf`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FromXbfError {
+    /// The value was not of the expected type.
+    TypeMismatch {
+        expected: Box<str>,
+        actual: XbfMetadata,
+    },
+    /// The struct did not contain the requested field.
+    MissingField {
+        struct_name: Box<str>,
+        field_name: Box<str>,
+    },
+}
+
+impl FromXbfError {
+    pub(crate) fn type_mismatch(expected: &str, actual: impl Into<XbfMetadata>) -> Self {
+        FromXbfError::TypeMismatch {
+            expected: expected.into(),
+            actual: actual.into(),
+        }
+    }
+}
+
+impl Display for FromXbfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FromXbfError::TypeMismatch { expected, actual } => {
+                write!(f, "Value is of type {actual:?}, expected {expected}")
+            }
+            FromXbfError::MissingField {
+                struct_name,
+                field_name,
+            } => write!(f, "Struct {struct_name} has no field {field_name}"),
+        }
+    }
+}
+
+impl Error for FromXbfError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::XbfStructMetadata;
+    use indexmap::indexmap;
+
+    #[test]
+    fn primitive_round_trip_works() {
+        macro_rules! round_trip {
+            ($ty:ty, $xbf_type:tt, $val:expr) => {
+                let native: $ty = $val;
+                let value = native.to_xbf_type();
+                assert_eq!(value, XbfPrimitive::$xbf_type(native.clone()).into());
+                assert_eq!(XbfMetadata::from(&value), <$ty>::xbf_metadata());
+                assert_eq!(<$ty>::from_xbf(&value), Ok(native.clone()));
+                assert_eq!(native.into_xbf_type(), value);
+            };
+        }
+
+        round_trip!(bool, Bool, true);
+        round_trip!(u8, U8, 1);
+        round_trip!(u16, U16, 2);
+        round_trip!(u32, U32, 3);
+        round_trip!(u64, U64, 4);
+        round_trip!(u128, U128, 5);
+        round_trip!(i8, I8, -1);
+        round_trip!(i16, I16, -2);
+        round_trip!(i32, I32, -3);
+        round_trip!(i64, I64, -4);
+        round_trip!(i128, I128, -5);
+        round_trip!(f32, F32, 1.5);
+        round_trip!(f64, F64, 2.5);
+        round_trip!(Vec<u8>, Bytes, vec![1, 2, 3]);
+        round_trip!(String, String, "hello".to_string());
+        round_trip!(char, Char, 'x');
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn uuid_round_trip_works() {
+        let native = uuid::Uuid::from_u128(0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8);
+        let value = native.to_xbf_type();
+        assert_eq!(value, XbfPrimitive::Uuid(native.into_bytes()).into());
+        assert_eq!(uuid::Uuid::from_xbf(&value), Ok(native));
+    }
+
+    #[cfg(feature = "half")]
+    #[test]
+    fn half_round_trip_works() {
+        let native = half::f16::from_f32(1.5);
+        let value = native.to_xbf_type();
+        assert_eq!(value, XbfPrimitive::F16(0x3e00).into());
+        assert_eq!(half::f16::from_xbf(&value), Ok(native));
+
+        let native = half::bf16::from_f32(1.5);
+        let value = native.to_xbf_type();
+        assert_eq!(value, XbfPrimitive::BF16(0x3fc0).into());
+        assert_eq!(half::bf16::from_xbf(&value), Ok(native));
+    }
+
+    #[test]
+    fn vec_round_trip_works() {
+        let native = vec![vec![1i32, 2], vec![3]];
+        let value = native.to_xbf_type();
+
+        assert_eq!(
+            Vec::<Vec<i32>>::xbf_metadata(),
+            XbfVecMetadata::new(XbfVecMetadata::new(XbfPrimitiveMetadata::I32)).into()
+        );
+        assert_eq!(XbfMetadata::from(&value), Vec::<Vec<i32>>::xbf_metadata());
+        assert_eq!(Vec::<Vec<i32>>::from_xbf(&value), Ok(native));
+    }
+
+    #[test]
+    fn type_mismatch_fails() {
+        let value = XbfPrimitive::I32(42).into_base_type();
+
+        assert_eq!(
+            String::from_xbf(&value),
+            Err(FromXbfError::TypeMismatch {
+                expected: "String".into(),
+                actual: XbfPrimitiveMetadata::I32.into(),
+            })
+        );
+        assert!(Vec::<i32>::from_xbf(&value).is_err());
+    }
+
+    #[test]
+    fn try_from_works() {
+        let value = XbfPrimitive::String("hello".to_string()).into_base_type();
+
+        assert_eq!(String::try_from(&value), Ok("hello".to_string()));
+        assert_eq!(
+            Option::<String>::try_from(&value),
+            Ok(Some("hello".to_string()))
+        );
+        assert!(i32::try_from(&value).is_err());
+        assert!(Vec::<String>::try_from(&value).is_err());
+    }
+
+    #[test]
+    fn option_from_xbf_field_works() {
+        let s = XbfStruct::new(
+            XbfStructMetadata::new("test", indexmap! {"a" => XbfPrimitiveMetadata::I32.into()}),
+            [XbfPrimitive::I32(42).into()],
+        )
+        .unwrap();
+
+        assert_eq!(Option::<i32>::from_xbf_field(&s, "a"), Ok(Some(42)));
+        assert_eq!(Option::<i32>::from_xbf_field(&s, "b"), Ok(None));
+        assert!(Option::<String>::from_xbf_field(&s, "a").is_err());
+    }
+
+    #[test]
+    fn option_round_trip_works() {
+        let values = vec![Some(1i32), None];
+        let value = values.to_xbf_type();
+
+        assert_eq!(
+            XbfMetadata::from(&value),
+            XbfVecMetadata::new(XbfOptionMetadata::new(XbfPrimitiveMetadata::I32)).into()
+        );
+        assert_eq!(Vec::<Option<i32>>::from_xbf(&value), Ok(values));
+
+        let s = XbfStruct::new(
+            XbfStructMetadata::new("test", indexmap! {"a" => Option::<i32>::xbf_metadata()}),
+            [None::<i32>.into()],
+        )
+        .unwrap();
+        assert_eq!(Option::<i32>::from_xbf_field(&s, "a"), Ok(None));
+    }
+
+    #[test]
+    fn map_round_trip_works() {
+        let native = IndexMap::from([(3u8, "Murtagh".to_string()), (1, "Eragon".to_string())]);
+        let value = native.to_xbf_type();
+
+        assert_eq!(
+            XbfMetadata::from(&value),
+            XbfMapMetadata::new(XbfPrimitiveMetadata::U8, XbfPrimitiveMetadata::String).into()
+        );
+        assert_eq!(
+            XbfMetadata::from(&value),
+            HashMap::<u8, String>::xbf_metadata()
+        );
+
+        let deserialized = IndexMap::<u8, String>::from_xbf(&value).unwrap();
+        assert!(deserialized.keys().eq(&[3, 1]));
+        assert_eq!(
+            HashMap::<u8, String>::try_from(&value),
+            Ok(native.into_iter().collect())
+        );
+        assert!(HashMap::<u8, u8>::from_xbf(&value).is_err());
+    }
+
+    #[test]
+    fn array_round_trip_works() {
+        let native = [[1.0f32, 2.0, 3.0], [4.0, 5.0, 6.0]];
+        let value = native.to_xbf_type();
+
+        assert_eq!(
+            XbfMetadata::from(&value),
+            XbfArrayMetadata::new(XbfArrayMetadata::new(XbfPrimitiveMetadata::F32, 3), 2).into()
+        );
+        assert_eq!(<[[f32; 3]; 2]>::from_xbf(&value), Ok(native));
+        assert!(<[[f32; 3]; 3]>::from_xbf(&value).is_err());
+        assert!(<[[f32; 2]; 2]>::from_xbf(&value).is_err());
+
+        let hash = [7u8; 32].into_xbf_type();
+        assert_eq!(
+            XbfMetadata::from(&hash),
+            XbfArrayMetadata::new(XbfPrimitiveMetadata::U8, 32).into()
+        );
+        assert_eq!(<[u8; 32]>::try_from(&hash), Ok([7; 32]));
+    }
+
+    #[test]
+    fn tuple_from_xbf_works() {
+        let value = XbfStruct::new(
+            XbfStructMetadata::new(
+                "Tuple",
+                indexmap! {
+                    "0" => XbfPrimitiveMetadata::I32.into(),
+                    "1" => XbfPrimitiveMetadata::String.into(),
+                },
+            ),
+            [
+                XbfPrimitive::I32(42).into(),
+                XbfPrimitive::String("hello".to_string()).into(),
+            ],
+        )
+        .unwrap()
+        .into_base_type();
+
+        assert_eq!(
+            <(i32, String)>::try_from(&value),
+            Ok((42, "hello".to_string()))
+        );
+        assert!(<(i32,)>::from_xbf(&value).is_err());
+        assert!(<(i32, String, bool)>::from_xbf(&value).is_err());
+        assert!(<(String, i32)>::from_xbf(&value).is_err());
+    }
+
+    #[test]
+    fn from_xbf_field_works() {
+        let s = XbfStruct::new(
+            XbfStructMetadata::new("test", indexmap! {"a" => XbfPrimitiveMetadata::I32.into()}),
+            [XbfPrimitive::I32(42).into()],
+        )
+        .unwrap();
+
+        assert_eq!(i32::from_xbf_field(&s, "a"), Ok(42));
+        assert_eq!(
+            i32::from_xbf_field(&s, "b"),
+            Err(FromXbfError::MissingField {
+                struct_name: "test".into(),
+                field_name: "b".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn reading_packed_vec_keeps_it_packed() {
+        let value = vec![1i32, -2, 3].into_xbf_type();
+        assert_eq!(Vec::<i32>::from_xbf(&value), Ok(vec![1, -2, 3]));
+        assert_eq!(
+            value.as_vec().unwrap().as_i32_slice(),
+            Some([1, -2, 3].as_slice())
+        );
+        assert!(!value.as_vec().unwrap().has_unpacked());
+    }
+}
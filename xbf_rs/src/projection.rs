@@ -0,0 +1,307 @@
+//! Selecting a subset of the fields of a value while deserializing it.
+
+use crate::{
+    XbfArrayMetadata, XbfError, XbfErrorKind, XbfMetadata, XbfOptionMetadata, XbfStructMetadata,
+    XbfVecMetadata,
+};
+use indexmap::IndexMap;
+
+/// A set of field paths selecting which parts of a value to deserialize.
+///
+/// A path is a sequence of field names separated by dots, such as `rider.name`. Vectors, arrays
+/// and options are transparent to paths, so a path applied to a vector selects that field of every
+/// element. Enums and maps can only be selected as a whole.
+///
+/// Deserializing with a projection produces values whose struct metadata contains only the
+/// selected fields, in the order they appear in the original metadata. Fields which are not
+/// selected are skipped over on the wire without being deserialized.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::{XbfPrimitiveMetadata, XbfProjection, XbfStruct, XbfStructMetadata};
+/// use indexmap::indexmap;
+///
+/// let metadata = XbfStructMetadata::new(
+///     "StockRecord",
+///     indexmap! {
+///         "symbol" => XbfPrimitiveMetadata::String.into(),
+///         "volume" => XbfPrimitiveMetadata::U64.into(),
+///         "close" => XbfPrimitiveMetadata::F64.into(),
+///     },
+/// );
+///
+/// let mut data = vec![];
+/// data.extend_from_slice(&4u64.to_le_bytes());
+/// data.extend_from_slice(b"AAPL");
+/// data.extend_from_slice(&1000u64.to_le_bytes());
+/// data.extend_from_slice(&189.5f64.to_le_bytes());
+///
+/// let projection = XbfProjection::new(["close", "symbol"]);
+/// let record =
+///     XbfStruct::deserialize_struct_type_projected(&metadata, &projection, &mut data.as_slice())
+///         .unwrap();
+///
+/// let metadata = record.get_metadata();
+/// let fields = metadata.fields().map(|(name, _)| name).collect::<Vec<_>>();
+/// assert_eq!(fields, ["symbol", "close"]);
+/// assert_eq!(record.get("close").unwrap().as_f64().unwrap(), 189.5);
+/// assert_eq!(record.get("volume"), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XbfProjection {
+    // `None` selects the whole value
+    fields: Option<IndexMap<Box<str>, XbfProjection>>,
+}
+
+impl XbfProjection {
+    /// Creates a projection selecting the given field paths.
+    ///
+    /// If both a field and one of its subfields are selected, the whole field is selected. A
+    /// projection with no paths selects the whole value.
+    pub fn new(paths: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        let mut projection = Self { fields: None };
+        for path in paths {
+            let fields = projection.fields.get_or_insert_with(IndexMap::new);
+            Self::insert(fields, path.as_ref());
+        }
+        projection
+    }
+
+    /// Creates a projection selecting the whole value.
+    pub fn all() -> Self {
+        Self { fields: None }
+    }
+
+    /// Returns `true` if this projection selects the whole value.
+    pub fn is_all(&self) -> bool {
+        self.fields.is_none()
+    }
+
+    fn insert(fields: &mut IndexMap<Box<str>, XbfProjection>, path: &str) {
+        let (name, rest) = match path.split_once('.') {
+            Some((name, rest)) => (name, Some(rest)),
+            None => (path, None),
+        };
+        let Some(rest) = rest else {
+            fields.insert(name.into(), Self::all());
+            return;
+        };
+        let field = fields.entry(name.into()).or_insert_with(|| XbfProjection {
+            fields: Some(IndexMap::new()),
+        });
+        // a field which is already selected as a whole stays that way
+        if let Some(fields) = &mut field.fields {
+            Self::insert(fields, rest);
+        }
+    }
+
+    /// Returns the projection of the given field of a struct, or `None` if it is not selected.
+    ///
+    /// Must only be called on a projection which does not select the whole value.
+    pub(crate) fn field(&self, name: &str) -> Option<&XbfProjection> {
+        self.fields
+            .as_ref()
+            .expect("only called when selecting part of a value")
+            .get(name)
+    }
+
+    /// Returns the metadata of values deserialized with this projection, checking that every
+    /// selected field exists.
+    ///
+    /// Any error is reported at `offset`, the position at which deserialization is starting.
+    pub(crate) fn project(
+        &self,
+        metadata: &XbfMetadata,
+        offset: u64,
+    ) -> Result<XbfMetadata, XbfError> {
+        match metadata {
+            _ if self.is_all() => Ok(metadata.clone()),
+            XbfMetadata::Vec(x) => self.project_vec(x, offset).map(Into::into),
+            XbfMetadata::Struct(x) => self.project_struct(x, offset).map(Into::into),
+            XbfMetadata::Option(x) => self
+                .project(&x.inner_type, offset)
+                .map(|x| XbfOptionMetadata::new(x).into()),
+            XbfMetadata::Array(x) => self
+                .project(&x.inner_type, offset)
+                .map(|inner_type| XbfArrayMetadata::new(inner_type, x.len).into()),
+            XbfMetadata::Primitive(_)
+            | XbfMetadata::Enum(_)
+            | XbfMetadata::Map(_)
+            | XbfMetadata::Tensor(_) => {
+                let name = self.fields.iter().flat_map(|x| x.keys()).next();
+                let name = name.expect("non empty when selecting part of a value");
+                Err(XbfError::new(
+                    XbfErrorKind::UnknownField(name.clone()),
+                    offset,
+                ))
+            }
+        }
+    }
+
+    pub(crate) fn project_vec(
+        &self,
+        metadata: &XbfVecMetadata,
+        offset: u64,
+    ) -> Result<XbfVecMetadata, XbfError> {
+        self.project(&metadata.inner_type, offset)
+            .map(XbfVecMetadata::new)
+    }
+
+    pub(crate) fn project_struct(
+        &self,
+        metadata: &XbfStructMetadata,
+        offset: u64,
+    ) -> Result<XbfStructMetadata, XbfError> {
+        let Some(selected) = &self.fields else {
+            return Ok(metadata.clone());
+        };
+        if let Some(name) = selected
+            .keys()
+            .find(|x| metadata.get_field_type(x).is_none())
+        {
+            return Err(XbfError::new(
+                XbfErrorKind::UnknownField(name.clone()),
+                offset,
+            ));
+        }
+        let mut fields = IndexMap::with_capacity(selected.len());
+        for (name, field_type) in metadata.fields() {
+            if let Some(projection) = selected.get(name) {
+                let field_type = projection
+                    .project(field_type, offset)
+                    .map_err(|e| e.in_field(name))?;
+                fields.insert(name, field_type);
+            }
+        }
+        Ok(XbfStructMetadata::new(metadata.name(), fields))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        XbfPrimitive, XbfPrimitiveMetadata, XbfReader, XbfStruct, XbfType, XbfTypeUpcast, XbfVec,
+    };
+    use indexmap::indexmap;
+
+    fn dragon_metadata() -> XbfStructMetadata {
+        XbfStructMetadata::new(
+            "Dragon",
+            indexmap! {
+                "name" => XbfPrimitiveMetadata::String.into(),
+                "age" => XbfPrimitiveMetadata::U16.into(),
+            },
+        )
+    }
+
+    fn rider_metadata() -> XbfStructMetadata {
+        XbfStructMetadata::new(
+            "DragonRider",
+            indexmap! {
+                "name" => XbfPrimitiveMetadata::String.into(),
+                "dragon" => dragon_metadata().into(),
+                "titles" => XbfVecMetadata::new(XbfPrimitiveMetadata::String).into(),
+            },
+        )
+    }
+
+    fn rider(name: &str, dragon: &str) -> XbfStruct {
+        XbfStruct::new(
+            rider_metadata(),
+            [
+                XbfType::from(XbfPrimitive::String(name.to_string())),
+                XbfStruct::new(
+                    dragon_metadata(),
+                    [
+                        XbfPrimitive::String(dragon.to_string()).into(),
+                        XbfPrimitive::U16(100).into(),
+                    ],
+                )
+                .unwrap()
+                .into(),
+                XbfVec::new(
+                    XbfVecMetadata::new(XbfPrimitiveMetadata::String),
+                    [XbfPrimitive::String("Shadeslayer".to_string())],
+                )
+                .unwrap()
+                .into(),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn new_merges_paths() {
+        let projection = XbfProjection::new(["dragon.name", "dragon", "name.first"]);
+        assert!(projection.field("dragon").unwrap().is_all());
+        assert!(!projection.field("name").unwrap().is_all());
+        assert!(XbfProjection::new([""; 0]).is_all());
+    }
+
+    #[test]
+    fn projected_vec_of_structs_works() {
+        let value = XbfVec::new(
+            XbfVecMetadata::new(rider_metadata()),
+            [rider("Eragon", "Saphira"), rider("Murtagh", "Thorn")],
+        )
+        .unwrap()
+        .into_base_type();
+        let mut data = vec![];
+        value.serialize_base_type(&mut data).unwrap();
+        data.push(42);
+
+        let projection = XbfProjection::new(["dragon.name"]);
+        let mut reader = XbfReader::new(data.as_slice());
+        let projected = reader
+            .read_value_projected(&(&value).into(), &projection)
+            .unwrap();
+        assert_eq!(reader.into_inner(), [42]);
+
+        let expected_dragon = XbfStructMetadata::new(
+            "Dragon",
+            indexmap! {"name" => XbfPrimitiveMetadata::String.into()},
+        );
+        let expected_rider = XbfStructMetadata::new(
+            "DragonRider",
+            indexmap! {"dragon" => expected_dragon.into()},
+        );
+        assert_eq!(
+            XbfMetadata::from(&projected),
+            XbfVecMetadata::new(expected_rider).into()
+        );
+
+        let riders = projected.as_vec().unwrap();
+        let thorn = riders[1].as_struct().unwrap().get("dragon").unwrap();
+        assert_eq!(
+            thorn.as_struct().unwrap().get("name").unwrap().as_str(),
+            Ok("Thorn")
+        );
+    }
+
+    #[test]
+    fn unknown_field_fails() {
+        let projection = XbfProjection::new(["dragon.colour"]);
+        let err = XbfType::deserialize_base_type_projected(
+            &XbfVecMetadata::new(rider_metadata()).into(),
+            &projection,
+            &mut [].as_slice(),
+        )
+        .unwrap_err();
+
+        assert!(matches!(err.kind(), XbfErrorKind::UnknownField(x) if &**x == "colour"));
+        assert_eq!(err.path(), "DragonRider.dragon");
+
+        let projection = XbfProjection::new(["name.first"]);
+        let err = XbfStruct::deserialize_struct_type_projected(
+            &rider_metadata(),
+            &projection,
+            &mut [].as_slice(),
+        )
+        .unwrap_err();
+
+        assert!(matches!(err.kind(), XbfErrorKind::UnknownField(x) if &**x == "first"));
+        assert_eq!(err.path(), "DragonRider.name");
+    }
+}
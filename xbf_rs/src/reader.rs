@@ -0,0 +1,735 @@
+//! A reader for deserializing XBF data with configurable limits.
+
+mod deserialize_options;
+mod skip;
+
+pub use deserialize_options::*;
+
+use crate::{
+    util::read_string, xbf_tensor::element_count, XbfEnumMetadata, XbfError, XbfErrorKind,
+    XbfLimit, XbfMetadata, XbfProjection, XbfTime, XbfType,
+};
+use byteorder::{LittleEndian, ReadBytesExt};
+use std::io::{self, Read};
+
+/// The most memory that will be allocated up front for a vector, string or bytes, regardless of
+/// the length it claims to have. Anything longer grows as its data is actually read.
+pub(crate) const MAX_PREALLOCATION: usize = 64 * 1024;
+
+/// A reader which deserializes XBF metadata and values according to a set of
+/// [`DeserializeOptions`].
+///
+/// The reader keeps track of how many bytes it has read, which is used to report the offset of
+/// any errors and to enforce [`max_total_bytes`](DeserializeOptions::max_total_bytes) across
+/// every metadata and value read from it.
+///
+/// [`XbfMetadata::deserialize_base_metadata`] and [`XbfType::deserialize_base_type`] are
+/// shorthands for reading from an [`XbfReader`] with no limits.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::prelude::*;
+/// use xbf_rs::{DeserializeOptions, XbfMetadata, XbfReader};
+///
+/// let value = 42i32.into_xbf_primitive().into_base_type();
+///
+/// let mut data = vec![];
+/// XbfMetadata::from(&value).serialize_base_metadata(&mut data).unwrap();
+/// value.serialize_base_type(&mut data).unwrap();
+///
+/// let options = DeserializeOptions::new().max_total_bytes(1024);
+/// let mut reader = XbfReader::with_options(data.as_slice(), options);
+///
+/// let metadata = reader.read_metadata().unwrap();
+/// assert_eq!(reader.read_value(&metadata).unwrap(), value);
+/// assert_eq!(reader.position(), 5);
+/// ```
+#[derive(Debug)]
+pub struct XbfReader<R> {
+    inner: R,
+    options: DeserializeOptions,
+    position: u64,
+    depth: u32,
+    charged: u64,
+    budget_exceeded: bool,
+}
+
+macro_rules! read_fns {
+    ($($fn_name:ident => $ty:ty),+ $(,)?) => {
+        $(
+            pub(crate) fn $fn_name(&mut self) -> Result<$ty, XbfError> {
+                self.read_with(|r| ReadBytesExt::$fn_name::<LittleEndian>(r))
+            }
+        )+
+    };
+}
+
+impl<R: Read> XbfReader<R> {
+    /// Creates a new reader with no limits.
+    pub fn new(inner: R) -> Self {
+        Self::with_options(inner, DeserializeOptions::default())
+    }
+
+    /// Creates a new reader which enforces the given options.
+    pub fn with_options(inner: R, options: DeserializeOptions) -> Self {
+        Self::at_position(inner, options, 0, 0)
+    }
+
+    /// Creates a reader whose input starts `position` bytes into a larger input, so that offsets
+    /// and [`max_total_bytes`](DeserializeOptions::max_total_bytes) account for what came before,
+    /// including the elements already `charged` by [`Self::charge_elements`].
+    pub(crate) fn at_position(
+        inner: R,
+        options: DeserializeOptions,
+        position: u64,
+        charged: u64,
+    ) -> Self {
+        Self {
+            inner,
+            options,
+            position,
+            depth: 0,
+            charged,
+            budget_exceeded: false,
+        }
+    }
+
+    /// Returns the options this reader is enforcing.
+    pub fn options(&self) -> &DeserializeOptions {
+        &self.options
+    }
+
+    /// Returns the number of bytes read so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Consumes this reader, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Reads metadata, as with [`XbfMetadata::deserialize_base_metadata`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails, the metadata is invalid, or a limit is
+    /// exceeded.
+    pub fn read_metadata(&mut self) -> Result<XbfMetadata, XbfError> {
+        XbfMetadata::read_base_metadata(self)
+    }
+
+    /// Reads a value described by `metadata`, as with [`XbfType::deserialize_base_type`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails, the data is invalid, or a limit is exceeded.
+    pub fn read_value(&mut self, metadata: &XbfMetadata) -> Result<XbfType, XbfError> {
+        XbfType::read_base_type(metadata, self).map_err(|e| e.in_root(metadata))
+    }
+
+    /// Reads the parts of a value selected by `projection`, as with
+    /// [`XbfType::deserialize_base_type_projected`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails, the data is invalid, a limit is exceeded, or
+    /// the projection selects a field which does not exist.
+    pub fn read_value_projected(
+        &mut self,
+        metadata: &XbfMetadata,
+        projection: &XbfProjection,
+    ) -> Result<XbfType, XbfError> {
+        projection
+            .project(metadata, self.position)
+            .and_then(|projected| XbfType::read_projected(metadata, &projected, projection, self))
+            .map_err(|e| e.in_root(metadata))
+    }
+
+    /// Finishes reading a top level value, returning the underlying reader.
+    ///
+    /// # Errors
+    ///
+    /// In [strict](DeserializeOptions::strict) mode, returns an [`XbfError`] if the input has
+    /// not been exhausted. Otherwise this never fails, and any remaining input is left unread.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use xbf_rs::{DeserializeOptions, XbfErrorKind, XbfPrimitiveMetadata, XbfReader};
+    ///
+    /// let options = DeserializeOptions::new().strict(true);
+    /// let mut reader = XbfReader::with_options([1u8, 2].as_slice(), options);
+    ///
+    /// reader.read_value(&XbfPrimitiveMetadata::U8.into()).unwrap();
+    /// let err = reader.finish().unwrap_err();
+    ///
+    /// assert!(matches!(err.kind(), XbfErrorKind::TrailingBytes));
+    /// assert_eq!(err.offset(), 1);
+    /// ```
+    pub fn finish(mut self) -> Result<R, XbfError> {
+        if self.options.strict {
+            let offset = self.position;
+            let mut buf = [0];
+            let n = self.read_with(|r| loop {
+                match r.inner.read(&mut buf) {
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    result => break result,
+                }
+            })?;
+            if n != 0 {
+                return Err(XbfError::new(XbfErrorKind::TrailingBytes, offset));
+            }
+        }
+        Ok(self.inner)
+    }
+
+    /// Runs a read against this reader, attributing any error to the position it started at.
+    fn read_with<T>(&mut self, f: impl FnOnce(&mut Self) -> io::Result<T>) -> Result<T, XbfError> {
+        let offset = self.position;
+        f(self).map_err(|e| {
+            if self.budget_exceeded {
+                self.limit_exceeded(XbfLimit::TotalBytes, self.consumed() + 1, offset)
+            } else {
+                XbfError::from_io(e, offset)
+            }
+        })
+    }
+
+    /// Returns the number of bytes charged for elements which encode to no bytes at all.
+    #[cfg(feature = "async")]
+    pub(crate) fn charged(&self) -> u64 {
+        self.charged
+    }
+
+    /// Returns how much of [`max_total_bytes`](DeserializeOptions::max_total_bytes) has been
+    /// used up, counting both the bytes read and the elements charged by
+    /// [`Self::charge_elements`].
+    pub(crate) fn consumed(&self) -> u64 {
+        self.position.saturating_add(self.charged)
+    }
+
+    fn limit_exceeded(&self, limit: XbfLimit, actual: u64, offset: u64) -> XbfError {
+        let max = match limit {
+            XbfLimit::BytesLength => self.options.max_bytes_len,
+            XbfLimit::VecLength => self.options.max_vec_len,
+            XbfLimit::Depth => self.options.max_depth.map(u64::from),
+            XbfLimit::StructFields => self.options.max_struct_fields.map(u64::from),
+            XbfLimit::TotalBytes => self.options.max_total_bytes,
+        };
+        XbfError::new(
+            XbfErrorKind::LimitExceeded {
+                limit,
+                actual,
+                max: max.expect("only exceeded limits are reported"),
+            },
+            offset,
+        )
+    }
+
+    /// Checks a length read at `offset` against one of the limits.
+    fn check_len(
+        &self,
+        limit: XbfLimit,
+        max: Option<u64>,
+        len: u64,
+        offset: u64,
+    ) -> Result<(), XbfError> {
+        match max {
+            Some(max) if len > max => Err(self.limit_exceeded(limit, len, offset)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Reads the length of a string or bytes and checks it against the limits.
+    pub(crate) fn read_bytes_len(&mut self) -> Result<u64, XbfError> {
+        let offset = self.position;
+        let len = self.read_u64()?;
+        self.check_len(
+            XbfLimit::BytesLength,
+            self.options.max_bytes_len,
+            len,
+            offset,
+        )?;
+        let total = self.consumed().saturating_add(len);
+        self.check_len(
+            XbfLimit::TotalBytes,
+            self.options.max_total_bytes,
+            total,
+            offset,
+        )?;
+        Ok(len)
+    }
+
+    /// Reads the length of a vector and checks it against the limits.
+    pub(crate) fn read_vec_len(&mut self) -> Result<u64, XbfError> {
+        let offset = self.position;
+        let len = self.read_u64()?;
+        self.check_len(XbfLimit::VecLength, self.options.max_vec_len, len, offset)?;
+        Ok(len)
+    }
+
+    /// Charges `len` elements of `element_size` bytes against
+    /// [`max_total_bytes`](DeserializeOptions::max_total_bytes) before they are read.
+    ///
+    /// Elements which encode to no bytes at all are charged one byte each, as otherwise a huge
+    /// length would have a reader spin over them without ever running out of input. Any other
+    /// element reads at least a byte of input, so is charged as it is read.
+    pub(crate) fn charge_elements(
+        &mut self,
+        element_size: Option<usize>,
+        len: u64,
+    ) -> Result<(), XbfError> {
+        if element_size != Some(0) {
+            return Ok(());
+        }
+        let total = self.consumed().saturating_add(len);
+        self.check_len(
+            XbfLimit::TotalBytes,
+            self.options.max_total_bytes,
+            total,
+            self.position,
+        )?;
+        self.charged = self.charged.saturating_add(len);
+        Ok(())
+    }
+
+    /// Reads the shape of a tensor and checks its number of elements against the limits.
+    pub(crate) fn read_tensor_shape(&mut self, rank: u8) -> Result<Vec<u64>, XbfError> {
+        let offset = self.position;
+        let shape = (0..rank)
+            .map(|_| self.read_u64())
+            .collect::<Result<Vec<_>, _>>()?;
+        let len = element_count(&shape);
+        self.check_len(XbfLimit::VecLength, self.options.max_vec_len, len, offset)?;
+        Ok(shape)
+    }
+
+    /// Reads the number of fields in struct metadata and checks it against the limits.
+    pub(crate) fn read_struct_fields_len(&mut self) -> Result<u16, XbfError> {
+        let offset = self.position;
+        let len = self.read_u16()?;
+        let max = self.options.max_struct_fields.map(u64::from);
+        self.check_len(XbfLimit::StructFields, max, len.into(), offset)?;
+        Ok(len)
+    }
+
+    /// Runs `f` one level of nesting deeper, checking the depth limit.
+    pub(crate) fn nested<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<T, XbfError>,
+    ) -> Result<T, XbfError> {
+        let depth = self.depth + 1;
+        let max = self.options.max_depth.map(u64::from);
+        self.check_len(XbfLimit::Depth, max, depth.into(), self.position)?;
+        self.depth = depth;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// Reads a boolean, which in strict mode must be exactly 0 or 1.
+    pub(crate) fn read_bool(&mut self) -> Result<bool, XbfError> {
+        let offset = self.position;
+        match self.read_u8()? {
+            x @ 2.. if self.options.strict => {
+                Err(XbfError::new(XbfErrorKind::InvalidBool(x), offset))
+            }
+            x => Ok(x != 0),
+        }
+    }
+
+    /// Reads a time of day, which in strict mode must be less than 86,401 seconds.
+    pub(crate) fn read_time(&mut self) -> Result<XbfTime, XbfError> {
+        let offset = self.position;
+        let time = XbfTime::from_nanos_since_midnight(self.read_u64()?);
+        if self.options.strict && !time.is_in_range() {
+            return Err(XbfError::new(
+                XbfErrorKind::InvalidTime(time.nanos_since_midnight()),
+                offset,
+            ));
+        }
+        Ok(time)
+    }
+
+    /// Reads a char, which must be a valid Unicode scalar value.
+    pub(crate) fn read_char(&mut self) -> Result<char, XbfError> {
+        let offset = self.position;
+        let x = self.read_u32()?;
+        char::from_u32(x).ok_or_else(|| XbfError::new(XbfErrorKind::InvalidChar(x), offset))
+    }
+
+    /// Reads the name of a struct, which in strict mode must be non-empty and contain no control
+    /// characters.
+    pub(crate) fn read_struct_name(&mut self) -> Result<String, XbfError> {
+        let offset = self.position;
+        let name = read_string(self)?;
+        if self.options.strict && (name.is_empty() || name.chars().any(char::is_control)) {
+            return Err(XbfError::new(
+                XbfErrorKind::InvalidStructName(name.into()),
+                offset,
+            ));
+        }
+        Ok(name)
+    }
+
+    /// Reads the index of an enum variant, returning it with the variant's name and metadata.
+    pub(crate) fn read_variant<'m>(
+        &mut self,
+        metadata: &'m XbfEnumMetadata,
+    ) -> Result<(u16, &'m str, &'m XbfMetadata), XbfError> {
+        let offset = self.position;
+        let index = self.read_u16()?;
+        match metadata.get_variant(index) {
+            Some((name, type_)) => Ok((index, name, type_)),
+            None => Err(XbfError::new(XbfErrorKind::UnknownVariant(index), offset)),
+        }
+    }
+
+    pub(crate) fn read_u8(&mut self) -> Result<u8, XbfError> {
+        self.read_with(ReadBytesExt::read_u8)
+    }
+
+    pub(crate) fn read_i8(&mut self) -> Result<i8, XbfError> {
+        self.read_with(ReadBytesExt::read_i8)
+    }
+
+    read_fns! {
+        read_u16 => u16,
+        read_u32 => u32,
+        read_u64 => u64,
+        read_u128 => u128,
+        read_i16 => i16,
+        read_i32 => i32,
+        read_i64 => i64,
+        read_i128 => i128,
+        read_f32 => f32,
+        read_f64 => f64,
+    }
+
+    /// Reads exactly `N` bytes.
+    pub(crate) fn read_byte_array<const N: usize>(&mut self) -> Result<[u8; N], XbfError> {
+        self.read_with(|r| {
+            let mut buf = [0; N];
+            r.read_exact(&mut buf)?;
+            Ok(buf)
+        })
+    }
+
+    /// Reads exactly `len` bytes, without trusting `len` for how much memory to allocate up
+    /// front.
+    pub(crate) fn read_to_vec(&mut self, len: u64) -> Result<Vec<u8>, XbfError> {
+        self.read_with(|r| {
+            let mut buf = Vec::with_capacity((len as usize).min(MAX_PREALLOCATION));
+            r.take(len).read_to_end(&mut buf)?;
+            if (buf.len() as u64) < len {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            Ok(buf)
+        })
+    }
+
+    /// Reads the bytes of `len` elements which are `size` bytes each in one go, attributing any
+    /// error to the element it occurred in as if they had been read one at a time.
+    pub(crate) fn read_elements(&mut self, len: u64, size: usize) -> Result<Vec<u8>, XbfError> {
+        let start = self.position;
+        let total = len.saturating_mul(size as u64);
+        let mut buf = Vec::with_capacity((total as usize).min(MAX_PREALLOCATION));
+        let result = self.by_ref().take(total).read_to_end(&mut buf);
+        if result.is_ok() && buf.len() as u64 == total {
+            return Ok(buf);
+        }
+        let index = buf.len() as u64 / size as u64;
+        let offset = start + index * size as u64;
+        let err = if self.budget_exceeded {
+            self.limit_exceeded(XbfLimit::TotalBytes, self.consumed() + 1, offset)
+        } else {
+            match result {
+                Ok(_) => XbfError::new(XbfErrorKind::UnexpectedEof, offset),
+                Err(e) => XbfError::from_io(e, offset),
+            }
+        };
+        Err(err.at_index(index))
+    }
+}
+
+impl<'a> XbfReader<&'a [u8]> {
+    /// Borrows exactly `len` bytes from the input. The length must already have been checked
+    /// against the limits with [`Self::read_bytes_len`].
+    pub(crate) fn read_slice(&mut self, len: u64) -> Result<&'a [u8], XbfError> {
+        match usize::try_from(len) {
+            Ok(len) if len <= self.inner.len() => {
+                let (slice, rest) = self.inner.split_at(len);
+                self.inner = rest;
+                self.position += len as u64;
+                Ok(slice)
+            }
+            _ => Err(XbfError::new(XbfErrorKind::UnexpectedEof, self.position)),
+        }
+    }
+}
+
+impl<R: Read> Read for XbfReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let buf = match self.options.max_total_bytes {
+            Some(max) => {
+                let remaining = max.saturating_sub(self.consumed());
+                if remaining == 0 && !buf.is_empty() {
+                    self.budget_exceeded = true;
+                    return Err(io::Error::other("maximum total bytes exceeded"));
+                }
+                let len = buf
+                    .len()
+                    .min(usize::try_from(remaining).unwrap_or(usize::MAX));
+                &mut buf[..len]
+            }
+            None => buf,
+        };
+        let n = self.inner.read(buf)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        XbfArrayMetadata, XbfMapMetadata, XbfPrimitive, XbfPrimitiveMetadata, XbfStructMetadata,
+        XbfVecMetadata,
+    };
+    use indexmap::{indexmap, IndexMap};
+
+    fn limit_of(err: &XbfError) -> XbfLimit {
+        match err.kind() {
+            XbfErrorKind::LimitExceeded { limit, .. } => *limit,
+            other => panic!("expected a limit error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn bytes_len_limit_works() {
+        let options = DeserializeOptions::new().max_bytes_len(4);
+
+        let mut data = vec![];
+        data.extend_from_slice(&4u64.to_le_bytes());
+        data.extend_from_slice(b"abcd");
+        let mut reader = XbfReader::with_options(data.as_slice(), options);
+        assert!(reader
+            .read_value(&XbfPrimitiveMetadata::Bytes.into())
+            .is_ok());
+
+        let data = u64::MAX.to_le_bytes();
+        let mut reader = XbfReader::with_options(data.as_slice(), options);
+        let err = reader
+            .read_value(&XbfPrimitiveMetadata::String.into())
+            .unwrap_err();
+        assert_eq!(limit_of(&err), XbfLimit::BytesLength);
+        assert_eq!(err.offset(), 0);
+    }
+
+    #[test]
+    fn vec_len_limit_works() {
+        let options = DeserializeOptions::new().max_vec_len(1);
+        let data = u64::MAX.to_le_bytes();
+        let mut reader = XbfReader::with_options(data.as_slice(), options);
+
+        let err = reader
+            .read_value(&XbfVecMetadata::new(XbfPrimitiveMetadata::U8).into())
+            .unwrap_err();
+        assert_eq!(limit_of(&err), XbfLimit::VecLength);
+    }
+
+    #[test]
+    fn unlimited_huge_lengths_fail_without_allocating() {
+        let data = u64::MAX.to_le_bytes();
+
+        let err = XbfReader::new(data.as_slice())
+            .read_value(&XbfVecMetadata::new(XbfPrimitiveMetadata::U8).into())
+            .unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::UnexpectedEof));
+
+        let err = XbfReader::new(data.as_slice())
+            .read_value(&XbfPrimitiveMetadata::Bytes.into())
+            .unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::UnexpectedEof));
+    }
+
+    #[test]
+    fn depth_limit_works() {
+        let metadata = XbfVecMetadata::new(XbfVecMetadata::new(XbfPrimitiveMetadata::U8));
+        let mut data = vec![];
+        metadata.serialize_vec_metadata(&mut data).unwrap();
+
+        let options = DeserializeOptions::new().max_depth(2);
+        let mut reader = XbfReader::with_options(data.as_slice(), options);
+        assert!(reader.read_metadata().is_ok());
+
+        let options = DeserializeOptions::new().max_depth(1);
+        let mut reader = XbfReader::with_options(data.as_slice(), options);
+        let err = reader.read_metadata().unwrap_err();
+        assert_eq!(limit_of(&err), XbfLimit::Depth);
+
+        let data = 1u64.to_le_bytes();
+        let mut reader = XbfReader::with_options(data.as_slice(), options);
+        let err = reader.read_value(&metadata.into()).unwrap_err();
+        assert_eq!(limit_of(&err), XbfLimit::Depth);
+        assert_eq!(err.path(), "U8[0]");
+    }
+
+    #[test]
+    fn struct_fields_limit_works() {
+        let metadata = XbfStructMetadata::new(
+            "test",
+            indexmap! {
+                "a" => XbfPrimitiveMetadata::U8.into(),
+                "b" => XbfPrimitiveMetadata::U8.into(),
+            },
+        );
+        let mut data = vec![];
+        metadata.serialize_struct_metadata(&mut data).unwrap();
+
+        let options = DeserializeOptions::new().max_struct_fields(1);
+        let mut reader = XbfReader::with_options(data.as_slice(), options);
+        let err = reader.read_metadata().unwrap_err();
+        assert_eq!(limit_of(&err), XbfLimit::StructFields);
+    }
+
+    #[test]
+    fn strict_struct_name_works() {
+        let strict = DeserializeOptions::new().strict(true);
+
+        for (name, valid) in [("DragonRider", true), ("", false), ("Dragon\nRider", false)] {
+            let metadata = XbfStructMetadata::new(name, IndexMap::<&str, _>::new());
+            let mut data = vec![];
+            metadata.serialize_struct_metadata(&mut data).unwrap();
+
+            assert!(XbfReader::new(data.as_slice()).read_metadata().is_ok());
+
+            let result = XbfReader::with_options(data.as_slice(), strict).read_metadata();
+            if valid {
+                assert_eq!(result.unwrap(), metadata.into());
+            } else {
+                let err = result.unwrap_err();
+                assert!(matches!(err.kind(), XbfErrorKind::InvalidStructName(x) if &**x == name));
+                assert_eq!(err.offset(), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn strict_time_works() {
+        let metadata = XbfPrimitiveMetadata::Time.into();
+        let strict = DeserializeOptions::new().strict(true);
+
+        let leap_second = 86_400_999_999_999u64.to_le_bytes();
+        let mut reader = XbfReader::with_options(leap_second.as_slice(), strict);
+        assert_eq!(
+            reader.read_value(&metadata).unwrap(),
+            XbfPrimitive::Time(XbfTime::from_nanos_since_midnight(86_400_999_999_999)).into()
+        );
+
+        let data = 86_401_000_000_000u64.to_le_bytes();
+        assert!(XbfReader::new(data.as_slice())
+            .read_value(&metadata)
+            .is_ok());
+
+        let mut reader = XbfReader::with_options(data.as_slice(), strict);
+        let err = reader.read_value(&metadata).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            XbfErrorKind::InvalidTime(86_401_000_000_000)
+        ));
+        assert_eq!(err.offset(), 0);
+    }
+
+    #[test]
+    fn strict_trailing_bytes_works() {
+        let metadata = XbfPrimitiveMetadata::U8.into();
+
+        let mut reader = XbfReader::new([1u8, 2].as_slice());
+        reader.read_value(&metadata).unwrap();
+        assert_eq!(reader.finish().unwrap(), [2]);
+
+        let strict = DeserializeOptions::new().strict(true);
+        let mut reader = XbfReader::with_options([1u8].as_slice(), strict);
+        reader.read_value(&metadata).unwrap();
+        assert!(reader.finish().is_ok());
+
+        let mut reader = XbfReader::with_options([1u8, 2].as_slice(), strict);
+        reader.read_value(&metadata).unwrap();
+        let err = reader.finish().unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::TrailingBytes));
+        assert_eq!(err.offset(), 1);
+    }
+
+    #[test]
+    fn total_bytes_limit_works() {
+        let options = DeserializeOptions::new().max_total_bytes(6);
+        let data = [1u32.to_le_bytes(), 2u32.to_le_bytes()].concat();
+        let metadata = XbfPrimitiveMetadata::U32.into();
+        let mut reader = XbfReader::with_options(data.as_slice(), options);
+
+        assert!(reader.read_value(&metadata).is_ok());
+        let err = reader.read_value(&metadata).unwrap_err();
+        assert_eq!(limit_of(&err), XbfLimit::TotalBytes);
+        assert_eq!(err.offset(), 4);
+
+        let mut data = vec![];
+        data.extend_from_slice(&100u64.to_le_bytes());
+        data.extend_from_slice(&[0; 100]);
+        let options = DeserializeOptions::new().max_total_bytes(64);
+        let mut reader = XbfReader::with_options(data.as_slice(), options);
+        let err = reader
+            .read_value(&XbfPrimitiveMetadata::Bytes.into())
+            .unwrap_err();
+        assert_eq!(limit_of(&err), XbfLimit::TotalBytes);
+        assert_eq!(err.offset(), 0);
+    }
+
+    #[test]
+    fn zero_size_elements_count_against_total_bytes() {
+        let empty = XbfStructMetadata::new("Empty", IndexMap::<&str, _>::new());
+        let options = DeserializeOptions::new().max_total_bytes(64);
+
+        let data = u64::MAX.to_le_bytes();
+        for metadata in [
+            XbfVecMetadata::new(empty.clone()).into(),
+            XbfVecMetadata::new(XbfArrayMetadata::new(XbfPrimitiveMetadata::U8, 0)).into(),
+            XbfMapMetadata::new(empty.clone(), empty.clone()).into(),
+        ] {
+            let mut reader = XbfReader::with_options(data.as_slice(), options);
+            let err = reader.read_value(&metadata).unwrap_err();
+            assert_eq!(limit_of(&err), XbfLimit::TotalBytes);
+            assert_eq!(err.offset(), 8);
+        }
+
+        let metadata = XbfArrayMetadata::new(empty.clone(), u64::MAX).into();
+        let mut reader = XbfReader::with_options([].as_slice(), options);
+        let err = reader.read_value(&metadata).unwrap_err();
+        assert_eq!(limit_of(&err), XbfLimit::TotalBytes);
+
+        // each element counts as a byte on top of the 8 bytes of the length
+        let metadata = XbfVecMetadata::new(empty).into();
+        let data = 56u64.to_le_bytes();
+        let mut reader = XbfReader::with_options(data.as_slice(), options);
+        assert_eq!(
+            reader
+                .read_value(&metadata)
+                .unwrap()
+                .as_vec()
+                .unwrap()
+                .len(),
+            56
+        );
+        let data = 57u64.to_le_bytes();
+        let mut reader = XbfReader::with_options(data.as_slice(), options);
+        assert!(reader.read_value(&metadata).is_err());
+    }
+}
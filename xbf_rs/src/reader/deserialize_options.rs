@@ -0,0 +1,121 @@
+/// Options controlling how an [`XbfReader`](crate::XbfReader) deserializes data.
+///
+/// Every limit is disabled by default. When reading data from an untrusted source, such as over
+/// the network, setting limits prevents a small malicious message from making the reader allocate
+/// huge amounts of memory or recurse until the stack overflows.
+///
+/// Limits are checked before any memory is allocated for the value being limited.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::{DeserializeOptions, XbfErrorKind, XbfLimit, XbfPrimitiveMetadata, XbfReader};
+///
+/// let options = DeserializeOptions::new()
+///     .max_bytes_len(1024)
+///     .max_vec_len(1024)
+///     .max_depth(16)
+///     .max_struct_fields(64)
+///     .max_total_bytes(1024 * 1024);
+///
+/// // a string claiming to be u64::MAX bytes long
+/// let data = u64::MAX.to_le_bytes();
+/// let mut reader = XbfReader::with_options(data.as_slice(), options);
+///
+/// let err = reader
+///     .read_value(&XbfPrimitiveMetadata::String.into())
+///     .unwrap_err();
+///
+/// assert!(matches!(
+///     err.kind(),
+///     XbfErrorKind::LimitExceeded { limit: XbfLimit::BytesLength, .. }
+/// ));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeserializeOptions {
+    pub(crate) max_bytes_len: Option<u64>,
+    pub(crate) max_vec_len: Option<u64>,
+    pub(crate) max_depth: Option<u32>,
+    pub(crate) max_struct_fields: Option<u16>,
+    pub(crate) max_total_bytes: Option<u64>,
+    pub(crate) strict: bool,
+}
+
+impl DeserializeOptions {
+    /// Creates a new set of options with every limit disabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum length in bytes of a string or bytes primitive, including strings in
+    /// struct metadata.
+    pub fn max_bytes_len(mut self, max: u64) -> Self {
+        self.max_bytes_len = Some(max);
+        self
+    }
+
+    /// Sets the maximum number of elements in a vector or array, or entries in a map.
+    pub fn max_vec_len(mut self, max: u64) -> Self {
+        self.max_vec_len = Some(max);
+        self
+    }
+
+    /// Sets the maximum nesting depth of vectors and structs, in both metadata and values.
+    ///
+    /// A top level primitive has a depth of 0, and each vector or struct it is contained in adds
+    /// one.
+    pub fn max_depth(mut self, max: u32) -> Self {
+        self.max_depth = Some(max);
+        self
+    }
+
+    /// Sets the maximum number of fields in struct metadata, which also limits the number of
+    /// variants in enum metadata.
+    pub fn max_struct_fields(mut self, max: u16) -> Self {
+        self.max_struct_fields = Some(max);
+        self
+    }
+
+    /// Sets the maximum number of bytes which will be read in total over the lifetime of the
+    /// reader.
+    ///
+    /// Elements which encode to no bytes at all, such as empty structs, count as one byte each,
+    /// so that a vector of them is bounded by its length like any other.
+    pub fn max_total_bytes(mut self, max: u64) -> Self {
+        self.max_total_bytes = Some(max);
+        self
+    }
+
+    /// Sets whether data which does not strictly conform to the XBF specification is rejected.
+    ///
+    /// By default decoding is lenient, and in strict mode the following are additionally
+    /// rejected:
+    ///
+    /// - booleans with a value other than 0 or 1.
+    /// - times of day of 86,401 seconds or more.
+    /// - struct names which are empty or contain control characters.
+    /// - struct metadata with the same field name more than once. Leniently, the last type given
+    ///   for the name replaces the earlier one, so values of the struct are likely to be misread.
+    /// - bytes remaining in the input when [`XbfReader::finish`](crate::XbfReader::finish) is
+    ///   called after reading the top level value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use xbf_rs::{DeserializeOptions, XbfErrorKind, XbfPrimitiveMetadata, XbfReader};
+    ///
+    /// let metadata = XbfPrimitiveMetadata::Bool.into();
+    ///
+    /// let mut reader = XbfReader::new([2u8].as_slice());
+    /// assert!(reader.read_value(&metadata).is_ok());
+    ///
+    /// let options = DeserializeOptions::new().strict(true);
+    /// let mut reader = XbfReader::with_options([2u8].as_slice(), options);
+    /// let err = reader.read_value(&metadata).unwrap_err();
+    /// assert!(matches!(err.kind(), XbfErrorKind::InvalidBool(2)));
+    /// ```
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+}
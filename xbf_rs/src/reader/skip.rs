@@ -0,0 +1,361 @@
+use crate::{
+    xbf_tensor::element_count, XbfError, XbfErrorKind, XbfLimit, XbfMetadata, XbfPrimitiveMetadata,
+    XbfReader, XbfStructMetadata, XbfVecMetadata,
+};
+use std::io::{self, Read};
+
+impl<R: Read> XbfReader<R> {
+    /// Skips over a value described by `metadata` without deserializing it.
+    ///
+    /// Fixed size values are skipped in one go, and variable length values are skipped by
+    /// reading only their length prefixes. No memory is allocated for the skipped value, and
+    /// its contents are not validated, so for example invalid UTF-8 in a skipped string is not
+    /// an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails or a limit is exceeded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use xbf_rs::{XbfPrimitiveMetadata, XbfReader};
+    ///
+    /// let mut data = vec![];
+    /// data.extend_from_slice(&7u64.to_le_bytes());
+    /// data.extend_from_slice(b"Saphira");
+    /// data.push(42);
+    ///
+    /// let mut reader = XbfReader::new(data.as_slice());
+    /// reader.skip_value(&XbfPrimitiveMetadata::String.into()).unwrap();
+    ///
+    /// assert_eq!(reader.position(), 15);
+    /// ```
+    pub fn skip_value(&mut self, metadata: &XbfMetadata) -> Result<(), XbfError> {
+        self.skip(metadata).map_err(|e| e.in_root(metadata))
+    }
+
+    /// Moves to the start of the field called `field_name` of a struct described by
+    /// `metadata`, skipping over the fields before it, and returns the metadata of the field.
+    ///
+    /// After reading the field, the fields following it must be read or skipped before
+    /// reading anything which comes after the struct.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] with [`XbfErrorKind::UnknownField`] if the struct has no such
+    /// field, or if the reader fails or a limit is exceeded while skipping.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use xbf_rs::{XbfPrimitiveMetadata, XbfReader, XbfStructMetadata};
+    /// use indexmap::indexmap;
+    ///
+    /// let metadata = XbfStructMetadata::new(
+    ///     "DragonRider",
+    ///     indexmap! {
+    ///         "age" => XbfPrimitiveMetadata::U16.into(),
+    ///         "name" => XbfPrimitiveMetadata::String.into(),
+    ///     },
+    /// );
+    ///
+    /// let mut data = vec![];
+    /// data.extend_from_slice(&16u16.to_le_bytes());
+    /// data.extend_from_slice(&6u64.to_le_bytes());
+    /// data.extend_from_slice(b"Eragon");
+    ///
+    /// let mut reader = XbfReader::new(data.as_slice());
+    /// let field_type = reader.seek_field(&metadata, "name").unwrap();
+    ///
+    /// assert_eq!(
+    ///     reader.read_value(field_type).unwrap().as_str().unwrap(),
+    ///     "Eragon"
+    /// );
+    /// ```
+    pub fn seek_field<'m>(
+        &mut self,
+        metadata: &'m XbfStructMetadata,
+        field_name: &str,
+    ) -> Result<&'m XbfMetadata, XbfError> {
+        let root = || XbfMetadata::from(metadata);
+        let Some(field_type) = metadata.get_field_type(field_name) else {
+            return Err(XbfError::new(
+                XbfErrorKind::UnknownField(field_name.into()),
+                self.position,
+            )
+            .in_root(&root()));
+        };
+        for (name, field_type) in metadata
+            .fields()
+            .take_while(|(name, _)| *name != field_name)
+        {
+            self.skip(field_type)
+                .map_err(|e| e.in_field(name).in_root(&root()))?;
+        }
+        Ok(field_type)
+    }
+
+    /// Moves to the start of the element at `index` of a vector described by `metadata`,
+    /// skipping over the length of the vector and the elements before it, and returns the
+    /// metadata of the element.
+    ///
+    /// If the elements have a [fixed size](XbfMetadata::fixed_size), they are skipped in one
+    /// go. After reading the element, the elements following it must be read or skipped before
+    /// reading anything which comes after the vector.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] with [`XbfErrorKind::IndexOutOfBounds`] if the vector has too
+    /// few elements, or if the reader fails or a limit is exceeded while skipping.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use xbf_rs::{XbfPrimitiveMetadata, XbfReader, XbfVecMetadata};
+    ///
+    /// let metadata = XbfVecMetadata::new(XbfPrimitiveMetadata::I32);
+    ///
+    /// let mut data = vec![];
+    /// data.extend_from_slice(&3u64.to_le_bytes());
+    /// for x in [1i32, 2, 3] {
+    ///     data.extend_from_slice(&x.to_le_bytes());
+    /// }
+    ///
+    /// let mut reader = XbfReader::new(data.as_slice());
+    /// let element_type = reader.seek_index(&metadata, 2).unwrap();
+    ///
+    /// assert_eq!(reader.read_value(element_type).unwrap().as_i32().unwrap(), 3);
+    /// ```
+    pub fn seek_index<'m>(
+        &mut self,
+        metadata: &'m XbfVecMetadata,
+        index: u64,
+    ) -> Result<&'m XbfMetadata, XbfError> {
+        let root = || XbfMetadata::from(metadata);
+        let offset = self.position;
+        let len = self.read_vec_len().map_err(|e| e.in_root(&root()))?;
+        if index >= len {
+            return Err(
+                XbfError::new(XbfErrorKind::IndexOutOfBounds { index, len }, offset)
+                    .in_root(&root()),
+            );
+        }
+        self.skip_elements(&metadata.inner_type, index)
+            .map_err(|e| e.in_root(&root()))?;
+        Ok(&metadata.inner_type)
+    }
+
+    pub(crate) fn skip(&mut self, metadata: &XbfMetadata) -> Result<(), XbfError> {
+        match metadata {
+            XbfMetadata::Primitive(x) => self.skip_primitive(x),
+            XbfMetadata::Vec(x) => self.nested(|reader| {
+                let len = reader.read_vec_len()?;
+                reader.skip_elements(&x.inner_type, len)
+            }),
+            XbfMetadata::Struct(x) => self.nested(|reader| {
+                for (name, field_type) in x.fields() {
+                    reader.skip(field_type).map_err(|e| e.in_field(name))?;
+                }
+                Ok(())
+            }),
+            XbfMetadata::Option(x) => self.nested(|reader| {
+                if reader.read_bool()? {
+                    reader.skip(&x.inner_type)?;
+                }
+                Ok(())
+            }),
+            XbfMetadata::Enum(x) => self.nested(|reader| {
+                let (_, name, variant_type) = reader.read_variant(x)?;
+                reader.skip(variant_type).map_err(|e| e.in_field(name))
+            }),
+            XbfMetadata::Array(x) => {
+                self.nested(|reader| reader.skip_elements(&x.inner_type, x.len))
+            }
+            XbfMetadata::Map(x) => self.nested(|reader| {
+                let len = reader.read_vec_len()?;
+                for i in 0..len {
+                    reader.skip(&x.key_type).map_err(|e| e.at_index(i))?;
+                    reader.skip(&x.value_type).map_err(|e| e.at_index(i))?;
+                }
+                Ok(())
+            }),
+            XbfMetadata::Tensor(x) => {
+                let count = match &x.shape {
+                    Some(shape) => element_count(shape),
+                    None => element_count(&self.read_tensor_shape(x.rank)?),
+                };
+                let size = x.element_type.fixed_size().unwrap_or_default();
+                self.skip_bytes(count.saturating_mul(size as u64))
+            }
+        }
+    }
+
+    fn skip_primitive(&mut self, metadata: &XbfPrimitiveMetadata) -> Result<(), XbfError> {
+        match metadata.fixed_size() {
+            Some(size) => self.skip_bytes(size as u64),
+            None => {
+                let len = self.read_bytes_len()?;
+                self.skip_bytes(len)
+            }
+        }
+    }
+
+    /// Skips the first `count` elements of a vector whose length has already been read.
+    fn skip_elements(&mut self, inner_type: &XbfMetadata, count: u64) -> Result<(), XbfError> {
+        match inner_type.fixed_size() {
+            Some(size) => self.skip_bytes(count.saturating_mul(size as u64)),
+            None => {
+                for i in 0..count {
+                    self.skip(inner_type).map_err(|e| e.at_index(i))?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn skip_bytes(&mut self, len: u64) -> Result<(), XbfError> {
+        let offset = self.position;
+        let total = self.consumed().saturating_add(len);
+        self.check_len(
+            XbfLimit::TotalBytes,
+            self.options.max_total_bytes,
+            total,
+            offset,
+        )?;
+        self.read_with(|r| {
+            let skipped = io::copy(&mut r.take(len), &mut io::sink())?;
+            if skipped < len {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        DeserializeOptions, XbfErrorKind, XbfLimit, XbfMetadata, XbfPrimitive,
+        XbfPrimitiveMetadata, XbfReader, XbfStruct, XbfStructMetadata, XbfType, XbfTypeUpcast,
+        XbfVec, XbfVecMetadata,
+    };
+    use indexmap::indexmap;
+
+    fn rider_metadata() -> XbfStructMetadata {
+        XbfStructMetadata::new(
+            "DragonRider",
+            indexmap! {
+                "name" => XbfPrimitiveMetadata::String.into(),
+                "age" => XbfPrimitiveMetadata::U16.into(),
+            },
+        )
+    }
+
+    fn riders(count: u16) -> XbfType {
+        XbfVec::new(
+            XbfVecMetadata::new(rider_metadata()),
+            (0..count).map(|i| {
+                XbfStruct::new(
+                    rider_metadata(),
+                    [
+                        XbfPrimitive::String(format!("Rider {i}")).into(),
+                        XbfPrimitive::U16(i).into(),
+                    ],
+                )
+                .unwrap()
+            }),
+        )
+        .unwrap()
+        .into_base_type()
+    }
+
+    #[test]
+    fn skip_value_works() {
+        let value = riders(10);
+        let mut data = vec![];
+        value.serialize_base_type(&mut data).unwrap();
+        data.push(42);
+
+        let mut reader = XbfReader::new(data.as_slice());
+        reader.skip_value(&XbfMetadata::from(&value)).unwrap();
+
+        assert_eq!(reader.into_inner(), [42]);
+    }
+
+    #[test]
+    fn seek_works() {
+        let value = riders(6000);
+        let mut data = vec![];
+        value.serialize_base_type(&mut data).unwrap();
+
+        let metadata = XbfVecMetadata::new(rider_metadata());
+        let mut reader = XbfReader::new(data.as_slice());
+        reader.seek_index(&metadata, 5000).unwrap();
+        let rider_metadata = rider_metadata();
+        let field_type = reader.seek_field(&rider_metadata, "age").unwrap();
+
+        assert_eq!(reader.read_value(field_type).unwrap().as_u16(), Ok(5000));
+    }
+
+    #[test]
+    fn seek_fixed_size_elements_works() {
+        let metadata = XbfVecMetadata::new(XbfPrimitiveMetadata::U64);
+        let mut data = vec![];
+        data.extend_from_slice(&1000u64.to_le_bytes());
+        for i in 0..1000u64 {
+            data.extend_from_slice(&i.to_le_bytes());
+        }
+
+        let mut reader = XbfReader::new(data.as_slice());
+        let element_type = reader.seek_index(&metadata, 999).unwrap();
+
+        assert_eq!(reader.position(), 8 + 999 * 8);
+        assert_eq!(reader.read_value(element_type).unwrap().as_u64(), Ok(999));
+    }
+
+    #[test]
+    fn seek_errors_work() {
+        let value = riders(2);
+        let mut data = vec![];
+        value.serialize_base_type(&mut data).unwrap();
+        let metadata = XbfVecMetadata::new(rider_metadata());
+
+        let err = XbfReader::new(data.as_slice())
+            .seek_index(&metadata, 2)
+            .unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            XbfErrorKind::IndexOutOfBounds { index: 2, len: 2 }
+        ));
+
+        let mut reader = XbfReader::new(data.as_slice());
+        reader.seek_index(&metadata, 0).unwrap();
+        let err = reader.seek_field(&rider_metadata(), "dragon").unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::UnknownField(x) if &**x == "dragon"));
+
+        let err = XbfReader::new(&data[..20])
+            .seek_index(&metadata, 1)
+            .unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::UnexpectedEof));
+        assert_eq!(err.path(), "DragonRider[0].name");
+    }
+
+    #[test]
+    fn skip_respects_limits() {
+        let data = u64::MAX.to_le_bytes();
+        let options = DeserializeOptions::new().max_total_bytes(1024);
+        let metadata = XbfVecMetadata::new(XbfPrimitiveMetadata::U64).into();
+
+        let err = XbfReader::with_options(data.as_slice(), options)
+            .skip_value(&metadata)
+            .unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            XbfErrorKind::LimitExceeded {
+                limit: XbfLimit::TotalBytes,
+                ..
+            }
+        ));
+    }
+}
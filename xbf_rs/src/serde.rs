@@ -0,0 +1,621 @@
+//! [Serde](https://serde.rs) support for XBF, enabled with the `serde` feature.
+//!
+//! Serialization maps serde's data model onto [`XbfPrimitive`](crate::XbfPrimitive),
+//! [`XbfVec`](crate::XbfVec) and [`XbfStruct`](crate::XbfStruct) values, inferring the
+//! [`XbfMetadata`] of the value from the type as it goes. Deserialization is always driven by a
+//! known [`XbfMetadata`], so payloads that were sent without their metadata can be read as well.
+//!
+//! The mapping from serde's data model is as follows:
+//!
+//! | Serde                          | XBF                                          |
+//! | ------------------------------ | -------------------------------------------- |
+//! | `bool`, integers, floats       | the matching primitive                       |
+//! | `char`, `str`                  | [`String`](crate::XbfPrimitive::String)      |
+//! | bytes                          | [`Bytes`](crate::XbfPrimitive::Bytes)        |
+//! | `Option`                       | an [`XbfOption`](crate::XbfOption)           |
+//! | newtype structs                | the inner value                              |
+//! | sequences                      | a vector                                     |
+//! | maps                           | an [`XbfMap`](crate::XbfMap)                 |
+//! | structs and unit structs       | a struct with the same name and fields       |
+//! | tuples and tuple structs       | a struct with fields named `0`, `1`, ...     |
+//! | enums                          | an [`XbfEnum`](crate::XbfEnum)               |
+//!
+//! Sequences and tuples, including fixed size arrays such as `[T; N]`, are written as an
+//! [`XbfArray`](crate::XbfArray) when the supplied metadata expects one, in which case they must
+//! have exactly as many elements as the array's length.
+//!
+//! Temporal primitives such as [`Timestamp`](crate::XbfPrimitive::Timestamp) are deserialized as
+//! their integer representation, and integers are written as them when the supplied metadata
+//! expects one. [`TimestampTz`](crate::XbfPrimitive::TimestampTz) has no single integer
+//! representation and is unsupported. Likewise, [`Decimal`](crate::XbfPrimitive::Decimal)s are
+//! deserialized as strings, and strings are parsed into them when the metadata expects one.
+//!
+//! A `char` is written as a [`String`](crate::XbfPrimitive::String) unless the metadata expects a
+//! [`Char`](crate::XbfPrimitive::Char). [`Uuid`](crate::XbfPrimitive::Uuid)s are deserialized as
+//! bytes, and accept 16 bytes or a hexadecimal string. [`F16`](crate::XbfPrimitive::F16) and
+//! [`BF16`](crate::XbfPrimitive::BF16) are deserialized as an `f32`, and accept the bits of the
+//! float as a `u16`, which is how the `half` crate serializes them.
+//!
+//! The value of an enum variant is mapped like the equivalent struct: unit variants hold an empty
+//! struct, newtype variants their inner value, and tuple and struct variants a struct named after
+//! the variant.
+//!
+//! Tensors have no serde equivalent, and deserializing one returns [`Error::Unsupported`].
+//!
+//! Anything else (`()`) cannot currently be represented in XBF and will return
+//! [`Error::Unsupported`].
+//!
+//! The element type of an empty sequence or map, the inner type of `None` and the variants of an enum
+//! cannot be inferred, so serializing them requires supplying the expected metadata with
+//! [`to_xbf_type_with_metadata`].
+//! When the supplied metadata is not an option where the value is, `Some(value)` is written as
+//! the inner value alone.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! use xbf_rs::XbfMetadata;
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct DragonRider {
+//!     name: String,
+//!     age: u16,
+//! }
+//!
+//! let rider = DragonRider {
+//!     name: "Eragon".to_string(),
+//!     age: 16,
+//! };
+//!
+//! let mut writer = vec![];
+//! xbf_rs::serde::to_writer(&rider, &mut writer).unwrap();
+//!
+//! let mut reader = std::io::Cursor::new(writer);
+//! let metadata = XbfMetadata::deserialize_base_metadata(&mut reader).unwrap();
+//! let deserialized: DragonRider = xbf_rs::serde::from_reader(&metadata, &mut reader).unwrap();
+//!
+//! assert_eq!(rider, deserialized);
+//! ```
+
+mod de;
+mod error;
+mod ser;
+
+pub use de::*;
+pub use error::*;
+pub use ser::*;
+
+use crate::{XbfMetadata, XbfType};
+use ::serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::io::{Read, Write};
+
+/// Convert a serializable value into an [`XbfType`], inferring its metadata from the value.
+///
+/// # Errors
+///
+/// Returns an error if the value contains something that cannot be represented in XBF, such as an
+/// empty sequence whose element type cannot be inferred.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::{XbfPrimitive, XbfType};
+///
+/// let value = xbf_rs::serde::to_xbf_type(&42i32).unwrap();
+///
+/// assert_eq!(value, XbfType::Primitive(XbfPrimitive::I32(42)));
+/// ```
+pub fn to_xbf_type<T: Serialize + ?Sized>(value: &T) -> Result<XbfType, Error> {
+    value.serialize(Serializer::new())
+}
+
+/// Convert a serializable value into an [`XbfType`] which must match the given metadata.
+///
+/// The metadata is also used to determine the type of empty sequences, which would otherwise be
+/// unknowable.
+///
+/// # Errors
+///
+/// Returns an error if the value does not match the metadata, or if the value contains something
+/// that cannot be represented in XBF.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::{XbfMetadata, XbfPrimitiveMetadata, XbfVecMetadata};
+///
+/// let metadata = XbfMetadata::from(XbfVecMetadata::new(XbfPrimitiveMetadata::I32));
+/// let empty: Vec<i32> = vec![];
+///
+/// let value = xbf_rs::serde::to_xbf_type_with_metadata(&empty, &metadata).unwrap();
+///
+/// assert_eq!(XbfMetadata::from(&value), metadata);
+/// assert!(xbf_rs::serde::to_xbf_type_with_metadata(&"hello", &metadata).is_err());
+/// ```
+pub fn to_xbf_type_with_metadata<T: Serialize + ?Sized>(
+    value: &T,
+    metadata: &XbfMetadata,
+) -> Result<XbfType, Error> {
+    value.serialize(Serializer::with_metadata(metadata))
+}
+
+/// Serialize a value's inferred metadata followed by the value itself to a writer.
+///
+/// # Errors
+///
+/// Returns an error if the value cannot be represented in XBF, or if writing fails.
+pub fn to_writer<T: Serialize + ?Sized>(value: &T, writer: &mut impl Write) -> Result<(), Error> {
+    let value = to_xbf_type(value)?;
+    XbfMetadata::from(&value).serialize_base_metadata(writer)?;
+    value.serialize_base_type(writer)?;
+    Ok(())
+}
+
+/// Deserialize a value from an [`XbfType`].
+///
+/// # Errors
+///
+/// Returns an error if the XBF value does not have the shape `T` expects.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::XbfPrimitive;
+///
+/// let value = XbfPrimitive::String("hello".to_string()).into();
+/// let deserialized: String = xbf_rs::serde::from_xbf_type(&value).unwrap();
+///
+/// assert_eq!(deserialized, "hello");
+/// ```
+pub fn from_xbf_type<'de, T: Deserialize<'de>>(value: &'de XbfType) -> Result<T, Error> {
+    T::deserialize(Deserializer::new(value))
+}
+
+/// Deserialize a value which was written without its metadata from a reader, using the given
+/// metadata to decode it.
+///
+/// # Errors
+///
+/// Returns an error if reading fails, or if the decoded value does not have the shape `T`
+/// expects.
+pub fn from_reader<T: DeserializeOwned>(
+    metadata: &XbfMetadata,
+    reader: &mut impl Read,
+) -> Result<T, Error> {
+    let value = XbfType::deserialize_base_type(metadata, reader)?;
+    from_xbf_type(&value)
+}
+
+/// Deserialize a value which was written along with its metadata (such as with [`to_writer`])
+/// from a reader.
+///
+/// # Errors
+///
+/// Returns an error if reading fails, or if the decoded value does not have the shape `T`
+/// expects.
+pub fn from_reader_with_metadata<T: DeserializeOwned>(reader: &mut impl Read) -> Result<T, Error> {
+    let metadata = XbfMetadata::deserialize_base_metadata(reader)?;
+    from_reader(&metadata, reader)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ArrayError, XbfArrayMetadata, XbfDecimal, XbfEnumMetadata, XbfMapMetadata, XbfMetadata,
+        XbfOption, XbfOptionMetadata, XbfPrimitive, XbfPrimitiveMetadata, XbfStruct,
+        XbfStructMetadata, XbfTimestamp, XbfTimestampTz, XbfVec, XbfVecMetadata,
+    };
+    use ::serde::{Deserialize, Serialize};
+    use indexmap::{indexmap, IndexMap};
+    use std::{collections::BTreeMap, io::Cursor};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct DragonRider {
+        name: String,
+        age: u16,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Dragon {
+        name: String,
+        rider: Option<DragonRider>,
+        scales: Vec<u32>,
+        position: (f32, f32),
+    }
+
+    fn rider_metadata() -> XbfStructMetadata {
+        XbfStructMetadata::new(
+            "DragonRider",
+            indexmap! {
+                "name" => XbfPrimitiveMetadata::String.into(),
+                "age" => XbfPrimitiveMetadata::U16.into(),
+            },
+        )
+    }
+
+    #[test]
+    fn primitive_round_trip_works() {
+        macro_rules! round_trip {
+            ($val:expr, $xbf_type:tt) => {
+                let value = to_xbf_type(&$val).unwrap();
+                assert_eq!(value, XbfPrimitive::$xbf_type($val.into()).into());
+                assert_eq!(from_xbf_type::<_>(&value).ok(), Some($val));
+            };
+        }
+
+        round_trip!(true, Bool);
+        round_trip!(1u8, U8);
+        round_trip!(2u16, U16);
+        round_trip!(3u32, U32);
+        round_trip!(4u64, U64);
+        round_trip!(5u128, U128);
+        round_trip!(-1i8, I8);
+        round_trip!(-2i16, I16);
+        round_trip!(-3i32, I32);
+        round_trip!(-4i64, I64);
+        round_trip!(-5i128, I128);
+        round_trip!(1.5f32, F32);
+        round_trip!(2.5f64, F64);
+        round_trip!("hello".to_string(), String);
+        round_trip!('c', String);
+    }
+
+    #[test]
+    fn struct_serialize_works() {
+        let rider = DragonRider {
+            name: "Eragon".to_string(),
+            age: 16,
+        };
+
+        let value = to_xbf_type(&rider).unwrap();
+        let expected = XbfStruct::new(
+            rider_metadata(),
+            [
+                XbfPrimitive::String("Eragon".to_string()).into(),
+                XbfPrimitive::U16(16).into(),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(value, expected.into());
+    }
+
+    #[test]
+    fn packed_vec_deserialize_keeps_it_packed() {
+        let value = XbfType::Vec(XbfVec::from(vec![1u32, 2, 3]));
+        assert_eq!(from_xbf_type::<Vec<u32>>(&value).unwrap(), vec![1, 2, 3]);
+        assert_eq!(
+            value.as_vec().unwrap().as_u32_slice(),
+            Some([1, 2, 3].as_slice())
+        );
+        assert!(!value.as_vec().unwrap().has_unpacked());
+    }
+
+    #[test]
+    fn nested_round_trip_works() {
+        let dragon = Dragon {
+            name: "Saphira".to_string(),
+            rider: Some(DragonRider {
+                name: "Eragon".to_string(),
+                age: 16,
+            }),
+            scales: vec![1, 2, 3],
+            position: (1.0, 2.0),
+        };
+
+        let mut writer = vec![];
+        to_writer(&dragon, &mut writer).unwrap();
+
+        let deserialized: Dragon = from_reader_with_metadata(&mut Cursor::new(writer)).unwrap();
+        assert_eq!(dragon, deserialized);
+    }
+
+    #[test]
+    fn vec_of_structs_shares_metadata() {
+        let riders = vec![
+            DragonRider {
+                name: "Eragon".to_string(),
+                age: 16,
+            },
+            DragonRider {
+                name: "Arya".to_string(),
+                age: 103,
+            },
+        ];
+
+        let value = to_xbf_type(&riders).unwrap();
+        let expected_metadata = XbfVecMetadata::new(rider_metadata());
+
+        match &value {
+            XbfType::Vec(v) => assert_eq!(v.get_metadata(), expected_metadata),
+            _ => panic!("expected a vec"),
+        }
+
+        let mut writer = vec![];
+        value.serialize_base_type(&mut writer).unwrap();
+        let deserialized: Vec<DragonRider> =
+            from_reader(&expected_metadata.into(), &mut Cursor::new(writer)).unwrap();
+        assert_eq!(riders, deserialized);
+    }
+
+    #[test]
+    fn empty_seq_requires_metadata() {
+        let empty: Vec<u8> = vec![];
+
+        assert!(matches!(
+            to_xbf_type(&empty),
+            Err(Error::UnknownElementType)
+        ));
+
+        let metadata = XbfVecMetadata::new(XbfPrimitiveMetadata::U8);
+        let value = to_xbf_type_with_metadata(&empty, &metadata.clone().into()).unwrap();
+        assert_eq!(
+            value,
+            XbfVec::new_unchecked(metadata, Vec::<XbfType>::new()).into()
+        );
+    }
+
+    #[test]
+    fn metadata_mismatch_fails() {
+        let rider = DragonRider {
+            name: "Eragon".to_string(),
+            age: 16,
+        };
+        let wrong_metadata = XbfStructMetadata::new(
+            "DragonRider",
+            indexmap! {
+                "name" => XbfPrimitiveMetadata::String.into(),
+                "age" => XbfPrimitiveMetadata::U32.into(),
+            },
+        );
+
+        assert!(to_xbf_type_with_metadata(&rider, &rider_metadata().into()).is_ok());
+        assert!(matches!(
+            to_xbf_type_with_metadata(&rider, &wrong_metadata.into()),
+            Err(Error::TypeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn option_round_trip_works() {
+        let metadata = XbfVecMetadata::new(XbfOptionMetadata::new(XbfPrimitiveMetadata::I32));
+        let values = vec![Some(1i32), None, Some(3)];
+
+        let value = to_xbf_type_with_metadata(&values, &metadata.clone().into()).unwrap();
+        let deserialized: Vec<Option<i32>> = from_xbf_type(&value).unwrap();
+        assert_eq!(deserialized, values);
+
+        assert!(matches!(
+            to_xbf_type(&None::<i32>),
+            Err(Error::UnknownOptionType)
+        ));
+        assert_eq!(
+            to_xbf_type(&Some(1i32)).unwrap(),
+            XbfOption::some(XbfPrimitive::I32(1)).into()
+        );
+
+        // an option is always present when its metadata isn't an option
+        let metadata = XbfPrimitiveMetadata::I32.into();
+        let value = to_xbf_type_with_metadata(&Some(1i32), &metadata).unwrap();
+        assert_eq!(value, XbfPrimitive::I32(1).into());
+        assert_eq!(from_xbf_type::<Option<i32>>(&value).unwrap(), Some(1));
+        assert!(to_xbf_type_with_metadata(&None::<i32>, &metadata).is_err());
+    }
+
+    #[test]
+    fn enum_round_trip_works() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        enum Event {
+            Login { user: String },
+            Logout(u64),
+            Move(i32, i32),
+            Crash,
+        }
+
+        let empty = IndexMap::<&str, XbfMetadata>::new();
+        let metadata = XbfEnumMetadata::new(
+            "Event",
+            indexmap! {
+                "Login" => XbfStructMetadata::new(
+                    "Login",
+                    indexmap! {"user" => XbfPrimitiveMetadata::String.into()},
+                )
+                .into(),
+                "Logout" => XbfPrimitiveMetadata::U64.into(),
+                "Move" => XbfStructMetadata::new(
+                    "Move",
+                    indexmap! {
+                        "0" => XbfPrimitiveMetadata::I32.into(),
+                        "1" => XbfPrimitiveMetadata::I32.into(),
+                    },
+                )
+                .into(),
+                "Crash" => XbfStructMetadata::new("Crash", empty).into(),
+            },
+        );
+        let events = vec![
+            Event::Login {
+                user: "Eragon".to_string(),
+            },
+            Event::Logout(42),
+            Event::Move(1, -1),
+            Event::Crash,
+        ];
+
+        let metadata = XbfVecMetadata::new(metadata).into();
+        let value = to_xbf_type_with_metadata(&events, &metadata).unwrap();
+        let logout = value.as_vec().unwrap()[1].as_enum().unwrap();
+        assert_eq!(logout.variant_name(), "Logout");
+        assert_eq!(logout.value(), &XbfPrimitive::U64(42).into());
+
+        let deserialized: Vec<Event> = from_xbf_type(&value).unwrap();
+        assert_eq!(deserialized, events);
+
+        assert!(matches!(
+            to_xbf_type(&Event::Crash),
+            Err(Error::UnknownEnumType)
+        ));
+    }
+
+    #[test]
+    fn map_round_trip_works() {
+        let ages = BTreeMap::from([("Murtagh".to_string(), 18u16), ("Eragon".to_string(), 16)]);
+
+        let value = to_xbf_type(&ages).unwrap();
+        let expected_metadata =
+            XbfMapMetadata::new(XbfPrimitiveMetadata::String, XbfPrimitiveMetadata::U16);
+        assert_eq!(value.as_map().unwrap().get_metadata(), expected_metadata);
+
+        let keys = value.as_map().unwrap().keys().cloned().collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            ["Eragon", "Murtagh"].map(|x| XbfType::from(XbfPrimitive::String(x.to_string())))
+        );
+
+        let deserialized: BTreeMap<String, u16> = from_xbf_type(&value).unwrap();
+        assert_eq!(deserialized, ages);
+
+        let empty = BTreeMap::<String, u16>::new();
+        assert!(matches!(
+            to_xbf_type(&empty),
+            Err(Error::UnknownElementType)
+        ));
+        assert!(to_xbf_type_with_metadata(&empty, &expected_metadata.into()).is_ok());
+    }
+
+    #[test]
+    fn array_round_trip_works() {
+        let metadata = XbfArrayMetadata::new(XbfPrimitiveMetadata::F32, 3).into();
+        let position = [1.0f32, 2.0, 3.0];
+
+        let value = to_xbf_type_with_metadata(&position, &metadata).unwrap();
+        assert_eq!(XbfMetadata::from(&value), metadata);
+
+        let deserialized: [f32; 3] = from_xbf_type(&value).unwrap();
+        assert_eq!(deserialized, position);
+        let deserialized: Vec<f32> = from_xbf_type(&value).unwrap();
+        assert_eq!(deserialized, position);
+
+        let value = to_xbf_type_with_metadata(&position.to_vec(), &metadata).unwrap();
+        assert_eq!(XbfMetadata::from(&value), metadata);
+
+        assert!(matches!(
+            to_xbf_type_with_metadata(&[1.0f32, 2.0], &metadata),
+            Err(Error::Array(ArrayError::LengthMismatch {
+                expected_len: 3,
+                actual_len: 2,
+            }))
+        ));
+    }
+
+    #[test]
+    fn temporal_round_trip_works() {
+        let metadata = XbfPrimitiveMetadata::Timestamp.into();
+        let value = to_xbf_type_with_metadata(&1_700_000_000_000_000_000i64, &metadata).unwrap();
+        assert_eq!(
+            value,
+            XbfPrimitive::Timestamp(XbfTimestamp::from_nanos(1_700_000_000_000_000_000)).into()
+        );
+        let deserialized: i64 = from_xbf_type(&value).unwrap();
+        assert_eq!(deserialized, 1_700_000_000_000_000_000);
+
+        let metadata = XbfPrimitiveMetadata::Date.into();
+        let value = to_xbf_type_with_metadata(&19_000i32, &metadata).unwrap();
+        assert_eq!(XbfMetadata::from(&value), metadata);
+        let deserialized: i32 = from_xbf_type(&value).unwrap();
+        assert_eq!(deserialized, 19_000);
+
+        let value =
+            XbfPrimitive::TimestampTz(XbfTimestampTz::new(XbfTimestamp::from_nanos(0), 3600))
+                .into();
+        assert!(matches!(
+            from_xbf_type::<i64>(&value),
+            Err(Error::Unsupported("TimestampTz"))
+        ));
+    }
+
+    #[test]
+    fn decimal_round_trip_works() {
+        let metadata = XbfPrimitiveMetadata::Decimal.into();
+        let value = to_xbf_type_with_metadata("-123.45", &metadata).unwrap();
+        assert_eq!(
+            value,
+            XbfPrimitive::Decimal(XbfDecimal::new(-12_345, 2)).into()
+        );
+        let deserialized: String = from_xbf_type(&value).unwrap();
+        assert_eq!(deserialized, "-123.45");
+
+        assert!(matches!(
+            to_xbf_type_with_metadata("12e3", &metadata),
+            Err(Error::Custom(_))
+        ));
+    }
+
+    #[test]
+    fn char_and_half_floats_work() {
+        let metadata = XbfPrimitiveMetadata::Char.into();
+        let value = to_xbf_type_with_metadata(&'x', &metadata).unwrap();
+        assert_eq!(value, XbfPrimitive::Char('x').into());
+        assert_eq!(from_xbf_type::<char>(&value).unwrap(), 'x');
+        assert_eq!(
+            to_xbf_type(&'x').unwrap(),
+            XbfPrimitive::String("x".into()).into()
+        );
+
+        for (bits, expected) in [
+            (0x3c00, 1.0),
+            (0xc000, -2.0),
+            (0x0001, 2f32.powi(-24)),
+            (0xfc00, f32::NEG_INFINITY),
+        ] {
+            let value = XbfPrimitive::F16(bits).into();
+            assert_eq!(from_xbf_type::<f32>(&value).unwrap(), expected);
+        }
+        let value = XbfPrimitive::BF16(0x3fc0).into();
+        assert_eq!(from_xbf_type::<f32>(&value).unwrap(), 1.5);
+    }
+
+    #[cfg(feature = "half")]
+    #[test]
+    fn half_round_trip_works() {
+        let metadata = XbfPrimitiveMetadata::F16.into();
+        let native = half::f16::from_f32(-0.5);
+        let value = to_xbf_type_with_metadata(&native, &metadata).unwrap();
+        assert_eq!(value, XbfPrimitive::F16(native.to_bits()).into());
+        assert_eq!(from_xbf_type::<half::f16>(&value).unwrap(), native);
+
+        let metadata = XbfPrimitiveMetadata::BF16.into();
+        let native = half::bf16::from_f32(-0.5);
+        let value = to_xbf_type_with_metadata(&native, &metadata).unwrap();
+        assert_eq!(value, XbfPrimitive::BF16(native.to_bits()).into());
+        assert_eq!(from_xbf_type::<half::bf16>(&value).unwrap(), native);
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn uuid_round_trip_works() {
+        let metadata = XbfPrimitiveMetadata::Uuid.into();
+        let native = uuid::Uuid::from_u128(0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8);
+        let value = to_xbf_type_with_metadata(&native, &metadata).unwrap();
+        assert_eq!(value, XbfPrimitive::Uuid(native.into_bytes()).into());
+        assert_eq!(from_xbf_type::<uuid::Uuid>(&value).unwrap(), native);
+
+        assert!(matches!(
+            to_xbf_type_with_metadata("67e55044-10b1-426f-9247", &metadata),
+            Err(Error::Custom(_))
+        ));
+    }
+
+    #[test]
+    fn unsupported_types_fail() {
+        assert!(matches!(to_xbf_type(&()), Err(Error::Unsupported(_))));
+    }
+
+    #[test]
+    fn deserialize_wrong_type_fails() {
+        let value = XbfType::Primitive(XbfPrimitive::String("hello".to_string()));
+        assert!(from_xbf_type::<DragonRider>(&value).is_err());
+    }
+}
@@ -0,0 +1,511 @@
+use super::Error;
+use crate::{XbfArray, XbfEnum, XbfMap, XbfPrimitive, XbfStruct, XbfType, XbfVec};
+use serde::de::{self, value::BorrowedStrDeserializer, IntoDeserializer, Visitor};
+
+/// A serde [`Deserializer`](serde::Deserializer) that reads from an [`XbfType`].
+#[derive(Debug, Clone, Copy)]
+pub struct Deserializer<'de> {
+    value: &'de XbfType,
+}
+
+impl<'de> Deserializer<'de> {
+    /// Creates a deserializer reading from the given value.
+    pub fn new(value: &'de XbfType) -> Self {
+        Self { value }
+    }
+
+    fn invalid_type(&self, exp: &dyn de::Expected) -> Error {
+        let unexpected = match self.value {
+            XbfType::Primitive(x) => unexpected_primitive(x),
+            XbfType::Vec(_) => de::Unexpected::Seq,
+            XbfType::Struct(_) => de::Unexpected::Map,
+            XbfType::Option(_) => de::Unexpected::Option,
+            XbfType::Enum(_) => de::Unexpected::Enum,
+            XbfType::Map(_) => de::Unexpected::Map,
+            XbfType::Array(_) => de::Unexpected::Seq,
+            XbfType::Tensor(_) => de::Unexpected::Other("tensor"),
+        };
+        de::Error::invalid_type(unexpected, exp)
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            XbfType::Primitive(XbfPrimitive::Bytes(x)) => visitor.visit_borrowed_bytes(x),
+            XbfType::Primitive(XbfPrimitive::Uuid(x)) => visitor.visit_borrowed_bytes(x),
+            XbfType::Primitive(XbfPrimitive::String(x)) => visitor.visit_borrowed_str(x),
+            XbfType::Primitive(x) => visit_primitive(x, visitor),
+            XbfType::Vec(x) => visitor.visit_seq(SeqAccess::from_vec(x)),
+            XbfType::Struct(x) => visitor.visit_map(MapAccess::new(x)),
+            XbfType::Option(x) => match x.value() {
+                Some(x) => visitor.visit_some(Deserializer::new(x)),
+                None => visitor.visit_none(),
+            },
+            XbfType::Enum(x) => visitor.visit_enum(EnumAccess(x)),
+            XbfType::Map(x) => visitor.visit_map(EntryAccess::new(x)),
+            XbfType::Array(x) => visitor.visit_seq(SeqAccess::from_array(x)),
+            XbfType::Tensor(_) => Err(Error::Unsupported("Tensor")),
+        }
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if let XbfType::Primitive(XbfPrimitive::Char(x)) = self.value {
+            return visitor.visit_char(*x);
+        }
+        if let XbfType::Primitive(XbfPrimitive::String(x)) = self.value {
+            let mut chars = x.chars();
+            if let (Some(c), None) = (chars.next(), chars.next()) {
+                return visitor.visit_char(c);
+            }
+        }
+        Err(self.invalid_type(&visitor))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            XbfType::Option(_) => self.deserialize_any(visitor),
+            // values written without an option, such as by older versions, are always present
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            XbfType::Struct(x) if x.metadata.fields().len() == 0 => visitor.visit_unit(),
+            _ => Err(self.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match (name, self.value) {
+            // half's floats are newtypes of their bits, but also accept the float itself
+            ("f16", XbfType::Primitive(XbfPrimitive::F16(_)))
+            | ("bf16", XbfType::Primitive(XbfPrimitive::BF16(_))) => self.deserialize_any(visitor),
+            _ => visitor.visit_newtype_struct(self),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.value {
+            XbfType::Vec(x) => visitor.visit_seq(SeqAccess::from_vec(x)),
+            XbfType::Array(x) => visitor.visit_seq(SeqAccess::from_array(x)),
+            XbfType::Struct(x) => visitor.visit_seq(SeqAccess::from_struct(x)),
+            _ => Err(self.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.value {
+            XbfType::Enum(x) => visitor.visit_enum(EnumAccess(x)),
+            _ => Err(self.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 str string
+        bytes byte_buf seq map struct identifier
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for &'de XbfType {
+    type Deserializer = Deserializer<'de>;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        Deserializer::new(self)
+    }
+}
+
+/// Gives serde sequential access to the elements of a vector or array, or the fields of a struct.
+enum SeqAccess<'de> {
+    Values(std::slice::Iter<'de, XbfType>),
+    // the elements of a packed vector, decoded one at a time rather than unpacking the vector
+    Packed(Box<dyn ExactSizeIterator<Item = XbfPrimitive> + 'de>),
+}
+
+impl<'de> SeqAccess<'de> {
+    fn from_vec(vec: &'de XbfVec) -> Self {
+        match vec.as_primitive_buffer() {
+            Some(buffer) => SeqAccess::Packed(Box::new(buffer.iter())),
+            None => SeqAccess::Values(vec.iter()),
+        }
+    }
+
+    fn from_array(array: &'de XbfArray) -> Self {
+        SeqAccess::Values(array.iter())
+    }
+
+    fn from_struct(s: &'de XbfStruct) -> Self {
+        SeqAccess::Values(s.fields.iter())
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for SeqAccess<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        match self {
+            SeqAccess::Values(iter) => iter
+                .next()
+                .map(|x| seed.deserialize(Deserializer::new(x)))
+                .transpose(),
+            SeqAccess::Packed(iter) => iter
+                .next()
+                .map(|x| seed.deserialize(PrimitiveDeserializer(x)))
+                .transpose(),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        match self {
+            SeqAccess::Values(iter) => Some(iter.len()),
+            SeqAccess::Packed(iter) => Some(iter.len()),
+        }
+    }
+}
+
+/// A serde [`Deserializer`](serde::Deserializer) for an element of a packed vector, which owns
+/// the primitive it reads from as packed elements are decoded one at a time.
+struct PrimitiveDeserializer(XbfPrimitive);
+
+impl PrimitiveDeserializer {
+    fn invalid_type(&self, exp: &dyn de::Expected) -> Error {
+        de::Error::invalid_type(unexpected_primitive(&self.0), exp)
+    }
+}
+
+impl<'de> de::Deserializer<'de> for PrimitiveDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visit_primitive(&self.0, visitor)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            XbfPrimitive::Char(x) => visitor.visit_char(x),
+            _ => Err(self.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        Err(self.invalid_type(&visitor))
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        Err(self.invalid_type(&visitor))
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match (name, &self.0) {
+            ("f16", XbfPrimitive::F16(_)) | ("bf16", XbfPrimitive::BF16(_)) => {
+                self.deserialize_any(visitor)
+            }
+            _ => visitor.visit_newtype_struct(self),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        Err(self.invalid_type(&visitor))
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        Err(self.invalid_type(&visitor))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        Err(self.invalid_type(&visitor))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 str string
+        bytes byte_buf seq map struct identifier
+    }
+}
+
+/// Gives serde access to the fields of a struct by name.
+struct MapAccess<'de> {
+    names: Box<dyn ExactSizeIterator<Item = &'de str> + 'de>,
+    values: std::slice::Iter<'de, XbfType>,
+}
+
+impl<'de> MapAccess<'de> {
+    fn new(s: &'de XbfStruct) -> Self {
+        Self {
+            names: Box::new(s.metadata.fields().map(|(name, _)| name)),
+            values: s.fields.iter(),
+        }
+    }
+}
+
+impl<'de> de::MapAccess<'de> for MapAccess<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        self.names
+            .next()
+            .map(|name| seed.deserialize(BorrowedStrDeserializer::new(name)))
+            .transpose()
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.values.next() {
+            Some(x) => seed.deserialize(Deserializer::new(x)),
+            None => Err(de::Error::custom("value requested before key")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.names.len())
+    }
+}
+
+/// Gives serde access to the entries of a map.
+struct EntryAccess<'de> {
+    entries: Box<dyn ExactSizeIterator<Item = (&'de XbfType, &'de XbfType)> + 'de>,
+    value: Option<&'de XbfType>,
+}
+
+impl<'de> EntryAccess<'de> {
+    fn new(map: &'de XbfMap) -> Self {
+        Self {
+            entries: Box::new(map.iter()),
+            value: None,
+        }
+    }
+}
+
+impl<'de> de::MapAccess<'de> for EntryAccess<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        self.entries
+            .next()
+            .map(|(key, value)| {
+                self.value = Some(value);
+                seed.deserialize(Deserializer::new(key))
+            })
+            .transpose()
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.value.take() {
+            Some(x) => seed.deserialize(Deserializer::new(x)),
+            None => Err(de::Error::custom("value requested before key")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+/// Gives serde access to the variant of an enum, identified by name.
+struct EnumAccess<'de>(&'de XbfEnum);
+
+impl<'de> de::EnumAccess<'de> for EnumAccess<'de> {
+    type Error = Error;
+    type Variant = Deserializer<'de>;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let name = BorrowedStrDeserializer::<Error>::new(self.0.variant_name());
+        let variant = seed.deserialize(name)?;
+        Ok((variant, Deserializer::new(self.0.value())))
+    }
+}
+
+/// The value of a variant is deserialized in the same way as any other value, with unit variants
+/// holding an empty struct and tuple variants holding a struct with fields named `0`, `1`, ...
+impl<'de> de::VariantAccess<'de> for Deserializer<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        de::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+}
+
+/// Returns how a primitive is described when it is not of the expected type.
+fn unexpected_primitive(x: &XbfPrimitive) -> de::Unexpected<'_> {
+    match x {
+        XbfPrimitive::Bool(x) => de::Unexpected::Bool(*x),
+        XbfPrimitive::String(x) => de::Unexpected::Str(x),
+        XbfPrimitive::Bytes(x) => de::Unexpected::Bytes(x),
+        XbfPrimitive::F32(x) => de::Unexpected::Float(f64::from(*x)),
+        XbfPrimitive::F64(x) => de::Unexpected::Float(*x),
+        XbfPrimitive::F16(x) => de::Unexpected::Float(f16_to_f32(*x).into()),
+        XbfPrimitive::BF16(x) => de::Unexpected::Float(bf16_to_f32(*x).into()),
+        XbfPrimitive::Char(x) => de::Unexpected::Char(*x),
+        XbfPrimitive::Uuid(x) => de::Unexpected::Bytes(x),
+        XbfPrimitive::Decimal(_) => de::Unexpected::Other("decimal"),
+        _ => de::Unexpected::Other("integer"),
+    }
+}
+
+/// Visits a primitive without borrowing from it, as it may have been decoded from a packed vector.
+fn visit_primitive<'de, V: Visitor<'de>>(x: &XbfPrimitive, visitor: V) -> Result<V::Value, Error> {
+    match x {
+        XbfPrimitive::Bool(x) => visitor.visit_bool(*x),
+        XbfPrimitive::U8(x) => visitor.visit_u8(*x),
+        XbfPrimitive::U16(x) => visitor.visit_u16(*x),
+        XbfPrimitive::U32(x) => visitor.visit_u32(*x),
+        XbfPrimitive::U64(x) => visitor.visit_u64(*x),
+        XbfPrimitive::U128(x) => visitor.visit_u128(*x),
+        XbfPrimitive::U256(_) => Err(Error::Unsupported("U256")),
+        XbfPrimitive::I8(x) => visitor.visit_i8(*x),
+        XbfPrimitive::I16(x) => visitor.visit_i16(*x),
+        XbfPrimitive::I32(x) => visitor.visit_i32(*x),
+        XbfPrimitive::I64(x) => visitor.visit_i64(*x),
+        XbfPrimitive::I128(x) => visitor.visit_i128(*x),
+        XbfPrimitive::I256(_) => Err(Error::Unsupported("I256")),
+        XbfPrimitive::F32(x) => visitor.visit_f32(*x),
+        XbfPrimitive::F64(x) => visitor.visit_f64(*x),
+        XbfPrimitive::Bytes(x) => visitor.visit_bytes(x),
+        XbfPrimitive::Timestamp(x) => visitor.visit_i64(x.nanos()),
+        XbfPrimitive::TimestampTz(_) => Err(Error::Unsupported("TimestampTz")),
+        XbfPrimitive::Date(x) => visitor.visit_i32(x.days_since_epoch()),
+        XbfPrimitive::Time(x) => visitor.visit_u64(x.nanos_since_midnight()),
+        XbfPrimitive::Duration(x) => visitor.visit_i64(x.nanos()),
+        XbfPrimitive::Decimal(x) => visitor.visit_string(x.to_string()),
+        XbfPrimitive::Uuid(x) => visitor.visit_bytes(x),
+        XbfPrimitive::Char(x) => visitor.visit_char(*x),
+        XbfPrimitive::F16(x) => visitor.visit_f32(f16_to_f32(*x)),
+        XbfPrimitive::BF16(x) => visitor.visit_f32(bf16_to_f32(*x)),
+        XbfPrimitive::String(x) => visitor.visit_str(x),
+    }
+}
+
+/// Converts the bits of an IEEE 754 half precision float to an `f32`, which represents every
+/// value exactly.
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = u32::from(bits & 0x8000) << 16;
+    let exponent = u32::from(bits >> 10) & 0x1f;
+    let mantissa = u32::from(bits & 0x3ff);
+    let magnitude = match exponent {
+        // subnormal, scaled by 2^-24
+        0 => (mantissa as f32 / 16_777_216.0).to_bits(),
+        // infinity or NaN
+        0x1f => 0x7f80_0000 | mantissa << 13,
+        _ => (exponent + 112) << 23 | mantissa << 13,
+    };
+    f32::from_bits(sign | magnitude)
+}
+
+/// Converts the bits of a bfloat16 to an `f32`, of which it is the upper half.
+fn bf16_to_f32(bits: u16) -> f32 {
+    f32::from_bits(u32::from(bits) << 16)
+}
@@ -0,0 +1,111 @@
+use crate::{ArrayError, StructError, XbfError, XbfMetadata};
+use std::{
+    error,
+    fmt::{self, Display},
+    io,
+};
+
+/// Error type for serializing and deserializing with serde.
+#[derive(Debug)]
+pub enum Error {
+    /// A custom error message produced by a `Serialize` or `Deserialize` implementation.
+    Custom(String),
+    /// An error from the underlying writer.
+    Io(io::Error),
+    /// An error reading XBF data from the underlying reader.
+    Xbf(XbfError),
+    /// The value uses a part of the serde data model that XBF cannot represent.
+    Unsupported(&'static str),
+    /// The element type of an empty sequence, or the key and value types of an empty map, could
+    /// not be inferred.
+    UnknownElementType,
+    /// The inner type of `None` could not be inferred.
+    UnknownOptionType,
+    /// The variants of an enum could not be inferred.
+    UnknownEnumType,
+    /// The value does not match the expected metadata.
+    TypeMismatch {
+        expected: XbfMetadata,
+        actual: XbfMetadata,
+    },
+    /// The fields of a struct do not match the expected metadata.
+    Struct(StructError),
+    /// The elements of an array do not match the expected metadata.
+    Array(ArrayError),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Custom(msg) => f.write_str(msg),
+            Error::Io(e) => write!(f, "{e}"),
+            Error::Xbf(e) => write!(f, "{e}"),
+            Error::Unsupported(what) => write!(f, "{what} cannot be represented in XBF"),
+            Error::UnknownElementType => write!(
+                f,
+                "unable to infer the element type of an empty sequence or map, supply metadata instead"
+            ),
+            Error::UnknownOptionType => write!(
+                f,
+                "unable to infer the inner type of None, supply metadata instead"
+            ),
+            Error::UnknownEnumType => write!(
+                f,
+                "unable to infer the variants of an enum, supply metadata instead"
+            ),
+            Error::TypeMismatch { expected, actual } => {
+                write!(f, "Value is of type {actual:?}, expected {expected:?}")
+            }
+            Error::Struct(e) => write!(f, "{e}"),
+            Error::Array(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Xbf(e) => Some(e),
+            Error::Struct(e) => Some(e),
+            Error::Array(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Self {
+        Error::Io(value)
+    }
+}
+
+impl From<XbfError> for Error {
+    fn from(value: XbfError) -> Self {
+        Error::Xbf(value)
+    }
+}
+
+impl From<StructError> for Error {
+    fn from(value: StructError) -> Self {
+        Error::Struct(value)
+    }
+}
+
+impl From<ArrayError> for Error {
+    fn from(value: ArrayError) -> Self {
+        Error::Array(value)
+    }
+}
+
+impl serde::ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+}
+
+impl serde::de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+}
@@ -0,0 +1,645 @@
+use super::Error;
+use crate::{
+    XbfArray, XbfDate, XbfDecimal, XbfDuration, XbfEnum, XbfEnumMetadata, XbfMap, XbfMapMetadata,
+    XbfMetadata, XbfOption, XbfPrimitive, XbfPrimitiveMetadata, XbfStruct, XbfStructMetadata,
+    XbfTime, XbfTimestamp, XbfType, XbfTypeUpcast, XbfVec, XbfVecMetadata,
+};
+use indexmap::IndexMap;
+use serde::{ser, Serialize};
+
+/// A serde [`Serializer`](serde::Serializer) that produces [`XbfType`] values.
+///
+/// Without metadata the serializer infers the metadata of the value as it goes. When given
+/// metadata, the produced value is checked against it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Serializer<'m> {
+    expected: Option<&'m XbfMetadata>,
+}
+
+impl<'m> Serializer<'m> {
+    /// Creates a serializer that infers the metadata of the value.
+    pub fn new() -> Self {
+        Self { expected: None }
+    }
+
+    /// Creates a serializer that checks the value against the given metadata.
+    pub fn with_metadata(metadata: &'m XbfMetadata) -> Self {
+        Self {
+            expected: Some(metadata),
+        }
+    }
+
+    fn primitive(self, value: XbfPrimitive) -> Result<XbfType, Error> {
+        // these primitives have no serde equivalent, so accept the representations they are
+        // serialized with instead
+        let value = match (self.expected, value) {
+            (Some(XbfMetadata::Primitive(expected)), XbfPrimitive::I64(x)) => match expected {
+                XbfPrimitiveMetadata::Timestamp => XbfTimestamp::from_nanos(x).into(),
+                XbfPrimitiveMetadata::Duration => XbfDuration::from_nanos(x).into(),
+                _ => XbfPrimitive::I64(x),
+            },
+            (Some(XbfMetadata::Primitive(XbfPrimitiveMetadata::Date)), XbfPrimitive::I32(x)) => {
+                XbfDate::from_days_since_epoch(x).into()
+            }
+            (Some(XbfMetadata::Primitive(XbfPrimitiveMetadata::Time)), XbfPrimitive::U64(x)) => {
+                XbfTime::from_nanos_since_midnight(x).into()
+            }
+            (
+                Some(XbfMetadata::Primitive(XbfPrimitiveMetadata::Decimal)),
+                XbfPrimitive::String(x),
+            ) => x
+                .parse::<XbfDecimal>()
+                .map_err(<Error as ser::Error>::custom)?
+                .into(),
+            (Some(XbfMetadata::Primitive(XbfPrimitiveMetadata::Uuid)), XbfPrimitive::Bytes(x)) => {
+                match <[u8; 16]>::try_from(x) {
+                    Ok(x) => XbfPrimitive::Uuid(x),
+                    Err(x) => XbfPrimitive::Bytes(x),
+                }
+            }
+            (Some(XbfMetadata::Primitive(XbfPrimitiveMetadata::Uuid)), XbfPrimitive::String(x)) => {
+                parse_uuid(&x)
+                    .map(XbfPrimitive::Uuid)
+                    .ok_or_else(|| <Error as ser::Error>::custom(format!("invalid UUID {x:?}")))?
+            }
+            // half's floats are serialized as their bits
+            (Some(XbfMetadata::Primitive(XbfPrimitiveMetadata::F16)), XbfPrimitive::U16(x)) => {
+                XbfPrimitive::F16(x)
+            }
+            (Some(XbfMetadata::Primitive(XbfPrimitiveMetadata::BF16)), XbfPrimitive::U16(x)) => {
+                XbfPrimitive::BF16(x)
+            }
+            (_, value) => value,
+        };
+        self.check(value.into_base_type())
+    }
+
+    fn check(self, value: XbfType) -> Result<XbfType, Error> {
+        match self.expected {
+            Some(expected) => {
+                let actual = XbfMetadata::from(&value);
+                if *expected == actual {
+                    Ok(value)
+                } else {
+                    Err(Error::TypeMismatch {
+                        expected: expected.clone(),
+                        actual,
+                    })
+                }
+            }
+            None => Ok(value),
+        }
+    }
+
+    fn struct_serializer(self, name: &str, len: usize) -> SerializeStruct<'m> {
+        SerializeStruct {
+            name: name.into(),
+            expected: self.expected,
+            fields: IndexMap::with_capacity(len),
+        }
+    }
+
+    /// Looks up a variant of the expected enum, whose metadata cannot be inferred from a single
+    /// variant.
+    fn variant(self, name: &str, variant: &str) -> Result<Variant<'m>, Error> {
+        let metadata = match self.expected {
+            Some(XbfMetadata::Enum(x)) => x,
+            Some(expected) => {
+                return Err(ser::Error::custom(format_args!(
+                    "expected a value of type {expected:?}, found enum {name}"
+                )))
+            }
+            None => return Err(Error::UnknownEnumType),
+        };
+        match metadata.variant_index(variant) {
+            Some(index) => Ok(Variant { metadata, index }),
+            None => Err(ser::Error::custom(format_args!(
+                "enum {} has no variant named {variant}",
+                metadata.name()
+            ))),
+        }
+    }
+}
+
+/// A variant of the enum being serialized.
+#[derive(Clone, Copy)]
+struct Variant<'m> {
+    metadata: &'m XbfEnumMetadata,
+    index: u16,
+}
+
+impl<'m> Variant<'m> {
+    fn serializer(self) -> Serializer<'m> {
+        let (_, variant_type) = self
+            .metadata
+            .get_variant(self.index)
+            .expect("the index was looked up by name");
+        Serializer::with_metadata(variant_type)
+    }
+
+    fn wrap(self, value: XbfType) -> XbfType {
+        XbfEnum::new_unchecked(self.metadata.clone(), self.index, value).into_base_type()
+    }
+}
+
+impl<'m> ser::Serializer for Serializer<'m> {
+    type Ok = XbfType;
+    type Error = Error;
+
+    type SerializeSeq = SerializeSeq<'m>;
+    type SerializeTuple = SerializeTuple<'m>;
+    type SerializeTupleStruct = SerializeStruct<'m>;
+    type SerializeTupleVariant = SerializeVariant<'m>;
+    type SerializeMap = SerializeMap<'m>;
+    type SerializeStruct = SerializeStruct<'m>;
+    type SerializeStructVariant = SerializeVariant<'m>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+        self.primitive(XbfPrimitive::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+        self.primitive(XbfPrimitive::I8(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+        self.primitive(XbfPrimitive::I16(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        self.primitive(XbfPrimitive::I32(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+        self.primitive(XbfPrimitive::I64(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        self.primitive(XbfPrimitive::I128(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        self.primitive(XbfPrimitive::U8(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        self.primitive(XbfPrimitive::U16(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        self.primitive(XbfPrimitive::U32(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        self.primitive(XbfPrimitive::U64(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        self.primitive(XbfPrimitive::U128(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+        self.primitive(XbfPrimitive::F32(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+        self.primitive(XbfPrimitive::F64(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        match self.expected {
+            Some(XbfMetadata::Primitive(XbfPrimitiveMetadata::Char)) => {
+                self.primitive(XbfPrimitive::Char(v))
+            }
+            _ => self.primitive(XbfPrimitive::String(v.to_string())),
+        }
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        self.primitive(XbfPrimitive::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        self.primitive(XbfPrimitive::Bytes(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        match self.expected {
+            Some(XbfMetadata::Option(x)) => Ok(XbfOption::none((*x.inner_type).clone()).into()),
+            Some(_) => Err(Error::Unsupported("Option::None")),
+            None => Err(Error::UnknownOptionType),
+        }
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        match self.expected {
+            Some(XbfMetadata::Option(x)) => {
+                let value = value.serialize(Serializer::with_metadata(&x.inner_type))?;
+                Ok(XbfOption::some(value).into())
+            }
+            // metadata without an option means the value is always present
+            Some(_) => value.serialize(self),
+            None => Ok(XbfOption::some(value.serialize(self)?).into()),
+        }
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Err(Error::Unsupported("()"))
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok, Self::Error> {
+        ser::SerializeStruct::end(self.struct_serializer(name, 0))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        let variant_info = self.variant(name, variant)?;
+        let value = variant_info.serializer().serialize_unit_struct(variant)?;
+        Ok(variant_info.wrap(value))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        let variant = self.variant(name, variant)?;
+        let value = value.serialize(variant.serializer())?;
+        Ok(variant.wrap(value))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        let element_metadata = match self.expected {
+            Some(XbfMetadata::Vec(x)) => Some((*x.inner_type).clone()),
+            Some(XbfMetadata::Array(x)) => Some((*x.inner_type).clone()),
+            _ => None,
+        };
+        Ok(SerializeSeq {
+            expected: self.expected,
+            element_metadata,
+            elements: Vec::with_capacity(len.unwrap_or_default()),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        let inner = match self.expected {
+            Some(XbfMetadata::Array(_)) => TupleInner::Seq(self.serialize_seq(Some(len))?),
+            _ => TupleInner::Struct(self.struct_serializer("Tuple", len)),
+        };
+        Ok(SerializeTuple { inner })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Ok(self.struct_serializer(name, len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        let variant_info = self.variant(name, variant)?;
+        Ok(SerializeVariant {
+            variant: variant_info,
+            payload: variant_info.serializer().struct_serializer(variant, len),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        let (key_metadata, value_metadata) = match self.expected {
+            Some(XbfMetadata::Map(x)) => {
+                (Some((*x.key_type).clone()), Some((*x.value_type).clone()))
+            }
+            _ => (None, None),
+        };
+        Ok(SerializeMap {
+            expected: self.expected,
+            key_metadata,
+            value_metadata,
+            entries: Vec::with_capacity(len.unwrap_or_default()),
+            next_key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(self.struct_serializer(name, len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        let variant_info = self.variant(name, variant)?;
+        Ok(SerializeVariant {
+            variant: variant_info,
+            payload: variant_info.serializer().struct_serializer(variant, len),
+        })
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Serializes a sequence into an [`XbfVec`], or into an [`XbfArray`] if one is expected.
+///
+/// The metadata of the first element is used for the rest of the sequence, so every element of a
+/// vector of structs shares the same struct metadata.
+pub struct SerializeSeq<'m> {
+    expected: Option<&'m XbfMetadata>,
+    element_metadata: Option<XbfMetadata>,
+    elements: Vec<XbfType>,
+}
+
+impl ser::SerializeSeq for SerializeSeq<'_> {
+    type Ok = XbfType;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        let serializer = Serializer {
+            expected: self.element_metadata.as_ref(),
+        };
+        let element = value.serialize(serializer)?;
+        if self.element_metadata.is_none() {
+            self.element_metadata = Some(XbfMetadata::from(&element));
+        }
+        self.elements.push(element);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        if let Some(XbfMetadata::Array(expected)) = self.expected {
+            return Ok(XbfArray::new(expected.clone(), self.elements)?.into_base_type());
+        }
+        let metadata = match (self.expected, self.element_metadata) {
+            (Some(XbfMetadata::Vec(expected)), _) => expected.clone(),
+            (_, Some(element_metadata)) => XbfVecMetadata::new(element_metadata),
+            (_, None) => return Err(Error::UnknownElementType),
+        };
+        let value = XbfVec::new_unchecked(metadata, self.elements).into_base_type();
+        Serializer {
+            expected: self.expected,
+        }
+        .check(value)
+    }
+}
+
+/// Serializes a map into an [`XbfMap`].
+///
+/// As with sequences, the metadata of the first key and value is used for the rest of the map.
+pub struct SerializeMap<'m> {
+    expected: Option<&'m XbfMetadata>,
+    key_metadata: Option<XbfMetadata>,
+    value_metadata: Option<XbfMetadata>,
+    entries: Vec<(XbfType, XbfType)>,
+    next_key: Option<XbfType>,
+}
+
+impl ser::SerializeMap for SerializeMap<'_> {
+    type Ok = XbfType;
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Self::Error> {
+        let serializer = Serializer {
+            expected: self.key_metadata.as_ref(),
+        };
+        let key = key.serialize(serializer)?;
+        if self.key_metadata.is_none() {
+            self.key_metadata = Some(XbfMetadata::from(&key));
+        }
+        self.next_key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        let Some(key) = self.next_key.take() else {
+            return Err(ser::Error::custom("value serialized before key"));
+        };
+        let serializer = Serializer {
+            expected: self.value_metadata.as_ref(),
+        };
+        let value = value.serialize(serializer)?;
+        if self.value_metadata.is_none() {
+            self.value_metadata = Some(XbfMetadata::from(&value));
+        }
+        self.entries.push((key, value));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        let metadata = match (self.expected, self.key_metadata, self.value_metadata) {
+            (Some(XbfMetadata::Map(expected)), _, _) => expected.clone(),
+            (_, Some(key_metadata), Some(value_metadata)) => {
+                XbfMapMetadata::new(key_metadata, value_metadata)
+            }
+            _ => return Err(Error::UnknownElementType),
+        };
+        let value = XbfMap::new_unchecked(metadata, self.entries).into_base_type();
+        Serializer {
+            expected: self.expected,
+        }
+        .check(value)
+    }
+}
+
+/// Serializes a struct, tuple, or tuple struct into an [`XbfStruct`].
+///
+/// Tuples have their fields named after their position, starting from `0`.
+pub struct SerializeStruct<'m> {
+    name: Box<str>,
+    expected: Option<&'m XbfMetadata>,
+    fields: IndexMap<Box<str>, XbfType>,
+}
+
+impl SerializeStruct<'_> {
+    fn push_field<T: ?Sized + Serialize>(&mut self, key: &str, value: &T) -> Result<(), Error> {
+        let serializer = match self.expected {
+            Some(XbfMetadata::Struct(expected)) => match expected.get_field_type(key) {
+                Some(field_type) => Serializer::with_metadata(field_type),
+                None => {
+                    return Err(ser::Error::custom(format_args!(
+                        "struct {} has no field named {key}",
+                        expected.name()
+                    )))
+                }
+            },
+            _ => Serializer::new(),
+        };
+        let field = value.serialize(serializer)?;
+        self.fields.insert(key.into(), field);
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<XbfType, Error> {
+        let value = match self.expected {
+            Some(XbfMetadata::Struct(expected)) => {
+                let fields = expected
+                    .fields()
+                    .map(|(name, _)| {
+                        self.fields.swap_remove(name).ok_or_else(|| {
+                            ser::Error::custom(format_args!(
+                                "missing field {name} of struct {}",
+                                expected.name()
+                            ))
+                        })
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                XbfStruct::new(expected.clone(), fields)?
+            }
+            _ => {
+                let metadata = XbfStructMetadata::new(
+                    self.name,
+                    self.fields
+                        .iter()
+                        .map(|(k, v)| (k.clone(), XbfMetadata::from(v)))
+                        .collect::<IndexMap<_, _>>(),
+                );
+                XbfStruct::new_unchecked(metadata, self.fields.into_values())
+            }
+        };
+        Serializer {
+            expected: self.expected,
+        }
+        .check(value.into_base_type())
+    }
+}
+
+impl ser::SerializeStruct for SerializeStruct<'_> {
+    type Ok = XbfType;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.push_field(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+/// Serializes a tuple into an [`XbfStruct`], or into an [`XbfArray`] if one is expected.
+///
+/// Fixed size arrays such as `[T; N]` are serialized by serde as tuples.
+pub struct SerializeTuple<'m> {
+    inner: TupleInner<'m>,
+}
+
+enum TupleInner<'m> {
+    Struct(SerializeStruct<'m>),
+    Seq(SerializeSeq<'m>),
+}
+
+impl ser::SerializeTuple for SerializeTuple<'_> {
+    type Ok = XbfType;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        match &mut self.inner {
+            TupleInner::Struct(x) => x.push_field(&x.fields.len().to_string(), value),
+            TupleInner::Seq(x) => ser::SerializeSeq::serialize_element(x, value),
+        }
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        match self.inner {
+            TupleInner::Struct(x) => x.finish(),
+            TupleInner::Seq(x) => ser::SerializeSeq::end(x),
+        }
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeStruct<'_> {
+    type Ok = XbfType;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push_field(&self.fields.len().to_string(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.finish()
+    }
+}
+
+/// Serializes a tuple or struct variant into an [`XbfEnum`] whose value is a struct.
+///
+/// As with tuples, the fields of a tuple variant are named after their position.
+pub struct SerializeVariant<'m> {
+    variant: Variant<'m>,
+    payload: SerializeStruct<'m>,
+}
+
+impl ser::SerializeTupleVariant for SerializeVariant<'_> {
+    type Ok = XbfType;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.payload
+            .push_field(&self.payload.fields.len().to_string(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.variant.wrap(self.payload.finish()?))
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeVariant<'_> {
+    type Ok = XbfType;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.payload.push_field(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.variant.wrap(self.payload.finish()?))
+    }
+}
+
+/// Parses a UUID in its hyphenated or simple hexadecimal form.
+fn parse_uuid(s: &str) -> Option<[u8; 16]> {
+    let hex = s.bytes().filter(|&b| b != b'-').collect::<Vec<_>>();
+    if hex.len() != 32 || !matches!(s.len(), 32 | 36) {
+        return None;
+    }
+    let mut uuid = [0; 16];
+    for (byte, pair) in uuid.iter_mut().zip(hex.chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(uuid)
+}
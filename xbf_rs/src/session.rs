@@ -0,0 +1,340 @@
+//! A session layer which sends the metadata of each type of value only once per stream.
+
+use crate::{XbfError, XbfErrorKind, XbfMetadata, XbfReader, XbfType};
+use byteorder::{LittleEndian, WriteBytesExt};
+use std::{
+    collections::HashMap,
+    io::{self, Read, Write},
+};
+
+/// The tag of a record defining the metadata of a schema.
+const DEFINITION_TAG: u8 = 0;
+/// The tag of a record containing a value of a previously defined schema.
+const DATA_TAG: u8 = 1;
+
+/// A writer which sends values over a session, sending the metadata of each distinct type of
+/// value only the first time a value of that type is written.
+///
+/// A session is a stream of records, each of which is either a definition, associating a schema
+/// ID with some metadata, or a value, referencing the schema ID of its metadata. Schema IDs are
+/// assigned in the order types are first written, starting from 0. Values are read back with an
+/// [`XbfSessionReader`].
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::prelude::*;
+/// use xbf_rs::{XbfReader, XbfSessionReader, XbfSessionWriter};
+///
+/// let mut writer = XbfSessionWriter::new(vec![]);
+/// assert_eq!(writer.write(&1u32.into_xbf_primitive().into_base_type()).unwrap(), 0);
+/// assert_eq!(writer.write(&"Eragon".to_string().into_xbf_primitive().into_base_type()).unwrap(), 1);
+/// assert_eq!(writer.write(&2u32.into_xbf_primitive().into_base_type()).unwrap(), 0);
+/// let data = writer.into_inner();
+///
+/// let mut reader = XbfSessionReader::new(XbfReader::new(data.as_slice()));
+/// let mut sum = 0;
+/// while let Some((schema_id, value)) = reader.read_value().unwrap() {
+///     if schema_id == 0 {
+///         sum += value.as_u32().unwrap();
+///     }
+/// }
+/// assert_eq!(sum, 3);
+/// ```
+#[derive(Debug)]
+pub struct XbfSessionWriter<W> {
+    writer: W,
+    // indexed by schema ID
+    schemas: Vec<XbfMetadata>,
+}
+
+impl<W: Write> XbfSessionWriter<W> {
+    /// Creates a writer for a new session with no schemas defined.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            schemas: vec![],
+        }
+    }
+
+    /// Returns the schema ID of the given metadata, if it has been defined.
+    pub fn schema_id(&self, metadata: &XbfMetadata) -> Option<u32> {
+        self.schemas
+            .iter()
+            .position(|x| x == metadata)
+            .map(|id| id as u32)
+    }
+
+    /// Returns the metadata of the given schema ID, if it has been defined.
+    pub fn metadata(&self, schema_id: u32) -> Option<&XbfMetadata> {
+        self.schemas.get(schema_id as usize)
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Consumes this writer, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Defines a schema for the given metadata, returning its schema ID.
+    ///
+    /// Nothing is written if the metadata has already been defined. Defining a schema up front
+    /// is never required, as [`XbfSessionWriter::write`] defines schemas as needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::Error`] if writing to the underlying writer fails, or if every schema ID
+    /// is already in use.
+    pub fn define(&mut self, metadata: &XbfMetadata) -> io::Result<u32> {
+        if let Some(schema_id) = self.schema_id(metadata) {
+            return Ok(schema_id);
+        }
+        let schema_id = u32::try_from(self.schemas.len()).map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidInput, "every schema ID is in use")
+        })?;
+        self.writer.write_u8(DEFINITION_TAG)?;
+        self.writer.write_u32::<LittleEndian>(schema_id)?;
+        metadata.serialize_base_metadata(&mut self.writer)?;
+        self.schemas.push(metadata.clone());
+        Ok(schema_id)
+    }
+
+    /// Writes a value, defining a schema for its metadata first if needed, and returns the
+    /// schema ID of its metadata.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::Error`] if writing to the underlying writer fails, or if a schema needs
+    /// to be defined and every schema ID is already in use.
+    pub fn write(&mut self, value: &XbfType) -> io::Result<u32> {
+        let schema_id = self.define(&value.into())?;
+        self.writer.write_u8(DATA_TAG)?;
+        self.writer.write_u32::<LittleEndian>(schema_id)?;
+        value.serialize_base_type(&mut self.writer)?;
+        Ok(schema_id)
+    }
+}
+
+/// A reader which reads values sent over a session by an [`XbfSessionWriter`], remembering the
+/// metadata of every schema defined so far.
+///
+/// The limits of the underlying [`XbfReader`] apply to the whole session, including the
+/// definitions of schemas.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::prelude::*;
+/// use xbf_rs::{XbfReader, XbfSessionReader, XbfSessionWriter};
+///
+/// let value = 42u16.into_xbf_primitive().into_base_type();
+/// let mut writer = XbfSessionWriter::new(vec![]);
+/// writer.write(&value).unwrap();
+/// let data = writer.into_inner();
+///
+/// let mut reader = XbfSessionReader::new(XbfReader::new(data.as_slice()));
+/// assert_eq!(reader.read_value().unwrap(), Some((0, value)));
+/// assert_eq!(reader.read_value().unwrap(), None);
+/// ```
+#[derive(Debug)]
+pub struct XbfSessionReader<R> {
+    reader: XbfReader<R>,
+    schemas: HashMap<u32, XbfMetadata>,
+}
+
+impl<R: Read> XbfSessionReader<R> {
+    /// Creates a reader for a new session with no schemas defined.
+    pub fn new(reader: XbfReader<R>) -> Self {
+        Self {
+            reader,
+            schemas: HashMap::new(),
+        }
+    }
+
+    /// Returns the metadata of the given schema ID, if it has been defined.
+    pub fn metadata(&self, schema_id: u32) -> Option<&XbfMetadata> {
+        self.schemas.get(&schema_id)
+    }
+
+    /// Returns a reference to the underlying [`XbfReader`].
+    pub fn get_ref(&self) -> &XbfReader<R> {
+        &self.reader
+    }
+
+    /// Consumes this reader, returning the underlying [`XbfReader`].
+    pub fn into_inner(self) -> XbfReader<R> {
+        self.reader
+    }
+
+    /// Reads the next value in the session, along with the schema ID of its metadata. Any
+    /// schema definitions before the value are read and remembered.
+    ///
+    /// Returns `None` if the session ends cleanly, between two records.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails, a record is invalid, a value references a
+    /// schema which has not been defined, a schema is defined more than once, or a limit is
+    /// exceeded.
+    pub fn read_value(&mut self) -> Result<Option<(u32, XbfType)>, XbfError> {
+        loop {
+            let offset = self.reader.position();
+            let tag = match self.reader.read_u8() {
+                Ok(tag) => tag,
+                Err(e) if matches!(e.kind(), XbfErrorKind::UnexpectedEof) => return Ok(None),
+                Err(e) => return Err(e),
+            };
+            match tag {
+                DEFINITION_TAG => {
+                    let schema_id = self.reader.read_u32()?;
+                    if self.schemas.contains_key(&schema_id) {
+                        return Err(XbfError::new(
+                            XbfErrorKind::DuplicateSchema(schema_id),
+                            offset,
+                        ));
+                    }
+                    let metadata = self.reader.read_metadata()?;
+                    self.schemas.insert(schema_id, metadata);
+                }
+                DATA_TAG => {
+                    let schema_id = self.reader.read_u32()?;
+                    let Some(metadata) = self.schemas.get(&schema_id) else {
+                        return Err(XbfError::new(
+                            XbfErrorKind::UnknownSchema(schema_id),
+                            offset,
+                        ));
+                    };
+                    let value = self.reader.read_value(metadata)?;
+                    return Ok(Some((schema_id, value)));
+                }
+                _ => return Err(XbfError::new(XbfErrorKind::UnknownRecordTag(tag), offset)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        DeserializeOptions, XbfLimit, XbfPrimitive, XbfPrimitiveMetadata, XbfStruct,
+        XbfStructMetadata, XbfTypeUpcast,
+    };
+    use indexmap::indexmap;
+
+    fn rider(name: &str, age: u16) -> XbfType {
+        let metadata = XbfStructMetadata::new(
+            "DragonRider",
+            indexmap! {
+                "name" => XbfPrimitiveMetadata::String.into(),
+                "age" => XbfPrimitiveMetadata::U16.into(),
+            },
+        );
+        XbfStruct::new(
+            metadata,
+            [
+                XbfPrimitive::String(name.to_string()).into(),
+                XbfPrimitive::U16(age).into(),
+            ],
+        )
+        .unwrap()
+        .into_base_type()
+    }
+
+    #[test]
+    fn metadata_is_sent_once() {
+        let mut writer = XbfSessionWriter::new(vec![]);
+        writer.write(&rider("Eragon", 16)).unwrap();
+        let first_len = writer.get_ref().len();
+        writer.write(&rider("Arya", 103)).unwrap();
+        writer
+            .write(&XbfPrimitive::U8(42).into_base_type())
+            .unwrap();
+        let data = writer.into_inner();
+
+        let mut value = vec![];
+        rider("Arya", 103).serialize_base_type(&mut value).unwrap();
+        // the second rider is only a tag, schema ID and the value itself
+        assert_eq!(data[first_len], DATA_TAG);
+        assert_eq!(&data[first_len + 5..first_len + 5 + value.len()], value);
+
+        let mut reader = XbfSessionReader::new(XbfReader::new(data.as_slice()));
+        let mut values = vec![];
+        while let Some(record) = reader.read_value().unwrap() {
+            values.push(record);
+        }
+        assert_eq!(
+            values,
+            [
+                (0, rider("Eragon", 16)),
+                (0, rider("Arya", 103)),
+                (1, XbfPrimitive::U8(42).into_base_type())
+            ]
+        );
+        assert_eq!(reader.metadata(1), Some(&XbfPrimitiveMetadata::U8.into()));
+    }
+
+    #[test]
+    fn invalid_records_fail() {
+        let mut data = vec![DATA_TAG];
+        data.extend_from_slice(&7u32.to_le_bytes());
+        let mut reader = XbfSessionReader::new(XbfReader::new(data.as_slice()));
+        let err = reader.read_value().unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::UnknownSchema(7)));
+
+        let mut reader = XbfSessionReader::new(XbfReader::new([2u8].as_slice()));
+        let err = reader.read_value().unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::UnknownRecordTag(2)));
+
+        let mut writer = XbfSessionWriter::new(vec![]);
+        writer.define(&XbfPrimitiveMetadata::U8.into()).unwrap();
+        let mut data = writer.into_inner();
+        data.extend(data.clone());
+        let mut reader = XbfSessionReader::new(XbfReader::new(data.as_slice()));
+        let err = reader.read_value().unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::DuplicateSchema(0)));
+        assert_eq!(err.offset(), 6);
+    }
+
+    #[test]
+    fn truncated_record_fails() {
+        let mut writer = XbfSessionWriter::new(vec![]);
+        writer.write(&rider("Eragon", 16)).unwrap();
+        let data = writer.into_inner();
+
+        let mut reader = XbfSessionReader::new(XbfReader::new(&data[..data.len() - 1]));
+        let err = reader.read_value().unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::UnexpectedEof));
+        assert_eq!(err.path(), "DragonRider.age");
+    }
+
+    #[test]
+    fn limits_apply_across_session() {
+        let mut writer = XbfSessionWriter::new(vec![]);
+        for _ in 0..3 {
+            writer
+                .write(&XbfPrimitive::U64(42).into_base_type())
+                .unwrap();
+        }
+        let data = writer.into_inner();
+
+        // the definition and two values fit, the third value does not
+        let options = DeserializeOptions::new().max_total_bytes(6 + 2 * 13);
+        let reader = XbfReader::with_options(data.as_slice(), options);
+        let mut reader = XbfSessionReader::new(reader);
+        assert!(reader.read_value().unwrap().is_some());
+        assert!(reader.read_value().unwrap().is_some());
+        let err = reader.read_value().unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            XbfErrorKind::LimitExceeded {
+                limit: XbfLimit::TotalBytes,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,1581 @@
+//! Borrowed views of serialized XBF values.
+
+use crate::{
+    reader::XbfReader, xbf_tensor::element_count, FromXbfError, OnceType, RcType, XbfArray,
+    XbfArrayMetadata, XbfDate, XbfDecimal, XbfDuration, XbfEnum, XbfEnumMetadata, XbfError,
+    XbfErrorKind, XbfI256, XbfMap, XbfMapMetadata, XbfMetadata, XbfOption, XbfOptionMetadata,
+    XbfPrimitive, XbfPrimitiveBuffer, XbfPrimitiveMetadata, XbfStruct, XbfStructMetadata,
+    XbfTensor, XbfTensorMetadata, XbfTime, XbfTimestamp, XbfTimestampTz, XbfType, XbfU256, XbfVec,
+    XbfVecMetadata,
+};
+use indexmap::{map::Entry, IndexMap};
+use std::ops::Range;
+
+/// A borrowed view of a serialized XBF value.
+///
+/// Parsing a value validates the entire value up front, only allocating to find duplicate map
+/// keys. Strings and bytes borrow directly from the input, and the elements of vectors and the
+/// fields of structs are only decoded when they are accessed.
+///
+/// Elements of a vector or array whose type has a [fixed size](XbfMetadata::fixed_size) can be
+/// accessed in constant time. Otherwise the first access of an element or field skips over the
+/// data to record where each one starts, so that later accesses through the same view are also
+/// constant time. Clones of a view share what it records, including the views returned by
+/// accessors such as [`as_vec`](Self::as_vec), so `value.as_vec()?.get(i)` only skips over the
+/// elements of `value` once.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::{XbfPrimitiveMetadata, XbfStructMetadata, XbfValueRef, XbfVecMetadata};
+/// use indexmap::indexmap;
+///
+/// let metadata = XbfVecMetadata::new(XbfStructMetadata::new(
+///     "DragonRider",
+///     indexmap! {
+///         "name" => XbfPrimitiveMetadata::String.into(),
+///         "age" => XbfPrimitiveMetadata::U16.into(),
+///     },
+/// ))
+/// .into();
+///
+/// let mut data = vec![];
+/// data.extend_from_slice(&1u64.to_le_bytes());
+/// data.extend_from_slice(&6u64.to_le_bytes());
+/// data.extend_from_slice(b"Eragon");
+/// data.extend_from_slice(&16u16.to_le_bytes());
+///
+/// let (value, rest) = XbfValueRef::parse(&metadata, &data).unwrap();
+/// assert!(rest.is_empty());
+///
+/// let rider = value.as_vec().unwrap().get(0).unwrap();
+/// let name = rider.as_struct().unwrap().get("name").unwrap();
+///
+/// // borrowed straight from `data`
+/// assert_eq!(name.as_str(), Ok("Eragon"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum XbfValueRef<'a> {
+    Primitive(XbfPrimitiveRef<'a>),
+    Vec(XbfVecRef<'a>),
+    Struct(XbfStructRef<'a>),
+    Option(XbfOptionRef<'a>),
+    Enum(XbfEnumRef<'a>),
+    Map(XbfMapRef<'a>),
+    Array(XbfArrayRef<'a>),
+    Tensor(XbfTensorRef<'a>),
+}
+
+/// A borrowed view of a serialized XBF primitive.
+///
+/// This is the same as an [`XbfPrimitive`], except that strings and bytes borrow from the input.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum XbfPrimitiveRef<'a> {
+    Bool(bool),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    U256(XbfU256),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    I256(XbfI256),
+    F32(f32),
+    F64(f64),
+    Bytes(&'a [u8]),
+    Timestamp(XbfTimestamp),
+    TimestampTz(XbfTimestampTz),
+    Date(XbfDate),
+    Time(XbfTime),
+    Duration(XbfDuration),
+    Decimal(XbfDecimal),
+    Uuid([u8; 16]),
+    Char(char),
+    F16(u16),
+    BF16(u16),
+    String(&'a str),
+}
+
+/// A borrowed view of a serialized XBF vector.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XbfVecRef<'a> {
+    metadata: &'a XbfVecMetadata,
+    len: usize,
+    elements: &'a [u8],
+    offsets: Offsets,
+}
+
+/// A borrowed view of a serialized XBF struct.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XbfStructRef<'a> {
+    metadata: &'a XbfStructMetadata,
+    fields: &'a [u8],
+    offsets: Offsets,
+}
+
+/// A borrowed view of a serialized XBF option.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct XbfOptionRef<'a> {
+    metadata: &'a XbfOptionMetadata,
+    value: Option<&'a [u8]>,
+}
+
+/// A borrowed view of a serialized XBF enum.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct XbfEnumRef<'a> {
+    metadata: &'a XbfEnumMetadata,
+    variant: u16,
+    value: &'a [u8],
+}
+
+/// A borrowed view of a serialized XBF map.
+///
+/// Like an [`XbfMap`], if a key appears more than once the map has a single entry for it, where
+/// the key first appears and with the value of its last entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XbfMapRef<'a> {
+    metadata: &'a XbfMapMetadata,
+    len: usize,
+    entries: &'a [u8],
+    deduped: Option<DedupedEntries<'a>>,
+}
+
+/// Each distinct key of a map with duplicate keys, and the offset of the value of its last entry.
+type DedupedEntries<'a> = RcType<[(&'a [u8], usize)]>;
+
+/// A borrowed view of a serialized XBF array.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XbfArrayRef<'a> {
+    metadata: &'a XbfArrayMetadata,
+    elements: &'a [u8],
+    offsets: Offsets,
+}
+
+/// A borrowed view of a serialized XBF tensor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct XbfTensorRef<'a> {
+    metadata: &'a XbfTensorMetadata,
+    // the serialized dimensions, empty if the shape is fixed in the metadata
+    shape: &'a [u8],
+    len: usize,
+    elements: &'a [u8],
+}
+
+/// Where each element of a vector or array, or each field of a struct, starts in its data.
+///
+/// The offsets are only found the first time they are needed, by skipping over each value. They
+/// are a cache which is shared between clones of a view, and ignored when comparing views.
+#[derive(Debug, Clone, Default)]
+struct Offsets(RcType<OnceType<Box<[usize]>>>);
+
+impl Offsets {
+    /// Returns the range of `data` holding the value at `index`, where `types` describes each
+    /// value in `data`.
+    fn range<'a>(
+        &self,
+        data: &'a [u8],
+        types: impl Iterator<Item = &'a XbfMetadata>,
+        index: usize,
+    ) -> Range<usize> {
+        let offsets = self.0.get_or_init(|| {
+            let mut rest = data;
+            types
+                .map(|metadata| {
+                    let offset = data.len() - rest.len();
+                    rest = XbfValueRef::skip(metadata, rest);
+                    offset
+                })
+                .collect()
+        });
+        let end = offsets.get(index + 1).copied().unwrap_or(data.len());
+        offsets[index]..end
+    }
+}
+
+impl PartialEq for Offsets {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl<'a> XbfValueRef<'a> {
+    /// Parses a value described by `metadata` from the start of `data`, returning a view of the
+    /// value along with the remaining data following it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the data is not a valid value of the given type.
+    pub fn parse(
+        metadata: &'a XbfMetadata,
+        data: &'a [u8],
+    ) -> Result<(XbfValueRef<'a>, &'a [u8]), XbfError> {
+        let mut reader = XbfReader::new(data);
+        let value = reader.read_value_ref(metadata)?;
+        Ok((value, reader.into_inner()))
+    }
+
+    pub(crate) fn read(
+        metadata: &'a XbfMetadata,
+        reader: &mut XbfReader<&'a [u8]>,
+    ) -> Result<XbfValueRef<'a>, XbfError> {
+        match metadata {
+            XbfMetadata::Primitive(x) => {
+                XbfPrimitiveRef::read(x, reader).map(XbfValueRef::Primitive)
+            }
+            XbfMetadata::Vec(x) => XbfVecRef::read(x, reader).map(XbfValueRef::Vec),
+            XbfMetadata::Struct(x) => XbfStructRef::read(x, reader).map(XbfValueRef::Struct),
+            XbfMetadata::Option(x) => XbfOptionRef::read(x, reader).map(XbfValueRef::Option),
+            XbfMetadata::Enum(x) => XbfEnumRef::read(x, reader).map(XbfValueRef::Enum),
+            XbfMetadata::Map(x) => XbfMapRef::read(x, reader).map(XbfValueRef::Map),
+            XbfMetadata::Array(x) => XbfArrayRef::read(x, reader).map(XbfValueRef::Array),
+            XbfMetadata::Tensor(x) => XbfTensorRef::read(x, reader).map(XbfValueRef::Tensor),
+        }
+    }
+
+    /// Splits a value from the start of data which is already known to be valid.
+    fn split(metadata: &'a XbfMetadata, data: &'a [u8]) -> (XbfValueRef<'a>, &'a [u8]) {
+        let rest = Self::skip(metadata, data);
+        (Self::view(metadata, &data[..data.len() - rest.len()]), rest)
+    }
+
+    /// Skips over a value at the start of data which is already known to be valid, returning
+    /// the data following it.
+    fn skip(metadata: &XbfMetadata, data: &'a [u8]) -> &'a [u8] {
+        let mut reader = XbfReader::new(data);
+        reader
+            .skip(metadata)
+            .expect("data was validated when parsed");
+        reader.into_inner()
+    }
+
+    /// Creates a view of a value from exactly its data, which is already known to be valid.
+    ///
+    /// Unlike [`Self::read`], the contents of vectors, structs and other containers are not
+    /// read, as the view only needs to know where they are.
+    fn view(metadata: &'a XbfMetadata, data: &'a [u8]) -> XbfValueRef<'a> {
+        const VALID: &str = "data was validated when parsed";
+        let mut reader = XbfReader::new(data);
+        match metadata {
+            XbfMetadata::Primitive(x) => {
+                XbfValueRef::Primitive(XbfPrimitiveRef::read(x, &mut reader).expect(VALID))
+            }
+            XbfMetadata::Vec(x) => {
+                let len = reader.read_vec_len().expect(VALID);
+                XbfValueRef::Vec(XbfVecRef {
+                    metadata: x,
+                    len: len as usize,
+                    elements: reader.into_inner(),
+                    offsets: Offsets::default(),
+                })
+            }
+            XbfMetadata::Struct(x) => XbfValueRef::Struct(XbfStructRef {
+                metadata: x,
+                fields: data,
+                offsets: Offsets::default(),
+            }),
+            XbfMetadata::Option(x) => {
+                let present = reader.read_bool().expect(VALID);
+                XbfValueRef::Option(XbfOptionRef {
+                    metadata: x,
+                    value: present.then(|| reader.into_inner()),
+                })
+            }
+            XbfMetadata::Enum(x) => {
+                let (variant, _, _) = reader.read_variant(x).expect(VALID);
+                XbfValueRef::Enum(XbfEnumRef {
+                    metadata: x,
+                    variant,
+                    value: reader.into_inner(),
+                })
+            }
+            XbfMetadata::Map(x) => {
+                let len = reader.read_vec_len().expect(VALID);
+                let entries = reader.into_inner();
+                XbfValueRef::Map(XbfMapRef {
+                    metadata: x,
+                    len: len as usize,
+                    entries,
+                    deduped: XbfMapRef::dedupe(x, len, entries),
+                })
+            }
+            XbfMetadata::Array(x) => XbfValueRef::Array(XbfArrayRef {
+                metadata: x,
+                elements: data,
+                offsets: Offsets::default(),
+            }),
+            XbfMetadata::Tensor(x) => {
+                let len = match &x.shape {
+                    Some(shape) => element_count(shape),
+                    None => element_count(&reader.read_tensor_shape(x.rank).expect(VALID)),
+                };
+                let elements = reader.into_inner();
+                XbfValueRef::Tensor(XbfTensorRef {
+                    metadata: x,
+                    shape: &data[..data.len() - elements.len()],
+                    len: len as usize,
+                    elements,
+                })
+            }
+        }
+    }
+
+    /// Returns the metadata of the value.
+    pub fn metadata(&self) -> XbfMetadata {
+        match self {
+            XbfValueRef::Primitive(x) => x.metadata().into(),
+            XbfValueRef::Vec(x) => x.metadata.into(),
+            XbfValueRef::Struct(x) => x.metadata.into(),
+            XbfValueRef::Option(x) => x.metadata.into(),
+            XbfValueRef::Enum(x) => x.metadata.into(),
+            XbfValueRef::Map(x) => x.metadata.into(),
+            XbfValueRef::Array(x) => x.metadata.into(),
+            XbfValueRef::Tensor(x) => x.metadata.into(),
+        }
+    }
+
+    /// Returns the inner [`XbfPrimitiveRef`] if this is a primitive.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FromXbfError::TypeMismatch`] if this is not a primitive.
+    pub fn as_primitive(&self) -> Result<XbfPrimitiveRef<'a>, FromXbfError> {
+        match self {
+            XbfValueRef::Primitive(x) => Ok(*x),
+            other => Err(FromXbfError::type_mismatch("Primitive", other.metadata())),
+        }
+    }
+
+    /// Returns the inner [`XbfVecRef`] if this is a vec.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FromXbfError::TypeMismatch`] if this is not a vec.
+    pub fn as_vec(&self) -> Result<XbfVecRef<'a>, FromXbfError> {
+        match self {
+            XbfValueRef::Vec(x) => Ok(x.clone()),
+            other => Err(FromXbfError::type_mismatch("Vec", other.metadata())),
+        }
+    }
+
+    /// Returns the inner [`XbfStructRef`] if this is a struct.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FromXbfError::TypeMismatch`] if this is not a struct.
+    pub fn as_struct(&self) -> Result<XbfStructRef<'a>, FromXbfError> {
+        match self {
+            XbfValueRef::Struct(x) => Ok(x.clone()),
+            other => Err(FromXbfError::type_mismatch("Struct", other.metadata())),
+        }
+    }
+
+    /// Returns the inner [`XbfOptionRef`] if this is an option.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FromXbfError::TypeMismatch`] if this is not an option.
+    pub fn as_option(&self) -> Result<XbfOptionRef<'a>, FromXbfError> {
+        match self {
+            XbfValueRef::Option(x) => Ok(*x),
+            other => Err(FromXbfError::type_mismatch("Option", other.metadata())),
+        }
+    }
+
+    /// Returns the inner [`XbfEnumRef`] if this is an enum.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FromXbfError::TypeMismatch`] if this is not an enum.
+    pub fn as_enum(&self) -> Result<XbfEnumRef<'a>, FromXbfError> {
+        match self {
+            XbfValueRef::Enum(x) => Ok(*x),
+            other => Err(FromXbfError::type_mismatch("Enum", other.metadata())),
+        }
+    }
+
+    /// Returns the inner [`XbfMapRef`] if this is a map.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FromXbfError::TypeMismatch`] if this is not a map.
+    pub fn as_map(&self) -> Result<XbfMapRef<'a>, FromXbfError> {
+        match self {
+            XbfValueRef::Map(x) => Ok(x.clone()),
+            other => Err(FromXbfError::type_mismatch("Map", other.metadata())),
+        }
+    }
+
+    /// Returns the inner [`XbfArrayRef`] if this is an array.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FromXbfError::TypeMismatch`] if this is not an array.
+    pub fn as_array(&self) -> Result<XbfArrayRef<'a>, FromXbfError> {
+        match self {
+            XbfValueRef::Array(x) => Ok(x.clone()),
+            other => Err(FromXbfError::type_mismatch("Array", other.metadata())),
+        }
+    }
+
+    /// Returns the inner [`XbfTensorRef`] if this is a tensor.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FromXbfError::TypeMismatch`] if this is not a tensor.
+    pub fn as_tensor(&self) -> Result<XbfTensorRef<'a>, FromXbfError> {
+        match self {
+            XbfValueRef::Tensor(x) => Ok(*x),
+            other => Err(FromXbfError::type_mismatch("Tensor", other.metadata())),
+        }
+    }
+
+    /// Returns the borrowed string if this is a string.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FromXbfError::TypeMismatch`] if this is not a string.
+    pub fn as_str(&self) -> Result<&'a str, FromXbfError> {
+        match self {
+            XbfValueRef::Primitive(XbfPrimitiveRef::String(x)) => Ok(x),
+            other => Err(FromXbfError::type_mismatch("String", other.metadata())),
+        }
+    }
+
+    /// Returns the borrowed bytes if this is bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FromXbfError::TypeMismatch`] if this is not bytes.
+    pub fn as_bytes(&self) -> Result<&'a [u8], FromXbfError> {
+        match self {
+            XbfValueRef::Primitive(XbfPrimitiveRef::Bytes(x)) => Ok(x),
+            other => Err(FromXbfError::type_mismatch("Bytes", other.metadata())),
+        }
+    }
+}
+
+macro_rules! impl_primitive_accessors {
+    ($($fn_name:ident, $xbf_type:tt, $ty:ty);+ $(;)?) => {
+        impl<'a> XbfValueRef<'a> {
+            $(
+                #[doc = concat!("Returns the inner value if this is a [`XbfPrimitiveRef::", stringify!($xbf_type), "`].")]
+                ///
+                /// # Errors
+                ///
+                #[doc = concat!("Returns [`FromXbfError::TypeMismatch`] if this is not a ", stringify!($xbf_type), ".")]
+                pub fn $fn_name(&self) -> Result<$ty, FromXbfError> {
+                    match self {
+                        XbfValueRef::Primitive(XbfPrimitiveRef::$xbf_type(x)) => Ok(*x),
+                        other => Err(FromXbfError::type_mismatch(
+                            stringify!($xbf_type),
+                            other.metadata(),
+                        )),
+                    }
+                }
+            )+
+        }
+    };
+}
+
+impl_primitive_accessors! {
+    as_bool, Bool, bool;
+    as_u8, U8, u8;
+    as_u16, U16, u16;
+    as_u32, U32, u32;
+    as_u64, U64, u64;
+    as_u128, U128, u128;
+    as_u256, U256, XbfU256;
+    as_i8, I8, i8;
+    as_i16, I16, i16;
+    as_i32, I32, i32;
+    as_i64, I64, i64;
+    as_i128, I128, i128;
+    as_i256, I256, XbfI256;
+    as_f32, F32, f32;
+    as_f64, F64, f64;
+    as_timestamp, Timestamp, XbfTimestamp;
+    as_timestamp_tz, TimestampTz, XbfTimestampTz;
+    as_date, Date, XbfDate;
+    as_time, Time, XbfTime;
+    as_duration, Duration, XbfDuration;
+    as_decimal, Decimal, XbfDecimal;
+    as_uuid, Uuid, [u8; 16];
+    as_char, Char, char;
+    as_f16, F16, u16;
+    as_bf16, BF16, u16;
+}
+
+impl<'a> XbfPrimitiveRef<'a> {
+    fn read(
+        metadata: &XbfPrimitiveMetadata,
+        reader: &mut XbfReader<&'a [u8]>,
+    ) -> Result<XbfPrimitiveRef<'a>, XbfError> {
+        match metadata {
+            XbfPrimitiveMetadata::Bytes => {
+                let len = reader.read_bytes_len()?;
+                reader.read_slice(len).map(XbfPrimitiveRef::Bytes)
+            }
+            XbfPrimitiveMetadata::String => {
+                let offset = reader.position();
+                let len = reader.read_bytes_len()?;
+                let bytes = reader.read_slice(len)?;
+                std::str::from_utf8(bytes)
+                    .map(XbfPrimitiveRef::String)
+                    .map_err(|_| XbfError::new(XbfErrorKind::InvalidUtf8, offset))
+            }
+            _ => XbfPrimitive::read_primitive_type(metadata, reader).map(|x| match x {
+                XbfPrimitive::Bool(x) => XbfPrimitiveRef::Bool(x),
+                XbfPrimitive::U8(x) => XbfPrimitiveRef::U8(x),
+                XbfPrimitive::U16(x) => XbfPrimitiveRef::U16(x),
+                XbfPrimitive::U32(x) => XbfPrimitiveRef::U32(x),
+                XbfPrimitive::U64(x) => XbfPrimitiveRef::U64(x),
+                XbfPrimitive::U128(x) => XbfPrimitiveRef::U128(x),
+                XbfPrimitive::U256(x) => XbfPrimitiveRef::U256(x),
+                XbfPrimitive::I8(x) => XbfPrimitiveRef::I8(x),
+                XbfPrimitive::I16(x) => XbfPrimitiveRef::I16(x),
+                XbfPrimitive::I32(x) => XbfPrimitiveRef::I32(x),
+                XbfPrimitive::I64(x) => XbfPrimitiveRef::I64(x),
+                XbfPrimitive::I128(x) => XbfPrimitiveRef::I128(x),
+                XbfPrimitive::I256(x) => XbfPrimitiveRef::I256(x),
+                XbfPrimitive::F32(x) => XbfPrimitiveRef::F32(x),
+                XbfPrimitive::F64(x) => XbfPrimitiveRef::F64(x),
+                XbfPrimitive::Timestamp(x) => XbfPrimitiveRef::Timestamp(x),
+                XbfPrimitive::TimestampTz(x) => XbfPrimitiveRef::TimestampTz(x),
+                XbfPrimitive::Date(x) => XbfPrimitiveRef::Date(x),
+                XbfPrimitive::Time(x) => XbfPrimitiveRef::Time(x),
+                XbfPrimitive::Duration(x) => XbfPrimitiveRef::Duration(x),
+                XbfPrimitive::Decimal(x) => XbfPrimitiveRef::Decimal(x),
+                XbfPrimitive::Uuid(x) => XbfPrimitiveRef::Uuid(x),
+                XbfPrimitive::Char(x) => XbfPrimitiveRef::Char(x),
+                XbfPrimitive::F16(x) => XbfPrimitiveRef::F16(x),
+                XbfPrimitive::BF16(x) => XbfPrimitiveRef::BF16(x),
+                XbfPrimitive::Bytes(_) | XbfPrimitive::String(_) => {
+                    unreachable!("variable length primitives are borrowed above")
+                }
+            }),
+        }
+    }
+
+    /// Returns the metadata of the primitive.
+    pub fn metadata(&self) -> XbfPrimitiveMetadata {
+        match self {
+            XbfPrimitiveRef::Bool(_) => XbfPrimitiveMetadata::Bool,
+            XbfPrimitiveRef::U8(_) => XbfPrimitiveMetadata::U8,
+            XbfPrimitiveRef::U16(_) => XbfPrimitiveMetadata::U16,
+            XbfPrimitiveRef::U32(_) => XbfPrimitiveMetadata::U32,
+            XbfPrimitiveRef::U64(_) => XbfPrimitiveMetadata::U64,
+            XbfPrimitiveRef::U128(_) => XbfPrimitiveMetadata::U128,
+            XbfPrimitiveRef::U256(_) => XbfPrimitiveMetadata::U256,
+            XbfPrimitiveRef::I8(_) => XbfPrimitiveMetadata::I8,
+            XbfPrimitiveRef::I16(_) => XbfPrimitiveMetadata::I16,
+            XbfPrimitiveRef::I32(_) => XbfPrimitiveMetadata::I32,
+            XbfPrimitiveRef::I64(_) => XbfPrimitiveMetadata::I64,
+            XbfPrimitiveRef::I128(_) => XbfPrimitiveMetadata::I128,
+            XbfPrimitiveRef::I256(_) => XbfPrimitiveMetadata::I256,
+            XbfPrimitiveRef::F32(_) => XbfPrimitiveMetadata::F32,
+            XbfPrimitiveRef::F64(_) => XbfPrimitiveMetadata::F64,
+            XbfPrimitiveRef::Bytes(_) => XbfPrimitiveMetadata::Bytes,
+            XbfPrimitiveRef::Timestamp(_) => XbfPrimitiveMetadata::Timestamp,
+            XbfPrimitiveRef::TimestampTz(_) => XbfPrimitiveMetadata::TimestampTz,
+            XbfPrimitiveRef::Date(_) => XbfPrimitiveMetadata::Date,
+            XbfPrimitiveRef::Time(_) => XbfPrimitiveMetadata::Time,
+            XbfPrimitiveRef::Duration(_) => XbfPrimitiveMetadata::Duration,
+            XbfPrimitiveRef::Decimal(_) => XbfPrimitiveMetadata::Decimal,
+            XbfPrimitiveRef::Uuid(_) => XbfPrimitiveMetadata::Uuid,
+            XbfPrimitiveRef::Char(_) => XbfPrimitiveMetadata::Char,
+            XbfPrimitiveRef::F16(_) => XbfPrimitiveMetadata::F16,
+            XbfPrimitiveRef::BF16(_) => XbfPrimitiveMetadata::BF16,
+            XbfPrimitiveRef::String(_) => XbfPrimitiveMetadata::String,
+        }
+    }
+}
+
+impl<'a> XbfVecRef<'a> {
+    fn read(
+        metadata: &'a XbfVecMetadata,
+        reader: &mut XbfReader<&'a [u8]>,
+    ) -> Result<XbfVecRef<'a>, XbfError> {
+        reader.nested(|reader| {
+            let len = reader.read_vec_len()?;
+            reader.charge_elements(metadata.inner_type.fixed_size(), len)?;
+            let start = *reader.get_ref();
+            for i in 0..len {
+                XbfValueRef::read(&metadata.inner_type, reader).map_err(|e| e.at_index(i))?;
+            }
+            let consumed = start.len() - reader.get_ref().len();
+            Ok(XbfVecRef {
+                metadata,
+                len: len as usize,
+                elements: &start[..consumed],
+                offsets: Offsets::default(),
+            })
+        })
+    }
+
+    /// Returns the metadata of the vector.
+    pub fn metadata(&self) -> &'a XbfVecMetadata {
+        self.metadata
+    }
+
+    /// Returns the number of elements in the vector.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the vector has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the element at `index`, or `None` if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<XbfValueRef<'a>> {
+        if index >= self.len {
+            return None;
+        }
+        let inner_type = &*self.metadata.inner_type;
+        let range = match inner_type.fixed_size() {
+            Some(size) => index * size..(index + 1) * size,
+            None => {
+                let types = std::iter::repeat_n(inner_type, self.len);
+                self.offsets.range(self.elements, types, index)
+            }
+        };
+        Some(XbfValueRef::view(inner_type, &self.elements[range]))
+    }
+
+    /// Returns an iterator over the elements of the vector.
+    pub fn iter(&self) -> XbfVecRefIter<'a> {
+        XbfVecRefIter {
+            inner_type: &self.metadata.inner_type,
+            remaining: self.len,
+            data: self.elements,
+        }
+    }
+}
+
+impl<'a> IntoIterator for XbfVecRef<'a> {
+    type Item = XbfValueRef<'a>;
+
+    type IntoIter = XbfVecRefIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the elements of an [`XbfVecRef`] or an [`XbfArrayRef`].
+#[derive(Debug, Clone)]
+pub struct XbfVecRefIter<'a> {
+    inner_type: &'a XbfMetadata,
+    remaining: usize,
+    data: &'a [u8],
+}
+
+impl<'a> Iterator for XbfVecRefIter<'a> {
+    type Item = XbfValueRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let (value, rest) = XbfValueRef::split(self.inner_type, self.data);
+        self.data = rest;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for XbfVecRefIter<'_> {}
+
+impl<'a> XbfStructRef<'a> {
+    fn read(
+        metadata: &'a XbfStructMetadata,
+        reader: &mut XbfReader<&'a [u8]>,
+    ) -> Result<XbfStructRef<'a>, XbfError> {
+        reader.nested(|reader| {
+            let start = *reader.get_ref();
+            for (field_name, field_type) in metadata.fields() {
+                XbfValueRef::read(field_type, reader).map_err(|e| e.in_field(field_name))?;
+            }
+            let consumed = start.len() - reader.get_ref().len();
+            Ok(XbfStructRef {
+                metadata,
+                fields: &start[..consumed],
+                offsets: Offsets::default(),
+            })
+        })
+    }
+
+    /// Returns the metadata of the struct.
+    pub fn metadata(&self) -> &'a XbfStructMetadata {
+        self.metadata
+    }
+
+    /// Returns the field with the given name, or `None` if the struct has no such field.
+    pub fn get(&self, field_name: &str) -> Option<XbfValueRef<'a>> {
+        let (index, field_type) = self.metadata.get_field_index(field_name)?;
+        let types = self.metadata.fields().map(|(_, field_type)| field_type);
+        let range = self.offsets.range(self.fields, types, index);
+        Some(XbfValueRef::view(field_type, &self.fields[range]))
+    }
+
+    /// Returns an iterator over the names and values of the fields, in the order they are
+    /// serialized.
+    pub fn fields(&self) -> impl ExactSizeIterator<Item = (&'a str, XbfValueRef<'a>)> {
+        let mut data = self.fields;
+        self.metadata.fields().map(move |(name, field_type)| {
+            let (value, rest) = XbfValueRef::split(field_type, data);
+            data = rest;
+            (name, value)
+        })
+    }
+}
+
+impl<'a> XbfOptionRef<'a> {
+    fn read(
+        metadata: &'a XbfOptionMetadata,
+        reader: &mut XbfReader<&'a [u8]>,
+    ) -> Result<XbfOptionRef<'a>, XbfError> {
+        reader.nested(|reader| {
+            if !reader.read_bool()? {
+                return Ok(XbfOptionRef {
+                    metadata,
+                    value: None,
+                });
+            }
+            let start = *reader.get_ref();
+            XbfValueRef::read(&metadata.inner_type, reader)?;
+            let consumed = start.len() - reader.get_ref().len();
+            Ok(XbfOptionRef {
+                metadata,
+                value: Some(&start[..consumed]),
+            })
+        })
+    }
+
+    /// Returns the metadata of the option.
+    pub fn metadata(&self) -> &'a XbfOptionMetadata {
+        self.metadata
+    }
+
+    /// Returns `true` if the option contains a value.
+    pub fn is_some(&self) -> bool {
+        self.value.is_some()
+    }
+
+    /// Returns `true` if the option is empty.
+    pub fn is_none(&self) -> bool {
+        self.value.is_none()
+    }
+
+    /// Returns the contained value, if there is one.
+    pub fn value(&self) -> Option<XbfValueRef<'a>> {
+        self.value
+            .map(|data| XbfValueRef::view(&self.metadata.inner_type, data))
+    }
+}
+
+impl<'a> XbfEnumRef<'a> {
+    fn read(
+        metadata: &'a XbfEnumMetadata,
+        reader: &mut XbfReader<&'a [u8]>,
+    ) -> Result<XbfEnumRef<'a>, XbfError> {
+        reader.nested(|reader| {
+            let (variant, variant_name, variant_type) = reader.read_variant(metadata)?;
+            let start = *reader.get_ref();
+            XbfValueRef::read(variant_type, reader).map_err(|e| e.in_field(variant_name))?;
+            let consumed = start.len() - reader.get_ref().len();
+            Ok(XbfEnumRef {
+                metadata,
+                variant,
+                value: &start[..consumed],
+            })
+        })
+    }
+
+    /// Returns the metadata of the enum.
+    pub fn metadata(&self) -> &'a XbfEnumMetadata {
+        self.metadata
+    }
+
+    /// Returns the index of the variant this enum holds.
+    pub fn variant_index(&self) -> u16 {
+        self.variant
+    }
+
+    /// Returns the name of the variant this enum holds.
+    pub fn variant_name(&self) -> &'a str {
+        self.variant().0
+    }
+
+    /// Returns the value of the variant.
+    pub fn value(&self) -> XbfValueRef<'a> {
+        XbfValueRef::view(self.variant().1, self.value)
+    }
+
+    fn variant(&self) -> (&'a str, &'a XbfMetadata) {
+        self.metadata
+            .get_variant(self.variant)
+            .expect("data was validated when parsed")
+    }
+}
+
+impl<'a> XbfMapRef<'a> {
+    fn read(
+        metadata: &'a XbfMapMetadata,
+        reader: &mut XbfReader<&'a [u8]>,
+    ) -> Result<XbfMapRef<'a>, XbfError> {
+        reader.nested(|reader| {
+            let len = reader.read_vec_len()?;
+            let entry_size = metadata
+                .key_type
+                .fixed_size()
+                .zip(metadata.value_type.fixed_size());
+            reader.charge_elements(entry_size.map(|(k, v)| k + v), len)?;
+            let start = *reader.get_ref();
+            // the offset of the last value of each distinct key
+            let mut keys = IndexMap::new();
+            for i in 0..len {
+                let offset = reader.position();
+                let key_start = *reader.get_ref();
+                XbfValueRef::read(&metadata.key_type, reader).map_err(|e| e.at_index(i))?;
+                let key = &key_start[..key_start.len() - reader.get_ref().len()];
+                let value_offset = start.len() - reader.get_ref().len();
+                match keys.entry(key) {
+                    Entry::Vacant(entry) => {
+                        entry.insert(value_offset);
+                    }
+                    Entry::Occupied(_) if reader.options().strict => {
+                        return Err(XbfError::new(XbfErrorKind::DuplicateKey, offset).at_index(i));
+                    }
+                    Entry::Occupied(mut entry) => *entry.get_mut() = value_offset,
+                }
+                XbfValueRef::read(&metadata.value_type, reader).map_err(|e| e.at_index(i))?;
+            }
+            let consumed = start.len() - reader.get_ref().len();
+            let deduped = (keys.len() as u64 != len).then(|| keys.into_iter().collect());
+            Ok(XbfMapRef {
+                metadata,
+                len: len as usize,
+                entries: &start[..consumed],
+                deduped,
+            })
+        })
+    }
+
+    /// Finds each distinct key of entries which are already known to be valid, or `None` if
+    /// every key is distinct.
+    fn dedupe(
+        metadata: &XbfMapMetadata,
+        len: u64,
+        entries: &'a [u8],
+    ) -> Option<DedupedEntries<'a>> {
+        // the offset of the last value of each distinct key
+        let mut keys = IndexMap::new();
+        let mut rest = entries;
+        for _ in 0..len {
+            let value = XbfValueRef::skip(&metadata.key_type, rest);
+            keys.insert(
+                &rest[..rest.len() - value.len()],
+                entries.len() - value.len(),
+            );
+            rest = XbfValueRef::skip(&metadata.value_type, value);
+        }
+        (keys.len() as u64 != len).then(|| keys.into_iter().collect())
+    }
+
+    /// Returns the metadata of the map.
+    pub fn metadata(&self) -> &'a XbfMapMetadata {
+        self.metadata
+    }
+
+    /// Returns the number of entries in the map.
+    ///
+    /// Outside of strict mode a key which appears more than once is only counted once.
+    pub fn len(&self) -> usize {
+        match &self.deduped {
+            Some(deduped) => deduped.len(),
+            None => self.len,
+        }
+    }
+
+    /// Returns `true` if the map has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the value of a key, or `None` if the map has no such key.
+    ///
+    /// If the key appears more than once, the value of its last entry is returned.
+    pub fn get(&self, key: &XbfType) -> Option<XbfValueRef<'a>> {
+        let mut key_bytes = vec![];
+        key.serialize_base_type(&mut key_bytes)
+            .expect("writing to a vec never fails");
+
+        if let Some(deduped) = &self.deduped {
+            return deduped
+                .iter()
+                .find(|(key, _)| *key == key_bytes)
+                .map(|(_, value)| {
+                    XbfValueRef::split(&self.metadata.value_type, &self.entries[*value..]).0
+                });
+        }
+
+        let mut data = self.entries;
+        for _ in 0..self.len {
+            let rest = XbfValueRef::skip(&self.metadata.key_type, data);
+            if data[..data.len() - rest.len()] == key_bytes {
+                return Some(XbfValueRef::split(&self.metadata.value_type, rest).0);
+            }
+            data = XbfValueRef::skip(&self.metadata.value_type, rest);
+        }
+        None
+    }
+
+    /// Returns an iterator over the keys and values of the entries, in the order their keys are
+    /// first serialized.
+    pub fn iter(&self) -> XbfMapRefIter<'a> {
+        XbfMapRefIter {
+            metadata: self.metadata,
+            remaining: self.len(),
+            data: self.entries,
+            deduped: self.deduped.clone(),
+        }
+    }
+}
+
+impl<'a> IntoIterator for XbfMapRef<'a> {
+    type Item = (XbfValueRef<'a>, XbfValueRef<'a>);
+
+    type IntoIter = XbfMapRefIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of an [`XbfMapRef`].
+#[derive(Debug, Clone)]
+pub struct XbfMapRefIter<'a> {
+    metadata: &'a XbfMapMetadata,
+    remaining: usize,
+    data: &'a [u8],
+    deduped: Option<DedupedEntries<'a>>,
+}
+
+impl<'a> Iterator for XbfMapRefIter<'a> {
+    type Item = (XbfValueRef<'a>, XbfValueRef<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        if let Some(deduped) = &self.deduped {
+            let (key, value) = deduped[deduped.len() - self.remaining - 1];
+            return Some((
+                XbfValueRef::view(&self.metadata.key_type, key),
+                XbfValueRef::split(&self.metadata.value_type, &self.data[value..]).0,
+            ));
+        }
+        let (key, rest) = XbfValueRef::split(&self.metadata.key_type, self.data);
+        let (value, rest) = XbfValueRef::split(&self.metadata.value_type, rest);
+        self.data = rest;
+        Some((key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for XbfMapRefIter<'_> {}
+
+impl<'a> XbfArrayRef<'a> {
+    fn read(
+        metadata: &'a XbfArrayMetadata,
+        reader: &mut XbfReader<&'a [u8]>,
+    ) -> Result<XbfArrayRef<'a>, XbfError> {
+        reader.nested(|reader| {
+            reader.charge_elements(metadata.inner_type.fixed_size(), metadata.len)?;
+            let start = *reader.get_ref();
+            for i in 0..metadata.len {
+                XbfValueRef::read(&metadata.inner_type, reader).map_err(|e| e.at_index(i))?;
+            }
+            let consumed = start.len() - reader.get_ref().len();
+            Ok(XbfArrayRef {
+                metadata,
+                elements: &start[..consumed],
+                offsets: Offsets::default(),
+            })
+        })
+    }
+
+    /// Returns the metadata of the array.
+    pub fn metadata(&self) -> &'a XbfArrayMetadata {
+        self.metadata
+    }
+
+    /// Returns the number of elements in the array.
+    pub fn len(&self) -> usize {
+        self.metadata.len as usize
+    }
+
+    /// Returns `true` if the array has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.metadata.is_empty()
+    }
+
+    /// Returns the element at `index`, or `None` if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<XbfValueRef<'a>> {
+        if index >= self.len() {
+            return None;
+        }
+        let inner_type = &*self.metadata.inner_type;
+        let range = match inner_type.fixed_size() {
+            Some(size) => index * size..(index + 1) * size,
+            None => {
+                let types = std::iter::repeat_n(inner_type, self.len());
+                self.offsets.range(self.elements, types, index)
+            }
+        };
+        Some(XbfValueRef::view(inner_type, &self.elements[range]))
+    }
+
+    /// Returns an iterator over the elements of the array.
+    pub fn iter(&self) -> XbfVecRefIter<'a> {
+        XbfVecRefIter {
+            inner_type: &self.metadata.inner_type,
+            remaining: self.len(),
+            data: self.elements,
+        }
+    }
+}
+
+impl<'a> IntoIterator for XbfArrayRef<'a> {
+    type Item = XbfValueRef<'a>;
+
+    type IntoIter = XbfVecRefIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> XbfTensorRef<'a> {
+    fn read(
+        metadata: &'a XbfTensorMetadata,
+        reader: &mut XbfReader<&'a [u8]>,
+    ) -> Result<XbfTensorRef<'a>, XbfError> {
+        let start = *reader.get_ref();
+        let len = match &metadata.shape {
+            Some(shape) => element_count(shape),
+            None => element_count(&reader.read_tensor_shape(metadata.rank)?),
+        };
+        let shape = &start[..start.len() - reader.get_ref().len()];
+
+        let start = *reader.get_ref();
+        for i in 0..len {
+            XbfPrimitiveRef::read(&metadata.element_type, reader).map_err(|e| e.at_index(i))?;
+        }
+        let consumed = start.len() - reader.get_ref().len();
+        Ok(XbfTensorRef {
+            metadata,
+            shape,
+            len: len as usize,
+            elements: &start[..consumed],
+        })
+    }
+
+    /// Returns the metadata of the tensor.
+    pub fn metadata(&self) -> &'a XbfTensorMetadata {
+        self.metadata
+    }
+
+    /// Returns the length of each dimension of the tensor.
+    pub fn shape(&self) -> Vec<u64> {
+        match &self.metadata.shape {
+            Some(shape) => shape.clone(),
+            None => self
+                .shape
+                .chunks_exact(8)
+                .map(|x| u64::from_le_bytes(x.try_into().expect("chunks are 8 bytes")))
+                .collect(),
+        }
+    }
+
+    /// Returns the number of elements in the tensor.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the tensor has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the element at `index` in row-major order, or `None` if it is out of bounds.
+    pub fn get(&self, index: usize) -> Option<XbfPrimitiveRef<'a>> {
+        if index >= self.len {
+            return None;
+        }
+        let size = self.element_size();
+        let mut reader = XbfReader::new(&self.elements[index * size..]);
+        let value = XbfPrimitiveRef::read(&self.metadata.element_type, &mut reader);
+        Some(value.expect("data was validated when parsed"))
+    }
+
+    /// Returns an iterator over the elements of the tensor in row-major order.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = XbfPrimitiveRef<'a>> + 'a {
+        let tensor = *self;
+        (0..self.len).map(move |i| tensor.get(i).expect("index is in bounds"))
+    }
+
+    fn element_size(&self) -> usize {
+        let size = self.metadata.element_type.fixed_size();
+        size.expect("tensor elements have a fixed size")
+    }
+}
+
+impl<'a> XbfReader<&'a [u8]> {
+    /// Reads a borrowed view of a value described by `metadata`, as with
+    /// [`XbfValueRef::parse`], while enforcing this reader's options.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the data is invalid or a limit is exceeded.
+    pub fn read_value_ref(
+        &mut self,
+        metadata: &'a XbfMetadata,
+    ) -> Result<XbfValueRef<'a>, XbfError> {
+        XbfValueRef::read(metadata, self).map_err(|e| e.in_root(metadata))
+    }
+}
+
+impl From<XbfPrimitiveRef<'_>> for XbfPrimitive {
+    fn from(value: XbfPrimitiveRef<'_>) -> Self {
+        match value {
+            XbfPrimitiveRef::Bool(x) => XbfPrimitive::Bool(x),
+            XbfPrimitiveRef::U8(x) => XbfPrimitive::U8(x),
+            XbfPrimitiveRef::U16(x) => XbfPrimitive::U16(x),
+            XbfPrimitiveRef::U32(x) => XbfPrimitive::U32(x),
+            XbfPrimitiveRef::U64(x) => XbfPrimitive::U64(x),
+            XbfPrimitiveRef::U128(x) => XbfPrimitive::U128(x),
+            XbfPrimitiveRef::U256(x) => XbfPrimitive::U256(x),
+            XbfPrimitiveRef::I8(x) => XbfPrimitive::I8(x),
+            XbfPrimitiveRef::I16(x) => XbfPrimitive::I16(x),
+            XbfPrimitiveRef::I32(x) => XbfPrimitive::I32(x),
+            XbfPrimitiveRef::I64(x) => XbfPrimitive::I64(x),
+            XbfPrimitiveRef::I128(x) => XbfPrimitive::I128(x),
+            XbfPrimitiveRef::I256(x) => XbfPrimitive::I256(x),
+            XbfPrimitiveRef::F32(x) => XbfPrimitive::F32(x),
+            XbfPrimitiveRef::F64(x) => XbfPrimitive::F64(x),
+            XbfPrimitiveRef::Bytes(x) => XbfPrimitive::Bytes(x.to_vec()),
+            XbfPrimitiveRef::Timestamp(x) => XbfPrimitive::Timestamp(x),
+            XbfPrimitiveRef::TimestampTz(x) => XbfPrimitive::TimestampTz(x),
+            XbfPrimitiveRef::Date(x) => XbfPrimitive::Date(x),
+            XbfPrimitiveRef::Time(x) => XbfPrimitive::Time(x),
+            XbfPrimitiveRef::Duration(x) => XbfPrimitive::Duration(x),
+            XbfPrimitiveRef::Decimal(x) => XbfPrimitive::Decimal(x),
+            XbfPrimitiveRef::Uuid(x) => XbfPrimitive::Uuid(x),
+            XbfPrimitiveRef::Char(x) => XbfPrimitive::Char(x),
+            XbfPrimitiveRef::F16(x) => XbfPrimitive::F16(x),
+            XbfPrimitiveRef::BF16(x) => XbfPrimitive::BF16(x),
+            XbfPrimitiveRef::String(x) => XbfPrimitive::String(x.to_string()),
+        }
+    }
+}
+
+impl From<XbfVecRef<'_>> for XbfVec {
+    fn from(value: XbfVecRef<'_>) -> Self {
+        if let XbfMetadata::Primitive(x) = &*value.metadata.inner_type {
+            if x.fixed_size().is_some() {
+                let mut reader = XbfReader::new(value.elements);
+                let elements = XbfPrimitiveBuffer::read_elements(*x, value.len as u64, &mut reader);
+                return elements.expect("data was validated when parsed").into();
+            }
+        }
+        XbfVec::new_unchecked(value.metadata.clone(), value.iter().map(XbfType::from))
+    }
+}
+
+impl From<XbfStructRef<'_>> for XbfStruct {
+    fn from(value: XbfStructRef<'_>) -> Self {
+        XbfStruct::new_unchecked(
+            value.metadata.clone(),
+            value.fields().map(|(_, x)| XbfType::from(x)),
+        )
+    }
+}
+
+impl From<XbfOptionRef<'_>> for XbfOption {
+    fn from(value: XbfOptionRef<'_>) -> Self {
+        match value.value() {
+            Some(x) => XbfOption::some(XbfType::from(x)),
+            None => XbfOption::none((*value.metadata.inner_type).clone()),
+        }
+    }
+}
+
+impl From<XbfEnumRef<'_>> for XbfEnum {
+    fn from(value: XbfEnumRef<'_>) -> Self {
+        XbfEnum::new_unchecked(
+            value.metadata.clone(),
+            value.variant,
+            XbfType::from(value.value()),
+        )
+    }
+}
+
+impl From<XbfMapRef<'_>> for XbfMap {
+    fn from(value: XbfMapRef<'_>) -> Self {
+        XbfMap::new_unchecked(
+            value.metadata.clone(),
+            value
+                .iter()
+                .map(|(key, value)| (XbfType::from(key), XbfType::from(value))),
+        )
+    }
+}
+
+impl From<XbfArrayRef<'_>> for XbfArray {
+    fn from(value: XbfArrayRef<'_>) -> Self {
+        XbfArray::new_unchecked(value.metadata.clone(), value.iter().map(XbfType::from))
+    }
+}
+
+impl From<XbfTensorRef<'_>> for XbfTensor {
+    fn from(value: XbfTensorRef<'_>) -> Self {
+        let mut reader = XbfReader::new(value.elements);
+        let elements = XbfPrimitiveBuffer::read_elements(
+            value.metadata.element_type,
+            value.len as u64,
+            &mut reader,
+        );
+        let elements = elements.expect("data was validated when parsed");
+        XbfTensor::new_unchecked(value.metadata.clone(), value.shape(), elements)
+    }
+}
+
+/// Copies a borrowed value into an owned [`XbfType`].
+impl From<XbfValueRef<'_>> for XbfType {
+    fn from(value: XbfValueRef<'_>) -> Self {
+        match value {
+            XbfValueRef::Primitive(x) => XbfPrimitive::from(x).into(),
+            XbfValueRef::Vec(x) => XbfVec::from(x).into(),
+            XbfValueRef::Struct(x) => XbfStruct::from(x).into(),
+            XbfValueRef::Option(x) => XbfOption::from(x).into(),
+            XbfValueRef::Enum(x) => XbfEnum::from(x).into(),
+            XbfValueRef::Map(x) => XbfMap::from(x).into(),
+            XbfValueRef::Array(x) => XbfArray::from(x).into(),
+            XbfValueRef::Tensor(x) => XbfTensor::from(x).into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DeserializeOptions, XbfLimit, XbfTypeUpcast};
+    use indexmap::{indexmap, IndexMap};
+
+    fn rider_metadata() -> XbfStructMetadata {
+        XbfStructMetadata::new(
+            "DragonRider",
+            indexmap! {
+                "name" => XbfPrimitiveMetadata::String.into(),
+                "age" => XbfPrimitiveMetadata::U16.into(),
+            },
+        )
+    }
+
+    fn rider(name: &str, age: u16) -> XbfType {
+        XbfStruct::new(
+            rider_metadata(),
+            [
+                XbfPrimitive::String(name.to_string()).into(),
+                XbfPrimitive::U16(age).into(),
+            ],
+        )
+        .unwrap()
+        .into_base_type()
+    }
+
+    #[test]
+    fn vec_of_structs_works() {
+        let value = XbfVec::new(
+            XbfVecMetadata::new(rider_metadata()),
+            [
+                rider("Eragon", 16),
+                rider("Arya", 103),
+                rider("Murtagh", 18),
+            ],
+        )
+        .unwrap()
+        .into_base_type();
+        let metadata = XbfMetadata::from(&value);
+
+        let mut data = vec![];
+        value.serialize_base_type(&mut data).unwrap();
+        data.push(42);
+
+        let (value_ref, rest) = XbfValueRef::parse(&metadata, &data).unwrap();
+        assert_eq!(rest, [42]);
+
+        let riders = value_ref.as_vec().unwrap();
+        assert_eq!(riders.len(), 3);
+        assert!(riders.get(3).is_none());
+
+        let arya = riders.get(1).unwrap().as_struct().unwrap();
+        assert_eq!(arya.get("name").unwrap().as_str(), Ok("Arya"));
+        assert_eq!(arya.get("age").unwrap().as_u16(), Ok(103));
+        assert!(arya.get("missing").is_none());
+
+        let ages = riders
+            .iter()
+            .map(|x| x.as_struct().unwrap().get("age").unwrap().as_u16().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(ages, [16, 103, 18]);
+
+        assert_eq!(XbfType::from(value_ref), value);
+    }
+
+    #[test]
+    fn variable_size_offsets_are_cached() {
+        let names = ["Eragon", "Arya", "Murtagh", "Saphira"];
+        let value = XbfVec::new(
+            XbfVecMetadata::new(rider_metadata()),
+            names.iter().map(|name| rider(name, 16)),
+        )
+        .unwrap()
+        .into_base_type();
+        let metadata = XbfMetadata::from(&value);
+
+        let mut data = vec![];
+        value.serialize_base_type(&mut data).unwrap();
+
+        let (value_ref, _) = XbfValueRef::parse(&metadata, &data).unwrap();
+        let riders = value_ref.as_vec().unwrap();
+        assert!(riders.offsets.0.get().is_none());
+
+        for (i, name) in names.iter().enumerate().rev() {
+            let rider = riders.get(i).unwrap().as_struct().unwrap();
+            assert!(rider.offsets.0.get().is_none());
+            assert_eq!(rider.get("age").unwrap().as_u16(), Ok(16));
+            assert_eq!(rider.get("name").unwrap().as_str(), Ok(*name));
+            assert_eq!(**rider.offsets.0.get().unwrap(), [0, 8 + name.len()]);
+        }
+        assert_eq!(**riders.offsets.0.get().unwrap(), [0, 16, 30, 47]);
+
+        // views returned by accessors share the cache of the value they came from
+        let riders = value_ref.as_vec().unwrap();
+        assert!(riders.offsets.0.get().is_some());
+
+        // the cache is not part of the value
+        let (uncached, _) = XbfValueRef::parse(&metadata, &data).unwrap();
+        assert_eq!(riders, uncached.as_vec().unwrap());
+    }
+
+    #[test]
+    fn fixed_size_elements_work() {
+        let metadata = XbfVecMetadata::new(XbfPrimitiveMetadata::I32).into();
+        let mut data = vec![];
+        data.extend_from_slice(&3u64.to_le_bytes());
+        for x in [1i32, 2, 3] {
+            data.extend_from_slice(&x.to_le_bytes());
+        }
+
+        let (value_ref, _) = XbfValueRef::parse(&metadata, &data).unwrap();
+        let vec = value_ref.as_vec().unwrap();
+
+        assert_eq!(vec.get(2).unwrap().as_i32(), Ok(3));
+        assert!(vec.get(0).unwrap().as_str().is_err());
+    }
+
+    #[test]
+    fn parse_invalid_data_fails() {
+        let metadata = XbfVecMetadata::new(rider_metadata()).into();
+        let mut data = vec![];
+        data.extend_from_slice(&1u64.to_le_bytes());
+        data.extend_from_slice(&2u64.to_le_bytes());
+        data.extend_from_slice(&[0xff, 0xff]);
+        data.extend_from_slice(&16u16.to_le_bytes());
+
+        let err = XbfValueRef::parse(&metadata, &data).unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::InvalidUtf8));
+        assert_eq!(err.path(), "DragonRider[0].name");
+
+        data[16..18].copy_from_slice(b"Ok");
+        let err = XbfValueRef::parse(&metadata, &data[..data.len() - 1]).unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::UnexpectedEof));
+        assert_eq!(err.path(), "DragonRider[0].age");
+    }
+
+    #[test]
+    fn map_of_structs_works() {
+        let metadata = XbfMapMetadata::new(XbfPrimitiveMetadata::U8, rider_metadata());
+        let value = XbfMap::new(
+            metadata.clone(),
+            [
+                (XbfPrimitive::U8(3), rider("Murtagh", 18)),
+                (XbfPrimitive::U8(1), rider("Eragon", 16)),
+            ],
+        )
+        .unwrap()
+        .into_base_type();
+        let metadata = metadata.into();
+
+        let mut data = vec![];
+        value.serialize_base_type(&mut data).unwrap();
+
+        let (value_ref, rest) = XbfValueRef::parse(&metadata, &data).unwrap();
+        assert!(rest.is_empty());
+
+        let riders = value_ref.as_map().unwrap();
+        assert_eq!(riders.len(), 2);
+        let eragon = riders.get(&XbfPrimitive::U8(1).into()).unwrap();
+        assert_eq!(
+            eragon.as_struct().unwrap().get("name").unwrap().as_str(),
+            Ok("Eragon")
+        );
+        assert!(riders.get(&XbfPrimitive::U8(2).into()).is_none());
+
+        let keys = riders
+            .iter()
+            .map(|(key, _)| key.as_u8().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, [3, 1]);
+
+        assert_eq!(XbfType::from(value_ref), value);
+    }
+
+    #[test]
+    fn duplicate_map_keys_match_owned_map() {
+        let metadata =
+            XbfMapMetadata::new(XbfPrimitiveMetadata::U8, XbfPrimitiveMetadata::String).into();
+        let mut data = vec![];
+        data.extend_from_slice(&3u64.to_le_bytes());
+        for (key, value) in [(1u8, "Eragon"), (2, "Arya"), (1, "Murtagh")] {
+            data.push(key);
+            data.extend_from_slice(&(value.len() as u64).to_le_bytes());
+            data.extend_from_slice(value.as_bytes());
+        }
+
+        let value = XbfReader::new(data.as_slice())
+            .read_value(&metadata)
+            .unwrap();
+        let mut reader = XbfReader::new(data.as_slice());
+        let value_ref = reader.read_value_ref(&metadata).unwrap();
+
+        let riders = value_ref.as_map().unwrap();
+        assert_eq!(riders.len(), 2);
+        assert_eq!(
+            riders.get(&XbfPrimitive::U8(1).into()).unwrap().as_str(),
+            Ok("Murtagh")
+        );
+        let entries = riders
+            .iter()
+            .map(|(key, value)| (key.as_u8().unwrap(), value.as_str().unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(entries, [(1, "Murtagh"), (2, "Arya")]);
+        assert_eq!(riders.iter().len(), 2);
+
+        assert_eq!(XbfType::from(value_ref), value);
+
+        let strict = DeserializeOptions::new().strict(true);
+        let mut reader = XbfReader::with_options(data.as_slice(), strict);
+        let err = reader.read_value_ref(&metadata).unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::DuplicateKey));
+        assert_eq!(err.offset(), 8 + 15 + 13);
+
+        // a nested map is only skipped over when parsed, so its view finds the duplicates
+        let option_metadata = XbfOptionMetadata::new(metadata.clone()).into();
+        let mut option_data = vec![1];
+        option_data.extend_from_slice(&data);
+        let (option, _) = XbfValueRef::parse(&option_metadata, &option_data).unwrap();
+        let nested = option.as_option().unwrap().value().unwrap();
+        assert_eq!(nested.as_map().unwrap(), riders);
+        assert_eq!(nested.as_map().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn array_of_structs_works() {
+        let metadata = XbfArrayMetadata::new(rider_metadata(), 2);
+        let value = XbfArray::new(metadata.clone(), [rider("Eragon", 16), rider("Arya", 103)])
+            .unwrap()
+            .into_base_type();
+        let metadata = metadata.into();
+
+        let mut data = vec![];
+        value.serialize_base_type(&mut data).unwrap();
+
+        let (value_ref, rest) = XbfValueRef::parse(&metadata, &data).unwrap();
+        assert!(rest.is_empty());
+
+        let riders = value_ref.as_array().unwrap();
+        assert_eq!(riders.len(), 2);
+        assert!(riders.get(2).is_none());
+        let arya = riders.get(1).unwrap().as_struct().unwrap();
+        assert_eq!(arya.get("name").unwrap().as_str(), Ok("Arya"));
+
+        assert_eq!(XbfType::from(value_ref), value);
+
+        let err = XbfValueRef::parse(&metadata, &data[..data.len() - 1]).unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::UnexpectedEof));
+        assert_eq!(err.path(), "DragonRider[1].age");
+    }
+
+    #[test]
+    fn tensor_works() {
+        let metadata = XbfTensorMetadata::new(XbfPrimitiveMetadata::U16, 2).unwrap();
+        let value = XbfTensor::new(metadata.clone(), [2, 2], vec![1u16, 2, 3, 4])
+            .unwrap()
+            .into_base_type();
+        let metadata = metadata.into();
+
+        let mut data = vec![];
+        value.serialize_base_type(&mut data).unwrap();
+
+        let (value_ref, rest) = XbfValueRef::parse(&metadata, &data).unwrap();
+        assert!(rest.is_empty());
+
+        let tensor = value_ref.as_tensor().unwrap();
+        assert_eq!(tensor.shape(), [2, 2]);
+        assert_eq!(tensor.len(), 4);
+        assert_eq!(tensor.get(2), Some(XbfPrimitiveRef::U16(3)));
+        assert!(tensor.get(4).is_none());
+        assert_eq!(tensor.iter().count(), 4);
+
+        assert_eq!(XbfType::from(value_ref), value);
+
+        let err = XbfValueRef::parse(&metadata, &data[..data.len() - 1]).unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::UnexpectedEof));
+        assert_eq!(err.path(), "U16[3]");
+    }
+
+    #[test]
+    fn zero_size_elements_count_against_total_bytes() {
+        let empty = XbfStructMetadata::new("Empty", IndexMap::<&str, _>::new());
+        let metadata = XbfVecMetadata::new(empty).into();
+        let data = u64::MAX.to_le_bytes();
+
+        let options = DeserializeOptions::new().max_total_bytes(64);
+        let mut reader = XbfReader::with_options(data.as_slice(), options);
+        let err = reader.read_value_ref(&metadata).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            XbfErrorKind::LimitExceeded {
+                limit: XbfLimit::TotalBytes,
+                ..
+            }
+        ));
+    }
+}
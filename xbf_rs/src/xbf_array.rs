@@ -0,0 +1,323 @@
+//! A fixed length array as defined by the XBF specification.
+
+mod array_metadata;
+
+pub use array_metadata::*;
+
+use crate::{
+    reader::{XbfReader, MAX_PREALLOCATION},
+    XbfError, XbfMetadata, XbfProjection, XbfType, XbfTypeUpcast,
+};
+use std::{
+    error::Error,
+    fmt::Display,
+    io::{self, Read, Write},
+    ops::Deref,
+    slice::{Iter, IterMut},
+    vec::IntoIter,
+};
+
+/// A fixed length array as defined by the XBF specification.
+///
+/// An array always has exactly as many elements as its metadata specifies, so unlike an
+/// [`XbfVec`](crate::XbfVec) its length is not written with each value.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::{XbfArray, XbfArrayMetadata, XbfPrimitive, XbfPrimitiveMetadata};
+///
+/// let metadata = XbfArrayMetadata::new(XbfPrimitiveMetadata::F32, 3);
+/// let position = XbfArray::new(metadata.clone(), [1.0, 2.0, 3.0].map(XbfPrimitive::F32)).unwrap();
+///
+/// let mut writer = vec![];
+/// position.serialize_array_type(&mut writer).unwrap();
+/// assert_eq!(writer.len(), 12);
+///
+/// assert!(XbfArray::new(metadata, [1.0, 2.0].map(XbfPrimitive::F32)).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct XbfArray {
+    pub(crate) metadata: XbfArrayMetadata,
+    elements: Vec<XbfType>,
+}
+
+impl XbfArray {
+    /// Tries to create a new array based on the supplied metadata.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`ArrayError`] if there are not exactly as many elements as the metadata
+    /// specifies, or if any element is not the XBF type specified in the metadata.
+    pub fn new(
+        metadata: XbfArrayMetadata,
+        elements: impl IntoIterator<Item = impl Into<XbfType>>,
+    ) -> Result<Self, ArrayError> {
+        let array = Self::new_unchecked(metadata, elements);
+
+        let actual_len = array.elements.len() as u64;
+        if actual_len != array.metadata.len {
+            return Err(ArrayError::LengthMismatch {
+                expected_len: array.metadata.len,
+                actual_len,
+            });
+        }
+        for (index, element) in array.elements.iter().enumerate() {
+            let actual_type = XbfMetadata::from(element);
+            if *array.metadata.inner_type != actual_type {
+                return Err(ArrayError::ElementMismatch {
+                    index: index as u64,
+                    expected_type: (*array.metadata.inner_type).clone(),
+                    actual_type,
+                });
+            }
+        }
+        Ok(array)
+    }
+
+    /// Creates a new array with the supplied metadata and elements without checking their
+    /// number or types.
+    ///
+    /// If you use this function you are proceeding at your own peril.
+    pub fn new_unchecked(
+        metadata: XbfArrayMetadata,
+        elements: impl IntoIterator<Item = impl Into<XbfType>>,
+    ) -> Self {
+        let elements = elements.into_iter().map(Into::into).collect::<Vec<_>>();
+        Self { metadata, elements }
+    }
+
+    /// Serialize an array as defined by the XBF specification.
+    ///
+    /// This function **does not** write out the metadata of the type. If you want to write out the
+    /// metadata, get the metadata with [`Self::get_metadata`] and serialize that with
+    /// [`XbfArrayMetadata::serialize_array_metadata`].
+    pub fn serialize_array_type(&self, writer: &mut impl Write) -> io::Result<()> {
+        self.elements
+            .iter()
+            .try_for_each(|e| e.serialize_base_type(writer))
+    }
+
+    /// Deserialize an array as defined by the XBF specification.
+    ///
+    /// This function **does not** read the metadata of the type from the reader. It is
+    /// expected that to call this function the metadata for a type is already known, be
+    /// that from reading it from the reader with
+    /// [`deserialize_base_metadata`](crate::XbfMetadata::deserialize_base_metadata)
+    /// or having it in some other manner.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails or the data is invalid, with a path locating the
+    /// element which failed.
+    pub fn deserialize_array_type(
+        metadata: &XbfArrayMetadata,
+        reader: &mut impl Read,
+    ) -> Result<XbfArray, XbfError> {
+        Self::read_array_type(metadata, &mut XbfReader::new(reader))
+            .map_err(|e| e.in_root(&metadata.inner_type))
+    }
+
+    pub(crate) fn read_array_type(
+        metadata: &XbfArrayMetadata,
+        reader: &mut XbfReader<impl Read>,
+    ) -> Result<XbfArray, XbfError> {
+        reader.nested(|reader| {
+            reader.charge_elements(metadata.inner_type.fixed_size(), metadata.len)?;
+            let max_capacity = MAX_PREALLOCATION / std::mem::size_of::<XbfType>();
+            let mut elements = Vec::with_capacity((metadata.len as usize).min(max_capacity));
+            for i in 0..metadata.len {
+                elements.push(
+                    XbfType::read_base_type(&metadata.inner_type, reader)
+                        .map_err(|e| e.at_index(i))?,
+                );
+            }
+            Ok(XbfArray::new_unchecked(metadata.clone(), elements))
+        })
+    }
+
+    pub(crate) fn read_array_type_projected(
+        metadata: &XbfArrayMetadata,
+        projected: &XbfArrayMetadata,
+        projection: &XbfProjection,
+        reader: &mut XbfReader<impl Read>,
+    ) -> Result<XbfArray, XbfError> {
+        reader.nested(|reader| {
+            reader.charge_elements(metadata.inner_type.fixed_size(), metadata.len)?;
+            let max_capacity = MAX_PREALLOCATION / std::mem::size_of::<XbfType>();
+            let mut elements = Vec::with_capacity((metadata.len as usize).min(max_capacity));
+            for i in 0..metadata.len {
+                elements.push(
+                    XbfType::read_projected(
+                        &metadata.inner_type,
+                        &projected.inner_type,
+                        projection,
+                        reader,
+                    )
+                    .map_err(|e| e.at_index(i))?,
+                );
+            }
+            Ok(XbfArray::new_unchecked(projected.clone(), elements))
+        })
+    }
+
+    /// Returns the metadata of the array.
+    pub fn get_metadata(&self) -> XbfArrayMetadata {
+        self.metadata.clone()
+    }
+}
+
+impl Deref for XbfArray {
+    type Target = [XbfType];
+
+    fn deref(&self) -> &Self::Target {
+        &self.elements
+    }
+}
+
+impl<'a> IntoIterator for &'a XbfArray {
+    type Item = &'a XbfType;
+
+    type IntoIter = Iter<'a, XbfType>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut XbfArray {
+    type Item = &'a mut XbfType;
+
+    type IntoIter = IterMut<'a, XbfType>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.iter_mut()
+    }
+}
+
+impl IntoIterator for XbfArray {
+    type Item = XbfType;
+
+    type IntoIter = IntoIter<XbfType>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.into_iter()
+    }
+}
+
+impl XbfTypeUpcast for XbfArray {
+    fn into_base_type(self) -> XbfType {
+        XbfType::Array(self)
+    }
+
+    fn to_base_type(&self) -> XbfType {
+        XbfType::Array(self.clone())
+    }
+}
+
+/// Error type for creating an [`XbfArray`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum ArrayError {
+    LengthMismatch {
+        expected_len: u64,
+        actual_len: u64,
+    },
+    ElementMismatch {
+        index: u64,
+        expected_type: XbfMetadata,
+        actual_type: XbfMetadata,
+    },
+}
+
+impl Display for ArrayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArrayError::LengthMismatch {
+                expected_len,
+                actual_len,
+            } => write!(
+                f,
+                "Provided {actual_len} elements, expected exactly {expected_len}"
+            ),
+            ArrayError::ElementMismatch {
+                index,
+                expected_type,
+                actual_type,
+            } => write!(
+                f,
+                "Provided element {index} is of type {actual_type:?}, expected {expected_type:?}"
+            ),
+        }
+    }
+}
+
+impl Error for ArrayError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{XbfErrorKind, XbfPrimitive, XbfPrimitiveMetadata};
+
+    fn position_metadata() -> XbfArrayMetadata {
+        XbfArrayMetadata::new(XbfPrimitiveMetadata::F32, 3)
+    }
+
+    #[test]
+    fn array_round_trip_works() {
+        let array =
+            XbfArray::new(position_metadata(), [1.0, 2.0, 3.0].map(XbfPrimitive::F32)).unwrap();
+
+        let mut data = vec![];
+        array.serialize_array_type(&mut data).unwrap();
+
+        let expected = [1.0f32, 2.0, 3.0]
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(data, expected);
+
+        let mut reader = data.as_slice();
+        let deserialized =
+            XbfArray::deserialize_array_type(&position_metadata(), &mut reader).unwrap();
+        assert_eq!(deserialized, array);
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn array_new_checks_elements() {
+        let err = XbfArray::new(position_metadata(), [XbfPrimitive::F32(1.0)]).unwrap_err();
+        assert_eq!(
+            err,
+            ArrayError::LengthMismatch {
+                expected_len: 3,
+                actual_len: 1,
+            }
+        );
+
+        let elements = [
+            XbfPrimitive::F32(1.0),
+            XbfPrimitive::F64(2.0),
+            XbfPrimitive::F32(3.0),
+        ];
+        let err = XbfArray::new(position_metadata(), elements).unwrap_err();
+        assert_eq!(
+            err,
+            ArrayError::ElementMismatch {
+                index: 1,
+                expected_type: XbfPrimitiveMetadata::F32.into(),
+                actual_type: XbfPrimitiveMetadata::F64.into(),
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_short_array_fails() {
+        let data = 1.0f32.to_le_bytes().repeat(2);
+
+        let err = XbfArray::deserialize_array_type(&position_metadata(), &mut data.as_slice())
+            .unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::UnexpectedEof));
+        assert_eq!(err.offset(), 8);
+        assert_eq!(err.path(), "F32[2]");
+    }
+}
@@ -0,0 +1,165 @@
+use crate::{
+    reader::XbfReader, RcType, XbfArray, XbfError, XbfMetadata, XbfMetadataUpcast,
+    MAP_METADATA_DISCRIMINANT,
+};
+use byteorder::{LittleEndian, WriteBytesExt};
+use std::io::{self, Read, Write};
+
+/// The metadata discriminant for an Array type.
+///
+/// This is the same for all arrays regardless of their contents. It's value should always be
+/// equal to the discriminant value of the map type plus one.
+pub const ARRAY_METADATA_DISCRIMINANT: u8 = MAP_METADATA_DISCRIMINANT + 1;
+
+/// Metadata for an Array type.
+///
+/// Unlike a vector, the number of elements of an array is part of its metadata rather than of
+/// each value.
+///
+/// Internally the metadata is stored on the heap to avoid having a recursive, infinitely sized
+/// type on the stack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XbfArrayMetadata {
+    pub(crate) inner_type: RcType<XbfMetadata>,
+    pub(crate) len: u64,
+}
+
+impl XbfArrayMetadata {
+    /// Creates a new Array metadata.
+    ///
+    /// This will cause a heap allocation with the moved inner type.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xbf_rs::{XbfArrayMetadata, XbfPrimitiveMetadata};
+    ///
+    /// let metadata = XbfArrayMetadata::new(XbfPrimitiveMetadata::F32, 3);
+    ///
+    /// assert_eq!(metadata.inner_type(), &XbfPrimitiveMetadata::F32.into());
+    /// assert_eq!(metadata.len(), 3);
+    /// ```
+    pub fn new(inner_type: impl Into<XbfMetadata>, len: u64) -> Self {
+        Self {
+            inner_type: RcType::new(inner_type.into()),
+            len,
+        }
+    }
+
+    /// Returns the metadata of the elements of the array.
+    pub fn inner_type(&self) -> &XbfMetadata {
+        &self.inner_type
+    }
+
+    /// Returns the number of elements every value of the array has.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns `true` if values of the array have no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Serialize Array metadata as defined by the XBF specification.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xbf_rs::{XbfArrayMetadata, XbfPrimitiveMetadata, ARRAY_METADATA_DISCRIMINANT};
+    ///
+    /// let metadata = XbfArrayMetadata::new(XbfPrimitiveMetadata::F32, 3);
+    /// let mut writer = Vec::new();
+    /// metadata.serialize_array_metadata(&mut writer).unwrap();
+    ///
+    /// let mut expected = vec![ARRAY_METADATA_DISCRIMINANT];
+    /// expected.extend_from_slice(&3u64.to_le_bytes());
+    /// expected.push(XbfPrimitiveMetadata::F32 as u8);
+    /// assert_eq!(writer, expected);
+    /// ```
+    pub fn serialize_array_metadata(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_u8(ARRAY_METADATA_DISCRIMINANT)?;
+        writer.write_u64::<LittleEndian>(self.len)?;
+        self.inner_type.serialize_base_metadata(writer)
+    }
+
+    /// Deserialize Array metadata as defined by the XBF specification.
+    ///
+    /// This method assumes that you know for a fact you are about to receive Array metadata. If
+    /// you do not know what sort of metadata you are receiving, use
+    /// [`deserialize_base_metadata`](crate::XbfMetadata::deserialize_base_metadata).
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails or the metadata is invalid.
+    pub fn deserialize_array_metadata(
+        reader: &mut impl Read,
+    ) -> Result<XbfArrayMetadata, XbfError> {
+        Self::read_array_metadata(&mut XbfReader::new(reader))
+    }
+
+    pub(crate) fn read_array_metadata(
+        reader: &mut XbfReader<impl Read>,
+    ) -> Result<XbfArrayMetadata, XbfError> {
+        reader.nested(|reader| {
+            let len = reader.read_vec_len()?;
+            let inner_type = XbfMetadata::read_base_metadata(reader)?;
+            Ok(XbfArrayMetadata::new(inner_type, len))
+        })
+    }
+}
+
+impl From<&XbfArray> for XbfArrayMetadata {
+    fn from(value: &XbfArray) -> Self {
+        value.get_metadata()
+    }
+}
+
+impl XbfMetadataUpcast for XbfArrayMetadata {
+    fn into_base_metadata(self) -> XbfMetadata {
+        XbfMetadata::Array(self)
+    }
+
+    fn to_base_metadata(&self) -> XbfMetadata {
+        XbfMetadata::Array(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DeserializeOptions, XbfErrorKind, XbfLimit, XbfPrimitiveMetadata};
+
+    #[test]
+    fn metadata_serde_works() {
+        let metadata =
+            XbfArrayMetadata::new(XbfArrayMetadata::new(XbfPrimitiveMetadata::F32, 3), 2);
+        let mut writer = vec![];
+        metadata.serialize_array_metadata(&mut writer).unwrap();
+
+        let deserialized = XbfMetadata::deserialize_base_metadata(&mut writer.as_slice()).unwrap();
+        assert_eq!(deserialized, metadata.clone().into());
+        assert_eq!(XbfMetadata::from(metadata).fixed_size(), Some(24));
+    }
+
+    #[test]
+    fn metadata_len_is_limited() {
+        let mut data = vec![];
+        XbfArrayMetadata::new(XbfPrimitiveMetadata::U8, 100)
+            .serialize_array_metadata(&mut data)
+            .unwrap();
+
+        let options = DeserializeOptions::new().max_vec_len(10);
+        let err = XbfReader::with_options(data.as_slice(), options)
+            .read_metadata()
+            .unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            XbfErrorKind::LimitExceeded {
+                limit: XbfLimit::VecLength,
+                actual: 100,
+                max: 10,
+            }
+        ));
+    }
+}
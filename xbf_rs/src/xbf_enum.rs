@@ -0,0 +1,358 @@
+//! An enum as defined by the XBF specification.
+mod enum_metadata;
+
+pub use enum_metadata::*;
+
+use crate::{reader::XbfReader, XbfError, XbfMetadata, XbfType, XbfTypeUpcast};
+use byteorder::{LittleEndian, WriteBytesExt};
+use std::{
+    error::Error,
+    fmt::Display,
+    io::{self, Read, Write},
+};
+
+/// An enum as defined by the XBF specification.
+///
+/// An enum holds the value of exactly one of the variants listed in its metadata. Variants without
+/// any data can be given the metadata of an empty struct.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::{XbfEnum, XbfEnumMetadata, XbfPrimitive, XbfPrimitiveMetadata};
+///
+/// use indexmap::indexmap;
+///
+/// let metadata = XbfEnumMetadata::new(
+///     "Event",
+///     indexmap! {
+///         "Login" => XbfPrimitiveMetadata::String.into(),
+///         "Logout" => XbfPrimitiveMetadata::U64.into(),
+///     },
+/// );
+///
+/// let event = XbfEnum::new(metadata, "Logout", XbfPrimitive::U64(42)).unwrap();
+///
+/// assert_eq!(event.variant_index(), 1);
+/// assert_eq!(event.variant_name(), "Logout");
+/// assert_eq!(event.value(), &XbfPrimitive::U64(42).into());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct XbfEnum {
+    pub(crate) metadata: XbfEnumMetadata,
+    variant: u16,
+    value: Box<XbfType>,
+}
+
+impl XbfEnum {
+    /// Tries to create a new [`XbfEnum`] holding `value` as the given variant.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`EnumError`] if the metadata has no such variant, or if the value is not the
+    /// XBF type the metadata specifies for the variant.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use xbf_rs::{XbfEnum, XbfEnumMetadata, XbfPrimitive, XbfPrimitiveMetadata};
+    ///
+    /// use indexmap::indexmap;
+    ///
+    /// let metadata = XbfEnumMetadata::new(
+    ///     "Event",
+    ///     indexmap! { "Logout" => XbfPrimitiveMetadata::U64.into() },
+    /// );
+    ///
+    /// let err = XbfEnum::new(metadata.clone(), "Login", XbfPrimitive::U64(42)).unwrap_err();
+    /// assert_eq!(err.to_string(), "Enum Event has no variant Login");
+    ///
+    /// let err = XbfEnum::new(metadata, "Logout", XbfPrimitive::U8(42)).unwrap_err();
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "Provided value for variant Logout is of type Primitive(U8), expected Primitive(U64)"
+    /// );
+    /// ```
+    pub fn new(
+        metadata: XbfEnumMetadata,
+        variant_name: &str,
+        value: impl Into<XbfType>,
+    ) -> Result<Self, EnumError> {
+        let value = value.into();
+        let Some(variant) = metadata.variant_index(variant_name) else {
+            return Err(EnumError::UnknownVariant {
+                enum_name: metadata.name().to_string(),
+                variant_name: variant_name.to_string(),
+            });
+        };
+
+        let expected_variant_type = &metadata.inner.variants[usize::from(variant)];
+        let actual_variant_type = XbfMetadata::from(&value);
+        if *expected_variant_type != actual_variant_type {
+            return Err(EnumError::VariantMismatch {
+                variant_name: variant_name.to_string(),
+                expected_variant_type: expected_variant_type.clone(),
+                actual_variant_type,
+            });
+        }
+
+        Ok(Self {
+            metadata,
+            variant,
+            value: Box::new(value),
+        })
+    }
+
+    /// Creates a new [`XbfEnum`] holding `value` as the variant at index `variant` without
+    /// checking that the variant exists or that the value is the correct type.
+    ///
+    /// If you use this function you are proceeding at your own peril.
+    pub fn new_unchecked(
+        metadata: XbfEnumMetadata,
+        variant: u16,
+        value: impl Into<XbfType>,
+    ) -> Self {
+        Self {
+            metadata,
+            variant,
+            value: Box::new(value.into()),
+        }
+    }
+
+    /// Serialize an enum as defined by the XBF specification.
+    ///
+    /// This function **does not** write out the metadata of the type. If you want to write out the
+    /// metadata, get the metadata with [`Self::get_metadata`] and serialize that with
+    /// [`XbfEnumMetadata::serialize_enum_metadata`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use xbf_rs::{XbfEnum, XbfEnumMetadata, XbfPrimitive, XbfPrimitiveMetadata};
+    ///
+    /// use indexmap::indexmap;
+    ///
+    /// let metadata = XbfEnumMetadata::new(
+    ///     "Event",
+    ///     indexmap! {
+    ///         "Login" => XbfPrimitiveMetadata::String.into(),
+    ///         "Logout" => XbfPrimitiveMetadata::U64.into(),
+    ///     },
+    /// );
+    /// let event = XbfEnum::new(metadata, "Logout", XbfPrimitive::U64(42)).unwrap();
+    ///
+    /// let mut writer = vec![];
+    /// event.serialize_enum_type(&mut writer).unwrap();
+    ///
+    /// let mut expected = 1u16.to_le_bytes().to_vec();
+    /// expected.extend_from_slice(&42u64.to_le_bytes());
+    /// assert_eq!(writer, expected);
+    /// ```
+    pub fn serialize_enum_type(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_u16::<LittleEndian>(self.variant)?;
+        self.value.serialize_base_type(writer)
+    }
+
+    /// Deserialize an enum as defined by the XBF specification.
+    ///
+    /// This function **does not** read the metadata of the type from the reader. It is expected
+    /// that to call this function the metadata for a type is already known, be that from reading
+    /// it from the reader with
+    /// [`deserialize_base_metadata`](crate::XbfMetadata::deserialize_base_metadata) or having it
+    /// in some other manner.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails or the data is invalid, including with
+    /// [`XbfErrorKind::UnknownVariant`](crate::XbfErrorKind::UnknownVariant) if the variant index
+    /// is out of range.
+    pub fn deserialize_enum_type(
+        metadata: &XbfEnumMetadata,
+        reader: &mut impl Read,
+    ) -> Result<XbfEnum, XbfError> {
+        Self::read_enum_type(metadata, &mut XbfReader::new(reader))
+            .map_err(|e| e.in_root(&metadata.into()))
+    }
+
+    pub(crate) fn read_enum_type(
+        metadata: &XbfEnumMetadata,
+        reader: &mut XbfReader<impl Read>,
+    ) -> Result<XbfEnum, XbfError> {
+        reader.nested(|reader| {
+            let (variant, variant_name, variant_type) = reader.read_variant(metadata)?;
+            let value = XbfType::read_base_type(variant_type, reader)
+                .map_err(|e| e.in_field(variant_name))?;
+            Ok(Self::new_unchecked(metadata.clone(), variant, value))
+        })
+    }
+
+    /// Returns the metadata of the enum.
+    pub fn get_metadata(&self) -> XbfEnumMetadata {
+        self.metadata.clone()
+    }
+
+    /// Returns the index of the variant this enum holds.
+    pub fn variant_index(&self) -> u16 {
+        self.variant
+    }
+
+    /// Returns the name of the variant this enum holds.
+    pub fn variant_name(&self) -> &str {
+        self.metadata
+            .get_variant(self.variant)
+            .map(|(name, _)| name)
+            .expect("the variant index is in range")
+    }
+
+    /// Returns a reference to the value of the variant.
+    pub fn value(&self) -> &XbfType {
+        &self.value
+    }
+
+    /// Consumes the enum, returning the value of the variant.
+    pub fn into_value(self) -> XbfType {
+        *self.value
+    }
+}
+
+impl XbfTypeUpcast for XbfEnum {
+    fn into_base_type(self) -> XbfType {
+        XbfType::Enum(self)
+    }
+
+    fn to_base_type(&self) -> XbfType {
+        XbfType::Enum(self.clone())
+    }
+}
+
+/// Error type for creating an [`XbfEnum`].
+#[derive(Debug)]
+pub enum EnumError {
+    UnknownVariant {
+        enum_name: String,
+        variant_name: String,
+    },
+    VariantMismatch {
+        variant_name: String,
+        expected_variant_type: XbfMetadata,
+        actual_variant_type: XbfMetadata,
+    },
+}
+
+impl Display for EnumError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnumError::UnknownVariant {
+                enum_name,
+                variant_name,
+            } => write!(f, "Enum {enum_name} has no variant {variant_name}"),
+            EnumError::VariantMismatch {
+                variant_name,
+                expected_variant_type,
+                actual_variant_type,
+            } => write!(
+                f,
+                "Provided value for variant {variant_name} is of type {actual_variant_type:?}, \
+                expected {expected_variant_type:?}"
+            ),
+        }
+    }
+}
+
+impl Error for EnumError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        XbfErrorKind, XbfPrimitive, XbfPrimitiveMetadata, XbfStruct, XbfStructMetadata, XbfVec,
+        XbfVecMetadata,
+    };
+    use indexmap::indexmap;
+
+    fn login_metadata() -> XbfStructMetadata {
+        XbfStructMetadata::new(
+            "Login",
+            indexmap! {"user" => XbfPrimitiveMetadata::String.into()},
+        )
+    }
+
+    fn event_metadata() -> XbfEnumMetadata {
+        XbfEnumMetadata::new(
+            "Event",
+            indexmap! {
+                "Login" => login_metadata().into(),
+                "Logout" => XbfPrimitiveMetadata::U64.into(),
+            },
+        )
+    }
+
+    #[test]
+    fn enum_round_trip_works() {
+        let metadata = event_metadata();
+        let login = XbfStruct::new(
+            login_metadata(),
+            [XbfPrimitive::String("Eragon".to_string()).into()],
+        )
+        .unwrap();
+        let values = [
+            XbfEnum::new(metadata.clone(), "Login", login).unwrap(),
+            XbfEnum::new(metadata.clone(), "Logout", XbfPrimitive::U64(42)).unwrap(),
+        ];
+
+        let mut data = vec![];
+        for value in &values {
+            value.serialize_enum_type(&mut data).unwrap();
+        }
+
+        let mut reader = data.as_slice();
+        for value in values {
+            let deserialized = XbfEnum::deserialize_enum_type(&metadata, &mut reader).unwrap();
+            assert_eq!(deserialized, value);
+        }
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn vec_of_enums_must_share_metadata() {
+        let metadata = event_metadata();
+        let logout = XbfEnum::new(metadata.clone(), "Logout", XbfPrimitive::U64(42)).unwrap();
+        let other = XbfEnum::new(
+            XbfEnumMetadata::new(
+                "Other",
+                indexmap! {"Logout" => XbfPrimitiveMetadata::U64.into()},
+            ),
+            "Logout",
+            XbfPrimitive::U64(42),
+        )
+        .unwrap();
+
+        assert!(XbfVec::new(XbfVecMetadata::new(metadata.clone()), [logout.clone()]).is_ok());
+        assert!(XbfVec::new(XbfVecMetadata::new(metadata), [logout, other]).is_err());
+    }
+
+    #[test]
+    fn unknown_variant_fails() {
+        let mut data = vec![];
+        data.extend_from_slice(&2u16.to_le_bytes());
+
+        let err =
+            XbfEnum::deserialize_enum_type(&event_metadata(), &mut data.as_slice()).unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::UnknownVariant(2)));
+        assert_eq!(err.offset(), 0);
+        assert_eq!(err.path(), "Event");
+    }
+
+    #[test]
+    fn error_path_includes_variant() {
+        let mut data = vec![];
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data.extend_from_slice(&2u64.to_le_bytes());
+        data.extend_from_slice(&[0xff, 0xff]);
+
+        let err =
+            XbfEnum::deserialize_enum_type(&event_metadata(), &mut data.as_slice()).unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::InvalidUtf8));
+        assert_eq!(err.path(), "Event.Login.user");
+    }
+}
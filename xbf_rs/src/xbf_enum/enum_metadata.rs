@@ -0,0 +1,277 @@
+use crate::{
+    base_metadata::XbfMetadataUpcast,
+    reader::XbfReader,
+    util::{read_string, write_string},
+    RcType, XbfEnum, XbfError, XbfErrorKind, XbfMetadata, OPTION_METADATA_DISCRIMINANT,
+};
+use byteorder::{LittleEndian, WriteBytesExt};
+use indexmap::IndexMap;
+use std::io::{self, Read, Write};
+
+/// The metadata discriminant for an Enum type.
+///
+/// This is the same for all enums regardless of their contents. It's value should always be
+/// equal to the discriminant value of the option type plus one.
+pub const ENUM_METADATA_DISCRIMINANT: u8 = OPTION_METADATA_DISCRIMINANT + 1;
+
+#[derive(Debug, PartialEq, Eq)]
+pub(in crate::xbf_enum) struct XbfEnumMetadataInner {
+    pub(in crate::xbf_enum) name: Box<str>,
+    pub(in crate::xbf_enum) variants: IndexMap<Box<str>, XbfMetadata>,
+}
+
+/// Metadata for an Enum type.
+///
+/// An enum has a name and an ordered list of named variants, each carrying a value of its own
+/// type. Variants are identified on the wire by their index in this list.
+///
+/// Enum metadata is immutable, and cannot be changed once created. Cloning this metadata is
+/// relatively inexpensive, as the current implementation is using reference-counted shared
+/// memory internally.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct XbfEnumMetadata {
+    pub(in crate::xbf_enum) inner: RcType<XbfEnumMetadataInner>,
+}
+
+impl XbfEnumMetadata {
+    /// Creates a new [`XbfEnumMetadata`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use xbf_rs::{XbfEnumMetadata, XbfPrimitiveMetadata};
+    ///
+    /// use indexmap::indexmap;
+    ///
+    /// let metadata = XbfEnumMetadata::new(
+    ///     "Event",
+    ///     indexmap! {
+    ///         "Login" => XbfPrimitiveMetadata::String.into(),
+    ///         "Logout" => XbfPrimitiveMetadata::U64.into(),
+    ///     },
+    /// );
+    ///
+    /// assert_eq!(metadata.name(), "Event");
+    /// assert_eq!(metadata.variant_index("Logout"), Some(1));
+    /// assert_eq!(metadata.get_variant_type("Login"), Some(&XbfPrimitiveMetadata::String.into()));
+    /// assert_eq!(metadata.get_variant_type("Crash"), None);
+    /// ```
+    pub fn new(
+        name: impl Into<Box<str>>,
+        variants: IndexMap<impl Into<Box<str>>, XbfMetadata>,
+    ) -> Self {
+        Self {
+            inner: RcType::new(XbfEnumMetadataInner {
+                name: name.into(),
+                variants: variants.into_iter().map(|(k, v)| (k.into(), v)).collect(),
+            }),
+        }
+    }
+
+    /// Returns the name of the enum.
+    pub fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    /// Returns the metadata of a variant if it exists, otherwise returns `None`.
+    pub fn get_variant_type(&self, variant: &str) -> Option<&XbfMetadata> {
+        self.inner.variants.get(variant)
+    }
+
+    /// Returns the index of a variant if it exists, otherwise returns `None`.
+    pub fn variant_index(&self, variant: &str) -> Option<u16> {
+        self.inner.variants.get_index_of(variant).map(|i| i as u16)
+    }
+
+    /// Returns the name and metadata of the variant at `index` if it exists, otherwise returns
+    /// `None`.
+    pub fn get_variant(&self, index: u16) -> Option<(&str, &XbfMetadata)> {
+        self.inner
+            .variants
+            .get_index(index.into())
+            .map(|(name, type_)| (&**name, type_))
+    }
+
+    /// Returns an iterator over the names and metadata of the variants, in index order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use xbf_rs::{XbfEnumMetadata, XbfPrimitiveMetadata};
+    ///
+    /// use indexmap::indexmap;
+    ///
+    /// let metadata = XbfEnumMetadata::new(
+    ///   "Event",
+    ///   indexmap! {
+    ///     "Login" => XbfPrimitiveMetadata::String.into(),
+    ///     "Logout" => XbfPrimitiveMetadata::U64.into(),
+    ///   },
+    /// );
+    ///
+    /// let names = metadata.variants().map(|(name, _)| name).collect::<Vec<_>>();
+    /// assert_eq!(names, ["Login", "Logout"]);
+    /// ```
+    pub fn variants(&self) -> impl ExactSizeIterator<Item = (&str, &XbfMetadata)> {
+        self.inner
+            .variants
+            .iter()
+            .map(|(name, type_)| (&**name, type_))
+    }
+
+    /// Serialize enum metadata as defined by the XBF specification.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xbf_rs::{XbfEnumMetadata, XbfPrimitiveMetadata, ENUM_METADATA_DISCRIMINANT};
+    ///
+    /// use indexmap::indexmap;
+    ///
+    /// let metadata = XbfEnumMetadata::new(
+    ///     "Event",
+    ///     indexmap! { "Logout" => XbfPrimitiveMetadata::U64.into() },
+    /// );
+    /// let mut writer = vec![];
+    ///
+    /// metadata.serialize_enum_metadata(&mut writer).unwrap();
+    ///
+    /// let mut expected = vec![ENUM_METADATA_DISCRIMINANT];
+    /// expected.extend_from_slice(&5u64.to_le_bytes());
+    /// expected.extend_from_slice(b"Event");
+    /// expected.extend_from_slice(&1u16.to_le_bytes());
+    /// expected.extend_from_slice(&6u64.to_le_bytes());
+    /// expected.extend_from_slice(b"Logout");
+    /// expected.push(XbfPrimitiveMetadata::U64 as u8);
+    ///
+    /// assert_eq!(writer, expected);
+    /// ```
+    pub fn serialize_enum_metadata(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_u8(ENUM_METADATA_DISCRIMINANT)?;
+
+        write_string(&self.inner.name, writer)?;
+
+        let variants = &self.inner.variants;
+        writer.write_u16::<LittleEndian>(variants.len() as u16)?;
+
+        variants.iter().try_for_each(|(name, type_)| {
+            write_string(name, writer).and_then(|_| type_.serialize_base_metadata(writer))
+        })
+    }
+
+    /// Deserialize enum metadata as defined by the XBF specification.
+    ///
+    /// This method assumes that you know for a fact you are about to receive Enum metadata. If you
+    /// do not know what sort of metadata you are receiving, use
+    /// [`deserialize_base_metadata`](crate::XbfMetadata::deserialize_base_metadata).
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails or the metadata is invalid, including if the
+    /// same variant name appears more than once.
+    pub fn deserialize_enum_metadata(reader: &mut impl Read) -> Result<XbfEnumMetadata, XbfError> {
+        Self::read_enum_metadata(&mut XbfReader::new(reader))
+    }
+
+    pub(crate) fn read_enum_metadata(
+        reader: &mut XbfReader<impl Read>,
+    ) -> Result<XbfEnumMetadata, XbfError> {
+        reader.nested(|reader| {
+            let name = reader.read_struct_name()?;
+            let len = reader.read_struct_fields_len()?;
+            let mut variants = IndexMap::with_capacity(len as usize);
+            for _ in 0..len {
+                let offset = reader.position();
+                let variant_name = read_string(reader)?;
+                if variants.contains_key(variant_name.as_str()) {
+                    return Err(XbfError::new(
+                        XbfErrorKind::DuplicateVariant(variant_name.into()),
+                        offset,
+                    ));
+                }
+                let variant_type = XbfMetadata::read_base_metadata(reader)
+                    .map_err(|e| e.in_field(&variant_name))?;
+                variants.insert(variant_name, variant_type);
+            }
+            Ok(XbfEnumMetadata::new(name, variants))
+        })
+    }
+}
+
+impl From<&XbfEnum> for XbfEnumMetadata {
+    fn from(value: &XbfEnum) -> Self {
+        value.get_metadata()
+    }
+}
+
+impl XbfMetadataUpcast for XbfEnumMetadata {
+    fn into_base_metadata(self) -> XbfMetadata {
+        XbfMetadata::Enum(self)
+    }
+
+    fn to_base_metadata(&self) -> XbfMetadata {
+        XbfMetadata::Enum(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{XbfPrimitiveMetadata, XbfStructMetadata};
+    use indexmap::indexmap;
+
+    #[test]
+    fn metadata_serde_works() {
+        let metadata = XbfEnumMetadata::new(
+            "Event",
+            indexmap! {
+                "Login" => XbfStructMetadata::new(
+                    "Login",
+                    indexmap! {"user" => XbfPrimitiveMetadata::String.into()},
+                )
+                .into(),
+                "Logout" => XbfPrimitiveMetadata::U64.into(),
+            },
+        );
+
+        let mut writer = vec![];
+        metadata.serialize_enum_metadata(&mut writer).unwrap();
+
+        let deserialized = XbfMetadata::deserialize_base_metadata(&mut writer.as_slice()).unwrap();
+        assert_eq!(deserialized, metadata.into());
+    }
+
+    #[test]
+    fn fixed_size_requires_equal_variants() {
+        let metadata = |second: XbfPrimitiveMetadata| -> XbfMetadata {
+            XbfEnumMetadata::new(
+                "Event",
+                indexmap! {
+                    "Login" => XbfPrimitiveMetadata::U32.into(),
+                    "Logout" => second.into(),
+                },
+            )
+            .into()
+        };
+
+        assert_eq!(metadata(XbfPrimitiveMetadata::F32).fixed_size(), Some(6));
+        assert_eq!(metadata(XbfPrimitiveMetadata::U64).fixed_size(), None);
+        assert_eq!(metadata(XbfPrimitiveMetadata::String).fixed_size(), None);
+    }
+
+    #[test]
+    fn metadata_deserialize_duplicate_variant_fails() {
+        let mut data = vec![];
+        write_string("Event", &mut data).unwrap();
+        data.extend_from_slice(&2u16.to_le_bytes());
+        for _ in 0..2 {
+            write_string("Login", &mut data).unwrap();
+            data.push(XbfPrimitiveMetadata::U8 as u8);
+        }
+
+        let err = XbfEnumMetadata::deserialize_enum_metadata(&mut data.as_slice())
+            .expect_err("should have failed deserialization");
+
+        assert!(matches!(err.kind(), XbfErrorKind::DuplicateVariant(x) if &**x == "Login"));
+        assert_eq!(err.offset(), 8 + 5 + 2 + 8 + 5 + 1);
+    }
+}
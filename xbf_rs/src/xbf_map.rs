@@ -0,0 +1,447 @@
+//! A map as defined by the XBF specification.
+
+mod map_metadata;
+
+pub use map_metadata::*;
+
+use crate::{
+    reader::{XbfReader, MAX_PREALLOCATION},
+    XbfError, XbfErrorKind, XbfMetadata, XbfType, XbfTypeUpcast,
+};
+use byteorder::{LittleEndian, WriteBytesExt};
+use indexmap::IndexMap;
+use std::{
+    borrow::Borrow,
+    error::Error,
+    fmt::Display,
+    hash::{Hash, Hasher},
+    io::{self, Read, Write},
+};
+
+/// A map as defined by the XBF specification.
+///
+/// Entries are kept in the order they were inserted, which is the order they are serialized in.
+/// Two keys are the same key when they serialize to the same bytes.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::{XbfMap, XbfMapMetadata, XbfPrimitive, XbfPrimitiveMetadata};
+///
+/// let metadata = XbfMapMetadata::new(XbfPrimitiveMetadata::String, XbfPrimitiveMetadata::U16);
+/// let mut ages = XbfMap::new(
+///     metadata,
+///     [
+///         (XbfPrimitive::String("Eragon".to_string()), XbfPrimitive::U16(16)),
+///         (XbfPrimitive::String("Arya".to_string()), XbfPrimitive::U16(103)),
+///     ],
+/// )
+/// .unwrap();
+///
+/// let old = ages
+///     .insert(XbfPrimitive::String("Eragon".to_string()), XbfPrimitive::U16(17))
+///     .unwrap();
+/// assert_eq!(old, Some(XbfPrimitive::U16(16).into()));
+///
+/// let key = XbfPrimitive::String("Eragon".to_string()).into();
+/// assert_eq!(ages.get(&key), Some(&XbfPrimitive::U16(17).into()));
+/// assert_eq!(ages.len(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct XbfMap {
+    pub(crate) metadata: XbfMapMetadata,
+    entries: IndexMap<MapKey, XbfType>,
+}
+
+/// A key of a map, compared and hashed by its serialized bytes.
+#[derive(Debug, Clone)]
+struct MapKey {
+    bytes: Box<[u8]>,
+    value: XbfType,
+}
+
+impl MapKey {
+    fn new(value: XbfType) -> Self {
+        Self {
+            bytes: key_bytes(&value),
+            value,
+        }
+    }
+}
+
+fn key_bytes(key: &XbfType) -> Box<[u8]> {
+    let mut bytes = vec![];
+    key.serialize_base_type(&mut bytes)
+        .expect("writing to a vec never fails");
+    bytes.into()
+}
+
+impl PartialEq for MapKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl Eq for MapKey {}
+
+impl Hash for MapKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bytes.hash(state)
+    }
+}
+
+impl Borrow<[u8]> for MapKey {
+    fn borrow(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl XbfMap {
+    /// Tries to create a new map based on the supplied metadata.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MapError`] if any key or value is not the XBF type specified in the metadata,
+    /// or if the same key is given more than once.
+    pub fn new(
+        metadata: XbfMapMetadata,
+        entries: impl IntoIterator<Item = (impl Into<XbfType>, impl Into<XbfType>)>,
+    ) -> Result<Self, MapError> {
+        let mut map = Self {
+            metadata,
+            entries: IndexMap::new(),
+        };
+        for (key, value) in entries {
+            let (key, value) = (key.into(), value.into());
+            map.check(&key, &value)?;
+            let key = MapKey::new(key);
+            if map.entries.contains_key(&key) {
+                return Err(MapError::DuplicateKey(key.value));
+            }
+            map.entries.insert(key, value);
+        }
+        Ok(map)
+    }
+
+    /// Creates a new map with the supplied metadata and entries without checking their types.
+    ///
+    /// If the same key is given more than once, the last value given for it is kept.
+    pub fn new_unchecked(
+        metadata: XbfMapMetadata,
+        entries: impl IntoIterator<Item = (impl Into<XbfType>, impl Into<XbfType>)>,
+    ) -> Self {
+        let entries = entries
+            .into_iter()
+            .map(|(key, value)| (MapKey::new(key.into()), value.into()))
+            .collect();
+        Self { metadata, entries }
+    }
+
+    fn check(&self, key: &XbfType, value: &XbfType) -> Result<(), MapError> {
+        let actual_key_type = XbfMetadata::from(key);
+        if *self.metadata.key_type != actual_key_type {
+            return Err(MapError::KeyMismatch {
+                expected_key_type: (*self.metadata.key_type).clone(),
+                actual_key_type,
+            });
+        }
+        let actual_value_type = XbfMetadata::from(value);
+        if *self.metadata.value_type != actual_value_type {
+            return Err(MapError::ValueMismatch {
+                expected_value_type: (*self.metadata.value_type).clone(),
+                actual_value_type,
+            });
+        }
+        Ok(())
+    }
+
+    /// Serialize a map as defined by the XBF specification.
+    ///
+    /// This function **does not** write out the metadata of the type. If you want to write out the
+    /// metadata, get the metadata with [`Self::get_metadata`] and serialize that with
+    /// [`XbfMapMetadata::serialize_map_metadata`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use xbf_rs::{XbfMap, XbfMapMetadata, XbfPrimitive, XbfPrimitiveMetadata};
+    ///
+    /// let map = XbfMap::new(
+    ///     XbfMapMetadata::new(XbfPrimitiveMetadata::U8, XbfPrimitiveMetadata::I32),
+    ///     [(XbfPrimitive::U8(1), XbfPrimitive::I32(42))],
+    /// )
+    /// .unwrap();
+    /// let mut writer = vec![];
+    /// map.serialize_map_type(&mut writer).unwrap();
+    ///
+    /// let mut expected = vec![];
+    /// expected.extend_from_slice(&1u64.to_le_bytes());
+    /// expected.push(1);
+    /// expected.extend_from_slice(&42i32.to_le_bytes());
+    /// assert_eq!(writer, expected);
+    /// ```
+    pub fn serialize_map_type(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_u64::<LittleEndian>(self.entries.len() as u64)?;
+        self.entries.iter().try_for_each(|(key, value)| {
+            writer.write_all(&key.bytes)?;
+            value.serialize_base_type(writer)
+        })
+    }
+
+    /// Deserialize a map as defined by the XBF specification.
+    ///
+    /// This function **does not** read the metadata of the type from the reader. It is
+    /// expected that to call this function the metadata for a type is already known, be
+    /// that from reading it from the reader with
+    /// [`deserialize_base_metadata`](crate::XbfMetadata::deserialize_base_metadata)
+    /// or having it in some other manner.
+    ///
+    /// If the same key appears more than once, the last value for it is kept, unless reading in
+    /// [strict](crate::DeserializeOptions::strict) mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails or the data is invalid, including with
+    /// [`XbfErrorKind::DuplicateKey`] in strict mode.
+    pub fn deserialize_map_type(
+        metadata: &XbfMapMetadata,
+        reader: &mut impl Read,
+    ) -> Result<XbfMap, XbfError> {
+        Self::read_map_type(metadata, &mut XbfReader::new(reader))
+            .map_err(|e| e.in_root(&metadata.into()))
+    }
+
+    pub(crate) fn read_map_type(
+        metadata: &XbfMapMetadata,
+        reader: &mut XbfReader<impl Read>,
+    ) -> Result<XbfMap, XbfError> {
+        reader.nested(|reader| {
+            let len = reader.read_vec_len()?;
+            let entry_size = metadata
+                .key_type
+                .fixed_size()
+                .zip(metadata.value_type.fixed_size());
+            reader.charge_elements(entry_size.map(|(k, v)| k + v), len)?;
+            let max_capacity = MAX_PREALLOCATION / std::mem::size_of::<(MapKey, XbfType)>();
+            let mut entries = IndexMap::with_capacity((len as usize).min(max_capacity));
+            for i in 0..len {
+                let offset = reader.position();
+                let key = XbfType::read_base_type(&metadata.key_type, reader)
+                    .map_err(|e| e.at_index(i))?;
+                let key = MapKey::new(key);
+                if reader.options().strict && entries.contains_key(&key) {
+                    return Err(XbfError::new(XbfErrorKind::DuplicateKey, offset).at_index(i));
+                }
+                let value = XbfType::read_base_type(&metadata.value_type, reader)
+                    .map_err(|e| e.at_index(i))?;
+                entries.insert(key, value);
+            }
+            Ok(XbfMap {
+                metadata: metadata.clone(),
+                entries,
+            })
+        })
+    }
+
+    /// Returns the metadata of the map.
+    pub fn get_metadata(&self) -> XbfMapMetadata {
+        self.metadata.clone()
+    }
+
+    /// Inserts an entry into the map, returning the previous value of the key if there was one.
+    ///
+    /// A new key is added to the end of the map, while the value of an existing key is replaced
+    /// in place.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MapError`] if the key or value is not the XBF type specified in the metadata.
+    pub fn insert(
+        &mut self,
+        key: impl Into<XbfType>,
+        value: impl Into<XbfType>,
+    ) -> Result<Option<XbfType>, MapError> {
+        let (key, value) = (key.into(), value.into());
+        self.check(&key, &value)?;
+        Ok(self.entries.insert(MapKey::new(key), value))
+    }
+
+    /// Returns a reference to the value of a key if it exists, otherwise returns `None`.
+    pub fn get(&self, key: &XbfType) -> Option<&XbfType> {
+        self.entries.get(&*key_bytes(key))
+    }
+
+    /// Returns `true` if the map contains the key.
+    pub fn contains_key(&self, key: &XbfType) -> bool {
+        self.entries.contains_key(&*key_bytes(key))
+    }
+
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the map has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns an iterator over the keys and values of the map, in order.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (&XbfType, &XbfType)> {
+        self.entries.iter().map(|(key, value)| (&key.value, value))
+    }
+
+    /// Returns an iterator over the keys of the map, in order.
+    pub fn keys(&self) -> impl ExactSizeIterator<Item = &XbfType> {
+        self.entries.keys().map(|key| &key.value)
+    }
+
+    /// Returns an iterator over the values of the map, in order.
+    pub fn values(&self) -> impl ExactSizeIterator<Item = &XbfType> {
+        self.entries.values()
+    }
+
+    /// Returns the serialized key and the value of the entry at `index` if it exists, otherwise
+    /// returns `None`.
+    #[cfg(feature = "async")]
+    pub(crate) fn get_serialized_entry(&self, index: usize) -> Option<(&[u8], &XbfType)> {
+        self.entries
+            .get_index(index)
+            .map(|(key, value)| (&*key.bytes, value))
+    }
+}
+
+impl XbfTypeUpcast for XbfMap {
+    fn into_base_type(self) -> XbfType {
+        XbfType::Map(self)
+    }
+
+    fn to_base_type(&self) -> XbfType {
+        XbfType::Map(self.clone())
+    }
+}
+
+/// Error type for creating an [`XbfMap`].
+#[derive(Debug)]
+pub enum MapError {
+    KeyMismatch {
+        expected_key_type: XbfMetadata,
+        actual_key_type: XbfMetadata,
+    },
+    ValueMismatch {
+        expected_value_type: XbfMetadata,
+        actual_value_type: XbfMetadata,
+    },
+    DuplicateKey(XbfType),
+}
+
+impl Display for MapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapError::KeyMismatch {
+                expected_key_type,
+                actual_key_type,
+            } => write!(
+                f,
+                "Provided key is of type {actual_key_type:?}, expected {expected_key_type:?}"
+            ),
+            MapError::ValueMismatch {
+                expected_value_type,
+                actual_value_type,
+            } => write!(
+                f,
+                "Provided value is of type {actual_value_type:?}, expected {expected_value_type:?}"
+            ),
+            MapError::DuplicateKey(key) => write!(f, "Provided key {key:?} is duplicated"),
+        }
+    }
+}
+
+impl Error for MapError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DeserializeOptions, XbfPrimitive, XbfPrimitiveMetadata};
+
+    fn string(x: &str) -> XbfType {
+        XbfPrimitive::String(x.to_string()).into()
+    }
+
+    fn ages_metadata() -> XbfMapMetadata {
+        XbfMapMetadata::new(XbfPrimitiveMetadata::String, XbfPrimitiveMetadata::U16)
+    }
+
+    #[test]
+    fn map_round_trip_preserves_order() {
+        let map = XbfMap::new(
+            ages_metadata(),
+            [
+                (string("Murtagh"), XbfPrimitive::U16(18)),
+                (string("Arya"), XbfPrimitive::U16(103)),
+                (string("Eragon"), XbfPrimitive::U16(16)),
+            ],
+        )
+        .unwrap();
+
+        let mut data = vec![];
+        map.serialize_map_type(&mut data).unwrap();
+        let deserialized =
+            XbfMap::deserialize_map_type(&ages_metadata(), &mut data.as_slice()).unwrap();
+
+        assert_eq!(deserialized, map);
+        let keys = deserialized.keys().cloned().collect::<Vec<_>>();
+        assert_eq!(keys, [string("Murtagh"), string("Arya"), string("Eragon")]);
+    }
+
+    #[test]
+    fn map_new_checks_entries() {
+        let err = XbfMap::new(
+            ages_metadata(),
+            [(XbfPrimitive::U16(1), XbfPrimitive::U16(1))],
+        )
+        .unwrap_err();
+        assert!(matches!(err, MapError::KeyMismatch { .. }));
+
+        let err =
+            XbfMap::new(ages_metadata(), [(string("Arya"), XbfPrimitive::U8(1))]).unwrap_err();
+        assert!(matches!(err, MapError::ValueMismatch { .. }));
+
+        let err = XbfMap::new(
+            ages_metadata(),
+            [
+                (string("Arya"), XbfPrimitive::U16(103)),
+                (string("Arya"), XbfPrimitive::U16(104)),
+            ],
+        )
+        .unwrap_err();
+        assert!(matches!(err, MapError::DuplicateKey(x) if x == string("Arya")));
+    }
+
+    #[test]
+    fn duplicate_keys_fail_in_strict_mode() {
+        let mut data = vec![];
+        data.extend_from_slice(&2u64.to_le_bytes());
+        for age in [103u16, 104] {
+            string("Arya").serialize_base_type(&mut data).unwrap();
+            data.extend_from_slice(&age.to_le_bytes());
+        }
+        let metadata = ages_metadata().into();
+
+        let mut reader = XbfReader::new(data.as_slice());
+        let map = reader.read_value(&metadata).unwrap();
+        let map = map.as_map().unwrap();
+        assert_eq!(map.len(), 1);
+        assert_eq!(
+            map.get(&string("Arya")),
+            Some(&XbfPrimitive::U16(104).into())
+        );
+
+        let options = DeserializeOptions::new().strict(true);
+        let mut reader = XbfReader::with_options(data.as_slice(), options);
+        let err = reader.read_value(&metadata).unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::DuplicateKey));
+        assert_eq!(err.offset(), 8 + 12 + 2);
+        assert_eq!(err.path(), "U16[1]");
+    }
+}
@@ -0,0 +1,154 @@
+use crate::{
+    reader::XbfReader, RcType, XbfError, XbfMap, XbfMetadata, XbfMetadataUpcast,
+    ENUM_METADATA_DISCRIMINANT,
+};
+use byteorder::WriteBytesExt;
+use std::io::{self, Read, Write};
+
+/// The metadata discriminant for a Map type.
+///
+/// This is the same for all maps regardless of their contents. It's value should always be
+/// equal to the discriminant value of the enum type plus one.
+pub const MAP_METADATA_DISCRIMINANT: u8 = ENUM_METADATA_DISCRIMINANT + 1;
+
+/// Metadata for a Map type.
+///
+/// Internally the metadata is stored on the heap to avoid having a recursive, infinitely sized
+/// type on the stack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XbfMapMetadata {
+    pub(crate) key_type: RcType<XbfMetadata>,
+    pub(crate) value_type: RcType<XbfMetadata>,
+}
+
+impl XbfMapMetadata {
+    /// Creates a new Map metadata.
+    ///
+    /// This will cause a heap allocation with the moved key and value types.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xbf_rs::{XbfMapMetadata, XbfPrimitiveMetadata};
+    ///
+    /// let metadata = XbfMapMetadata::new(XbfPrimitiveMetadata::String, XbfPrimitiveMetadata::I32);
+    ///
+    /// assert_eq!(metadata.key_type(), &XbfPrimitiveMetadata::String.into());
+    /// assert_eq!(metadata.value_type(), &XbfPrimitiveMetadata::I32.into());
+    /// ```
+    pub fn new(key_type: impl Into<XbfMetadata>, value_type: impl Into<XbfMetadata>) -> Self {
+        Self {
+            key_type: RcType::new(key_type.into()),
+            value_type: RcType::new(value_type.into()),
+        }
+    }
+
+    /// Returns the metadata of the keys of the map.
+    pub fn key_type(&self) -> &XbfMetadata {
+        &self.key_type
+    }
+
+    /// Returns the metadata of the values of the map.
+    pub fn value_type(&self) -> &XbfMetadata {
+        &self.value_type
+    }
+
+    /// Serialize Map metadata as defined by the XBF specification.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xbf_rs::{XbfMapMetadata, XbfPrimitiveMetadata, MAP_METADATA_DISCRIMINANT};
+    ///
+    /// let metadata = XbfMapMetadata::new(XbfPrimitiveMetadata::String, XbfPrimitiveMetadata::I32);
+    /// let mut writer = Vec::new();
+    /// metadata.serialize_map_metadata(&mut writer).unwrap();
+    ///
+    /// assert_eq!(
+    ///     writer,
+    ///     [
+    ///         MAP_METADATA_DISCRIMINANT,
+    ///         XbfPrimitiveMetadata::String as u8,
+    ///         XbfPrimitiveMetadata::I32 as u8,
+    ///     ]
+    /// );
+    /// ```
+    pub fn serialize_map_metadata(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_u8(MAP_METADATA_DISCRIMINANT)?;
+        self.key_type.serialize_base_metadata(writer)?;
+        self.value_type.serialize_base_metadata(writer)
+    }
+
+    /// Deserialize Map metadata as defined by the XBF specification.
+    ///
+    /// This method assumes that you know for a fact you are about to receive Map metadata. If you
+    /// do not know what sort of metadata you are receiving, use
+    /// [`deserialize_base_metadata`](crate::XbfMetadata::deserialize_base_metadata).
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails or the metadata is invalid.
+    pub fn deserialize_map_metadata(reader: &mut impl Read) -> Result<XbfMapMetadata, XbfError> {
+        Self::read_map_metadata(&mut XbfReader::new(reader))
+    }
+
+    pub(crate) fn read_map_metadata(
+        reader: &mut XbfReader<impl Read>,
+    ) -> Result<XbfMapMetadata, XbfError> {
+        reader.nested(|reader| {
+            let key_type = XbfMetadata::read_base_metadata(reader)?;
+            let value_type = XbfMetadata::read_base_metadata(reader)?;
+            Ok(XbfMapMetadata::new(key_type, value_type))
+        })
+    }
+}
+
+impl From<&XbfMap> for XbfMapMetadata {
+    fn from(value: &XbfMap) -> Self {
+        value.get_metadata()
+    }
+}
+
+impl XbfMetadataUpcast for XbfMapMetadata {
+    fn into_base_metadata(self) -> XbfMetadata {
+        XbfMetadata::Map(self)
+    }
+
+    fn to_base_metadata(&self) -> XbfMetadata {
+        XbfMetadata::Map(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{XbfPrimitiveMetadata, XbfVecMetadata};
+
+    #[test]
+    fn nested_metadata_serde_works() {
+        let metadata = XbfMapMetadata::new(
+            XbfPrimitiveMetadata::String,
+            XbfMapMetadata::new(
+                XbfPrimitiveMetadata::U8,
+                XbfVecMetadata::new(XbfPrimitiveMetadata::I32),
+            ),
+        );
+        let mut writer = vec![];
+        metadata.serialize_map_metadata(&mut writer).unwrap();
+
+        assert_eq!(
+            writer,
+            [
+                MAP_METADATA_DISCRIMINANT,
+                XbfPrimitiveMetadata::String as u8,
+                MAP_METADATA_DISCRIMINANT,
+                XbfPrimitiveMetadata::U8 as u8,
+                crate::VEC_METADATA_DISCRIMINANT,
+                XbfPrimitiveMetadata::I32 as u8,
+            ]
+        );
+
+        let deserialized = XbfMetadata::deserialize_base_metadata(&mut writer.as_slice()).unwrap();
+        assert_eq!(deserialized, metadata.into());
+    }
+}
@@ -0,0 +1,281 @@
+//! An optional value as defined by the XBF specification.
+
+mod option_metadata;
+
+pub use option_metadata::*;
+
+use crate::{
+    reader::XbfReader, NativeToXbfType, XbfError, XbfMetadata, XbfProjection, XbfType,
+    XbfTypeUpcast,
+};
+use byteorder::WriteBytesExt;
+use std::io::{self, Read, Write};
+
+/// An optional value as defined by the XBF specification.
+///
+/// An option either contains a value of its inner type, or is empty. The metadata of an empty
+/// option still records the type of value it could have contained.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::{XbfOption, XbfOptionMetadata, XbfPrimitive, XbfPrimitiveMetadata, XbfType};
+///
+/// let some = XbfOption::some(XbfPrimitive::U16(16));
+/// let none = XbfOption::none(XbfPrimitiveMetadata::U16);
+///
+/// assert_eq!(some.get_metadata(), none.get_metadata());
+/// assert_eq!(some.value(), Some(&XbfPrimitive::U16(16).into()));
+/// assert_eq!(none.value(), None);
+///
+/// // native options convert directly
+/// assert_eq!(XbfType::from(Some(16u16)), some.into());
+/// assert_eq!(XbfType::from(None::<u16>), none.into());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct XbfOption {
+    pub(crate) metadata: XbfOptionMetadata,
+    value: Option<Box<XbfType>>,
+}
+
+impl XbfOption {
+    /// Creates an option containing `value`, whose inner type is the type of the value.
+    pub fn some(value: impl Into<XbfType>) -> Self {
+        let value = value.into();
+        Self {
+            metadata: XbfOptionMetadata::new(XbfMetadata::from(&value)),
+            value: Some(Box::new(value)),
+        }
+    }
+
+    /// Creates an empty option which could have contained a value of `inner_type`.
+    pub fn none(inner_type: impl Into<XbfMetadata>) -> Self {
+        Self {
+            metadata: XbfOptionMetadata::new(inner_type),
+            value: None,
+        }
+    }
+
+    /// Serialize an option as defined by the XBF specification.
+    ///
+    /// This function **does not** write out the metadata of the type. If you want to write out the
+    /// metadata, convert this type to a [`XbfOptionMetadata`] and call
+    /// [`XbfOptionMetadata::serialize_option_metadata`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use xbf_rs::{XbfOption, XbfPrimitive, XbfPrimitiveMetadata};
+    ///
+    /// let mut writer = vec![];
+    /// XbfOption::some(XbfPrimitive::I32(42))
+    ///     .serialize_option_type(&mut writer)
+    ///     .unwrap();
+    /// XbfOption::none(XbfPrimitiveMetadata::I32)
+    ///     .serialize_option_type(&mut writer)
+    ///     .unwrap();
+    ///
+    /// let mut expected = vec![1];
+    /// expected.extend_from_slice(&42i32.to_le_bytes());
+    /// expected.push(0);
+    /// assert_eq!(writer, expected);
+    /// ```
+    pub fn serialize_option_type(&self, writer: &mut impl Write) -> io::Result<()> {
+        match &self.value {
+            Some(value) => {
+                writer.write_u8(1)?;
+                value.serialize_base_type(writer)
+            }
+            None => writer.write_u8(0),
+        }
+    }
+
+    /// Deserialize an option as defined by the XBF specification.
+    ///
+    /// This function **does not** read the metadata of the type from the reader. It is
+    /// expected that to call this function the metadata for a type is already known, be
+    /// that from reading it from the reader with
+    /// [`deserialize_base_metadata`](crate::XbfMetadata::deserialize_base_metadata)
+    /// or having it in some other manner.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails or the data is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use xbf_rs::{XbfOption, XbfOptionMetadata, XbfPrimitive, XbfPrimitiveMetadata};
+    ///
+    /// let metadata = XbfOptionMetadata::new(XbfPrimitiveMetadata::I32);
+    /// let mut data = vec![1];
+    /// data.extend_from_slice(&42i32.to_le_bytes());
+    ///
+    /// let option = XbfOption::deserialize_option_type(&metadata, &mut data.as_slice()).unwrap();
+    ///
+    /// assert_eq!(option, XbfOption::some(XbfPrimitive::I32(42)));
+    /// ```
+    pub fn deserialize_option_type(
+        metadata: &XbfOptionMetadata,
+        reader: &mut impl Read,
+    ) -> Result<XbfOption, XbfError> {
+        Self::read_option_type(metadata, &mut XbfReader::new(reader))
+            .map_err(|e| e.in_root(&metadata.inner_type))
+    }
+
+    pub(crate) fn read_option_type(
+        metadata: &XbfOptionMetadata,
+        reader: &mut XbfReader<impl Read>,
+    ) -> Result<XbfOption, XbfError> {
+        reader.nested(|reader| {
+            let value = if reader.read_bool()? {
+                Some(Box::new(XbfType::read_base_type(
+                    &metadata.inner_type,
+                    reader,
+                )?))
+            } else {
+                None
+            };
+            Ok(XbfOption {
+                metadata: metadata.clone(),
+                value,
+            })
+        })
+    }
+
+    pub(crate) fn read_option_type_projected(
+        metadata: &XbfOptionMetadata,
+        projected: &XbfOptionMetadata,
+        projection: &XbfProjection,
+        reader: &mut XbfReader<impl Read>,
+    ) -> Result<XbfOption, XbfError> {
+        reader.nested(|reader| {
+            let value = if reader.read_bool()? {
+                Some(Box::new(XbfType::read_projected(
+                    &metadata.inner_type,
+                    &projected.inner_type,
+                    projection,
+                    reader,
+                )?))
+            } else {
+                None
+            };
+            Ok(XbfOption {
+                metadata: projected.clone(),
+                value,
+            })
+        })
+    }
+
+    /// Returns the metadata of the option.
+    pub fn get_metadata(&self) -> XbfOptionMetadata {
+        self.metadata.clone()
+    }
+
+    /// Returns a reference to the contained value, if there is one.
+    pub fn value(&self) -> Option<&XbfType> {
+        self.value.as_deref()
+    }
+
+    /// Returns `true` if the option contains a value.
+    pub fn is_some(&self) -> bool {
+        self.value.is_some()
+    }
+
+    /// Returns `true` if the option is empty.
+    pub fn is_none(&self) -> bool {
+        self.value.is_none()
+    }
+
+    /// Consumes the option, returning the contained value, if there is one.
+    pub fn into_value(self) -> Option<XbfType> {
+        self.value.map(|x| *x)
+    }
+}
+
+impl XbfTypeUpcast for XbfOption {
+    fn into_base_type(self) -> XbfType {
+        XbfType::Option(self)
+    }
+
+    fn to_base_type(&self) -> XbfType {
+        XbfType::Option(self.clone())
+    }
+}
+
+impl<T: NativeToXbfType> From<Option<T>> for XbfOption {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(x) => XbfOption::some(x.into_xbf_type()),
+            None => XbfOption::none(T::xbf_metadata()),
+        }
+    }
+}
+
+impl<T: NativeToXbfType> From<Option<T>> for XbfType {
+    fn from(value: Option<T>) -> Self {
+        XbfOption::from(value).into_base_type()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        DeserializeOptions, XbfErrorKind, XbfPrimitive, XbfPrimitiveMetadata, XbfStructMetadata,
+    };
+    use indexmap::indexmap;
+
+    #[test]
+    fn option_round_trip_works() {
+        let metadata = XbfOptionMetadata::new(XbfPrimitiveMetadata::String);
+        let values = [
+            XbfOption::some(XbfPrimitive::String("Saphira".to_string())),
+            XbfOption::none(XbfPrimitiveMetadata::String),
+        ];
+
+        let mut data = vec![];
+        for value in &values {
+            assert_eq!(value.get_metadata(), metadata);
+            value.serialize_option_type(&mut data).unwrap();
+        }
+
+        let mut reader = data.as_slice();
+        for value in values {
+            let deserialized = XbfOption::deserialize_option_type(&metadata, &mut reader).unwrap();
+            assert_eq!(deserialized, value);
+        }
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn invalid_presence_fails_in_strict_mode() {
+        let metadata = XbfOptionMetadata::new(XbfPrimitiveMetadata::U8).into();
+        let data = [2u8, 42];
+
+        let mut reader = XbfReader::new(data.as_slice());
+        let value = reader.read_value(&metadata).unwrap();
+        assert_eq!(value, XbfOption::some(XbfPrimitive::U8(42)).into());
+
+        let options = DeserializeOptions::new().strict(true);
+        let mut reader = XbfReader::with_options(data.as_slice(), options);
+        let err = reader.read_value(&metadata).unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::InvalidBool(2)));
+    }
+
+    #[test]
+    fn error_path_looks_through_option() {
+        let rider = XbfStructMetadata::new(
+            "DragonRider",
+            indexmap! {"name" => XbfPrimitiveMetadata::String.into()},
+        );
+        let metadata = XbfOptionMetadata::new(rider).into();
+        let mut data = vec![1];
+        data.extend_from_slice(&2u64.to_le_bytes());
+        data.extend_from_slice(&[0xff, 0xff]);
+
+        let err = XbfType::deserialize_base_type(&metadata, &mut data.as_slice()).unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::InvalidUtf8));
+        assert_eq!(err.path(), "DragonRider.name");
+    }
+}
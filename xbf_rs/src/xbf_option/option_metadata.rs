@@ -0,0 +1,148 @@
+use crate::{
+    reader::XbfReader, RcType, XbfError, XbfMetadata, XbfMetadataUpcast, XbfOption,
+    STRUCT_METADATA_DISCRIMINANT,
+};
+use byteorder::WriteBytesExt;
+use std::io::{self, Read, Write};
+
+/// The metadata discriminant for an Option type.
+///
+/// This is the same for all options regardless of their contents. It's value should always be
+/// equal to the discriminant value of the struct type plus one.
+pub const OPTION_METADATA_DISCRIMINANT: u8 = STRUCT_METADATA_DISCRIMINANT + 1;
+
+/// Metadata for an Option type.
+///
+/// Internally the metadata is stored on the heap to avoid having a recursive, infinitely sized
+/// type on the stack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XbfOptionMetadata {
+    pub(crate) inner_type: RcType<XbfMetadata>,
+}
+
+impl XbfOptionMetadata {
+    /// Creates a new Option metadata.
+    ///
+    /// This will cause a heap allocation with the moved inner type.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xbf_rs::XbfOptionMetadata;
+    /// use xbf_rs::XbfPrimitiveMetadata;
+    ///
+    /// let metadata = XbfOptionMetadata::new(XbfPrimitiveMetadata::I32);
+    ///
+    /// assert_eq!(metadata.inner_type(), &XbfPrimitiveMetadata::I32.into());
+    /// ```
+    pub fn new(inner_type: impl Into<XbfMetadata>) -> Self {
+        Self {
+            inner_type: RcType::new(inner_type.into()),
+        }
+    }
+
+    /// Returns the metadata of the value the option may contain.
+    pub fn inner_type(&self) -> &XbfMetadata {
+        &self.inner_type
+    }
+
+    /// Serialize Option metadata as defined by the XBF specification.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xbf_rs::XbfOptionMetadata;
+    /// use xbf_rs::XbfPrimitiveMetadata;
+    /// use xbf_rs::OPTION_METADATA_DISCRIMINANT;
+    ///
+    /// let metadata = XbfOptionMetadata::new(XbfPrimitiveMetadata::I32);
+    /// let mut writer = Vec::new();
+    /// metadata.serialize_option_metadata(&mut writer).unwrap();
+    ///
+    /// assert_eq!(writer, [OPTION_METADATA_DISCRIMINANT, XbfPrimitiveMetadata::I32 as u8]);
+    /// ```
+    pub fn serialize_option_metadata(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_u8(OPTION_METADATA_DISCRIMINANT)?;
+        self.inner_type.serialize_base_metadata(writer)
+    }
+
+    /// Deserialize Option metadata as defined by the XBF specification.
+    ///
+    /// This method assumes that you know for a fact you are about to receive Option metadata. If
+    /// you do not know what sort of metadata you are receiving, use
+    /// [`deserialize_base_metadata`](crate::XbfMetadata::deserialize_base_metadata).
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails or the metadata is invalid.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xbf_rs::XbfOptionMetadata;
+    /// use xbf_rs::XbfPrimitiveMetadata;
+    ///
+    /// let data = vec![XbfPrimitiveMetadata::I32 as u8];
+    /// let mut reader = std::io::Cursor::new(data);
+    ///
+    /// let metadata = XbfOptionMetadata::deserialize_option_metadata(&mut reader).unwrap();
+    ///
+    /// assert_eq!(metadata, XbfOptionMetadata::new(XbfPrimitiveMetadata::I32));
+    /// ```
+    pub fn deserialize_option_metadata(
+        reader: &mut impl Read,
+    ) -> Result<XbfOptionMetadata, XbfError> {
+        Self::read_option_metadata(&mut XbfReader::new(reader))
+    }
+
+    pub(crate) fn read_option_metadata(
+        reader: &mut XbfReader<impl Read>,
+    ) -> Result<XbfOptionMetadata, XbfError> {
+        let inner_type = reader.nested(XbfMetadata::read_base_metadata)?;
+        Ok(XbfOptionMetadata::new(inner_type))
+    }
+}
+
+impl From<&XbfOption> for XbfOptionMetadata {
+    fn from(value: &XbfOption) -> Self {
+        value.get_metadata()
+    }
+}
+
+impl XbfMetadataUpcast for XbfOptionMetadata {
+    fn into_base_metadata(self) -> XbfMetadata {
+        XbfMetadata::Option(self)
+    }
+
+    fn to_base_metadata(&self) -> XbfMetadata {
+        XbfMetadata::Option(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{XbfPrimitiveMetadata, XbfVecMetadata};
+
+    #[test]
+    fn nested_metadata_serde_works() {
+        let metadata = XbfOptionMetadata::new(XbfVecMetadata::new(XbfOptionMetadata::new(
+            XbfPrimitiveMetadata::String,
+        )));
+        let mut writer = vec![];
+        metadata.serialize_option_metadata(&mut writer).unwrap();
+
+        assert_eq!(
+            writer,
+            [
+                OPTION_METADATA_DISCRIMINANT,
+                crate::VEC_METADATA_DISCRIMINANT,
+                OPTION_METADATA_DISCRIMINANT,
+                XbfPrimitiveMetadata::String as u8
+            ]
+        );
+
+        let deserialized = XbfMetadata::deserialize_base_metadata(&mut writer.as_slice()).unwrap();
+        assert_eq!(deserialized, metadata.into());
+    }
+}
@@ -1,20 +1,36 @@
 //! A primitive type as defined by the XBF specification.
 
+mod decimal;
+mod int256;
+mod primitive_buffer;
 mod primitive_metadata;
+mod temporal;
 
+pub use decimal::*;
+pub use int256::*;
+pub use primitive_buffer::*;
 pub use primitive_metadata::*;
+pub use temporal::*;
 
 use crate::{
+    reader::XbfReader,
     util::{read_bytes, read_string, write_bytes, write_string},
-    XbfType, XbfTypeUpcast,
+    XbfError, XbfType, XbfTypeUpcast,
 };
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use byteorder::{LittleEndian, WriteBytesExt};
 use std::io::{self, Read, Write};
 
 /// A primitive type as defined by the XBF specification.
 ///
 /// Each XBF primitive maps to the corresponding Rust type, with the exception of 256 bit numbers,
-/// which are represented as a `[u64; 4]`.
+/// which are represented by [`XbfU256`] and [`XbfI256`], UUIDs, which are represented by their bytes, half
+/// precision floats, which are represented by their bits, the temporal primitives, which are
+/// represented by [`XbfTimestamp`], [`XbfTimestampTz`], [`XbfDate`], [`XbfTime`] and
+/// [`XbfDuration`], and decimals, which are represented by [`XbfDecimal`].
+///
+/// With the `uuid` and `half` features enabled, [`uuid::Uuid`], [`half::f16`] and [`half::bf16`]
+/// implement [`NativeToXbfPrimitive`], as do the 256 bit integers of `primitive-types` and `ethnum`
+/// with their features enabled.
 #[derive(Debug, Clone, PartialEq)]
 pub enum XbfPrimitive {
     Bool(bool),
@@ -23,16 +39,26 @@ pub enum XbfPrimitive {
     U32(u32),
     U64(u64),
     U128(u128),
-    U256([u64; 4]),
+    U256(XbfU256),
     I8(i8),
     I16(i16),
     I32(i32),
     I64(i64),
     I128(i128),
-    I256([u64; 4]),
+    I256(XbfI256),
     F32(f32),
     F64(f64),
     Bytes(Vec<u8>),
+    Timestamp(XbfTimestamp),
+    TimestampTz(XbfTimestampTz),
+    Date(XbfDate),
+    Time(XbfTime),
+    Duration(XbfDuration),
+    Decimal(XbfDecimal),
+    Uuid([u8; 16]),
+    Char(char),
+    F16(u16),
+    BF16(u16),
     String(String),
 }
 
@@ -63,6 +89,7 @@ impl XbfPrimitive {
             XbfPrimitive::U64(x) => writer.write_u64::<LittleEndian>(*x),
             XbfPrimitive::U128(x) => writer.write_u128::<LittleEndian>(*x),
             XbfPrimitive::U256(x) => x
+                .limbs()
                 .iter()
                 .try_for_each(|x| writer.write_u64::<LittleEndian>(*x)),
             XbfPrimitive::I8(x) => writer.write_i8(*x),
@@ -71,11 +98,27 @@ impl XbfPrimitive {
             XbfPrimitive::I64(x) => writer.write_i64::<LittleEndian>(*x),
             XbfPrimitive::I128(x) => writer.write_i128::<LittleEndian>(*x),
             XbfPrimitive::I256(x) => x
+                .limbs()
                 .iter()
                 .try_for_each(|x| writer.write_u64::<LittleEndian>(*x)),
             XbfPrimitive::F32(x) => writer.write_f32::<LittleEndian>(*x),
             XbfPrimitive::F64(x) => writer.write_f64::<LittleEndian>(*x),
             XbfPrimitive::Bytes(x) => write_bytes(x, writer),
+            XbfPrimitive::Timestamp(x) => writer.write_i64::<LittleEndian>(x.nanos()),
+            XbfPrimitive::TimestampTz(x) => {
+                writer.write_i64::<LittleEndian>(x.timestamp().nanos())?;
+                writer.write_i32::<LittleEndian>(x.offset_seconds())
+            }
+            XbfPrimitive::Date(x) => writer.write_i32::<LittleEndian>(x.days_since_epoch()),
+            XbfPrimitive::Time(x) => writer.write_u64::<LittleEndian>(x.nanos_since_midnight()),
+            XbfPrimitive::Duration(x) => writer.write_i64::<LittleEndian>(x.nanos()),
+            XbfPrimitive::Decimal(x) => {
+                writer.write_i128::<LittleEndian>(x.mantissa())?;
+                writer.write_u8(x.scale())
+            }
+            XbfPrimitive::Uuid(x) => writer.write_all(x),
+            XbfPrimitive::Char(x) => writer.write_u32::<LittleEndian>(u32::from(*x)),
+            XbfPrimitive::F16(x) | XbfPrimitive::BF16(x) => writer.write_u16::<LittleEndian>(*x),
             XbfPrimitive::String(x) => write_string(x, writer),
         }
     }
@@ -87,6 +130,10 @@ impl XbfPrimitive {
     /// it from the reader with [`deserialize_base_metadata`](crate::XbfMetadata::deserialize_base_metadata)
     /// or having it in some other manner.
     ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`](crate::XbfError) if the reader fails or runs out of data.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -101,40 +148,71 @@ impl XbfPrimitive {
     pub fn deserialize_primitive_type(
         primitive_metadata: &XbfPrimitiveMetadata,
         reader: &mut impl Read,
-    ) -> io::Result<XbfPrimitive> {
+    ) -> Result<XbfPrimitive, XbfError> {
+        Self::read_primitive_type(primitive_metadata, &mut XbfReader::new(reader))
+            .map_err(|e| e.in_root(&primitive_metadata.into()))
+    }
+
+    pub(crate) fn read_primitive_type(
+        primitive_metadata: &XbfPrimitiveMetadata,
+        reader: &mut XbfReader<impl Read>,
+    ) -> Result<XbfPrimitive, XbfError> {
         match primitive_metadata {
-            XbfPrimitiveMetadata::Bool => reader.read_u8().map(|x| XbfPrimitive::Bool(x != 0)),
+            XbfPrimitiveMetadata::Bool => reader.read_bool().map(XbfPrimitive::Bool),
             XbfPrimitiveMetadata::U8 => reader.read_u8().map(XbfPrimitive::U8),
-            XbfPrimitiveMetadata::U16 => reader.read_u16::<LittleEndian>().map(XbfPrimitive::U16),
-            XbfPrimitiveMetadata::U32 => reader.read_u32::<LittleEndian>().map(XbfPrimitive::U32),
-            XbfPrimitiveMetadata::U64 => reader.read_u64::<LittleEndian>().map(XbfPrimitive::U64),
-            XbfPrimitiveMetadata::U128 => {
-                reader.read_u128::<LittleEndian>().map(XbfPrimitive::U128)
-            }
+            XbfPrimitiveMetadata::U16 => reader.read_u16().map(XbfPrimitive::U16),
+            XbfPrimitiveMetadata::U32 => reader.read_u32().map(XbfPrimitive::U32),
+            XbfPrimitiveMetadata::U64 => reader.read_u64().map(XbfPrimitive::U64),
+            XbfPrimitiveMetadata::U128 => reader.read_u128().map(XbfPrimitive::U128),
             XbfPrimitiveMetadata::U256 => {
                 let mut data = [0; 4];
                 for i in &mut data {
-                    *i = reader.read_u64::<LittleEndian>()?
+                    *i = reader.read_u64()?
                 }
-                Ok(XbfPrimitive::U256(data))
+                Ok(XbfPrimitive::U256(XbfU256::from_limbs(data)))
             }
             XbfPrimitiveMetadata::I8 => reader.read_i8().map(XbfPrimitive::I8),
-            XbfPrimitiveMetadata::I16 => reader.read_i16::<LittleEndian>().map(XbfPrimitive::I16),
-            XbfPrimitiveMetadata::I32 => reader.read_i32::<LittleEndian>().map(XbfPrimitive::I32),
-            XbfPrimitiveMetadata::I64 => reader.read_i64::<LittleEndian>().map(XbfPrimitive::I64),
-            XbfPrimitiveMetadata::I128 => {
-                reader.read_i128::<LittleEndian>().map(XbfPrimitive::I128)
-            }
+            XbfPrimitiveMetadata::I16 => reader.read_i16().map(XbfPrimitive::I16),
+            XbfPrimitiveMetadata::I32 => reader.read_i32().map(XbfPrimitive::I32),
+            XbfPrimitiveMetadata::I64 => reader.read_i64().map(XbfPrimitive::I64),
+            XbfPrimitiveMetadata::I128 => reader.read_i128().map(XbfPrimitive::I128),
             XbfPrimitiveMetadata::I256 => {
                 let mut data = [0; 4];
                 for i in &mut data {
-                    *i = reader.read_u64::<LittleEndian>()?
+                    *i = reader.read_u64()?
                 }
-                Ok(XbfPrimitive::I256(data))
+                Ok(XbfPrimitive::I256(XbfI256::from_limbs(data)))
             }
-            XbfPrimitiveMetadata::F32 => reader.read_f32::<LittleEndian>().map(XbfPrimitive::F32),
-            XbfPrimitiveMetadata::F64 => reader.read_f64::<LittleEndian>().map(XbfPrimitive::F64),
+            XbfPrimitiveMetadata::F32 => reader.read_f32().map(XbfPrimitive::F32),
+            XbfPrimitiveMetadata::F64 => reader.read_f64().map(XbfPrimitive::F64),
             XbfPrimitiveMetadata::Bytes => read_bytes(reader).map(XbfPrimitive::Bytes),
+            XbfPrimitiveMetadata::Timestamp => reader
+                .read_i64()
+                .map(|x| XbfPrimitive::Timestamp(XbfTimestamp::from_nanos(x))),
+            XbfPrimitiveMetadata::TimestampTz => {
+                let timestamp = XbfTimestamp::from_nanos(reader.read_i64()?);
+                let offset_seconds = reader.read_i32()?;
+                Ok(XbfPrimitive::TimestampTz(XbfTimestampTz::new(
+                    timestamp,
+                    offset_seconds,
+                )))
+            }
+            XbfPrimitiveMetadata::Date => reader
+                .read_i32()
+                .map(|x| XbfPrimitive::Date(XbfDate::from_days_since_epoch(x))),
+            XbfPrimitiveMetadata::Time => reader.read_time().map(XbfPrimitive::Time),
+            XbfPrimitiveMetadata::Duration => reader
+                .read_i64()
+                .map(|x| XbfPrimitive::Duration(XbfDuration::from_nanos(x))),
+            XbfPrimitiveMetadata::Decimal => {
+                let mantissa = reader.read_i128()?;
+                let scale = reader.read_u8()?;
+                Ok(XbfPrimitive::Decimal(XbfDecimal::new(mantissa, scale)))
+            }
+            XbfPrimitiveMetadata::Uuid => reader.read_byte_array().map(XbfPrimitive::Uuid),
+            XbfPrimitiveMetadata::Char => reader.read_char().map(XbfPrimitive::Char),
+            XbfPrimitiveMetadata::F16 => reader.read_u16().map(XbfPrimitive::F16),
+            XbfPrimitiveMetadata::BF16 => reader.read_u16().map(XbfPrimitive::BF16),
             XbfPrimitiveMetadata::String => read_string(reader).map(XbfPrimitive::String),
         }
     }
@@ -213,6 +291,9 @@ pub trait NativeToXbfPrimitive: private::Sealed {
 
 macro_rules! impl_NativeToXbfPrimitive {
     ($ty:ty, $xbf_type:tt) => {
+        impl_NativeToXbfPrimitive!($ty, $xbf_type, |x| x);
+    };
+    ($ty:ty, $xbf_type:tt, |$x:ident| $into_xbf:expr) => {
         impl From<$ty> for XbfPrimitive {
             fn from(x: $ty) -> Self {
                 x.into_xbf_primitive()
@@ -227,11 +308,12 @@ macro_rules! impl_NativeToXbfPrimitive {
 
         impl NativeToXbfPrimitive for $ty {
             fn into_xbf_primitive(self) -> XbfPrimitive {
-                XbfPrimitive::$xbf_type(self)
+                let $x = self;
+                XbfPrimitive::$xbf_type($into_xbf)
             }
 
             fn to_xbf_primitive(&self) -> XbfPrimitive {
-                XbfPrimitive::$xbf_type(self.clone())
+                self.clone().into_xbf_primitive()
             }
         }
 
@@ -245,15 +327,36 @@ impl_NativeToXbfPrimitive!(u16, U16);
 impl_NativeToXbfPrimitive!(u32, U32);
 impl_NativeToXbfPrimitive!(u64, U64);
 impl_NativeToXbfPrimitive!(u128, U128);
+impl_NativeToXbfPrimitive!(XbfU256, U256);
 impl_NativeToXbfPrimitive!(i8, I8);
 impl_NativeToXbfPrimitive!(i16, I16);
 impl_NativeToXbfPrimitive!(i32, I32);
 impl_NativeToXbfPrimitive!(i64, I64);
 impl_NativeToXbfPrimitive!(i128, I128);
+impl_NativeToXbfPrimitive!(XbfI256, I256);
 impl_NativeToXbfPrimitive!(f32, F32);
 impl_NativeToXbfPrimitive!(f64, F64);
+impl_NativeToXbfPrimitive!(char, Char);
 impl_NativeToXbfPrimitive!(Vec<u8>, Bytes);
 impl_NativeToXbfPrimitive!(String, String);
+impl_NativeToXbfPrimitive!(XbfTimestamp, Timestamp);
+impl_NativeToXbfPrimitive!(XbfTimestampTz, TimestampTz);
+impl_NativeToXbfPrimitive!(XbfDate, Date);
+impl_NativeToXbfPrimitive!(XbfTime, Time);
+impl_NativeToXbfPrimitive!(XbfDuration, Duration);
+impl_NativeToXbfPrimitive!(XbfDecimal, Decimal);
+#[cfg(feature = "uuid")]
+impl_NativeToXbfPrimitive!(uuid::Uuid, Uuid, |x| x.into_bytes());
+#[cfg(feature = "half")]
+impl_NativeToXbfPrimitive!(half::f16, F16, |x| x.to_bits());
+#[cfg(feature = "half")]
+impl_NativeToXbfPrimitive!(half::bf16, BF16, |x| x.to_bits());
+#[cfg(feature = "primitive-types")]
+impl_NativeToXbfPrimitive!(primitive_types::U256, U256, |x| XbfU256::from(x));
+#[cfg(feature = "ethnum")]
+impl_NativeToXbfPrimitive!(ethnum::U256, U256, |x| XbfU256::from(x));
+#[cfg(feature = "ethnum")]
+impl_NativeToXbfPrimitive!(ethnum::I256, I256, |x| XbfI256::from(x));
 
 mod private {
     pub trait Sealed {}
@@ -262,7 +365,7 @@ mod private {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{XbfMetadata, XbfType};
+    use crate::{XbfErrorKind, XbfMetadata, XbfType};
     use std::io::Cursor;
 
     mod serde {
@@ -309,7 +412,7 @@ mod tests {
         #[test]
         fn u256_works() {
             const TEST_NUM: [u64; 4] = [1, 2, 3, 4];
-            let primitive = XbfPrimitive::U256(TEST_NUM);
+            let primitive = XbfPrimitive::U256(XbfU256::from_limbs(TEST_NUM));
             let mut writer = Vec::new();
 
             primitive.serialize_primitive_type(&mut writer).unwrap();
@@ -341,7 +444,7 @@ mod tests {
         #[test]
         fn i256_works() {
             const TEST_NUM: [u64; 4] = [1, 2, 3, 4];
-            let primitive = XbfPrimitive::I256(TEST_NUM);
+            let primitive = XbfPrimitive::I256(XbfI256::from_limbs(TEST_NUM));
             let mut writer = Vec::new();
 
             primitive.serialize_primitive_type(&mut writer).unwrap();
@@ -367,6 +470,81 @@ mod tests {
             serde_primitive_test!(F64, 69.0f64);
         }
 
+        #[test]
+        fn temporal_works() {
+            serde_primitive_test!(Timestamp, XbfTimestamp::from_nanos(-42), -42i64);
+            serde_primitive_test!(Date, XbfDate::from_days_since_epoch(-42), -42i32);
+            serde_primitive_test!(Time, XbfTime::from_nanos_since_midnight(42), 42u64);
+            serde_primitive_test!(Duration, XbfDuration::from_nanos(-42), -42i64);
+
+            let primitive =
+                XbfPrimitive::TimestampTz(XbfTimestampTz::new(XbfTimestamp::from_nanos(42), -3600));
+            let mut writer = vec![];
+            primitive.serialize_primitive_type(&mut writer).unwrap();
+
+            let mut expected = 42i64.to_le_bytes().to_vec();
+            expected.extend_from_slice(&(-3600i32).to_le_bytes());
+            assert_eq!(writer, expected);
+
+            let deserialized = XbfType::deserialize_base_type(
+                &XbfMetadata::Primitive(XbfPrimitiveMetadata::TimestampTz),
+                &mut writer.as_slice(),
+            )
+            .unwrap();
+            assert_eq!(deserialized, primitive.into_base_type());
+        }
+
+        #[test]
+        fn decimal_works() {
+            let primitive = XbfPrimitive::Decimal(XbfDecimal::new(-12_345, 2));
+            let mut writer = vec![];
+            primitive.serialize_primitive_type(&mut writer).unwrap();
+
+            let mut expected = (-12_345i128).to_le_bytes().to_vec();
+            expected.push(2);
+            assert_eq!(writer, expected);
+
+            let deserialized = XbfType::deserialize_base_type(
+                &XbfMetadata::Primitive(XbfPrimitiveMetadata::Decimal),
+                &mut writer.as_slice(),
+            )
+            .unwrap();
+            assert_eq!(deserialized, primitive.into_base_type());
+        }
+
+        #[test]
+        fn char_works() {
+            serde_primitive_test!(Char, 'é', 0xe9u32);
+
+            let err = XbfType::deserialize_base_type(
+                &XbfMetadata::Primitive(XbfPrimitiveMetadata::Char),
+                &mut 0xd800u32.to_le_bytes().as_slice(),
+            )
+            .unwrap_err();
+            assert!(matches!(err.kind(), XbfErrorKind::InvalidChar(0xd800)));
+        }
+
+        #[test]
+        fn uuid_and_half_floats_work() {
+            let bytes: [u8; 16] = std::array::from_fn(|i| i as u8);
+            for (primitive, expected) in [
+                (XbfPrimitive::Uuid(bytes), bytes.to_vec()),
+                (XbfPrimitive::F16(0x3c00), 0x3c00u16.to_le_bytes().to_vec()),
+                (XbfPrimitive::BF16(0x3f80), 0x3f80u16.to_le_bytes().to_vec()),
+            ] {
+                let mut writer = vec![];
+                primitive.serialize_primitive_type(&mut writer).unwrap();
+                assert_eq!(writer, expected);
+
+                let deserialized = XbfType::deserialize_base_type(
+                    &XbfMetadata::Primitive(primitive.get_metadata()),
+                    &mut writer.as_slice(),
+                )
+                .unwrap();
+                assert_eq!(deserialized, primitive.into_base_type());
+            }
+        }
+
         #[test]
         fn string_works() {
             let test_string = "hello world".to_string();
@@ -0,0 +1,179 @@
+//! Values of the decimal primitive, and their conversions to and from text and floats.
+
+#[cfg(feature = "rust_decimal")]
+mod rust_decimal;
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+/// An exact decimal number, as an integer mantissa scaled down by a power of ten.
+///
+/// The value of a decimal is `mantissa * 10^-scale`. Decimals are compared by their mantissa and
+/// scale, so `1.0` and `1.00` are not equal, in the same way as their serialized representations
+/// differ.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::XbfDecimal;
+///
+/// let price: XbfDecimal = "-123.450".parse().unwrap();
+/// assert_eq!(price, XbfDecimal::new(-123_450, 3));
+/// assert_eq!(price.to_string(), "-123.450");
+///
+/// assert_eq!(XbfDecimal::try_from(0.1f64), Ok(XbfDecimal::new(1, 1)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct XbfDecimal {
+    mantissa: i128,
+    scale: u8,
+}
+
+impl XbfDecimal {
+    /// Creates a decimal with the value `mantissa * 10^-scale`.
+    pub fn new(mantissa: i128, scale: u8) -> Self {
+        Self { mantissa, scale }
+    }
+
+    /// Returns the mantissa of this decimal.
+    pub fn mantissa(&self) -> i128 {
+        self.mantissa
+    }
+
+    /// Returns the number of digits after the decimal point.
+    pub fn scale(&self) -> u8 {
+        self.scale
+    }
+}
+
+impl Display for XbfDecimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.mantissa < 0 {
+            write!(f, "-")?;
+        }
+        let scale = usize::from(self.scale);
+        let digits = format!(
+            "{:0>width$}",
+            self.mantissa.unsigned_abs(),
+            width = scale + 1
+        );
+        let (integer, fraction) = digits.split_at(digits.len() - scale);
+        if fraction.is_empty() {
+            write!(f, "{integer}")
+        } else {
+            write!(f, "{integer}.{fraction}")
+        }
+    }
+}
+
+/// Parses a decimal in the form `-123.45`, keeping every digit after the decimal point in the
+/// scale.
+impl FromStr for XbfDecimal {
+    type Err = DecimalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, unsigned) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+        if integer.is_empty() && fraction.is_empty()
+            || !integer
+                .bytes()
+                .chain(fraction.bytes())
+                .all(|b| b.is_ascii_digit())
+        {
+            return Err(DecimalError);
+        }
+        let scale = u8::try_from(fraction.len()).map_err(|_| DecimalError)?;
+        let sign = if negative { "-" } else { "" };
+        let mantissa = format!("{sign}{integer}{fraction}")
+            .parse::<i128>()
+            .map_err(|_| DecimalError)?;
+        Ok(Self::new(mantissa, scale))
+    }
+}
+
+/// Converts a float to the shortest decimal which converts back to the same float.
+impl TryFrom<f64> for XbfDecimal {
+    type Error = DecimalError;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        if !value.is_finite() {
+            return Err(DecimalError);
+        }
+        value.to_string().parse()
+    }
+}
+
+/// Error type for decimal values which are malformed, or out of the range of the type they are
+/// being converted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecimalError;
+
+impl Display for DecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Decimal value is malformed or out of range")
+    }
+}
+
+impl Error for DecimalError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decimal_display_works() {
+        assert_eq!(XbfDecimal::new(12_345, 2).to_string(), "123.45");
+        assert_eq!(XbfDecimal::new(-5, 3).to_string(), "-0.005");
+        assert_eq!(XbfDecimal::new(42, 0).to_string(), "42");
+        assert_eq!(XbfDecimal::new(0, 2).to_string(), "0.00");
+        assert_eq!(
+            XbfDecimal::new(i128::MIN, 0).to_string(),
+            i128::MIN.to_string()
+        );
+    }
+
+    #[test]
+    fn decimal_parse_works() {
+        for s in [
+            "123.45",
+            "-0.005",
+            "42",
+            "0.00",
+            "-170141183460469231731687303715884105728",
+        ] {
+            assert_eq!(s.parse::<XbfDecimal>().unwrap().to_string(), s);
+        }
+        assert_eq!("+.5".parse(), Ok(XbfDecimal::new(5, 1)));
+        assert_eq!("7.".parse(), Ok(XbfDecimal::new(7, 0)));
+
+        for s in [
+            "",
+            "-",
+            ".",
+            "1.2.3",
+            "1e5",
+            "--1",
+            "1.-2",
+            "170141183460469231731687303715884105728",
+        ] {
+            assert_eq!(s.parse::<XbfDecimal>(), Err(DecimalError), "{s}");
+        }
+    }
+
+    #[test]
+    fn decimal_from_float_works() {
+        assert_eq!(
+            XbfDecimal::try_from(171.279_998_779_296_88),
+            Ok(XbfDecimal::new(17_127_999_877_929_688, 14))
+        );
+        assert_eq!(XbfDecimal::try_from(-2.5), Ok(XbfDecimal::new(-25, 1)));
+        assert_eq!(XbfDecimal::try_from(f64::NAN), Err(DecimalError));
+        assert_eq!(XbfDecimal::try_from(1e300), Err(DecimalError));
+    }
+}
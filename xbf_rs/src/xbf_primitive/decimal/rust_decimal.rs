@@ -0,0 +1,38 @@
+//! Conversions between [`XbfDecimal`] and [`rust_decimal`], enabled with the `rust_decimal`
+//! feature.
+
+use super::{DecimalError, XbfDecimal};
+use rust_decimal::Decimal;
+
+impl From<Decimal> for XbfDecimal {
+    fn from(value: Decimal) -> Self {
+        // rust_decimal's scale is at most 28
+        Self::new(value.mantissa(), value.scale() as u8)
+    }
+}
+
+impl TryFrom<XbfDecimal> for Decimal {
+    type Error = DecimalError;
+
+    fn try_from(value: XbfDecimal) -> Result<Self, Self::Error> {
+        Decimal::try_from_i128_with_scale(value.mantissa(), u32::from(value.scale()))
+            .map_err(|_| DecimalError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions_work() {
+        let decimal = Decimal::new(-12_345, 2);
+        let xbf_decimal = XbfDecimal::from(decimal);
+        assert_eq!(xbf_decimal, XbfDecimal::new(-12_345, 2));
+        assert_eq!(Decimal::try_from(xbf_decimal), Ok(decimal));
+        assert_eq!(decimal.to_string(), xbf_decimal.to_string());
+
+        assert!(Decimal::try_from(XbfDecimal::new(1, 29)).is_err());
+        assert!(Decimal::try_from(XbfDecimal::new(i128::MAX, 0)).is_err());
+    }
+}
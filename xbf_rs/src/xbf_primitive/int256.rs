@@ -0,0 +1,788 @@
+//! Values of the 256 bit integer primitives, with arithmetic, parsing and formatting.
+
+#[cfg(feature = "ethnum")]
+mod ethnum;
+#[cfg(feature = "primitive-types")]
+mod primitive_types;
+
+use std::{
+    cmp::Ordering,
+    error::Error,
+    fmt::{self, Display},
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign},
+    str::FromStr,
+};
+
+/// The 64 bit limbs of a 256 bit integer, least significant first.
+type Limbs = [u64; 4];
+
+const SIGN_BIT: u64 = 1 << 63;
+
+fn overflowing_add(a: Limbs, b: Limbs) -> (Limbs, bool) {
+    let mut out = [0; 4];
+    let mut carry = false;
+    for i in 0..4 {
+        let (x, c1) = a[i].overflowing_add(b[i]);
+        let (x, c2) = x.overflowing_add(u64::from(carry));
+        out[i] = x;
+        carry = c1 || c2;
+    }
+    (out, carry)
+}
+
+fn overflowing_sub(a: Limbs, b: Limbs) -> (Limbs, bool) {
+    let mut out = [0; 4];
+    let mut borrow = false;
+    for i in 0..4 {
+        let (x, b1) = a[i].overflowing_sub(b[i]);
+        let (x, b2) = x.overflowing_sub(u64::from(borrow));
+        out[i] = x;
+        borrow = b1 || b2;
+    }
+    (out, borrow)
+}
+
+fn overflowing_mul(a: Limbs, b: Limbs) -> (Limbs, bool) {
+    let mut out = [0; 8];
+    for i in 0..4 {
+        let mut carry = 0;
+        for j in 0..4 {
+            // cannot overflow, as (2^64 - 1)^2 + 2 * (2^64 - 1) = 2^128 - 1
+            let x = u128::from(a[i]) * u128::from(b[j]) + u128::from(out[i + j]) + carry;
+            out[i + j] = x as u64;
+            carry = x >> 64;
+        }
+        out[i + 4] = carry as u64;
+    }
+    let mut low = [0; 4];
+    low.copy_from_slice(&out[..4]);
+    (low, out[4..].iter().any(|&x| x != 0))
+}
+
+fn div_rem_u64(a: Limbs, b: u64) -> (Limbs, u64) {
+    let mut quotient = [0; 4];
+    let mut rem = 0;
+    for i in (0..4).rev() {
+        let x = u128::from(rem) << 64 | u128::from(a[i]);
+        quotient[i] = (x / u128::from(b)) as u64;
+        rem = (x % u128::from(b)) as u64;
+    }
+    (quotient, rem)
+}
+
+/// Divides `a` by a non-zero `b`, returning the quotient and remainder.
+fn div_rem(a: Limbs, b: Limbs) -> (Limbs, Limbs) {
+    if b[1..] == [0; 3] {
+        let (quotient, rem) = div_rem_u64(a, b[0]);
+        return (quotient, [rem, 0, 0, 0]);
+    }
+    let mut quotient = [0; 4];
+    let mut rem = [0; 4];
+    for bit in (0..256).rev() {
+        let carry = rem[3] >> 63;
+        rem = [
+            rem[0] << 1 | a[bit / 64] >> (bit % 64) & 1,
+            rem[1] << 1 | rem[0] >> 63,
+            rem[2] << 1 | rem[1] >> 63,
+            rem[3] << 1 | rem[2] >> 63,
+        ];
+        // the remainder is always less than twice `b`, so one subtraction is enough even when the
+        // shift overflowed
+        if carry != 0 || cmp(rem, b) != Ordering::Less {
+            rem = overflowing_sub(rem, b).0;
+            quotient[bit / 64] |= 1 << (bit % 64);
+        }
+    }
+    (quotient, rem)
+}
+
+fn cmp(a: Limbs, b: Limbs) -> Ordering {
+    a.iter().rev().cmp(b.iter().rev())
+}
+
+fn wrapping_neg(a: Limbs) -> Limbs {
+    overflowing_add(a.map(|x| !x), [1, 0, 0, 0]).0
+}
+
+fn to_radix_string(mut a: Limbs, radix: u32, upper: bool) -> String {
+    let mut digits = vec![];
+    loop {
+        let (quotient, digit) = div_rem_u64(a, radix.into());
+        let digit = char::from_digit(digit as u32, radix).expect("digit is less than radix");
+        digits.push(if upper {
+            digit.to_ascii_uppercase()
+        } else {
+            digit
+        });
+        a = quotient;
+        if a == [0; 4] {
+            break;
+        }
+    }
+    digits.iter().rev().collect()
+}
+
+fn parse_radix(digits: &str, radix: u32) -> Result<Limbs, Int256Error> {
+    assert!(
+        (2..=36).contains(&radix),
+        "from_str_radix: radix must lie in the range `[2, 36]` - found {radix}"
+    );
+    if digits.is_empty() {
+        return Err(Int256Error);
+    }
+    digits.chars().try_fold([0; 4], |acc, c| {
+        let digit = c.to_digit(radix).ok_or(Int256Error)?;
+        let (acc, mul_overflow) = overflowing_mul(acc, [radix.into(), 0, 0, 0]);
+        let (acc, add_overflow) = overflowing_add(acc, [digit.into(), 0, 0, 0]);
+        if mul_overflow || add_overflow {
+            return Err(Int256Error);
+        }
+        Ok(acc)
+    })
+}
+
+/// An unsigned 256 bit integer.
+///
+/// Arithmetic operators panic on overflow and division by zero, use the `checked_`,
+/// `overflowing_` and `wrapping_` methods to handle those cases instead.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::XbfU256;
+///
+/// let wei: XbfU256 = "1000000000000000000000".parse().unwrap();
+/// let total = wei * XbfU256::from(3u8) + XbfU256::ONE;
+///
+/// assert_eq!(total.to_string(), "3000000000000000000001");
+/// assert_eq!(format!("{:#x}", XbfU256::from(255u8)), "0xff");
+/// assert_eq!(XbfU256::MAX.checked_add(XbfU256::ONE), None);
+/// assert!(u128::try_from(total).is_ok());
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct XbfU256 {
+    limbs: Limbs,
+}
+
+impl XbfU256 {
+    /// The value 0.
+    pub const ZERO: Self = Self::from_limbs([0; 4]);
+    /// The value 1.
+    pub const ONE: Self = Self::from_limbs([1, 0, 0, 0]);
+    /// The largest value, 2^256 - 1.
+    pub const MAX: Self = Self::from_limbs([u64::MAX; 4]);
+
+    /// Creates an integer from its 64 bit limbs, least significant first.
+    pub const fn from_limbs(limbs: [u64; 4]) -> Self {
+        Self { limbs }
+    }
+
+    /// Returns the 64 bit limbs of this integer, least significant first.
+    pub const fn limbs(&self) -> [u64; 4] {
+        self.limbs
+    }
+
+    /// Parses an integer in the given radix, in the same manner as [`u128::from_str_radix`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Int256Error`] if the string is empty, contains a digit which is not valid in
+    /// the radix, or the value is too large.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `radix` is not in the range from 2 to 36.
+    pub fn from_str_radix(s: &str, radix: u32) -> Result<Self, Int256Error> {
+        parse_radix(s.strip_prefix('+').unwrap_or(s), radix).map(Self::from_limbs)
+    }
+
+    /// Calculates `self + rhs`, returning whether the addition overflowed.
+    pub fn overflowing_add(self, rhs: Self) -> (Self, bool) {
+        let (limbs, overflow) = overflowing_add(self.limbs, rhs.limbs);
+        (Self::from_limbs(limbs), overflow)
+    }
+
+    /// Calculates `self - rhs`, returning whether the subtraction overflowed.
+    pub fn overflowing_sub(self, rhs: Self) -> (Self, bool) {
+        let (limbs, overflow) = overflowing_sub(self.limbs, rhs.limbs);
+        (Self::from_limbs(limbs), overflow)
+    }
+
+    /// Calculates `self * rhs`, returning whether the multiplication overflowed.
+    pub fn overflowing_mul(self, rhs: Self) -> (Self, bool) {
+        let (limbs, overflow) = overflowing_mul(self.limbs, rhs.limbs);
+        (Self::from_limbs(limbs), overflow)
+    }
+
+    /// Calculates `self / rhs`, or returns `None` if `rhs` is zero.
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        (rhs != Self::ZERO).then(|| Self::from_limbs(div_rem(self.limbs, rhs.limbs).0))
+    }
+
+    /// Calculates `self % rhs`, or returns `None` if `rhs` is zero.
+    pub fn checked_rem(self, rhs: Self) -> Option<Self> {
+        (rhs != Self::ZERO).then(|| Self::from_limbs(div_rem(self.limbs, rhs.limbs).1))
+    }
+}
+
+/// A signed 256 bit integer in two's complement.
+///
+/// Arithmetic operators panic on overflow and division by zero, use the `checked_`,
+/// `overflowing_` and `wrapping_` methods to handle those cases instead.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::XbfI256;
+///
+/// let debt = XbfI256::from(-5i8) * "1000000000000000000000".parse().unwrap();
+///
+/// assert_eq!(debt.to_string(), "-5000000000000000000000");
+/// assert_eq!(format!("{:x}", XbfI256::from(-1i8)), "f".repeat(64));
+/// assert_eq!(XbfI256::MIN.checked_neg(), None);
+/// assert_eq!(i128::try_from(XbfI256::from(i128::MIN)), Ok(i128::MIN));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct XbfI256 {
+    limbs: Limbs,
+}
+
+impl XbfI256 {
+    /// The value 0.
+    pub const ZERO: Self = Self::from_limbs([0; 4]);
+    /// The value 1.
+    pub const ONE: Self = Self::from_limbs([1, 0, 0, 0]);
+    /// The smallest value, -2^255.
+    pub const MIN: Self = Self::from_limbs([0, 0, 0, SIGN_BIT]);
+    /// The largest value, 2^255 - 1.
+    pub const MAX: Self = Self::from_limbs([u64::MAX, u64::MAX, u64::MAX, !SIGN_BIT]);
+
+    /// Creates an integer from the 64 bit limbs of its two's complement representation, least
+    /// significant first.
+    pub const fn from_limbs(limbs: [u64; 4]) -> Self {
+        Self { limbs }
+    }
+
+    /// Returns the 64 bit limbs of the two's complement representation of this integer, least
+    /// significant first.
+    pub const fn limbs(&self) -> [u64; 4] {
+        self.limbs
+    }
+
+    /// Parses an integer in the given radix, in the same manner as [`i128::from_str_radix`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Int256Error`] if the string is empty, contains a digit which is not valid in
+    /// the radix, or the value is out of range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `radix` is not in the range from 2 to 36.
+    pub fn from_str_radix(s: &str, radix: u32) -> Result<Self, Int256Error> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let magnitude = XbfU256::from_limbs(parse_radix(digits, radix)?);
+        Self::from_sign_and_magnitude(negative, magnitude).ok_or(Int256Error)
+    }
+
+    /// Returns `true` if this integer is less than zero.
+    pub const fn is_negative(&self) -> bool {
+        self.limbs[3] & SIGN_BIT != 0
+    }
+
+    /// Returns the absolute value of this integer, which cannot overflow as an unsigned integer.
+    pub fn unsigned_abs(self) -> XbfU256 {
+        if self.is_negative() {
+            XbfU256::from_limbs(wrapping_neg(self.limbs))
+        } else {
+            XbfU256::from_limbs(self.limbs)
+        }
+    }
+
+    /// Calculates `-self`, returning whether the negation overflowed, which only happens for
+    /// [`Self::MIN`].
+    pub fn overflowing_neg(self) -> (Self, bool) {
+        (
+            Self::from_limbs(wrapping_neg(self.limbs)),
+            self == Self::MIN,
+        )
+    }
+
+    /// Calculates `-self`, or returns `None` if it overflowed.
+    pub fn checked_neg(self) -> Option<Self> {
+        checked(self.overflowing_neg())
+    }
+
+    /// Calculates `-self`, wrapping around on overflow.
+    pub fn wrapping_neg(self) -> Self {
+        self.overflowing_neg().0
+    }
+
+    /// Calculates `self + rhs`, returning whether the addition overflowed.
+    pub fn overflowing_add(self, rhs: Self) -> (Self, bool) {
+        let sum = Self::from_limbs(overflowing_add(self.limbs, rhs.limbs).0);
+        let overflow =
+            self.is_negative() == rhs.is_negative() && sum.is_negative() != self.is_negative();
+        (sum, overflow)
+    }
+
+    /// Calculates `self - rhs`, returning whether the subtraction overflowed.
+    pub fn overflowing_sub(self, rhs: Self) -> (Self, bool) {
+        let difference = Self::from_limbs(overflowing_sub(self.limbs, rhs.limbs).0);
+        let overflow = self.is_negative() != rhs.is_negative()
+            && difference.is_negative() != self.is_negative();
+        (difference, overflow)
+    }
+
+    /// Calculates `self * rhs`, returning whether the multiplication overflowed.
+    pub fn overflowing_mul(self, rhs: Self) -> (Self, bool) {
+        let product = Self::from_limbs(overflowing_mul(self.limbs, rhs.limbs).0);
+        let (magnitude, overflow) = self.unsigned_abs().overflowing_mul(rhs.unsigned_abs());
+        let negative = self.is_negative() != rhs.is_negative();
+        let overflow = overflow || Self::from_sign_and_magnitude(negative, magnitude).is_none();
+        (product, overflow)
+    }
+
+    /// Calculates `self / rhs` rounded towards zero, or returns `None` if `rhs` is zero or the
+    /// division overflowed, which only happens for [`Self::MIN`] divided by -1.
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        let quotient = self.unsigned_abs().checked_div(rhs.unsigned_abs())?;
+        Self::from_sign_and_magnitude(self.is_negative() != rhs.is_negative(), quotient)
+    }
+
+    /// Calculates `self % rhs`, which has the same sign as `self`, or returns `None` if `rhs` is
+    /// zero or the division overflowed, which only happens for [`Self::MIN`] divided by -1.
+    pub fn checked_rem(self, rhs: Self) -> Option<Self> {
+        if self == Self::MIN && rhs == Self::from(-1i8) {
+            return None;
+        }
+        let rem = self.unsigned_abs().checked_rem(rhs.unsigned_abs())?;
+        Self::from_sign_and_magnitude(self.is_negative(), rem)
+    }
+
+    fn from_sign_and_magnitude(negative: bool, magnitude: XbfU256) -> Option<Self> {
+        match (negative, cmp(magnitude.limbs, Self::MIN.limbs)) {
+            (true, Ordering::Less | Ordering::Equal) => {
+                Some(Self::from_limbs(wrapping_neg(magnitude.limbs)))
+            }
+            (false, Ordering::Less) => Some(Self::from_limbs(magnitude.limbs)),
+            _ => None,
+        }
+    }
+}
+
+fn checked<T>((value, overflow): (T, bool)) -> Option<T> {
+    (!overflow).then_some(value)
+}
+
+macro_rules! impl_int256_ops {
+    ($ty:ty) => {
+        impl $ty {
+            /// Calculates `self + rhs`, or returns `None` if it overflowed.
+            pub fn checked_add(self, rhs: Self) -> Option<Self> {
+                checked(self.overflowing_add(rhs))
+            }
+
+            /// Calculates `self - rhs`, or returns `None` if it overflowed.
+            pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+                checked(self.overflowing_sub(rhs))
+            }
+
+            /// Calculates `self * rhs`, or returns `None` if it overflowed.
+            pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+                checked(self.overflowing_mul(rhs))
+            }
+
+            /// Calculates `self + rhs`, wrapping around on overflow.
+            pub fn wrapping_add(self, rhs: Self) -> Self {
+                self.overflowing_add(rhs).0
+            }
+
+            /// Calculates `self - rhs`, wrapping around on overflow.
+            pub fn wrapping_sub(self, rhs: Self) -> Self {
+                self.overflowing_sub(rhs).0
+            }
+
+            /// Calculates `self * rhs`, wrapping around on overflow.
+            pub fn wrapping_mul(self, rhs: Self) -> Self {
+                self.overflowing_mul(rhs).0
+            }
+        }
+
+        impl_int256_ops!(
+            $ty,
+            Add,
+            add,
+            AddAssign,
+            add_assign,
+            checked_add,
+            "attempt to add with overflow"
+        );
+        impl_int256_ops!(
+            $ty,
+            Sub,
+            sub,
+            SubAssign,
+            sub_assign,
+            checked_sub,
+            "attempt to subtract with overflow"
+        );
+        impl_int256_ops!(
+            $ty,
+            Mul,
+            mul,
+            MulAssign,
+            mul_assign,
+            checked_mul,
+            "attempt to multiply with overflow"
+        );
+        impl_int256_ops!(
+            $ty,
+            Div,
+            div,
+            DivAssign,
+            div_assign,
+            checked_div,
+            "attempt to divide by zero or with overflow"
+        );
+        impl_int256_ops!(
+            $ty,
+            Rem,
+            rem,
+            RemAssign,
+            rem_assign,
+            checked_rem,
+            "attempt to calculate the remainder with a divisor of zero or with overflow"
+        );
+
+        impl fmt::Debug for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                Display::fmt(self, f)
+            }
+        }
+
+        impl fmt::LowerHex for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.pad_integral(true, "0x", &to_radix_string(self.limbs, 16, false))
+            }
+        }
+
+        impl fmt::UpperHex for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.pad_integral(true, "0x", &to_radix_string(self.limbs, 16, true))
+            }
+        }
+
+        impl fmt::Octal for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.pad_integral(true, "0o", &to_radix_string(self.limbs, 8, false))
+            }
+        }
+
+        impl fmt::Binary for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.pad_integral(true, "0b", &to_radix_string(self.limbs, 2, false))
+            }
+        }
+
+        impl FromStr for $ty {
+            type Err = Int256Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Self::from_str_radix(s, 10)
+            }
+        }
+    };
+    ($ty:ty, $trait:ident, $fn_name:ident, $assign_trait:ident, $assign_fn_name:ident, $checked:ident, $msg:literal) => {
+        impl $trait for $ty {
+            type Output = Self;
+
+            fn $fn_name(self, rhs: Self) -> Self {
+                self.$checked(rhs).expect($msg)
+            }
+        }
+
+        impl $assign_trait for $ty {
+            fn $assign_fn_name(&mut self, rhs: Self) {
+                *self = self.$fn_name(rhs);
+            }
+        }
+    };
+}
+
+impl_int256_ops!(XbfU256);
+impl_int256_ops!(XbfI256);
+
+impl Neg for XbfI256 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self.checked_neg().expect("attempt to negate with overflow")
+    }
+}
+
+impl Ord for XbfU256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        cmp(self.limbs, other.limbs)
+    }
+}
+
+impl PartialOrd for XbfU256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for XbfI256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // flipping the sign bit maps the signed range onto the unsigned range in order
+        let flip = |[a, b, c, d]: Limbs| [a, b, c, d ^ SIGN_BIT];
+        cmp(flip(self.limbs), flip(other.limbs))
+    }
+}
+
+impl PartialOrd for XbfI256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for XbfU256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad_integral(true, "", &to_radix_string(self.limbs, 10, false))
+    }
+}
+
+impl Display for XbfI256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = to_radix_string(self.unsigned_abs().limbs, 10, false);
+        f.pad_integral(!self.is_negative(), "", &digits)
+    }
+}
+
+macro_rules! impl_from_unsigned {
+    ($($ty:ty),+) => {
+        $(
+            impl From<$ty> for XbfU256 {
+                fn from(value: $ty) -> Self {
+                    let value = u128::from(value);
+                    Self::from_limbs([value as u64, (value >> 64) as u64, 0, 0])
+                }
+            }
+
+            impl From<$ty> for XbfI256 {
+                fn from(value: $ty) -> Self {
+                    Self::from_limbs(XbfU256::from(value).limbs)
+                }
+            }
+
+            impl TryFrom<XbfU256> for $ty {
+                type Error = Int256Error;
+
+                fn try_from(value: XbfU256) -> Result<Self, Self::Error> {
+                    match value.limbs {
+                        [low, high, 0, 0] => {
+                            <$ty>::try_from(u128::from(high) << 64 | u128::from(low))
+                                .map_err(|_| Int256Error)
+                        }
+                        _ => Err(Int256Error),
+                    }
+                }
+            }
+
+            impl TryFrom<XbfI256> for $ty {
+                type Error = Int256Error;
+
+                fn try_from(value: XbfI256) -> Result<Self, Self::Error> {
+                    <$ty>::try_from(XbfU256::try_from(value)?)
+                }
+            }
+        )+
+    };
+}
+
+macro_rules! impl_from_signed {
+    ($($ty:ty),+) => {
+        $(
+            impl From<$ty> for XbfI256 {
+                fn from(value: $ty) -> Self {
+                    let value = i128::from(value);
+                    let extension = if value < 0 { u64::MAX } else { 0 };
+                    Self::from_limbs([value as u64, (value >> 64) as u64, extension, extension])
+                }
+            }
+
+            impl TryFrom<XbfI256> for $ty {
+                type Error = Int256Error;
+
+                fn try_from(value: XbfI256) -> Result<Self, Self::Error> {
+                    let [low, high, a, b] = value.limbs;
+                    let value = (u128::from(high) << 64 | u128::from(low)) as i128;
+                    let extension = if value < 0 { u64::MAX } else { 0 };
+                    if a != extension || b != extension {
+                        return Err(Int256Error);
+                    }
+                    <$ty>::try_from(value).map_err(|_| Int256Error)
+                }
+            }
+        )+
+    };
+}
+
+impl_from_unsigned!(u8, u16, u32, u64, u128);
+impl_from_signed!(i8, i16, i32, i64, i128);
+
+impl TryFrom<XbfI256> for XbfU256 {
+    type Error = Int256Error;
+
+    fn try_from(value: XbfI256) -> Result<Self, Self::Error> {
+        if value.is_negative() {
+            return Err(Int256Error);
+        }
+        Ok(Self::from_limbs(value.limbs))
+    }
+}
+
+impl TryFrom<XbfU256> for XbfI256 {
+    type Error = Int256Error;
+
+    fn try_from(value: XbfU256) -> Result<Self, Self::Error> {
+        Self::from_sign_and_magnitude(false, value).ok_or(Int256Error)
+    }
+}
+
+/// Error type for 256 bit integers which are malformed, or out of the range of the type they are
+/// being converted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Int256Error;
+
+impl Display for Int256Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Integer is malformed or out of range")
+    }
+}
+
+impl Error for Int256Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u256(s: &str) -> XbfU256 {
+        s.parse().unwrap()
+    }
+
+    fn i256(s: &str) -> XbfI256 {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn u256_arithmetic_works() {
+        let max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+        assert_eq!(u256(max), XbfU256::MAX);
+        assert_eq!(XbfU256::MAX.to_string(), max);
+
+        let a = XbfU256::from(u128::MAX);
+        assert_eq!(
+            a * a,
+            u256("115792089237316195423570985008687907852589419931798687112530834793049593217025")
+        );
+        let b = a + XbfU256::ONE;
+        assert_eq!(b.checked_mul(b), None);
+        assert_eq!((a * a + XbfU256::from(5u8)) / a, a);
+        assert_eq!((a * a + XbfU256::from(5u8)) % a, XbfU256::from(5u8));
+        assert_eq!((a * b) / b, a);
+        assert_eq!(XbfU256::MAX / XbfU256::MAX, XbfU256::ONE);
+        assert_eq!(XbfU256::MAX % u256("10"), u256("5"));
+        assert_eq!(XbfU256::ONE.checked_div(XbfU256::ZERO), None);
+        assert_eq!(
+            XbfU256::ZERO.overflowing_sub(XbfU256::ONE),
+            (XbfU256::MAX, true)
+        );
+        assert!(b > a && a > XbfU256::ZERO);
+    }
+
+    #[test]
+    fn i256_arithmetic_works() {
+        let min = "-57896044618658097711785492504343953926634992332820282019728792003956564819968";
+        assert_eq!(i256(min), XbfI256::MIN);
+        assert_eq!(XbfI256::MIN.to_string(), min);
+        assert_eq!(XbfI256::MIN.unsigned_abs().to_string(), &min[1..]);
+        assert_eq!(XbfI256::MAX + XbfI256::MIN, XbfI256::from(-1i8));
+
+        let a = XbfI256::from(i128::MIN);
+        let b = XbfI256::from(-7i8);
+        assert_eq!(a * b, i256("1190988284223284622121811126011188740096"));
+        assert_eq!(a / b, XbfI256::from(i128::MIN / -7));
+        assert_eq!(a % b, XbfI256::from(-2i8));
+        assert_eq!(-b, XbfI256::from(7u8));
+        assert!(a < b && b < XbfI256::ZERO && XbfI256::MIN < a);
+
+        assert_eq!(XbfI256::MAX.checked_add(XbfI256::ONE), None);
+        assert_eq!(XbfI256::MIN.checked_sub(XbfI256::ONE), None);
+        assert_eq!(XbfI256::MIN.checked_mul(XbfI256::from(-1i8)), None);
+        assert_eq!(XbfI256::MIN.checked_div(XbfI256::from(-1i8)), None);
+        assert_eq!(XbfI256::MIN.checked_rem(XbfI256::from(-1i8)), None);
+        assert_eq!(XbfI256::MIN.checked_mul(XbfI256::ONE), Some(XbfI256::MIN));
+    }
+
+    #[test]
+    fn parse_and_format_work() {
+        assert_eq!(XbfU256::from_str_radix("ff", 16), Ok(XbfU256::from(255u8)));
+        assert_eq!(
+            XbfI256::from_str_radix("-ff", 16),
+            Ok(XbfI256::from(-255i16))
+        );
+        assert_eq!(format!("{:#x}", XbfU256::from(255u8)), "0xff");
+        assert_eq!(format!("{:X}", XbfU256::from(255u8)), "FF");
+        assert_eq!(format!("{:#b}", XbfU256::from(5u8)), "0b101");
+        assert_eq!(format!("{:o}", XbfU256::from(8u8)), "10");
+        assert_eq!(format!("{:>5}", XbfI256::from(-3i8)), "   -3");
+        assert_eq!(format!("{:+}", XbfU256::ZERO), "+0");
+        assert_eq!(format!("{:?}", XbfI256::from(-3i8)), "-3");
+
+        for s in [
+            "",
+            "-",
+            "+",
+            "1a",
+            "-1",
+            "115792089237316195423570985008687907853269984665640564039457584007913129639936",
+        ] {
+            assert_eq!(s.parse::<XbfU256>(), Err(Int256Error), "{s}");
+        }
+        for s in [
+            "",
+            "--1",
+            "57896044618658097711785492504343953926634992332820282019728792003956564819968",
+        ] {
+            assert_eq!(s.parse::<XbfI256>(), Err(Int256Error), "{s}");
+        }
+    }
+
+    #[test]
+    fn conversions_work() {
+        assert_eq!(u128::try_from(XbfU256::from(u128::MAX)), Ok(u128::MAX));
+        assert_eq!(
+            u128::try_from(XbfU256::from(u128::MAX) + XbfU256::ONE),
+            Err(Int256Error)
+        );
+        assert_eq!(u8::try_from(XbfU256::from(256u16)), Err(Int256Error));
+
+        assert_eq!(i128::try_from(XbfI256::from(i128::MIN)), Ok(i128::MIN));
+        assert_eq!(i128::try_from(XbfI256::from(u128::MAX)), Err(Int256Error));
+        assert_eq!(u128::try_from(XbfI256::from(u128::MAX)), Ok(u128::MAX));
+        assert_eq!(u128::try_from(XbfI256::from(-1i8)), Err(Int256Error));
+        assert_eq!(i8::try_from(XbfI256::from(-128i16)), Ok(-128));
+
+        assert_eq!(
+            XbfU256::try_from(XbfI256::MAX),
+            Ok(XbfU256::MAX / XbfU256::from(2u8))
+        );
+        assert_eq!(XbfU256::try_from(XbfI256::from(-1i8)), Err(Int256Error));
+        assert_eq!(XbfI256::try_from(XbfU256::MAX), Err(Int256Error));
+    }
+}
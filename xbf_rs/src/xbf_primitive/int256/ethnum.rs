@@ -0,0 +1,55 @@
+//! Conversions between the 256 bit integers and [`ethnum`](::ethnum), enabled with the `ethnum`
+//! feature.
+
+use super::{XbfI256, XbfU256};
+use ::ethnum::{I256, U256};
+
+impl From<U256> for XbfU256 {
+    fn from(value: U256) -> Self {
+        let (high, low) = value.into_words();
+        Self::from_limbs([
+            low as u64,
+            (low >> 64) as u64,
+            high as u64,
+            (high >> 64) as u64,
+        ])
+    }
+}
+
+impl From<XbfU256> for U256 {
+    fn from(value: XbfU256) -> Self {
+        let [a, b, c, d] = value.limbs().map(u128::from);
+        U256::from_words(d << 64 | c, b << 64 | a)
+    }
+}
+
+impl From<I256> for XbfI256 {
+    fn from(value: I256) -> Self {
+        Self::from_limbs(XbfU256::from(value.as_u256()).limbs())
+    }
+}
+
+impl From<XbfI256> for I256 {
+    fn from(value: XbfI256) -> Self {
+        U256::from(XbfU256::from_limbs(value.limbs())).as_i256()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions_work() {
+        let value = U256::MAX - U256::from(u128::MAX);
+        let xbf_value = XbfU256::from(value);
+        assert_eq!(xbf_value, XbfU256::MAX - XbfU256::from(u128::MAX));
+        assert_eq!(U256::from(xbf_value), value);
+
+        let value = I256::MIN + I256::from(i128::MAX);
+        let xbf_value = XbfI256::from(value);
+        assert_eq!(xbf_value, XbfI256::MIN + XbfI256::from(i128::MAX));
+        assert_eq!(xbf_value.to_string(), value.to_string());
+        assert_eq!(I256::from(xbf_value), value);
+    }
+}
@@ -0,0 +1,31 @@
+//! Conversions between [`XbfU256`] and [`primitive_types`](::primitive_types), enabled with the
+//! `primitive-types` feature.
+
+use super::XbfU256;
+use ::primitive_types::U256;
+
+impl From<U256> for XbfU256 {
+    fn from(value: U256) -> Self {
+        Self::from_limbs(value.0)
+    }
+}
+
+impl From<XbfU256> for U256 {
+    fn from(value: XbfU256) -> Self {
+        U256(value.limbs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions_work() {
+        let value = U256::MAX - U256::from(u128::MAX);
+        let xbf_value = XbfU256::from(value);
+        assert_eq!(xbf_value, XbfU256::MAX - XbfU256::from(u128::MAX));
+        assert_eq!(xbf_value.to_string(), value.to_string());
+        assert_eq!(U256::from(xbf_value), value);
+    }
+}
@@ -0,0 +1,522 @@
+//! Contiguous storage for many primitives of the same type.
+
+use crate::{
+    reader::{XbfReader, MAX_PREALLOCATION},
+    XbfDate, XbfDecimal, XbfDuration, XbfError, XbfI256, XbfPrimitive, XbfPrimitiveMetadata,
+    XbfTime, XbfTimestamp, XbfTimestampTz, XbfU256,
+};
+use byteorder::{ByteOrder, LittleEndian};
+use std::{
+    io::{self, Read, Write},
+    ops::Range,
+};
+
+/// The number of elements encoded at a time when writing a buffer in bulk.
+const WRITE_CHUNK_LEN: usize = 4096;
+
+macro_rules! primitive_buffer {
+    ($($variant:ident($ty:ty)),+ $(,)?) => {
+        /// A contiguous buffer of primitives which all have the same [fixed
+        /// size](XbfPrimitiveMetadata::fixed_size) type.
+        ///
+        /// Each variant holds the same native type as the matching [`XbfPrimitive`] variant, so a
+        /// buffer of `F32`s is a `Vec<f32>`, rather than a `Vec` of [`XbfPrimitive`]s. Buffers of
+        /// plain numbers are read and written as a single block of little endian bytes.
+        ///
+        /// # Examples
+        ///
+        /// ```rust
+        /// use xbf_rs::{XbfPrimitive, XbfPrimitiveBuffer, XbfPrimitiveMetadata};
+        ///
+        /// let mut buffer = XbfPrimitiveBuffer::from(vec![1.5f32, 2.5]);
+        /// assert_eq!(buffer.element_type(), XbfPrimitiveMetadata::F32);
+        ///
+        /// buffer.push(XbfPrimitive::F32(3.5)).unwrap();
+        /// assert!(buffer.push(XbfPrimitive::F64(4.5)).is_err());
+        ///
+        /// assert_eq!(buffer.as_slice::<f32>(), Some([1.5, 2.5, 3.5].as_slice()));
+        /// assert_eq!(buffer.get(1), Some(XbfPrimitive::F32(2.5)));
+        /// ```
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum XbfPrimitiveBuffer {
+            $($variant(Vec<$ty>),)+
+        }
+
+        impl XbfPrimitiveBuffer {
+            /// Creates an empty buffer of the given element type, or `None` if values of the type
+            /// do not have a fixed size.
+            pub fn new(element_type: XbfPrimitiveMetadata) -> Option<Self> {
+                match element_type {
+                    $(XbfPrimitiveMetadata::$variant => Some(Self::$variant(vec![])),)+
+                    XbfPrimitiveMetadata::Bytes | XbfPrimitiveMetadata::String => None,
+                }
+            }
+
+            /// Returns the type of the elements of the buffer.
+            pub fn element_type(&self) -> XbfPrimitiveMetadata {
+                match self {
+                    $(Self::$variant(_) => XbfPrimitiveMetadata::$variant,)+
+                }
+            }
+
+            /// Returns the number of elements in the buffer.
+            pub fn len(&self) -> usize {
+                match self {
+                    $(Self::$variant(x) => x.len(),)+
+                }
+            }
+
+            /// Returns the element at `index`, or `None` if it is out of bounds.
+            pub fn get(&self, index: usize) -> Option<XbfPrimitive> {
+                match self {
+                    $(Self::$variant(x) => x.get(index).map(|x| XbfPrimitive::$variant(*x)),)+
+                }
+            }
+
+            /// Appends an element to the end of the buffer.
+            ///
+            /// # Errors
+            ///
+            /// Returns the element back if it is not of the buffer's element type.
+            pub fn push(&mut self, value: XbfPrimitive) -> Result<(), XbfPrimitive> {
+                match (self, value) {
+                    $((Self::$variant(x), XbfPrimitive::$variant(value)) => {
+                        x.push(value);
+                        Ok(())
+                    })+
+                    (_, value) => Err(value),
+                }
+            }
+        }
+    };
+}
+
+primitive_buffer! {
+    Bool(bool),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    U256(XbfU256),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    I256(XbfI256),
+    F32(f32),
+    F64(f64),
+    Timestamp(XbfTimestamp),
+    TimestampTz(XbfTimestampTz),
+    Date(XbfDate),
+    Time(XbfTime),
+    Duration(XbfDuration),
+    Decimal(XbfDecimal),
+    Uuid([u8; 16]),
+    Char(char),
+    F16(u16),
+    BF16(u16),
+}
+
+impl XbfPrimitiveBuffer {
+    /// Returns `true` if the buffer has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over the elements of the buffer.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = XbfPrimitive> + '_ {
+        (0..self.len()).map(|i| self.get(i).expect("index is in bounds"))
+    }
+
+    /// Returns the elements of the buffer as a slice of `T`, or `None` if they are not of the
+    /// type `T` is stored as.
+    ///
+    /// Half precision floats are stored as their bits, which can be accessed with
+    /// [`Self::as_f16_bits`] and [`Self::as_bf16_bits`].
+    pub fn as_slice<T: XbfBufferElement>(&self) -> Option<&[T]> {
+        T::as_slice(self)
+    }
+
+    /// Returns the elements of the buffer as a `Vec<T>`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the buffer back if its elements are not of the type `T` is stored as.
+    pub fn into_vec<T: XbfBufferElement>(self) -> Result<Vec<T>, Self> {
+        T::from_buffer(self)
+    }
+
+    /// Returns the bits of the elements if this is a buffer of [`F16`](Self::F16)s.
+    pub fn as_f16_bits(&self) -> Option<&[u16]> {
+        match self {
+            Self::F16(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    /// Returns the bits of the elements if this is a buffer of [`BF16`](Self::BF16)s.
+    pub fn as_bf16_bits(&self) -> Option<&[u16]> {
+        match self {
+            Self::BF16(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    /// Serializes every element of the buffer in sequence, with no length prefix.
+    pub(crate) fn serialize_elements(&self, writer: &mut impl Write) -> io::Result<()> {
+        self.serialize_range(0..self.len(), writer)
+    }
+
+    /// Serializes the elements in `range` in sequence, with no length prefix.
+    pub(crate) fn serialize_range(
+        &self,
+        range: Range<usize>,
+        writer: &mut impl Write,
+    ) -> io::Result<()> {
+        match self {
+            Self::Bool(x) => write_each(&x[range], writer),
+            Self::U8(x) => writer.write_all(&x[range]),
+            Self::U16(x) | Self::F16(x) | Self::BF16(x) => {
+                write_le(&x[range], LittleEndian::write_u16_into, writer)
+            }
+            Self::U32(x) => write_le(&x[range], LittleEndian::write_u32_into, writer),
+            Self::U64(x) => write_le(&x[range], LittleEndian::write_u64_into, writer),
+            Self::U128(x) => write_le(&x[range], LittleEndian::write_u128_into, writer),
+            Self::U256(x) => write_each(&x[range], writer),
+            Self::I8(x) => write_each(&x[range], writer),
+            Self::I16(x) => write_le(&x[range], LittleEndian::write_i16_into, writer),
+            Self::I32(x) => write_le(&x[range], LittleEndian::write_i32_into, writer),
+            Self::I64(x) => write_le(&x[range], LittleEndian::write_i64_into, writer),
+            Self::I128(x) => write_le(&x[range], LittleEndian::write_i128_into, writer),
+            Self::I256(x) => write_each(&x[range], writer),
+            Self::F32(x) => write_le(&x[range], LittleEndian::write_f32_into, writer),
+            Self::F64(x) => write_le(&x[range], LittleEndian::write_f64_into, writer),
+            Self::Timestamp(x) => write_each(&x[range], writer),
+            Self::TimestampTz(x) => write_each(&x[range], writer),
+            Self::Date(x) => write_each(&x[range], writer),
+            Self::Time(x) => write_each(&x[range], writer),
+            Self::Duration(x) => write_each(&x[range], writer),
+            Self::Decimal(x) => write_each(&x[range], writer),
+            Self::Uuid(x) => write_each(&x[range], writer),
+            Self::Char(x) => write_each(&x[range], writer),
+        }
+    }
+
+    /// Reads `len` elements of the given type, which must have a fixed size.
+    pub(crate) fn read_elements(
+        element_type: XbfPrimitiveMetadata,
+        len: u64,
+        reader: &mut XbfReader<impl Read>,
+    ) -> Result<Self, XbfError> {
+        Ok(match element_type {
+            XbfPrimitiveMetadata::Bool => Self::Bool(read_each(len, reader)?),
+            XbfPrimitiveMetadata::U8 => Self::U8(reader.read_elements(len, 1)?),
+            XbfPrimitiveMetadata::U16 => {
+                Self::U16(read_le(len, LittleEndian::read_u16_into, reader)?)
+            }
+            XbfPrimitiveMetadata::U32 => {
+                Self::U32(read_le(len, LittleEndian::read_u32_into, reader)?)
+            }
+            XbfPrimitiveMetadata::U64 => {
+                Self::U64(read_le(len, LittleEndian::read_u64_into, reader)?)
+            }
+            XbfPrimitiveMetadata::U128 => {
+                Self::U128(read_le(len, LittleEndian::read_u128_into, reader)?)
+            }
+            XbfPrimitiveMetadata::U256 => Self::U256(read_each(len, reader)?),
+            XbfPrimitiveMetadata::I8 => Self::I8(read_each(len, reader)?),
+            XbfPrimitiveMetadata::I16 => {
+                Self::I16(read_le(len, LittleEndian::read_i16_into, reader)?)
+            }
+            XbfPrimitiveMetadata::I32 => {
+                Self::I32(read_le(len, LittleEndian::read_i32_into, reader)?)
+            }
+            XbfPrimitiveMetadata::I64 => {
+                Self::I64(read_le(len, LittleEndian::read_i64_into, reader)?)
+            }
+            XbfPrimitiveMetadata::I128 => {
+                Self::I128(read_le(len, LittleEndian::read_i128_into, reader)?)
+            }
+            XbfPrimitiveMetadata::I256 => Self::I256(read_each(len, reader)?),
+            XbfPrimitiveMetadata::F32 => {
+                Self::F32(read_le(len, LittleEndian::read_f32_into, reader)?)
+            }
+            XbfPrimitiveMetadata::F64 => {
+                Self::F64(read_le(len, LittleEndian::read_f64_into, reader)?)
+            }
+            XbfPrimitiveMetadata::Timestamp => Self::Timestamp(read_each(len, reader)?),
+            XbfPrimitiveMetadata::TimestampTz => Self::TimestampTz(read_each(len, reader)?),
+            XbfPrimitiveMetadata::Date => Self::Date(read_each(len, reader)?),
+            XbfPrimitiveMetadata::Time => Self::Time(read_each(len, reader)?),
+            XbfPrimitiveMetadata::Duration => Self::Duration(read_each(len, reader)?),
+            XbfPrimitiveMetadata::Decimal => Self::Decimal(read_each(len, reader)?),
+            XbfPrimitiveMetadata::Uuid => Self::Uuid(read_each(len, reader)?),
+            XbfPrimitiveMetadata::Char => Self::Char(read_each(len, reader)?),
+            XbfPrimitiveMetadata::F16 => {
+                Self::F16(read_le(len, LittleEndian::read_u16_into, reader)?)
+            }
+            XbfPrimitiveMetadata::BF16 => {
+                Self::BF16(read_le(len, LittleEndian::read_u16_into, reader)?)
+            }
+            XbfPrimitiveMetadata::Bytes | XbfPrimitiveMetadata::String => {
+                unreachable!("only primitives with a fixed size are buffered")
+            }
+        })
+    }
+}
+
+impl<T: XbfBufferElement> From<Vec<T>> for XbfPrimitiveBuffer {
+    fn from(value: Vec<T>) -> Self {
+        T::into_buffer(value)
+    }
+}
+
+#[cfg(feature = "half")]
+impl From<Vec<half::f16>> for XbfPrimitiveBuffer {
+    fn from(value: Vec<half::f16>) -> Self {
+        Self::F16(value.into_iter().map(half::f16::to_bits).collect())
+    }
+}
+
+#[cfg(feature = "half")]
+impl From<Vec<half::bf16>> for XbfPrimitiveBuffer {
+    fn from(value: Vec<half::bf16>) -> Self {
+        Self::BF16(value.into_iter().map(half::bf16::to_bits).collect())
+    }
+}
+
+/// Writes plain numbers as little endian bytes a chunk at a time.
+fn write_le<T>(
+    values: &[T],
+    encode: fn(&[T], &mut [u8]),
+    writer: &mut impl Write,
+) -> io::Result<()> {
+    let mut buf = vec![];
+    for chunk in values.chunks(WRITE_CHUNK_LEN) {
+        buf.resize(std::mem::size_of_val(chunk), 0);
+        encode(chunk, &mut buf);
+        writer.write_all(&buf)?;
+    }
+    Ok(())
+}
+
+fn write_each<T: XbfBufferElement>(values: &[T], writer: &mut impl Write) -> io::Result<()> {
+    values
+        .iter()
+        .try_for_each(|x| x.into_primitive().serialize_primitive_type(writer))
+}
+
+/// Reads plain numbers from a single block of little endian bytes.
+fn read_le<T: Copy + Default>(
+    len: u64,
+    decode: fn(&[u8], &mut [T]),
+    reader: &mut XbfReader<impl Read>,
+) -> Result<Vec<T>, XbfError> {
+    let bytes = reader.read_elements(len, std::mem::size_of::<T>())?;
+    let mut values = vec![T::default(); bytes.len() / std::mem::size_of::<T>()];
+    decode(&bytes, &mut values);
+    Ok(values)
+}
+
+fn read_each<T: XbfBufferElement>(
+    len: u64,
+    reader: &mut XbfReader<impl Read>,
+) -> Result<Vec<T>, XbfError> {
+    let max_capacity = MAX_PREALLOCATION / std::mem::size_of::<T>();
+    let mut values = Vec::with_capacity((len as usize).min(max_capacity));
+    for i in 0..len {
+        let value = XbfPrimitive::read_primitive_type(&T::ELEMENT_TYPE, reader)
+            .map_err(|e| e.at_index(i))?;
+        values.push(T::from_primitive(value).expect("read as the element type"));
+    }
+    Ok(values)
+}
+
+/// A native type which is stored in an [`XbfPrimitiveBuffer`].
+///
+/// This trait is implemented for the native type of every [`XbfPrimitiveBuffer`] variant, apart
+/// from the half precision floats, which are stored as `u16` bits. You should not implement this
+/// trait yourself.
+pub trait XbfBufferElement: Copy + private::Element {
+    /// The type of the primitives this type is stored as.
+    const ELEMENT_TYPE: XbfPrimitiveMetadata;
+}
+
+macro_rules! impl_XbfBufferElement {
+    ($ty:ty, $variant:tt) => {
+        impl XbfBufferElement for $ty {
+            const ELEMENT_TYPE: XbfPrimitiveMetadata = XbfPrimitiveMetadata::$variant;
+        }
+
+        impl private::Element for $ty {
+            fn into_buffer(values: Vec<Self>) -> XbfPrimitiveBuffer {
+                XbfPrimitiveBuffer::$variant(values)
+            }
+
+            fn from_buffer(buffer: XbfPrimitiveBuffer) -> Result<Vec<Self>, XbfPrimitiveBuffer> {
+                match buffer {
+                    XbfPrimitiveBuffer::$variant(x) => Ok(x),
+                    other => Err(other),
+                }
+            }
+
+            fn as_slice(buffer: &XbfPrimitiveBuffer) -> Option<&[Self]> {
+                match buffer {
+                    XbfPrimitiveBuffer::$variant(x) => Some(x),
+                    _ => None,
+                }
+            }
+
+            fn into_primitive(self) -> XbfPrimitive {
+                XbfPrimitive::$variant(self)
+            }
+
+            fn from_primitive(value: XbfPrimitive) -> Option<Self> {
+                match value {
+                    XbfPrimitive::$variant(x) => Some(x),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+impl_XbfBufferElement!(bool, Bool);
+impl_XbfBufferElement!(u8, U8);
+impl_XbfBufferElement!(u16, U16);
+impl_XbfBufferElement!(u32, U32);
+impl_XbfBufferElement!(u64, U64);
+impl_XbfBufferElement!(u128, U128);
+impl_XbfBufferElement!(XbfU256, U256);
+impl_XbfBufferElement!(i8, I8);
+impl_XbfBufferElement!(i16, I16);
+impl_XbfBufferElement!(i32, I32);
+impl_XbfBufferElement!(i64, I64);
+impl_XbfBufferElement!(i128, I128);
+impl_XbfBufferElement!(XbfI256, I256);
+impl_XbfBufferElement!(f32, F32);
+impl_XbfBufferElement!(f64, F64);
+impl_XbfBufferElement!(XbfTimestamp, Timestamp);
+impl_XbfBufferElement!(XbfTimestampTz, TimestampTz);
+impl_XbfBufferElement!(XbfDate, Date);
+impl_XbfBufferElement!(XbfTime, Time);
+impl_XbfBufferElement!(XbfDuration, Duration);
+impl_XbfBufferElement!(XbfDecimal, Decimal);
+impl_XbfBufferElement!([u8; 16], Uuid);
+impl_XbfBufferElement!(char, Char);
+
+mod private {
+    use crate::{XbfPrimitive, XbfPrimitiveBuffer};
+
+    pub trait Element: Sized {
+        fn into_buffer(values: Vec<Self>) -> XbfPrimitiveBuffer;
+
+        fn from_buffer(buffer: XbfPrimitiveBuffer) -> Result<Vec<Self>, XbfPrimitiveBuffer>;
+
+        fn as_slice(buffer: &XbfPrimitiveBuffer) -> Option<&[Self]>;
+
+        fn into_primitive(self) -> XbfPrimitive;
+
+        fn from_primitive(value: XbfPrimitive) -> Option<Self>;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DeserializeOptions, XbfErrorKind, XbfLimit};
+
+    fn serialize(buffer: &XbfPrimitiveBuffer) -> Vec<u8> {
+        let mut data = vec![];
+        buffer.serialize_elements(&mut data).unwrap();
+        data
+    }
+
+    fn read(
+        element_type: XbfPrimitiveMetadata,
+        len: u64,
+        data: &[u8],
+    ) -> Result<XbfPrimitiveBuffer, XbfError> {
+        XbfPrimitiveBuffer::read_elements(element_type, len, &mut XbfReader::new(data))
+    }
+
+    #[test]
+    fn bulk_matches_primitives() {
+        let buffers = [
+            XbfPrimitiveBuffer::from(vec![true, false]),
+            XbfPrimitiveBuffer::from(vec![1u8, 2, 3]),
+            XbfPrimitiveBuffer::from(vec![-1i8, 2]),
+            XbfPrimitiveBuffer::from(vec![u128::MAX, 7]),
+            XbfPrimitiveBuffer::from(vec![-5i32; WRITE_CHUNK_LEN + 3]),
+            XbfPrimitiveBuffer::from(vec![1.5f64, -0.0, f64::INFINITY]),
+            XbfPrimitiveBuffer::from(vec![XbfI256::MIN, XbfI256::ONE]),
+            XbfPrimitiveBuffer::from(vec![XbfDecimal::new(-12_345, 2)]),
+            XbfPrimitiveBuffer::from(vec!['a', '🐉']),
+            XbfPrimitiveBuffer::BF16(vec![0x3f80, 0xc000]),
+        ];
+        for buffer in buffers {
+            let data = serialize(&buffer);
+
+            let mut expected = vec![];
+            for x in buffer.iter() {
+                x.serialize_primitive_type(&mut expected).unwrap();
+            }
+            assert_eq!(data, expected);
+
+            let len = buffer.len() as u64;
+            assert_eq!(read(buffer.element_type(), len, &data).unwrap(), buffer);
+        }
+    }
+
+    #[test]
+    fn typed_access_works() {
+        let buffer = XbfPrimitiveBuffer::from(vec![1i32, 2, 3]);
+        assert_eq!(buffer.as_slice::<i32>(), Some([1, 2, 3].as_slice()));
+        assert_eq!(buffer.as_slice::<u32>(), None);
+        assert_eq!(buffer.as_f16_bits(), None);
+
+        let buffer = buffer.into_vec::<i64>().unwrap_err();
+        assert_eq!(buffer.into_vec::<i32>(), Ok(vec![1, 2, 3]));
+
+        assert_eq!(XbfPrimitiveBuffer::new(XbfPrimitiveMetadata::String), None);
+        let empty = XbfPrimitiveBuffer::new(XbfPrimitiveMetadata::F16).unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(empty.as_f16_bits(), Some([].as_slice()));
+    }
+
+    #[test]
+    fn read_errors_locate_the_element() {
+        let data = [1u32, 2, 3]
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect::<Vec<_>>();
+
+        let err = read(XbfPrimitiveMetadata::U32, 3, &data[..10]).unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::UnexpectedEof));
+        assert_eq!(err.offset(), 8);
+        assert_eq!(err.path(), "[2]");
+
+        let options = DeserializeOptions::new().max_total_bytes(6);
+        let err = XbfPrimitiveBuffer::read_elements(
+            XbfPrimitiveMetadata::U32,
+            3,
+            &mut XbfReader::with_options(data.as_slice(), options),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            XbfErrorKind::LimitExceeded {
+                limit: XbfLimit::TotalBytes,
+                ..
+            }
+        ));
+        assert_eq!(err.offset(), 4);
+
+        let data = 0xd800u32.to_le_bytes().repeat(2);
+        let err = read(XbfPrimitiveMetadata::Char, 2, &data).unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::InvalidChar(0xd800)));
+        assert_eq!(err.path(), "[0]");
+    }
+}
@@ -1,8 +1,12 @@
-use crate::{XbfMetadata, XbfMetadataUpcast, XbfPrimitive};
+use crate::{XbfErrorKind, XbfMetadata, XbfMetadataUpcast, XbfPrimitive};
 use byteorder::WriteBytesExt;
 use std::io::{self, Write};
 
 /// Metadata for a primitive type.
+///
+/// The discriminant of each primitive never changes once it has been assigned. Primitives added
+/// after the composite types take the next discriminant free at the time, which is why there is a
+/// gap after [`String`](Self::String).
 #[derive(PartialEq, Eq, Debug, Copy, Clone)]
 #[repr(u8)]
 pub enum XbfPrimitiveMetadata {
@@ -23,6 +27,17 @@ pub enum XbfPrimitiveMetadata {
     F64,
     Bytes,
     String,
+    // 17 to 22 are vectors, structs, options, enums, maps and arrays
+    Timestamp = 23,
+    TimestampTz,
+    Date,
+    Time,
+    Duration,
+    Decimal,
+    Uuid,
+    Char,
+    F16,
+    BF16,
 }
 
 impl XbfPrimitiveMetadata {
@@ -42,10 +57,51 @@ impl XbfPrimitiveMetadata {
     pub fn serialize_primitive_metadata(&self, writer: &mut impl Write) -> io::Result<()> {
         writer.write_u8(*self as u8)
     }
+
+    /// Returns the number of bytes every serialized value of this type takes up, or `None` for
+    /// bytes and strings, whose size depends on their length.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use xbf_rs::XbfPrimitiveMetadata;
+    ///
+    /// assert_eq!(XbfPrimitiveMetadata::U256.fixed_size(), Some(32));
+    /// assert_eq!(XbfPrimitiveMetadata::String.fixed_size(), None);
+    /// ```
+    pub fn fixed_size(&self) -> Option<usize> {
+        match self {
+            XbfPrimitiveMetadata::Bool | XbfPrimitiveMetadata::U8 | XbfPrimitiveMetadata::I8 => {
+                Some(1)
+            }
+            XbfPrimitiveMetadata::U16
+            | XbfPrimitiveMetadata::I16
+            | XbfPrimitiveMetadata::F16
+            | XbfPrimitiveMetadata::BF16 => Some(2),
+            XbfPrimitiveMetadata::U32
+            | XbfPrimitiveMetadata::I32
+            | XbfPrimitiveMetadata::F32
+            | XbfPrimitiveMetadata::Date
+            | XbfPrimitiveMetadata::Char => Some(4),
+            XbfPrimitiveMetadata::U64
+            | XbfPrimitiveMetadata::I64
+            | XbfPrimitiveMetadata::F64
+            | XbfPrimitiveMetadata::Timestamp
+            | XbfPrimitiveMetadata::Time
+            | XbfPrimitiveMetadata::Duration => Some(8),
+            XbfPrimitiveMetadata::TimestampTz => Some(12),
+            XbfPrimitiveMetadata::U128
+            | XbfPrimitiveMetadata::I128
+            | XbfPrimitiveMetadata::Uuid => Some(16),
+            XbfPrimitiveMetadata::Decimal => Some(17),
+            XbfPrimitiveMetadata::U256 | XbfPrimitiveMetadata::I256 => Some(32),
+            XbfPrimitiveMetadata::Bytes | XbfPrimitiveMetadata::String => None,
+        }
+    }
 }
 
 impl TryFrom<u8> for XbfPrimitiveMetadata {
-    type Error = io::Error;
+    type Error = XbfErrorKind;
 
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
@@ -66,10 +122,17 @@ impl TryFrom<u8> for XbfPrimitiveMetadata {
             14 => Ok(Self::F64),
             15 => Ok(Self::Bytes),
             16 => Ok(Self::String),
-            _ => Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "invalid primitive metadata",
-            )),
+            23 => Ok(Self::Timestamp),
+            24 => Ok(Self::TimestampTz),
+            25 => Ok(Self::Date),
+            26 => Ok(Self::Time),
+            27 => Ok(Self::Duration),
+            28 => Ok(Self::Decimal),
+            29 => Ok(Self::Uuid),
+            30 => Ok(Self::Char),
+            31 => Ok(Self::F16),
+            32 => Ok(Self::BF16),
+            _ => Err(XbfErrorKind::UnknownDiscriminant(value)),
         }
     }
 }
@@ -103,6 +166,16 @@ impl From<&XbfPrimitive> for XbfPrimitiveMetadata {
             XbfPrimitive::F32(_) => XbfPrimitiveMetadata::F32,
             XbfPrimitive::F64(_) => XbfPrimitiveMetadata::F64,
             XbfPrimitive::Bytes(_) => XbfPrimitiveMetadata::Bytes,
+            XbfPrimitive::Timestamp(_) => XbfPrimitiveMetadata::Timestamp,
+            XbfPrimitive::TimestampTz(_) => XbfPrimitiveMetadata::TimestampTz,
+            XbfPrimitive::Date(_) => XbfPrimitiveMetadata::Date,
+            XbfPrimitive::Time(_) => XbfPrimitiveMetadata::Time,
+            XbfPrimitive::Duration(_) => XbfPrimitiveMetadata::Duration,
+            XbfPrimitive::Decimal(_) => XbfPrimitiveMetadata::Decimal,
+            XbfPrimitive::Uuid(_) => XbfPrimitiveMetadata::Uuid,
+            XbfPrimitive::Char(_) => XbfPrimitiveMetadata::Char,
+            XbfPrimitive::F16(_) => XbfPrimitiveMetadata::F16,
+            XbfPrimitive::BF16(_) => XbfPrimitiveMetadata::BF16,
             XbfPrimitive::String(_) => XbfPrimitiveMetadata::String,
         }
     }
@@ -111,7 +184,10 @@ impl From<&XbfPrimitive> for XbfPrimitiveMetadata {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::XbfMetadata;
+    use crate::{
+        XbfDate, XbfDecimal, XbfDuration, XbfI256, XbfMetadata, XbfTime, XbfTimestamp,
+        XbfTimestampTz, XbfU256,
+    };
     use std::io::Cursor;
 
     macro_rules! serialize_primitive_metadata_test {
@@ -142,6 +218,16 @@ mod tests {
         serialize_primitive_metadata_test!(F64, 14);
         serialize_primitive_metadata_test!(Bytes, 15);
         serialize_primitive_metadata_test!(String, 16);
+        serialize_primitive_metadata_test!(Timestamp, 23);
+        serialize_primitive_metadata_test!(TimestampTz, 24);
+        serialize_primitive_metadata_test!(Date, 25);
+        serialize_primitive_metadata_test!(Time, 26);
+        serialize_primitive_metadata_test!(Duration, 27);
+        serialize_primitive_metadata_test!(Decimal, 28);
+        serialize_primitive_metadata_test!(Uuid, 29);
+        serialize_primitive_metadata_test!(Char, 30);
+        serialize_primitive_metadata_test!(F16, 31);
+        serialize_primitive_metadata_test!(BF16, 32);
     }
 
     macro_rules! deserialize_primitive_metadata_test {
@@ -175,14 +261,27 @@ mod tests {
         deserialize_primitive_metadata_test!(F64);
         deserialize_primitive_metadata_test!(Bytes);
         deserialize_primitive_metadata_test!(String);
+        deserialize_primitive_metadata_test!(Timestamp);
+        deserialize_primitive_metadata_test!(TimestampTz);
+        deserialize_primitive_metadata_test!(Date);
+        deserialize_primitive_metadata_test!(Time);
+        deserialize_primitive_metadata_test!(Duration);
+        deserialize_primitive_metadata_test!(Decimal);
+        deserialize_primitive_metadata_test!(Uuid);
+        deserialize_primitive_metadata_test!(Char);
+        deserialize_primitive_metadata_test!(F16);
+        deserialize_primitive_metadata_test!(BF16);
     }
 
     #[test]
     fn metadata_try_from_u8_err_for_unknown_id() {
         let err =
             XbfPrimitiveMetadata::try_from(XbfPrimitiveMetadata::String as u8 + 1).unwrap_err();
-        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
-        assert_eq!(err.to_string(), "invalid primitive metadata");
+        assert!(matches!(err, XbfErrorKind::UnknownDiscriminant(17)));
+        assert_eq!(err.to_string(), "Unknown metadata discriminant 17");
+
+        let err = XbfPrimitiveMetadata::try_from(XbfPrimitiveMetadata::BF16 as u8 + 1).unwrap_err();
+        assert!(matches!(err, XbfErrorKind::UnknownDiscriminant(33)));
     }
 
     macro_rules! primitive_metadata_from_primitive_test {
@@ -206,16 +305,29 @@ mod tests {
         primitive_metadata_from_primitive_test!(U32, 1);
         primitive_metadata_from_primitive_test!(U64, 1);
         primitive_metadata_from_primitive_test!(U128, 1);
-        primitive_metadata_from_primitive_test!(U256, [1, 2, 3, 4]);
+        primitive_metadata_from_primitive_test!(U256, XbfU256::ONE);
         primitive_metadata_from_primitive_test!(I8, 1);
         primitive_metadata_from_primitive_test!(I16, 1);
         primitive_metadata_from_primitive_test!(I32, 1);
         primitive_metadata_from_primitive_test!(I64, 1);
         primitive_metadata_from_primitive_test!(I128, 1);
-        primitive_metadata_from_primitive_test!(I256, [1, 2, 3, 4]);
+        primitive_metadata_from_primitive_test!(I256, XbfI256::ONE);
         primitive_metadata_from_primitive_test!(F32, 1.0);
         primitive_metadata_from_primitive_test!(F64, 1.0);
         primitive_metadata_from_primitive_test!(Bytes, vec![1, 2, 3, 4]);
+        primitive_metadata_from_primitive_test!(Timestamp, XbfTimestamp::from_nanos(1));
+        primitive_metadata_from_primitive_test!(
+            TimestampTz,
+            XbfTimestampTz::new(XbfTimestamp::from_nanos(1), 3600)
+        );
+        primitive_metadata_from_primitive_test!(Date, XbfDate::from_days_since_epoch(1));
+        primitive_metadata_from_primitive_test!(Time, XbfTime::from_nanos_since_midnight(1));
+        primitive_metadata_from_primitive_test!(Duration, XbfDuration::from_nanos(1));
+        primitive_metadata_from_primitive_test!(Decimal, XbfDecimal::new(1, 1));
+        primitive_metadata_from_primitive_test!(Uuid, [0u8; 16]);
+        primitive_metadata_from_primitive_test!(Char, 'x');
+        primitive_metadata_from_primitive_test!(F16, 0x3c00u16);
+        primitive_metadata_from_primitive_test!(BF16, 0x3f80u16);
         primitive_metadata_from_primitive_test!(String, "Hello World".to_string());
     }
 
@@ -0,0 +1,408 @@
+//! Values of the temporal primitives, and their conversions to and from the standard library.
+
+#[cfg(feature = "chrono")]
+mod chrono;
+#[cfg(feature = "time")]
+mod time;
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+/// An instant in time, as the number of nanoseconds since the Unix epoch in UTC.
+///
+/// This covers roughly the years 1677 to 2262.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use xbf_rs::XbfTimestamp;
+///
+/// let timestamp = XbfTimestamp::from_nanos(1_500_000_000);
+/// let time = UNIX_EPOCH + Duration::from_millis(1500);
+///
+/// assert_eq!(XbfTimestamp::try_from(time), Ok(timestamp));
+/// assert_eq!(std::time::SystemTime::try_from(timestamp), Ok(time));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct XbfTimestamp {
+    nanos: i64,
+}
+
+impl XbfTimestamp {
+    /// Creates a timestamp the given number of nanoseconds after the Unix epoch.
+    pub fn from_nanos(nanos: i64) -> Self {
+        Self { nanos }
+    }
+
+    /// Returns the number of nanoseconds since the Unix epoch.
+    pub fn nanos(&self) -> i64 {
+        self.nanos
+    }
+}
+
+/// An instant in time along with the UTC offset it was observed at.
+///
+/// The instant itself is always in UTC, so two timestamps with different offsets refer to the same
+/// instant if their [`timestamp`](Self::timestamp)s are equal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct XbfTimestampTz {
+    timestamp: XbfTimestamp,
+    offset_seconds: i32,
+}
+
+impl XbfTimestampTz {
+    /// Creates a timestamp with an offset from UTC in seconds, positive east of Greenwich.
+    pub fn new(timestamp: XbfTimestamp, offset_seconds: i32) -> Self {
+        Self {
+            timestamp,
+            offset_seconds,
+        }
+    }
+
+    /// Returns the instant in UTC.
+    pub fn timestamp(&self) -> XbfTimestamp {
+        self.timestamp
+    }
+
+    /// Returns the offset from UTC in seconds.
+    pub fn offset_seconds(&self) -> i32 {
+        self.offset_seconds
+    }
+}
+
+/// A calendar date in the proleptic Gregorian calendar, as the number of days since 1970-01-01.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::XbfDate;
+///
+/// let date: XbfDate = "2023-08-02".parse().unwrap();
+///
+/// assert_eq!(date, XbfDate::from_ymd(2023, 8, 2).unwrap());
+/// assert_eq!(date.days_since_epoch(), 19_571);
+/// assert_eq!(date.to_string(), "2023-08-02");
+/// assert!(XbfDate::from_ymd(2023, 2, 29).is_none());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct XbfDate {
+    days: i32,
+}
+
+impl XbfDate {
+    /// Creates a date the given number of days after 1970-01-01.
+    pub fn from_days_since_epoch(days: i32) -> Self {
+        Self { days }
+    }
+
+    /// Creates a date from a year, month and day, or returns `None` if there is no such date.
+    pub fn from_ymd(year: i32, month: u32, day: u32) -> Option<Self> {
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+        // the algorithm used by Howard Hinnant's `days_from_civil`, with years starting in March
+        let year = i64::from(year) - i64::from(month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let day_of_year = (153 * ((i64::from(month) + 9) % 12) + 2) / 5 + i64::from(day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        let days = era * 146_097 + day_of_era - 719_468;
+        days.try_into().ok().map(Self::from_days_since_epoch)
+    }
+
+    /// Returns the number of days since 1970-01-01.
+    pub fn days_since_epoch(&self) -> i32 {
+        self.days
+    }
+
+    /// Returns the year, month and day of this date.
+    pub fn to_ymd(&self) -> (i32, u32, u32) {
+        // the inverse of `from_ymd`, Howard Hinnant's `civil_from_days`
+        let days = i64::from(self.days) + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        (year as i32, month as u32, day as u32)
+    }
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Formats the date as `YYYY-MM-DD`.
+impl Display for XbfDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (year, month, day) = self.to_ymd();
+        if year < 0 {
+            write!(f, "-")?;
+        }
+        write!(f, "{:04}-{month:02}-{day:02}", year.unsigned_abs())
+    }
+}
+
+/// Parses a date in the form `YYYY-MM-DD`, with a leading `-` for years before year 0.
+impl FromStr for XbfDate {
+    type Err = TemporalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, unsigned) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        // `u32::from_str` also accepts a leading `+`, so check for digits first
+        let mut parts = unsigned.splitn(3, '-').map(|part| {
+            part.bytes()
+                .all(|b| b.is_ascii_digit())
+                .then(|| part.parse::<u32>().ok())
+                .flatten()
+        });
+        let (Some(Some(year)), Some(Some(month)), Some(Some(day))) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(TemporalError);
+        };
+        let year = i32::try_from(year).map_err(|_| TemporalError)?;
+        let year = if negative { -year } else { year };
+        Self::from_ymd(year, month, day).ok_or(TemporalError)
+    }
+}
+
+/// A time of day, as the number of nanoseconds since midnight.
+///
+/// Values of a day or more are only meaningful as leap seconds, so the value is less than 86,401
+/// seconds. Leap seconds fail to convert to types which do not support them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct XbfTime {
+    nanos: u64,
+}
+
+impl XbfTime {
+    /// Creates a time of day the given number of nanoseconds after midnight.
+    pub fn from_nanos_since_midnight(nanos: u64) -> Self {
+        Self { nanos }
+    }
+
+    /// Creates a time of day from an hour, minute, second and nanosecond, or returns `None` if
+    /// any of them are out of range.
+    ///
+    /// A second of 60 is a leap second, and is only allowed in the last minute of the day.
+    pub fn from_hms_nano(hour: u32, minute: u32, second: u32, nano: u32) -> Option<Self> {
+        let leap_second = hour == 23 && minute == 59 && second == 60;
+        if hour >= 24
+            || minute >= 60
+            || (second >= 60 && !leap_second)
+            || u64::from(nano) >= NANOS_PER_SEC
+        {
+            return None;
+        }
+        let seconds = u64::from(hour) * 3600 + u64::from(minute) * 60 + u64::from(second);
+        Some(Self::from_nanos_since_midnight(
+            seconds * NANOS_PER_SEC + u64::from(nano),
+        ))
+    }
+
+    /// Returns the number of nanoseconds since midnight.
+    pub fn nanos_since_midnight(&self) -> u64 {
+        self.nanos
+    }
+
+    /// Returns whether the time is before the end of a day ending in a leap second.
+    pub(crate) fn is_in_range(&self) -> bool {
+        self.nanos < 86_401 * NANOS_PER_SEC
+    }
+}
+
+/// A signed span of time, as a number of nanoseconds.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::time::Duration;
+/// use xbf_rs::XbfDuration;
+///
+/// let duration = XbfDuration::try_from(Duration::from_secs(90)).unwrap();
+/// assert_eq!(duration.nanos(), 90_000_000_000);
+///
+/// assert!(Duration::try_from(XbfDuration::from_nanos(-1)).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct XbfDuration {
+    nanos: i64,
+}
+
+impl XbfDuration {
+    /// Creates a duration of the given number of nanoseconds.
+    pub fn from_nanos(nanos: i64) -> Self {
+        Self { nanos }
+    }
+
+    /// Returns the number of nanoseconds in this duration.
+    pub fn nanos(&self) -> i64 {
+        self.nanos
+    }
+}
+
+impl TryFrom<SystemTime> for XbfTimestamp {
+    type Error = TemporalError;
+
+    fn try_from(value: SystemTime) -> Result<Self, Self::Error> {
+        let nanos = match value.duration_since(UNIX_EPOCH) {
+            Ok(after) => i128::try_from(after.as_nanos()),
+            Err(before) => i128::try_from(before.duration().as_nanos()).map(|x| -x),
+        };
+        nanos
+            .ok()
+            .and_then(|x| i64::try_from(x).ok())
+            .map(Self::from_nanos)
+            .ok_or(TemporalError)
+    }
+}
+
+impl TryFrom<XbfTimestamp> for SystemTime {
+    type Error = TemporalError;
+
+    fn try_from(value: XbfTimestamp) -> Result<Self, Self::Error> {
+        let offset = Duration::from_nanos(value.nanos.unsigned_abs());
+        if value.nanos >= 0 {
+            UNIX_EPOCH.checked_add(offset)
+        } else {
+            UNIX_EPOCH.checked_sub(offset)
+        }
+        .ok_or(TemporalError)
+    }
+}
+
+impl TryFrom<Duration> for XbfDuration {
+    type Error = TemporalError;
+
+    fn try_from(value: Duration) -> Result<Self, Self::Error> {
+        i64::try_from(value.as_nanos())
+            .map(Self::from_nanos)
+            .map_err(|_| TemporalError)
+    }
+}
+
+impl TryFrom<XbfDuration> for Duration {
+    type Error = TemporalError;
+
+    fn try_from(value: XbfDuration) -> Result<Self, Self::Error> {
+        u64::try_from(value.nanos)
+            .map(Duration::from_nanos)
+            .map_err(|_| TemporalError)
+    }
+}
+
+/// Error type for temporal values which are malformed, or out of the range of the type they are
+/// being converted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TemporalError;
+
+impl Display for TemporalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Temporal value is malformed or out of range")
+    }
+}
+
+impl Error for TemporalError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn date_ymd_round_trip_works() {
+        for (days, ymd) in [
+            (0, (1970, 1, 1)),
+            (-1, (1969, 12, 31)),
+            (59, (1970, 3, 1)),
+            (11_016, (2000, 2, 29)),
+            (-719_528, (0, 1, 1)),
+            (i32::MAX, (5_881_580, 7, 11)),
+            (i32::MIN, (-5_877_641, 6, 23)),
+        ] {
+            let date = XbfDate::from_days_since_epoch(days);
+            assert_eq!(date.to_ymd(), ymd);
+            assert_eq!(XbfDate::from_ymd(ymd.0, ymd.1, ymd.2), Some(date));
+        }
+        assert!(XbfDate::from_ymd(1900, 2, 29).is_none());
+        assert!(XbfDate::from_ymd(2023, 13, 1).is_none());
+        assert!(XbfDate::from_ymd(5_881_580, 7, 12).is_none());
+    }
+
+    #[test]
+    fn date_parse_works() {
+        assert_eq!(
+            "-0001-12-31".parse::<XbfDate>().unwrap().to_string(),
+            "-0001-12-31"
+        );
+        assert_eq!(
+            "0033-01-05".parse::<XbfDate>().unwrap().to_ymd(),
+            (33, 1, 5)
+        );
+        assert!("2023-02-30".parse::<XbfDate>().is_err());
+        assert!("2023-02".parse::<XbfDate>().is_err());
+        assert!("".parse::<XbfDate>().is_err());
+        assert!("2023-+1-01".parse::<XbfDate>().is_err());
+    }
+
+    #[test]
+    fn std_conversions_work() {
+        let before_epoch = UNIX_EPOCH - Duration::from_nanos(5);
+        assert_eq!(
+            XbfTimestamp::try_from(before_epoch),
+            Ok(XbfTimestamp::from_nanos(-5))
+        );
+        assert_eq!(
+            SystemTime::try_from(XbfTimestamp::from_nanos(-5)),
+            Ok(before_epoch)
+        );
+        assert!(XbfTimestamp::try_from(UNIX_EPOCH + Duration::from_secs(1 << 40)).is_err());
+
+        assert!(XbfDuration::try_from(Duration::MAX).is_err());
+        assert_eq!(
+            Duration::try_from(XbfDuration::from_nanos(1)),
+            Ok(Duration::from_nanos(1))
+        );
+
+        assert_eq!(
+            XbfTime::from_hms_nano(23, 59, 59, 999_999_999),
+            Some(XbfTime::from_nanos_since_midnight(
+                86_400 * NANOS_PER_SEC - 1
+            ))
+        );
+        assert!(XbfTime::from_hms_nano(24, 0, 0, 0).is_none());
+
+        let leap_second = XbfTime::from_hms_nano(23, 59, 60, 999_999_999).unwrap();
+        assert_eq!(
+            leap_second.nanos_since_midnight(),
+            86_401 * NANOS_PER_SEC - 1
+        );
+        assert!(leap_second.is_in_range());
+        assert!(!XbfTime::from_nanos_since_midnight(86_401 * NANOS_PER_SEC).is_in_range());
+        assert!(XbfTime::from_hms_nano(12, 0, 60, 0).is_none());
+        assert!(XbfTime::from_hms_nano(23, 59, 61, 0).is_none());
+    }
+}
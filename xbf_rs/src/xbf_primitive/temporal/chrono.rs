@@ -0,0 +1,151 @@
+//! Conversions between the temporal primitives and [`chrono`](::chrono), enabled with the
+//! `chrono` feature.
+
+use super::{TemporalError, XbfDate, XbfDuration, XbfTime, XbfTimestamp, XbfTimestampTz};
+use ::chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveTime, TimeDelta, Timelike, Utc};
+
+/// The number of days from 0001-01-01 to 1970-01-01, which is where chrono counts days from.
+const EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+impl TryFrom<DateTime<Utc>> for XbfTimestamp {
+    type Error = TemporalError;
+
+    fn try_from(value: DateTime<Utc>) -> Result<Self, Self::Error> {
+        value
+            .timestamp_nanos_opt()
+            .map(Self::from_nanos)
+            .ok_or(TemporalError)
+    }
+}
+
+impl From<XbfTimestamp> for DateTime<Utc> {
+    fn from(value: XbfTimestamp) -> Self {
+        DateTime::from_timestamp_nanos(value.nanos())
+    }
+}
+
+impl TryFrom<DateTime<FixedOffset>> for XbfTimestampTz {
+    type Error = TemporalError;
+
+    fn try_from(value: DateTime<FixedOffset>) -> Result<Self, Self::Error> {
+        let timestamp = XbfTimestamp::try_from(value.to_utc())?;
+        Ok(Self::new(timestamp, value.offset().local_minus_utc()))
+    }
+}
+
+impl TryFrom<XbfTimestampTz> for DateTime<FixedOffset> {
+    type Error = TemporalError;
+
+    fn try_from(value: XbfTimestampTz) -> Result<Self, Self::Error> {
+        let offset = FixedOffset::east_opt(value.offset_seconds()).ok_or(TemporalError)?;
+        Ok(DateTime::<Utc>::from(value.timestamp()).with_timezone(&offset))
+    }
+}
+
+impl From<NaiveDate> for XbfDate {
+    fn from(value: NaiveDate) -> Self {
+        Self::from_days_since_epoch(value.num_days_from_ce() - EPOCH_DAYS_FROM_CE)
+    }
+}
+
+impl TryFrom<XbfDate> for NaiveDate {
+    type Error = TemporalError;
+
+    fn try_from(value: XbfDate) -> Result<Self, Self::Error> {
+        value
+            .days_since_epoch()
+            .checked_add(EPOCH_DAYS_FROM_CE)
+            .and_then(NaiveDate::from_num_days_from_ce_opt)
+            .ok_or(TemporalError)
+    }
+}
+
+/// Leap seconds, which chrono represents with a nanosecond of a second or more, are kept as a
+/// time past the end of the second before them.
+impl From<NaiveTime> for XbfTime {
+    fn from(value: NaiveTime) -> Self {
+        let seconds = u64::from(value.num_seconds_from_midnight());
+        Self::from_nanos_since_midnight(seconds * 1_000_000_000 + u64::from(value.nanosecond()))
+    }
+}
+
+impl TryFrom<XbfTime> for NaiveTime {
+    type Error = TemporalError;
+
+    fn try_from(value: XbfTime) -> Result<Self, Self::Error> {
+        let nanos = value.nanos_since_midnight();
+        let mut seconds = nanos / 1_000_000_000;
+        let mut nano = nanos % 1_000_000_000;
+        // a leap second is the second before it with a nanosecond of a second or more
+        if seconds == 86_400 {
+            seconds -= 1;
+            nano += 1_000_000_000;
+        }
+        u32::try_from(seconds)
+            .ok()
+            .and_then(|seconds| NaiveTime::from_num_seconds_from_midnight_opt(seconds, nano as u32))
+            .ok_or(TemporalError)
+    }
+}
+
+impl TryFrom<TimeDelta> for XbfDuration {
+    type Error = TemporalError;
+
+    fn try_from(value: TimeDelta) -> Result<Self, Self::Error> {
+        value
+            .num_nanoseconds()
+            .map(Self::from_nanos)
+            .ok_or(TemporalError)
+    }
+}
+
+impl From<XbfDuration> for TimeDelta {
+    fn from(value: XbfDuration) -> Self {
+        TimeDelta::nanoseconds(value.nanos())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions_work() {
+        let date = NaiveDate::from_ymd_opt(2023, 8, 2).unwrap();
+        let xbf_date = XbfDate::from(date);
+        assert_eq!(xbf_date, XbfDate::from_ymd(2023, 8, 2).unwrap());
+        assert_eq!(NaiveDate::try_from(xbf_date), Ok(date));
+        assert!(NaiveDate::try_from(XbfDate::from_days_since_epoch(i32::MAX)).is_err());
+
+        let time = NaiveTime::from_hms_nano_opt(23, 59, 59, 1_500_000_000).unwrap();
+        let xbf_time = XbfTime::from(time);
+        assert_eq!(xbf_time.nanos_since_midnight(), 86_400_500_000_000);
+        assert_eq!(NaiveTime::try_from(xbf_time), Ok(time));
+        assert!(NaiveTime::try_from(XbfTime::from_nanos_since_midnight(u64::MAX)).is_err());
+
+        // leap seconds cannot be represented by a timestamp
+        let date_time = date
+            .and_hms_milli_opt(12, 30, 0, 500)
+            .unwrap()
+            .and_local_timezone(FixedOffset::east_opt(3600).unwrap())
+            .unwrap();
+        let xbf_date_time = XbfTimestampTz::try_from(date_time).unwrap();
+        assert_eq!(xbf_date_time.offset_seconds(), 3600);
+        assert_eq!(
+            xbf_date_time.timestamp(),
+            XbfTimestamp::try_from(date_time.to_utc()).unwrap()
+        );
+        assert_eq!(DateTime::try_from(xbf_date_time), Ok(date_time));
+
+        let delta = TimeDelta::milliseconds(-1500);
+        assert_eq!(
+            XbfDuration::try_from(delta),
+            Ok(XbfDuration::from_nanos(-1_500_000_000))
+        );
+        assert_eq!(
+            TimeDelta::from(XbfDuration::from_nanos(-1_500_000_000)),
+            delta
+        );
+        assert!(XbfDuration::try_from(TimeDelta::MAX).is_err());
+    }
+}
@@ -0,0 +1,144 @@
+//! Conversions between the temporal primitives and [`time`](::time), enabled with the `time`
+//! feature.
+
+use super::{TemporalError, XbfDate, XbfDuration, XbfTime, XbfTimestamp, XbfTimestampTz};
+use ::time::{Date, Duration, OffsetDateTime, Time, UtcOffset};
+
+/// The Julian day number of 1970-01-01.
+const EPOCH_JULIAN_DAY: i32 = 2_440_588;
+
+impl TryFrom<OffsetDateTime> for XbfTimestamp {
+    type Error = TemporalError;
+
+    fn try_from(value: OffsetDateTime) -> Result<Self, Self::Error> {
+        i64::try_from(value.unix_timestamp_nanos())
+            .map(Self::from_nanos)
+            .map_err(|_| TemporalError)
+    }
+}
+
+/// The result is in UTC.
+impl From<XbfTimestamp> for OffsetDateTime {
+    fn from(value: XbfTimestamp) -> Self {
+        OffsetDateTime::from_unix_timestamp_nanos(value.nanos().into())
+            .expect("every timestamp is within the range of OffsetDateTime")
+    }
+}
+
+impl TryFrom<OffsetDateTime> for XbfTimestampTz {
+    type Error = TemporalError;
+
+    fn try_from(value: OffsetDateTime) -> Result<Self, Self::Error> {
+        let timestamp = XbfTimestamp::try_from(value)?;
+        Ok(Self::new(timestamp, value.offset().whole_seconds()))
+    }
+}
+
+impl TryFrom<XbfTimestampTz> for OffsetDateTime {
+    type Error = TemporalError;
+
+    fn try_from(value: XbfTimestampTz) -> Result<Self, Self::Error> {
+        let offset =
+            UtcOffset::from_whole_seconds(value.offset_seconds()).map_err(|_| TemporalError)?;
+        Ok(OffsetDateTime::from(value.timestamp()).to_offset(offset))
+    }
+}
+
+impl From<Date> for XbfDate {
+    fn from(value: Date) -> Self {
+        Self::from_days_since_epoch(value.to_julian_day() - EPOCH_JULIAN_DAY)
+    }
+}
+
+impl TryFrom<XbfDate> for Date {
+    type Error = TemporalError;
+
+    fn try_from(value: XbfDate) -> Result<Self, Self::Error> {
+        value
+            .days_since_epoch()
+            .checked_add(EPOCH_JULIAN_DAY)
+            .and_then(|day| Date::from_julian_day(day).ok())
+            .ok_or(TemporalError)
+    }
+}
+
+impl From<Time> for XbfTime {
+    fn from(value: Time) -> Self {
+        let (hour, minute, second, nano) = value.as_hms_nano();
+        Self::from_hms_nano(hour.into(), minute.into(), second.into(), nano)
+            .expect("every time is within the range of XbfTime")
+    }
+}
+
+impl TryFrom<XbfTime> for Time {
+    type Error = TemporalError;
+
+    fn try_from(value: XbfTime) -> Result<Self, Self::Error> {
+        let nanos = value.nanos_since_midnight();
+        let seconds = nanos / 1_000_000_000;
+        let hour = u8::try_from(seconds / 3600).map_err(|_| TemporalError)?;
+        let minute = (seconds / 60 % 60) as u8;
+        let second = (seconds % 60) as u8;
+        Time::from_hms_nano(hour, minute, second, (nanos % 1_000_000_000) as u32)
+            .map_err(|_| TemporalError)
+    }
+}
+
+impl TryFrom<Duration> for XbfDuration {
+    type Error = TemporalError;
+
+    fn try_from(value: Duration) -> Result<Self, Self::Error> {
+        i64::try_from(value.whole_nanoseconds())
+            .map(Self::from_nanos)
+            .map_err(|_| TemporalError)
+    }
+}
+
+impl From<XbfDuration> for Duration {
+    fn from(value: XbfDuration) -> Self {
+        Duration::nanoseconds(value.nanos())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::time::Month;
+
+    #[test]
+    fn conversions_work() {
+        let date = Date::from_calendar_date(2023, Month::August, 2).unwrap();
+        let xbf_date = XbfDate::from(date);
+        assert_eq!(xbf_date, XbfDate::from_ymd(2023, 8, 2).unwrap());
+        assert_eq!(Date::try_from(xbf_date), Ok(date));
+        assert!(Date::try_from(XbfDate::from_days_since_epoch(i32::MIN)).is_err());
+
+        let time = Time::from_hms_nano(23, 59, 59, 999_999_999).unwrap();
+        let xbf_time = XbfTime::from(time);
+        assert_eq!(xbf_time.nanos_since_midnight(), 86_399_999_999_999);
+        assert_eq!(Time::try_from(xbf_time), Ok(time));
+        assert!(Time::try_from(XbfTime::from_nanos_since_midnight(86_400_000_000_000)).is_err());
+
+        let date_time = date
+            .with_time(time)
+            .assume_offset(UtcOffset::from_hms(-5, 0, 0).unwrap());
+        let xbf_date_time = XbfTimestampTz::try_from(date_time).unwrap();
+        assert_eq!(xbf_date_time.offset_seconds(), -5 * 3600);
+        assert_eq!(OffsetDateTime::try_from(xbf_date_time), Ok(date_time));
+        assert!(
+            XbfTimestamp::try_from(date.replace_year(9999).unwrap().midnight().assume_utc())
+                .is_err()
+        );
+
+        let duration = Duration::milliseconds(-1500);
+        assert_eq!(
+            XbfDuration::try_from(duration),
+            Ok(XbfDuration::from_nanos(-1_500_000_000))
+        );
+        assert_eq!(
+            Duration::from(XbfDuration::from_nanos(-1_500_000_000)),
+            duration
+        );
+        assert!(XbfDuration::try_from(Duration::MAX).is_err());
+    }
+}
@@ -3,7 +3,7 @@ mod struct_metadata;
 
 pub use struct_metadata::*;
 
-use crate::{XbfMetadata, XbfType, XbfTypeUpcast};
+use crate::{reader::XbfReader, XbfError, XbfMetadata, XbfProjection, XbfType, XbfTypeUpcast};
 use std::{
     error::Error,
     fmt::Display,
@@ -17,7 +17,7 @@ use std::{
 #[derive(Debug, Clone, PartialEq)]
 pub struct XbfStruct {
     pub(crate) metadata: XbfStructMetadata,
-    fields: Box<[XbfType]>,
+    pub(crate) fields: Box<[XbfType]>,
 }
 
 impl XbfStruct {
@@ -218,6 +218,11 @@ impl XbfStruct {
     /// from the reader with [`deserialize_base_metadata`](crate::XbfMetadata::deserialize_base_metadata)
     /// or having it in some other manner.
     ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails or the data is invalid, with a path locating the
+    /// field which failed.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -249,12 +254,77 @@ impl XbfStruct {
     pub fn deserialize_struct_type(
         metadata: &XbfStructMetadata,
         reader: &mut impl Read,
-    ) -> io::Result<XbfStruct> {
-        let mut struct_fields = vec![];
-        for (_, field_type) in metadata.inner.fields.iter() {
-            struct_fields.push(XbfType::deserialize_base_type(field_type, reader)?);
-        }
-        Ok(Self::new_unchecked(metadata.clone(), struct_fields))
+    ) -> Result<XbfStruct, XbfError> {
+        Self::read_struct_type(metadata, &mut XbfReader::new(reader))
+            .map_err(|e| e.in_root(&metadata.into()))
+    }
+
+    pub(crate) fn read_struct_type(
+        metadata: &XbfStructMetadata,
+        reader: &mut XbfReader<impl Read>,
+    ) -> Result<XbfStruct, XbfError> {
+        reader.nested(|reader| {
+            let mut struct_fields = Vec::with_capacity(metadata.inner.fields.len());
+            for (field_name, field_type) in metadata.inner.fields.iter() {
+                struct_fields.push(
+                    XbfType::read_base_type(field_type, reader)
+                        .map_err(|e| e.in_field(field_name))?,
+                );
+            }
+            Ok(Self::new_unchecked(metadata.clone(), struct_fields))
+        })
+    }
+
+    /// Deserialize only the fields of a struct selected by a projection, skipping over the rest.
+    ///
+    /// The deserialized struct has metadata containing only the selected fields. See
+    /// [`XbfProjection`] for how fields are selected.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails or the data is invalid, or with
+    /// [`XbfErrorKind::UnknownField`](crate::XbfErrorKind::UnknownField) before reading anything
+    /// if the projection selects a field which does not exist.
+    pub fn deserialize_struct_type_projected(
+        metadata: &XbfStructMetadata,
+        projection: &XbfProjection,
+        reader: &mut impl Read,
+    ) -> Result<XbfStruct, XbfError> {
+        let reader = &mut XbfReader::new(reader);
+        projection
+            .project_struct(metadata, reader.position())
+            .and_then(|projected| {
+                Self::read_struct_type_projected(metadata, &projected, projection, reader)
+            })
+            .map_err(|e| e.in_root(&metadata.into()))
+    }
+
+    pub(crate) fn read_struct_type_projected(
+        metadata: &XbfStructMetadata,
+        projected: &XbfStructMetadata,
+        projection: &XbfProjection,
+        reader: &mut XbfReader<impl Read>,
+    ) -> Result<XbfStruct, XbfError> {
+        reader.nested(|reader| {
+            let mut struct_fields = Vec::with_capacity(projected.inner.fields.len());
+            for (field_name, field_type) in metadata.inner.fields.iter() {
+                let result = match projection.field(field_name) {
+                    Some(field_projection) => {
+                        let projected_type = &projected.inner.fields[field_name];
+                        XbfType::read_projected(
+                            field_type,
+                            projected_type,
+                            field_projection,
+                            reader,
+                        )
+                        .map(|x| struct_fields.push(x))
+                    }
+                    None => reader.skip(field_type),
+                };
+                result.map_err(|e| e.in_field(field_name))?;
+            }
+            Ok(Self::new_unchecked(projected.clone(), struct_fields))
+        })
     }
 
     /// Returns the metadata of the struct.
@@ -592,6 +662,39 @@ mod tests {
         assert_eq!(my_struct, deserialized);
     }
 
+    #[test]
+    fn deserialize_error_path_works() {
+        let rider_metadata = XbfStructMetadata::new(
+            "DragonRider",
+            indexmap! {
+                "age" => XbfPrimitiveMetadata::U16.into(),
+                "name" => XbfPrimitiveMetadata::String.into(),
+            },
+        );
+        let metadata = XbfStructMetadata::new(
+            "Dragon",
+            indexmap! {"riders" => XbfVecMetadata::new(rider_metadata).into()},
+        );
+
+        let mut data = vec![];
+        data.extend_from_slice(&3u64.to_le_bytes());
+        for _ in 0..2 {
+            data.extend_from_slice(&16u16.to_le_bytes());
+            data.extend_from_slice(&6u64.to_le_bytes());
+            data.extend_from_slice(b"Eragon");
+        }
+        data.extend_from_slice(&16u16.to_le_bytes());
+        data.extend_from_slice(&6u64.to_le_bytes());
+        data.extend_from_slice(b"Era");
+
+        let err = XbfStruct::deserialize_struct_type(&metadata, &mut Cursor::new(data))
+            .expect_err("should have failed deserialization");
+
+        assert!(matches!(err.kind(), crate::XbfErrorKind::UnexpectedEof));
+        assert_eq!(err.path(), "Dragon.riders[2].name");
+        assert_eq!(err.offset(), 8 + 2 * 16 + 2 + 8);
+    }
+
     #[test]
     fn set_works() {
         let mut s = XbfStruct::new(
@@ -1,9 +1,10 @@
 use crate::{
     base_metadata::XbfMetadataUpcast,
+    reader::XbfReader,
     util::{read_string, write_string},
-    RcType, XbfMetadata, XbfStruct, VEC_METADATA_DISCRIMINANT,
+    RcType, XbfError, XbfErrorKind, XbfMetadata, XbfStruct, VEC_METADATA_DISCRIMINANT,
 };
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use byteorder::{LittleEndian, WriteBytesExt};
 use indexmap::IndexMap;
 use std::io::{self, Read, Write};
 
@@ -118,6 +119,59 @@ impl XbfStructMetadata {
         self.inner.fields.get(field)
     }
 
+    /// Returns an iterator over the names and metadata of the fields, in the order they are
+    /// serialized.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use xbf_rs::XbfStructMetadata;
+    /// use xbf_rs::XbfPrimitiveMetadata;
+    ///
+    /// use indexmap::indexmap;
+    ///
+    /// let metadata = XbfStructMetadata::new(
+    ///   "test_struct",
+    ///   indexmap! {
+    ///     "a" => XbfPrimitiveMetadata::I32.into(),
+    ///     "b" => XbfPrimitiveMetadata::U64.into(),
+    ///   },
+    /// );
+    ///
+    /// let names = metadata.fields().map(|(name, _)| name).collect::<Vec<_>>();
+    /// assert_eq!(names, ["a", "b"]);
+    /// ```
+    pub fn fields(&self) -> impl ExactSizeIterator<Item = (&str, &XbfMetadata)> {
+        self.inner
+            .fields
+            .iter()
+            .map(|(name, type_)| (&**name, type_))
+    }
+
+    /// Returns the index and metadata of the field with the given name if it exists, otherwise
+    /// returns `None`.
+    pub(crate) fn get_field_index(&self, field: &str) -> Option<(usize, &XbfMetadata)> {
+        self.inner
+            .fields
+            .get_full(field)
+            .map(|(index, _, type_)| (index, type_))
+    }
+
+    /// Returns the name and metadata of the field at `index` if it exists, otherwise returns
+    /// `None`.
+    #[cfg(feature = "async")]
+    pub(crate) fn get_field(&self, index: u16) -> Option<(&str, &XbfMetadata)> {
+        self.inner
+            .fields
+            .get_index(index.into())
+            .map(|(name, type_)| (&**name, type_))
+    }
+
+    /// Returns an iterator over the metadata of the fields, in the order they are serialized.
+    #[cfg(feature = "async")]
+    pub(crate) fn field_types(&self) -> indexmap::map::Values<'_, Box<str>, XbfMetadata> {
+        self.inner.fields.values()
+    }
+
     /// Serialize struct metadata as defined by the XBF specification.
     ///
     /// # Example
@@ -183,6 +237,13 @@ impl XbfStructMetadata {
     /// do not know what sort of metadata you are receiving, use
     /// [`deserialize_base_metadata`](crate::XbfMetadata::deserialize_base_metadata).
     ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails or the metadata is invalid. The same field
+    /// name appearing more than once is only rejected in
+    /// [strict](crate::DeserializeOptions::strict) mode, and otherwise the last type given for it
+    /// replaces the earlier one.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -217,17 +278,34 @@ impl XbfStructMetadata {
     ///     field1_name => XbfPrimitiveMetadata::I32.into(),
     ///     field2_name => XbfPrimitiveMetadata::U64.into(),
     /// }));
-    pub fn deserialize_struct_metadata(reader: &mut impl Read) -> io::Result<XbfStructMetadata> {
-        let name = read_string(reader)?;
-        let len = reader.read_u16::<LittleEndian>()?;
-        let mut fields = IndexMap::with_capacity(len as usize);
-        for _ in 0..len {
-            fields.insert(
-                read_string(reader)?,
-                XbfMetadata::deserialize_base_metadata(reader)?,
-            );
-        }
-        Ok(XbfStructMetadata::new(name, fields))
+    pub fn deserialize_struct_metadata(
+        reader: &mut impl Read,
+    ) -> Result<XbfStructMetadata, XbfError> {
+        Self::read_struct_metadata(&mut XbfReader::new(reader))
+    }
+
+    pub(crate) fn read_struct_metadata(
+        reader: &mut XbfReader<impl Read>,
+    ) -> Result<XbfStructMetadata, XbfError> {
+        reader.nested(|reader| {
+            let name = reader.read_struct_name()?;
+            let len = reader.read_struct_fields_len()?;
+            let mut fields = IndexMap::with_capacity(len as usize);
+            for _ in 0..len {
+                let offset = reader.position();
+                let field_name = read_string(reader)?;
+                if reader.options().strict && fields.contains_key(field_name.as_str()) {
+                    return Err(XbfError::new(
+                        XbfErrorKind::DuplicateField(field_name.into()),
+                        offset,
+                    ));
+                }
+                let field_type =
+                    XbfMetadata::read_base_metadata(reader).map_err(|e| e.in_field(&field_name))?;
+                fields.insert(field_name, field_type);
+            }
+            Ok(XbfStructMetadata::new(name, fields))
+        })
     }
 }
 
@@ -252,7 +330,7 @@ mod tests {
     use indexmap::indexmap;
 
     use super::*;
-    use crate::{xbf_primitive::XbfPrimitiveMetadata, XbfVecMetadata};
+    use crate::{xbf_primitive::XbfPrimitiveMetadata, DeserializeOptions, XbfVecMetadata};
     use std::io::Cursor;
 
     #[test]
@@ -325,6 +403,34 @@ mod tests {
         assert_eq!(XbfMetadata::Struct(metadata), deserialized);
     }
 
+    #[test]
+    fn metadata_deserialize_duplicate_field_works() {
+        let mut data = vec![];
+        write_string("test", &mut data).unwrap();
+        data.extend_from_slice(&2u16.to_le_bytes());
+        for field_type in [XbfPrimitiveMetadata::I32, XbfPrimitiveMetadata::U64] {
+            write_string("a", &mut data).unwrap();
+            field_type.serialize_primitive_metadata(&mut data).unwrap();
+        }
+
+        let metadata =
+            XbfStructMetadata::deserialize_struct_metadata(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(
+            metadata,
+            XbfStructMetadata::new("test", indexmap! {"a" => XbfPrimitiveMetadata::U64.into()})
+        );
+
+        let strict = DeserializeOptions::new().strict(true);
+        let err = XbfStructMetadata::read_struct_metadata(&mut XbfReader::with_options(
+            data.as_slice(),
+            strict,
+        ))
+        .expect_err("should have failed deserialization");
+
+        assert!(matches!(err.kind(), XbfErrorKind::DuplicateField(x) if &**x == "a"));
+        assert_eq!(err.offset(), 8 + 4 + 2 + 8 + 1 + 1);
+    }
+
     #[test]
     fn upcast_works() {
         let struct_metadata = XbfStructMetadata::new(
@@ -0,0 +1,408 @@
+//! A multi-dimensional tensor as defined by the XBF specification.
+
+#[cfg(feature = "ndarray")]
+mod ndarray;
+mod tensor_metadata;
+
+pub use tensor_metadata::*;
+
+use crate::{
+    reader::XbfReader, XbfError, XbfPrimitive, XbfPrimitiveBuffer, XbfPrimitiveMetadata, XbfType,
+    XbfTypeUpcast,
+};
+use byteorder::{LittleEndian, WriteBytesExt};
+use std::{
+    error::Error,
+    fmt::Display,
+    io::{self, Read, Write},
+};
+
+/// A dense, multi-dimensional tensor of primitives as defined by the XBF specification.
+///
+/// The elements of a tensor are stored contiguously in row-major order, as an
+/// [`XbfPrimitiveBuffer`] of their native type, and are read and written in bulk. With the
+/// `ndarray` feature enabled, tensors can be converted to and from [`ndarray::ArrayD`].
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::{XbfPrimitive, XbfPrimitiveMetadata, XbfTensor, XbfTensorMetadata};
+///
+/// let metadata = XbfTensorMetadata::new(XbfPrimitiveMetadata::F32, 2).unwrap();
+/// let matrix = XbfTensor::new(metadata, [2, 3], vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+///
+/// assert_eq!(matrix.get(&[1, 0]), Some(XbfPrimitive::F32(4.0)));
+///
+/// let mut writer = vec![];
+/// matrix.serialize_tensor_type(&mut writer).unwrap();
+/// // the shape, followed by the elements
+/// assert_eq!(writer.len(), 2 * 8 + 6 * 4);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct XbfTensor {
+    pub(crate) metadata: XbfTensorMetadata,
+    shape: Vec<u64>,
+    elements: XbfPrimitiveBuffer,
+}
+
+impl XbfTensor {
+    /// Tries to create a new tensor with the given shape based on the supplied metadata.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`TensorError`] if the shape does not match the rank or shape in the metadata,
+    /// if the elements are not of the element type in the metadata, or if there are not exactly
+    /// as many elements as the shape specifies.
+    pub fn new(
+        metadata: XbfTensorMetadata,
+        shape: impl Into<Vec<u64>>,
+        elements: impl Into<XbfPrimitiveBuffer>,
+    ) -> Result<Self, TensorError> {
+        let tensor = Self::new_unchecked(metadata, shape, elements);
+
+        match &tensor.metadata.shape {
+            Some(expected_shape) if *expected_shape != tensor.shape => {
+                return Err(TensorError::ShapeMismatch {
+                    expected_shape: expected_shape.clone(),
+                    actual_shape: tensor.shape,
+                });
+            }
+            _ if tensor.shape.len() != usize::from(tensor.metadata.rank) => {
+                return Err(TensorError::RankMismatch {
+                    expected_rank: tensor.metadata.rank,
+                    actual_rank: tensor.shape.len(),
+                });
+            }
+            _ => {}
+        }
+        let actual_type = tensor.elements.element_type();
+        if actual_type != tensor.metadata.element_type {
+            return Err(TensorError::ElementMismatch {
+                expected_type: tensor.metadata.element_type,
+                actual_type,
+            });
+        }
+        let expected_len = element_count(&tensor.shape);
+        let actual_len = tensor.elements.len() as u64;
+        if actual_len != expected_len {
+            return Err(TensorError::LengthMismatch {
+                expected_len,
+                actual_len,
+            });
+        }
+        Ok(tensor)
+    }
+
+    /// Creates a new tensor with the supplied metadata, shape and elements without checking that
+    /// they agree with each other.
+    ///
+    /// If you use this function you are proceeding at your own peril.
+    pub fn new_unchecked(
+        metadata: XbfTensorMetadata,
+        shape: impl Into<Vec<u64>>,
+        elements: impl Into<XbfPrimitiveBuffer>,
+    ) -> Self {
+        Self {
+            metadata,
+            shape: shape.into(),
+            elements: elements.into(),
+        }
+    }
+
+    /// Serialize a tensor as defined by the XBF specification.
+    ///
+    /// This function **does not** write out the metadata of the type. If you want to write out the
+    /// metadata, get the metadata with [`Self::get_metadata`] and serialize that with
+    /// [`XbfTensorMetadata::serialize_tensor_metadata`].
+    pub fn serialize_tensor_type(&self, writer: &mut impl Write) -> io::Result<()> {
+        if self.metadata.shape.is_none() {
+            self.shape
+                .iter()
+                .try_for_each(|x| writer.write_u64::<LittleEndian>(*x))?;
+        }
+        self.elements.serialize_elements(writer)
+    }
+
+    /// Deserialize a tensor as defined by the XBF specification.
+    ///
+    /// This function **does not** read the metadata of the type from the reader. It is
+    /// expected that to call this function the metadata for a type is already known, be
+    /// that from reading it from the reader with
+    /// [`deserialize_base_metadata`](crate::XbfMetadata::deserialize_base_metadata)
+    /// or having it in some other manner.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails or the data is invalid, with a path locating the
+    /// element which failed by its index in row-major order.
+    pub fn deserialize_tensor_type(
+        metadata: &XbfTensorMetadata,
+        reader: &mut impl Read,
+    ) -> Result<XbfTensor, XbfError> {
+        Self::read_tensor_type(metadata, &mut XbfReader::new(reader))
+            .map_err(|e| e.in_root(&metadata.into()))
+    }
+
+    pub(crate) fn read_tensor_type(
+        metadata: &XbfTensorMetadata,
+        reader: &mut XbfReader<impl Read>,
+    ) -> Result<XbfTensor, XbfError> {
+        let shape = match &metadata.shape {
+            Some(shape) => shape.clone(),
+            None => reader.read_tensor_shape(metadata.rank)?,
+        };
+        let len = element_count(&shape);
+        let elements = XbfPrimitiveBuffer::read_elements(metadata.element_type, len, reader)?;
+        Ok(XbfTensor::new_unchecked(metadata.clone(), shape, elements))
+    }
+
+    /// Returns the metadata of the tensor.
+    pub fn get_metadata(&self) -> XbfTensorMetadata {
+        self.metadata.clone()
+    }
+
+    /// Returns the length of each dimension of the tensor.
+    pub fn shape(&self) -> &[u64] {
+        &self.shape
+    }
+
+    /// Returns the elements of the tensor in row-major order.
+    pub fn elements(&self) -> &XbfPrimitiveBuffer {
+        &self.elements
+    }
+
+    /// Consumes the tensor, returning its elements in row-major order.
+    pub fn into_elements(self) -> XbfPrimitiveBuffer {
+        self.elements
+    }
+
+    /// Returns the number of elements in the tensor.
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns `true` if the tensor has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Returns the element at the given index in each dimension, or `None` if the index does not
+    /// have one entry per dimension or is out of bounds.
+    pub fn get(&self, index: &[u64]) -> Option<XbfPrimitive> {
+        if index.len() != self.shape.len() {
+            return None;
+        }
+        let mut flat = 0u64;
+        for (i, len) in index.iter().zip(&self.shape) {
+            if i >= len {
+                return None;
+            }
+            flat = flat * len + i;
+        }
+        self.elements.get(usize::try_from(flat).ok()?)
+    }
+}
+
+/// Returns the number of elements in a tensor of the given shape.
+pub(crate) fn element_count(shape: &[u64]) -> u64 {
+    shape.iter().fold(1u64, |acc, x| acc.saturating_mul(*x))
+}
+
+impl XbfTypeUpcast for XbfTensor {
+    fn into_base_type(self) -> XbfType {
+        XbfType::Tensor(self)
+    }
+
+    fn to_base_type(&self) -> XbfType {
+        XbfType::Tensor(self.clone())
+    }
+}
+
+/// Error type for creating an [`XbfTensor`] or [`XbfTensorMetadata`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum TensorError {
+    UnsizedElement(XbfPrimitiveMetadata),
+    RankTooLarge(usize),
+    RankMismatch {
+        expected_rank: u8,
+        actual_rank: usize,
+    },
+    ShapeMismatch {
+        expected_shape: Vec<u64>,
+        actual_shape: Vec<u64>,
+    },
+    ElementMismatch {
+        expected_type: XbfPrimitiveMetadata,
+        actual_type: XbfPrimitiveMetadata,
+    },
+    LengthMismatch {
+        expected_len: u64,
+        actual_len: u64,
+    },
+}
+
+impl Display for TensorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TensorError::UnsizedElement(x) => {
+                write!(f, "Tensor elements must have a fixed size, {x:?} does not")
+            }
+            TensorError::RankTooLarge(x) => {
+                write!(f, "Provided {x} dimensions, expected at most {}", u8::MAX)
+            }
+            TensorError::RankMismatch {
+                expected_rank,
+                actual_rank,
+            } => write!(
+                f,
+                "Provided {actual_rank} dimensions, expected exactly {expected_rank}"
+            ),
+            TensorError::ShapeMismatch {
+                expected_shape,
+                actual_shape,
+            } => write!(
+                f,
+                "Provided shape {actual_shape:?}, expected {expected_shape:?}"
+            ),
+            TensorError::ElementMismatch {
+                expected_type,
+                actual_type,
+            } => write!(
+                f,
+                "Provided elements are of type {actual_type:?}, expected {expected_type:?}"
+            ),
+            TensorError::LengthMismatch {
+                expected_len,
+                actual_len,
+            } => write!(
+                f,
+                "Provided {actual_len} elements, expected exactly {expected_len}"
+            ),
+        }
+    }
+}
+
+impl Error for TensorError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DeserializeOptions, XbfErrorKind, XbfLimit};
+
+    fn matrix_metadata() -> XbfTensorMetadata {
+        XbfTensorMetadata::new(XbfPrimitiveMetadata::F32, 2).unwrap()
+    }
+
+    fn matrix() -> XbfTensor {
+        XbfTensor::new(
+            matrix_metadata(),
+            [2, 3],
+            vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn tensor_round_trip_works() {
+        let tensor = matrix();
+
+        let mut data = vec![];
+        tensor.serialize_tensor_type(&mut data).unwrap();
+
+        let mut expected = vec![];
+        expected.extend_from_slice(&2u64.to_le_bytes());
+        expected.extend_from_slice(&3u64.to_le_bytes());
+        for x in [1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0] {
+            expected.extend_from_slice(&x.to_le_bytes());
+        }
+        assert_eq!(data, expected);
+
+        let mut reader = data.as_slice();
+        let deserialized =
+            XbfTensor::deserialize_tensor_type(&matrix_metadata(), &mut reader).unwrap();
+        assert_eq!(deserialized, tensor);
+        assert!(reader.is_empty());
+
+        assert_eq!(tensor.get(&[0, 2]), Some(XbfPrimitive::F32(3.0)));
+        assert_eq!(tensor.get(&[2, 0]), None);
+        assert_eq!(tensor.get(&[0]), None);
+    }
+
+    #[test]
+    fn fixed_shape_is_not_sent() {
+        let metadata = XbfTensorMetadata::with_shape(XbfPrimitiveMetadata::U8, [2, 2]).unwrap();
+        let tensor = XbfTensor::new(metadata.clone(), [2, 2], vec![1u8, 2, 3, 4]).unwrap();
+
+        let mut data = vec![];
+        tensor.serialize_tensor_type(&mut data).unwrap();
+        assert_eq!(data, [1, 2, 3, 4]);
+
+        let deserialized = XbfTensor::deserialize_tensor_type(&metadata, &mut data.as_slice());
+        assert_eq!(deserialized.unwrap(), tensor);
+    }
+
+    #[test]
+    fn tensor_new_checks_elements() {
+        let err = XbfTensor::new(matrix_metadata(), [6], vec![1.0f32; 6]).unwrap_err();
+        assert_eq!(
+            err,
+            TensorError::RankMismatch {
+                expected_rank: 2,
+                actual_rank: 1,
+            }
+        );
+
+        let err = XbfTensor::new(matrix_metadata(), [2, 3], vec![1.0f64; 6]).unwrap_err();
+        assert_eq!(
+            err,
+            TensorError::ElementMismatch {
+                expected_type: XbfPrimitiveMetadata::F32,
+                actual_type: XbfPrimitiveMetadata::F64,
+            }
+        );
+
+        let err = XbfTensor::new(matrix_metadata(), [2, 3], vec![1.0f32; 5]).unwrap_err();
+        assert_eq!(
+            err,
+            TensorError::LengthMismatch {
+                expected_len: 6,
+                actual_len: 5,
+            }
+        );
+
+        let metadata = XbfTensorMetadata::with_shape(XbfPrimitiveMetadata::F32, [2, 3]).unwrap();
+        let err = XbfTensor::new(metadata, [3, 2], vec![1.0f32; 6]).unwrap_err();
+        assert_eq!(
+            err,
+            TensorError::ShapeMismatch {
+                expected_shape: vec![2, 3],
+                actual_shape: vec![3, 2],
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_invalid_tensor_fails() {
+        let mut data = vec![];
+        matrix().serialize_tensor_type(&mut data).unwrap();
+
+        let err =
+            XbfTensor::deserialize_tensor_type(&matrix_metadata(), &mut &data[..30]).unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::UnexpectedEof));
+        assert_eq!(err.offset(), 28);
+        assert_eq!(err.path(), "F32[3]");
+
+        let options = DeserializeOptions::new().max_vec_len(5);
+        let err = XbfReader::with_options(data.as_slice(), options)
+            .read_value(&matrix_metadata().into())
+            .unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            XbfErrorKind::LimitExceeded {
+                limit: XbfLimit::VecLength,
+                actual: 6,
+                max: 5,
+            }
+        ));
+    }
+}
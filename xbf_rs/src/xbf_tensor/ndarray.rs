@@ -0,0 +1,103 @@
+//! Conversions between [`XbfTensor`] and [`ndarray::ArrayD`], enabled with the `ndarray` feature.
+
+use super::{element_count, TensorError, XbfTensor, XbfTensorMetadata};
+use crate::XbfBufferElement;
+use ndarray::{ArrayD, IxDyn};
+
+/// Converts an array into a tensor whose shape is sent with each value.
+impl<T: XbfBufferElement> TryFrom<ArrayD<T>> for XbfTensor {
+    type Error = TensorError;
+
+    fn try_from(value: ArrayD<T>) -> Result<Self, Self::Error> {
+        let shape = value.shape().iter().map(|x| *x as u64).collect::<Vec<_>>();
+        let rank = u8::try_from(shape.len()).map_err(|_| TensorError::RankTooLarge(shape.len()))?;
+        let metadata = XbfTensorMetadata::new(T::ELEMENT_TYPE, rank)?;
+
+        let len = value.len();
+        let elements = if value.is_standard_layout() {
+            // an array which has been sliced may still own elements outside of its view
+            let (mut elements, offset) = value.into_raw_vec_and_offset();
+            elements.drain(..offset.unwrap_or(0));
+            elements.truncate(len);
+            elements
+        } else {
+            value.iter().copied().collect()
+        };
+        Ok(XbfTensor::new_unchecked(metadata, shape, elements))
+    }
+}
+
+impl<T: XbfBufferElement> TryFrom<XbfTensor> for ArrayD<T> {
+    type Error = TensorError;
+
+    fn try_from(value: XbfTensor) -> Result<Self, Self::Error> {
+        let expected_len = element_count(&value.shape);
+        let shape = value
+            .shape
+            .iter()
+            .map(|x| usize::try_from(*x).unwrap_or(usize::MAX))
+            .collect::<Vec<_>>();
+        let elements =
+            value
+                .elements
+                .into_vec::<T>()
+                .map_err(|elements| TensorError::ElementMismatch {
+                    expected_type: T::ELEMENT_TYPE,
+                    actual_type: elements.element_type(),
+                })?;
+        let actual_len = elements.len() as u64;
+        ArrayD::from_shape_vec(IxDyn(&shape), elements).map_err(|_| TensorError::LengthMismatch {
+            expected_len,
+            actual_len,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::XbfPrimitiveMetadata;
+    use ndarray::{s, Array};
+
+    #[test]
+    fn conversions_work() {
+        let array = Array::from_shape_vec((2, 3), vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0])
+            .unwrap()
+            .into_dyn();
+
+        let tensor = XbfTensor::try_from(array.clone()).unwrap();
+        assert_eq!(
+            tensor.get_metadata(),
+            XbfTensorMetadata::new(XbfPrimitiveMetadata::F32, 2).unwrap()
+        );
+        assert_eq!(tensor.shape(), [2, 3]);
+        assert_eq!(ArrayD::<f32>::try_from(tensor.clone()), Ok(array.clone()));
+
+        assert_eq!(
+            ArrayD::<f64>::try_from(tensor),
+            Err(TensorError::ElementMismatch {
+                expected_type: XbfPrimitiveMetadata::F64,
+                actual_type: XbfPrimitiveMetadata::F32,
+            })
+        );
+
+        // non standard layouts are copied in row-major order
+        let transposed = array.t().to_owned();
+        let tensor = XbfTensor::try_from(transposed.clone()).unwrap();
+        assert_eq!(tensor.shape(), [3, 2]);
+        assert_eq!(
+            tensor.elements().as_slice::<f32>(),
+            Some([1.0, 4.0, 2.0, 5.0, 3.0, 6.0].as_slice())
+        );
+        assert_eq!(ArrayD::<f32>::try_from(tensor), Ok(transposed));
+
+        let mut sliced = array;
+        sliced.slice_collapse(s![1.., 1..]);
+        let tensor = XbfTensor::try_from(sliced).unwrap();
+        assert_eq!(tensor.shape(), [1, 2]);
+        assert_eq!(
+            tensor.elements().as_slice::<f32>(),
+            Some([5.0, 6.0].as_slice())
+        );
+    }
+}
@@ -0,0 +1,255 @@
+use crate::{
+    reader::XbfReader, TensorError, XbfError, XbfErrorKind, XbfMetadata, XbfMetadataUpcast,
+    XbfPrimitiveMetadata, XbfTensor,
+};
+use byteorder::{LittleEndian, WriteBytesExt};
+use std::io::{self, Read, Write};
+
+/// The metadata discriminant for a Tensor type.
+///
+/// This is the same for all tensors regardless of their contents. It's value should always be
+/// equal to the discriminant value of a primitive BF16 plus one, the last type added before
+/// tensors.
+pub const TENSOR_METADATA_DISCRIMINANT: u8 = XbfPrimitiveMetadata::BF16 as u8 + 1;
+
+/// Metadata for a Tensor type.
+///
+/// A tensor always has a primitive element type with a [fixed
+/// size](XbfPrimitiveMetadata::fixed_size) and a rank, which is its number of dimensions. Its
+/// shape is either fixed in the metadata, or sent with each value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XbfTensorMetadata {
+    pub(crate) element_type: XbfPrimitiveMetadata,
+    pub(crate) rank: u8,
+    pub(crate) shape: Option<Vec<u64>>,
+}
+
+impl XbfTensorMetadata {
+    /// Creates a new Tensor metadata whose shape is sent with each value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TensorError::UnsizedElement`] if the element type does not have a fixed size.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xbf_rs::{XbfPrimitiveMetadata, XbfTensorMetadata};
+    ///
+    /// let metadata = XbfTensorMetadata::new(XbfPrimitiveMetadata::F32, 2).unwrap();
+    ///
+    /// assert_eq!(metadata.element_type(), XbfPrimitiveMetadata::F32);
+    /// assert_eq!(metadata.rank(), 2);
+    /// assert_eq!(metadata.shape(), None);
+    ///
+    /// assert!(XbfTensorMetadata::new(XbfPrimitiveMetadata::String, 2).is_err());
+    /// ```
+    pub fn new(element_type: XbfPrimitiveMetadata, rank: u8) -> Result<Self, TensorError> {
+        if element_type.fixed_size().is_none() {
+            return Err(TensorError::UnsizedElement(element_type));
+        }
+        Ok(Self {
+            element_type,
+            rank,
+            shape: None,
+        })
+    }
+
+    /// Creates a new Tensor metadata whose shape is fixed, so that it is not sent with each
+    /// value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TensorError::UnsizedElement`] if the element type does not have a fixed size, or
+    /// [`TensorError::RankTooLarge`] if the shape has more than 255 dimensions.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xbf_rs::{XbfPrimitiveMetadata, XbfTensorMetadata};
+    ///
+    /// let metadata = XbfTensorMetadata::with_shape(XbfPrimitiveMetadata::F32, [3, 4]).unwrap();
+    ///
+    /// assert_eq!(metadata.rank(), 2);
+    /// assert_eq!(metadata.shape(), Some([3, 4].as_slice()));
+    /// ```
+    pub fn with_shape(
+        element_type: XbfPrimitiveMetadata,
+        shape: impl Into<Vec<u64>>,
+    ) -> Result<Self, TensorError> {
+        let shape = shape.into();
+        let rank = u8::try_from(shape.len()).map_err(|_| TensorError::RankTooLarge(shape.len()))?;
+        let mut metadata = Self::new(element_type, rank)?;
+        metadata.shape = Some(shape);
+        Ok(metadata)
+    }
+
+    /// Returns the type of the elements of the tensor.
+    pub fn element_type(&self) -> XbfPrimitiveMetadata {
+        self.element_type
+    }
+
+    /// Returns the number of dimensions of the tensor.
+    pub fn rank(&self) -> u8 {
+        self.rank
+    }
+
+    /// Returns the shape every value of the tensor has, or `None` if it is sent with each value.
+    pub fn shape(&self) -> Option<&[u64]> {
+        self.shape.as_deref()
+    }
+
+    /// Serialize Tensor metadata as defined by the XBF specification.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use xbf_rs::{XbfPrimitiveMetadata, XbfTensorMetadata, TENSOR_METADATA_DISCRIMINANT};
+    ///
+    /// let metadata = XbfTensorMetadata::with_shape(XbfPrimitiveMetadata::F32, [3]).unwrap();
+    /// let mut writer = Vec::new();
+    /// metadata.serialize_tensor_metadata(&mut writer).unwrap();
+    ///
+    /// let mut expected = vec![TENSOR_METADATA_DISCRIMINANT, XbfPrimitiveMetadata::F32 as u8, 1, 1];
+    /// expected.extend_from_slice(&3u64.to_le_bytes());
+    /// assert_eq!(writer, expected);
+    /// ```
+    pub fn serialize_tensor_metadata(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_u8(TENSOR_METADATA_DISCRIMINANT)?;
+        self.element_type.serialize_primitive_metadata(writer)?;
+        writer.write_u8(self.rank)?;
+        writer.write_u8(u8::from(self.shape.is_some()))?;
+        self.shape
+            .iter()
+            .flatten()
+            .try_for_each(|x| writer.write_u64::<LittleEndian>(*x))
+    }
+
+    /// Deserialize Tensor metadata as defined by the XBF specification.
+    ///
+    /// This method assumes that you know for a fact you are about to receive Tensor metadata. If
+    /// you do not know what sort of metadata you are receiving, use
+    /// [`deserialize_base_metadata`](crate::XbfMetadata::deserialize_base_metadata).
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails or the metadata is invalid, such as when the
+    /// element type does not have a fixed size.
+    pub fn deserialize_tensor_metadata(
+        reader: &mut impl Read,
+    ) -> Result<XbfTensorMetadata, XbfError> {
+        Self::read_tensor_metadata(&mut XbfReader::new(reader))
+    }
+
+    pub(crate) fn read_tensor_metadata(
+        reader: &mut XbfReader<impl Read>,
+    ) -> Result<XbfTensorMetadata, XbfError> {
+        let offset = reader.position();
+        let discriminant = reader.read_u8()?;
+        let element_type =
+            XbfPrimitiveMetadata::try_from(discriminant).map_err(|e| XbfError::new(e, offset))?;
+        if element_type.fixed_size().is_none() {
+            return Err(XbfError::new(
+                XbfErrorKind::InvalidTensorElement(element_type),
+                offset,
+            ));
+        }
+        let rank = reader.read_u8()?;
+        let shape = if reader.read_bool()? {
+            Some(reader.read_tensor_shape(rank)?)
+        } else {
+            None
+        };
+        Ok(XbfTensorMetadata {
+            element_type,
+            rank,
+            shape,
+        })
+    }
+}
+
+impl From<&XbfTensor> for XbfTensorMetadata {
+    fn from(value: &XbfTensor) -> Self {
+        value.get_metadata()
+    }
+}
+
+impl XbfMetadataUpcast for XbfTensorMetadata {
+    fn into_base_metadata(self) -> XbfMetadata {
+        XbfMetadata::Tensor(self)
+    }
+
+    fn to_base_metadata(&self) -> XbfMetadata {
+        XbfMetadata::Tensor(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DeserializeOptions, XbfLimit};
+
+    #[test]
+    fn metadata_serde_works() {
+        for metadata in [
+            XbfTensorMetadata::new(XbfPrimitiveMetadata::F32, 3).unwrap(),
+            XbfTensorMetadata::with_shape(XbfPrimitiveMetadata::I16, [2, 5]).unwrap(),
+        ] {
+            let mut writer = vec![];
+            metadata.serialize_tensor_metadata(&mut writer).unwrap();
+
+            let deserialized =
+                XbfMetadata::deserialize_base_metadata(&mut writer.as_slice()).unwrap();
+            assert_eq!(deserialized, metadata.into());
+        }
+
+        let fixed = XbfTensorMetadata::with_shape(XbfPrimitiveMetadata::I16, [2, 5]).unwrap();
+        assert_eq!(XbfMetadata::from(fixed).fixed_size(), Some(20));
+        let dynamic = XbfTensorMetadata::new(XbfPrimitiveMetadata::I16, 2).unwrap();
+        assert_eq!(XbfMetadata::from(dynamic).fixed_size(), None);
+    }
+
+    #[test]
+    fn unsized_elements_fail() {
+        assert_eq!(
+            XbfTensorMetadata::with_shape(XbfPrimitiveMetadata::Bytes, [2]),
+            Err(TensorError::UnsizedElement(XbfPrimitiveMetadata::Bytes))
+        );
+
+        let data = [
+            TENSOR_METADATA_DISCRIMINANT,
+            XbfPrimitiveMetadata::String as u8,
+            1,
+            0,
+        ];
+        let err = XbfMetadata::deserialize_base_metadata(&mut data.as_slice()).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            XbfErrorKind::InvalidTensorElement(XbfPrimitiveMetadata::String)
+        ));
+        assert_eq!(err.offset(), 1);
+    }
+
+    #[test]
+    fn metadata_shape_is_limited() {
+        let mut data = vec![];
+        XbfTensorMetadata::with_shape(XbfPrimitiveMetadata::U8, [10, 10])
+            .unwrap()
+            .serialize_tensor_metadata(&mut data)
+            .unwrap();
+
+        let options = DeserializeOptions::new().max_vec_len(50);
+        let err = XbfReader::with_options(data.as_slice(), options)
+            .read_metadata()
+            .unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            XbfErrorKind::LimitExceeded {
+                limit: XbfLimit::VecLength,
+                actual: 100,
+                max: 50,
+            }
+        ));
+        assert_eq!(err.offset(), 4);
+    }
+}
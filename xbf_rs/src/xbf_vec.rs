@@ -1,23 +1,96 @@
 //! A vector as defined by the XBF specification.
 
 mod vec_metadata;
+mod vec_reader;
+mod vec_writer;
 
 pub use vec_metadata::*;
+pub use vec_reader::*;
+pub use vec_writer::*;
 
-use crate::{XbfMetadata, XbfType, XbfTypeUpcast};
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use crate::{
+    reader::{XbfReader, MAX_PREALLOCATION},
+    OnceType, XbfBufferElement, XbfError, XbfMetadata, XbfPrimitiveBuffer, XbfProjection, XbfType,
+    XbfTypeUpcast,
+};
+use byteorder::{LittleEndian, WriteBytesExt};
 use std::{
+    borrow::Cow,
+    fmt,
     io::{self, Read, Write},
+    iter,
     ops::Deref,
     slice::{Iter, IterMut},
     vec::IntoIter,
 };
 
 /// A vector type as defined by the XBF specification.
-#[derive(Debug, Clone, PartialEq)]
+///
+/// A vector of primitives with a [fixed size](crate::XbfPrimitiveMetadata::fixed_size) stores its
+/// elements packed in their native type, as an [`XbfPrimitiveBuffer`], so that for example a
+/// vector of `I32`s takes four bytes per element and is read and written in bulk. The packed
+/// elements can be borrowed with accessors such as [`as_i32_slice`](XbfVec::as_i32_slice).
+///
+/// Borrowing the elements of a packed vector as [`XbfType`]s, through [`Deref`] or by iterating
+/// over a reference, is an explicit and costly unpack. The first time it is done every element is
+/// converted to an [`XbfType`], which takes the size of an [`XbfType`] per element, and the
+/// unpacked elements are kept alongside the packed ones for the life of the vector. Serializing
+/// the vector, and reading it with serde or [`FromXbf`](crate::FromXbf), never unpack it. Mutably
+/// iterating over a packed vector unpacks it for good, after which the typed accessors return
+/// `None`.
+#[derive(Clone)]
 pub struct XbfVec {
     pub(crate) metadata: XbfVecMetadata,
-    elements: Vec<XbfType>,
+    elements: VecElements,
+}
+
+#[derive(Clone)]
+enum VecElements {
+    Packed {
+        buffer: XbfPrimitiveBuffer,
+        // the elements as `XbfType`s, created the first time they are borrowed as such
+        unpacked: OnceType<Vec<XbfType>>,
+    },
+    Unpacked(Vec<XbfType>),
+}
+
+impl VecElements {
+    fn packed(buffer: XbfPrimitiveBuffer) -> Self {
+        VecElements::Packed {
+            buffer,
+            unpacked: OnceType::new(),
+        }
+    }
+
+    /// Collects elements of the given type, packing them if it's a primitive with a fixed size.
+    fn collect(inner_type: &XbfMetadata, elements: impl IntoIterator<Item = XbfType>) -> Self {
+        let buffer = match inner_type {
+            XbfMetadata::Primitive(x) => XbfPrimitiveBuffer::new(*x),
+            _ => None,
+        };
+        let mut elements = elements.into_iter();
+        let Some(mut buffer) = buffer else {
+            return VecElements::Unpacked(elements.collect());
+        };
+        for element in elements.by_ref() {
+            let rejected = match element {
+                XbfType::Primitive(x) => match buffer.push(x) {
+                    Ok(()) => continue,
+                    Err(x) => x.into(),
+                },
+                other => other,
+            };
+            // elements of another type can only be stored unpacked
+            let unpacked = buffer.iter().map(XbfType::from);
+            return VecElements::Unpacked(
+                unpacked
+                    .chain(iter::once(rejected))
+                    .chain(elements)
+                    .collect(),
+            );
+        }
+        VecElements::packed(buffer)
+    }
 }
 
 impl XbfVec {
@@ -57,11 +130,15 @@ impl XbfVec {
         metadata: XbfVecMetadata,
         elements: impl IntoIterator<Item = impl Into<XbfType>>,
     ) -> Result<Self, ElementsNotHomogenousError> {
-        let elements = elements.into_iter().map(Into::into).collect::<Vec<_>>();
+        let elements =
+            VecElements::collect(&metadata.inner_type, elements.into_iter().map(Into::into));
 
-        let all_same_type = elements
-            .iter()
-            .all(|x| *metadata.inner_type == XbfMetadata::from(x));
+        let all_same_type = match &elements {
+            VecElements::Packed { .. } => true,
+            VecElements::Unpacked(elements) => elements
+                .iter()
+                .all(|x| *metadata.inner_type == XbfMetadata::from(x)),
+        };
 
         if all_same_type {
             Ok(Self { metadata, elements })
@@ -94,7 +171,8 @@ impl XbfVec {
         metadata: XbfVecMetadata,
         elements: impl IntoIterator<Item = impl Into<XbfType>>,
     ) -> Self {
-        let elements = elements.into_iter().map(Into::into).collect::<Vec<_>>();
+        let elements =
+            VecElements::collect(&metadata.inner_type, elements.into_iter().map(Into::into));
         Self { metadata, elements }
     }
 
@@ -125,10 +203,13 @@ impl XbfVec {
     /// assert_eq!(writer, expected);
     /// ```
     pub fn serialize_vec_type(&self, writer: &mut impl Write) -> io::Result<()> {
-        writer.write_u64::<LittleEndian>(self.elements.len() as u64)?;
-        self.elements
-            .iter()
-            .try_for_each(|e| e.serialize_base_type(writer))
+        writer.write_u64::<LittleEndian>(self.len() as u64)?;
+        match &self.elements {
+            VecElements::Packed { buffer, .. } => buffer.serialize_elements(writer),
+            VecElements::Unpacked(elements) => elements
+                .iter()
+                .try_for_each(|e| e.serialize_base_type(writer)),
+        }
     }
 
     /// Deserialize a vector as defined by the XBF specification.
@@ -139,6 +220,11 @@ impl XbfVec {
     /// [`deserialize_base_metadata`](crate::XbfMetadata::deserialize_base_metadata)
     /// or having it in some other manner.
     ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails or the data is invalid, with a path locating the
+    /// element which failed.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -161,14 +247,84 @@ impl XbfVec {
     pub fn deserialize_vec_type(
         metadata: &XbfVecMetadata,
         reader: &mut impl Read,
-    ) -> io::Result<XbfVec> {
-        let inner_type = &metadata.inner_type;
-        let len = reader.read_u64::<LittleEndian>()? as usize;
-        let mut elements = Vec::with_capacity(len);
-        for _ in 0..len {
-            elements.push(XbfType::deserialize_base_type(inner_type, reader)?);
-        }
-        Ok(XbfVec::new_unchecked(metadata.clone(), elements))
+    ) -> Result<XbfVec, XbfError> {
+        Self::read_vec_type(metadata, &mut XbfReader::new(reader))
+            .map_err(|e| e.in_root(&metadata.inner_type))
+    }
+
+    pub(crate) fn read_vec_type(
+        metadata: &XbfVecMetadata,
+        reader: &mut XbfReader<impl Read>,
+    ) -> Result<XbfVec, XbfError> {
+        reader.nested(|reader| {
+            let inner_type = &metadata.inner_type;
+            let len = reader.read_vec_len()?;
+            reader.charge_elements(inner_type.fixed_size(), len)?;
+            if let XbfMetadata::Primitive(x) = &**inner_type {
+                if x.fixed_size().is_some() {
+                    let buffer = XbfPrimitiveBuffer::read_elements(*x, len, reader)?;
+                    return Ok(XbfVec::from(buffer));
+                }
+            }
+            let max_capacity = MAX_PREALLOCATION / std::mem::size_of::<XbfType>();
+            let mut elements = Vec::with_capacity((len as usize).min(max_capacity));
+            for i in 0..len {
+                elements
+                    .push(XbfType::read_base_type(inner_type, reader).map_err(|e| e.at_index(i))?);
+            }
+            Ok(XbfVec::new_unchecked(metadata.clone(), elements))
+        })
+    }
+
+    /// Deserialize only the parts of a vector's elements selected by a projection, skipping
+    /// over the rest.
+    ///
+    /// The deserialized vector has metadata containing only the selected fields. See
+    /// [`XbfProjection`] for how fields are selected.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails or the data is invalid, or with
+    /// [`XbfErrorKind::UnknownField`](crate::XbfErrorKind::UnknownField) before reading anything
+    /// if the projection selects a field which does not exist.
+    pub fn deserialize_vec_type_projected(
+        metadata: &XbfVecMetadata,
+        projection: &XbfProjection,
+        reader: &mut impl Read,
+    ) -> Result<XbfVec, XbfError> {
+        let reader = &mut XbfReader::new(reader);
+        projection
+            .project_vec(metadata, reader.position())
+            .and_then(|projected| {
+                Self::read_vec_type_projected(metadata, &projected, projection, reader)
+            })
+            .map_err(|e| e.in_root(&metadata.inner_type))
+    }
+
+    pub(crate) fn read_vec_type_projected(
+        metadata: &XbfVecMetadata,
+        projected: &XbfVecMetadata,
+        projection: &XbfProjection,
+        reader: &mut XbfReader<impl Read>,
+    ) -> Result<XbfVec, XbfError> {
+        reader.nested(|reader| {
+            let len = reader.read_vec_len()?;
+            reader.charge_elements(metadata.inner_type.fixed_size(), len)?;
+            let max_capacity = MAX_PREALLOCATION / std::mem::size_of::<XbfType>();
+            let mut elements = Vec::with_capacity((len as usize).min(max_capacity));
+            for i in 0..len {
+                elements.push(
+                    XbfType::read_projected(
+                        &metadata.inner_type,
+                        &projected.inner_type,
+                        projection,
+                        reader,
+                    )
+                    .map_err(|e| e.at_index(i))?,
+                );
+            }
+            Ok(XbfVec::new_unchecked(projected.clone(), elements))
+        })
     }
 
     /// Returns the metadata of the vector.
@@ -193,13 +349,190 @@ impl XbfVec {
     pub fn get_metadata(&self) -> XbfVecMetadata {
         self.metadata.clone()
     }
+
+    /// Returns the number of elements in the vector, without unpacking it.
+    pub fn len(&self) -> usize {
+        match &self.elements {
+            VecElements::Packed { buffer, .. } => buffer.len(),
+            VecElements::Unpacked(elements) => elements.len(),
+        }
+    }
+
+    /// Returns `true` if the vector has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the packed elements of the vector, or `None` if it is not packed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use xbf_rs::{XbfPrimitiveMetadata, XbfVec, XbfVecMetadata};
+    ///
+    /// let vec = XbfVec::from(vec![1u16, 2, 3]);
+    /// let buffer = vec.as_primitive_buffer().unwrap();
+    ///
+    /// assert_eq!(buffer.element_type(), XbfPrimitiveMetadata::U16);
+    /// assert_eq!(vec.get_metadata(), XbfVecMetadata::new(XbfPrimitiveMetadata::U16));
+    /// ```
+    pub fn as_primitive_buffer(&self) -> Option<&XbfPrimitiveBuffer> {
+        match &self.elements {
+            VecElements::Packed { buffer, .. } => Some(buffer),
+            VecElements::Unpacked(_) => None,
+        }
+    }
+
+    /// Returns the packed elements of the vector as a slice of `T`, or `None` if the vector is
+    /// not packed or its elements are not of type `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use xbf_rs::{XbfDate, XbfPrimitive, XbfPrimitiveMetadata, XbfVec, XbfVecMetadata};
+    ///
+    /// let days = [XbfDate::from_days_since_epoch(0), XbfDate::from_days_since_epoch(1)];
+    /// let vec = XbfVec::new(
+    ///     XbfVecMetadata::new(XbfPrimitiveMetadata::Date),
+    ///     days.map(XbfPrimitive::Date),
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(vec.as_primitive_slice::<XbfDate>(), Some(days.as_slice()));
+    /// assert_eq!(vec.as_primitive_slice::<i32>(), None);
+    /// ```
+    pub fn as_primitive_slice<T: XbfBufferElement>(&self) -> Option<&[T]> {
+        self.as_primitive_buffer()?.as_slice()
+    }
+
+    /// Returns an iterator over the elements of the vector which does not unpack it, instead
+    /// decoding the elements of a packed vector one at a time.
+    pub(crate) fn values(&self) -> impl ExactSizeIterator<Item = Cow<'_, XbfType>> {
+        (0..self.len()).map(move |i| match &self.elements {
+            VecElements::Packed { buffer, .. } => {
+                Cow::Owned(buffer.get(i).expect("index is within the buffer").into())
+            }
+            VecElements::Unpacked(elements) => Cow::Borrowed(&elements[i]),
+        })
+    }
+
+    /// Returns whether a packed vector has been unpacked by borrowing its elements.
+    #[cfg(test)]
+    pub(crate) fn has_unpacked(&self) -> bool {
+        match &self.elements {
+            VecElements::Packed { unpacked, .. } => unpacked.get().is_some(),
+            VecElements::Unpacked(_) => false,
+        }
+    }
+
+    /// Unpacks the elements of the vector for good, so that they can be mutated.
+    fn unpack(&mut self) -> &mut Vec<XbfType> {
+        if let VecElements::Packed { buffer, unpacked } = &mut self.elements {
+            let elements = unpacked
+                .take()
+                .unwrap_or_else(|| buffer.iter().map(XbfType::from).collect());
+            self.elements = VecElements::Unpacked(elements);
+        }
+        match &mut self.elements {
+            VecElements::Unpacked(elements) => elements,
+            VecElements::Packed { .. } => unreachable!("the vector was unpacked above"),
+        }
+    }
+}
+
+macro_rules! impl_slice_accessors {
+    ($($fn_name:ident, $ty:ty);+ $(;)?) => {
+        impl XbfVec {
+            $(
+                #[doc = concat!("Returns the packed elements of the vector as a slice of `", stringify!($ty), "`, or `None` if the vector is not packed or its elements are not of that type.")]
+                pub fn $fn_name(&self) -> Option<&[$ty]> {
+                    self.as_primitive_slice()
+                }
+            )+
+        }
+    };
+}
+
+impl_slice_accessors! {
+    as_bool_slice, bool;
+    as_u8_slice, u8;
+    as_u16_slice, u16;
+    as_u32_slice, u32;
+    as_u64_slice, u64;
+    as_u128_slice, u128;
+    as_i8_slice, i8;
+    as_i16_slice, i16;
+    as_i32_slice, i32;
+    as_i64_slice, i64;
+    as_i128_slice, i128;
+    as_f32_slice, f32;
+    as_f64_slice, f64;
+    as_char_slice, char;
+}
+
+/// Creates a packed vector of the elements in the buffer.
+impl From<XbfPrimitiveBuffer> for XbfVec {
+    fn from(value: XbfPrimitiveBuffer) -> Self {
+        Self {
+            metadata: XbfVecMetadata::new(value.element_type()),
+            elements: VecElements::packed(value),
+        }
+    }
+}
+
+/// Creates a packed vector of the elements.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::XbfVec;
+///
+/// let vec = XbfVec::from(vec![1i32, 2, 3]);
+///
+/// assert_eq!(vec.as_i32_slice(), Some([1, 2, 3].as_slice()));
+/// assert_eq!(vec.as_u32_slice(), None);
+/// ```
+impl<T: XbfBufferElement> From<Vec<T>> for XbfVec {
+    fn from(value: Vec<T>) -> Self {
+        XbfPrimitiveBuffer::from(value).into()
+    }
+}
+
+impl PartialEq for XbfVec {
+    fn eq(&self, other: &Self) -> bool {
+        if self.metadata != other.metadata {
+            return false;
+        }
+        match (&self.elements, &other.elements) {
+            (VecElements::Packed { buffer, .. }, VecElements::Packed { buffer: other, .. }) => {
+                buffer == other
+            }
+            _ => self.len() == other.len() && self.values().eq(other.values()),
+        }
+    }
+}
+
+/// Formats packed and unpacked vectors the same way, as a list of [`XbfType`]s.
+impl fmt::Debug for XbfVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("XbfVec")
+            .field("metadata", &self.metadata)
+            .field("elements", &self.values().collect::<Vec<_>>())
+            .finish()
+    }
 }
 
+/// Borrows the elements as [`XbfType`]s, unpacking a packed vector the first time it is called.
 impl Deref for XbfVec {
     type Target = [XbfType];
 
     fn deref(&self) -> &Self::Target {
-        &self.elements
+        match &self.elements {
+            VecElements::Packed { buffer, unpacked } => {
+                unpacked.get_or_init(|| buffer.iter().map(XbfType::from).collect())
+            }
+            VecElements::Unpacked(elements) => elements,
+        }
     }
 }
 
@@ -209,17 +542,18 @@ impl<'a> IntoIterator for &'a XbfVec {
     type IntoIter = Iter<'a, XbfType>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.elements.iter()
+        self.deref().iter()
     }
 }
 
+/// Mutably iterating over a packed vector unpacks it for good.
 impl<'a> IntoIterator for &'a mut XbfVec {
     type Item = &'a mut XbfType;
 
     type IntoIter = IterMut<'a, XbfType>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.elements.iter_mut()
+        self.unpack().iter_mut()
     }
 }
 
@@ -228,8 +562,8 @@ impl IntoIterator for XbfVec {
 
     type IntoIter = IntoIter<XbfType>;
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.elements.into_iter()
+    fn into_iter(mut self) -> Self::IntoIter {
+        std::mem::take(self.unpack()).into_iter()
     }
 }
 
@@ -253,7 +587,7 @@ pub struct ElementsNotHomogenousError;
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{XbfMetadataUpcast, XbfPrimitive, XbfPrimitiveMetadata};
+    use crate::{XbfErrorKind, XbfMetadataUpcast, XbfPrimitive, XbfPrimitiveMetadata};
     use std::io::Cursor;
 
     #[test]
@@ -460,4 +794,73 @@ mod tests {
         assert_eq!(expected, vec.clone().into());
         assert_eq!(expected, vec.into_base_type());
     }
+
+    #[test]
+    fn primitive_vec_is_packed() {
+        let data = [1i32, -2, 4].map(XbfPrimitive::from);
+        let vec = XbfVec::new(XbfVecMetadata::new(XbfPrimitiveMetadata::I32), data).unwrap();
+        assert_eq!(vec.as_i32_slice(), Some([1, -2, 4].as_slice()));
+        assert_eq!(vec.as_u32_slice(), None);
+        assert_eq!(vec, XbfVec::from(vec![1i32, -2, 4]));
+
+        let mut writer = vec![];
+        vec.serialize_vec_type(&mut writer).unwrap();
+        let metadata = vec.get_metadata();
+        let deserialized = XbfVec::deserialize_vec_type(&metadata, &mut writer.as_slice()).unwrap();
+        assert_eq!(deserialized.as_i32_slice(), Some([1, -2, 4].as_slice()));
+
+        let strings = XbfVec::new(
+            XbfVecMetadata::new(XbfPrimitiveMetadata::String),
+            [XbfPrimitive::String("Saphira".to_string())],
+        )
+        .unwrap();
+        assert!(strings.as_primitive_buffer().is_none());
+    }
+
+    #[test]
+    fn unpacking_preserves_elements() {
+        let packed = XbfVec::from(vec![1.5f64, 2.5]);
+        let mut unpacked = packed.clone();
+        for x in &mut unpacked {
+            assert!(matches!(x, XbfType::Primitive(XbfPrimitive::F64(_))));
+        }
+        assert_eq!(unpacked.as_f64_slice(), None);
+
+        assert_eq!(packed, unpacked);
+        assert_eq!(format!("{packed:?}"), format!("{unpacked:?}"));
+        assert!(!packed.has_unpacked());
+        assert_eq!(packed[1], XbfPrimitive::F64(2.5).into());
+        assert!(packed.has_unpacked());
+        assert_eq!(packed.as_f64_slice(), Some([1.5, 2.5].as_slice()));
+        assert_eq!(packed.clone().into_iter().len(), 2);
+
+        let mut packed_data = vec![];
+        packed.serialize_vec_type(&mut packed_data).unwrap();
+        let mut unpacked_data = vec![];
+        unpacked.serialize_vec_type(&mut unpacked_data).unwrap();
+        assert_eq!(packed_data, unpacked_data);
+
+        // an unchecked vector with elements of other types is stored unpacked
+        let mixed = XbfVec::new_unchecked(
+            XbfVecMetadata::new(XbfPrimitiveMetadata::F64),
+            [XbfPrimitive::F64(1.5), XbfPrimitive::U8(2)],
+        );
+        assert!(mixed.as_primitive_buffer().is_none());
+        assert_eq!(mixed[1], XbfPrimitive::U8(2).into());
+    }
+
+    #[test]
+    fn packed_read_errors_locate_the_element() {
+        let mut data = vec![];
+        XbfVec::from(vec![1u64, 2, 3])
+            .serialize_vec_type(&mut data)
+            .unwrap();
+        data.truncate(data.len() - 1);
+
+        let metadata = XbfVecMetadata::new(XbfPrimitiveMetadata::U64);
+        let err = XbfVec::deserialize_vec_type(&metadata, &mut data.as_slice()).unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::UnexpectedEof));
+        assert_eq!(err.offset(), 24);
+        assert_eq!(err.path(), "U64[2]");
+    }
 }
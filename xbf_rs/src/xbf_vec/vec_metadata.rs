@@ -1,4 +1,7 @@
-use crate::{xbf_primitive::XbfPrimitiveMetadata, RcType, XbfMetadata, XbfMetadataUpcast, XbfVec};
+use crate::{
+    reader::XbfReader, xbf_primitive::XbfPrimitiveMetadata, RcType, XbfError, XbfMetadata,
+    XbfMetadataUpcast, XbfVec,
+};
 use byteorder::WriteBytesExt;
 use std::io::{self, Read, Write};
 
@@ -65,6 +68,10 @@ impl XbfVecMetadata {
     /// do not know what sort of metadata you are receiving, use
     /// [`deserialize_base_metadata`](crate::XbfMetadata::deserialize_base_metadata).
     ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the reader fails or the metadata is invalid.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -79,8 +86,14 @@ impl XbfVecMetadata {
     ///
     /// assert_eq!(metadata, XbfVecMetadata::new(XbfPrimitiveMetadata::I32));
     /// ```
-    pub fn deserialize_vec_metadata(reader: &mut impl Read) -> io::Result<XbfVecMetadata> {
-        let inner_type = XbfMetadata::deserialize_base_metadata(reader)?;
+    pub fn deserialize_vec_metadata(reader: &mut impl Read) -> Result<XbfVecMetadata, XbfError> {
+        Self::read_vec_metadata(&mut XbfReader::new(reader))
+    }
+
+    pub(crate) fn read_vec_metadata(
+        reader: &mut XbfReader<impl Read>,
+    ) -> Result<XbfVecMetadata, XbfError> {
+        let inner_type = reader.nested(XbfMetadata::read_base_metadata)?;
         Ok(XbfVecMetadata::new(inner_type))
     }
 }
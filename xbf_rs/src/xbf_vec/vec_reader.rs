@@ -0,0 +1,184 @@
+use crate::{reader::XbfReader, XbfError, XbfType, XbfVecMetadata};
+use std::io::Read;
+
+/// A reader which deserializes the elements of a vector one at a time, without collecting every
+/// element into memory at once.
+///
+/// Creating the reader reads the length of the vector, and each element is then deserialized as
+/// the reader is iterated. Once an element fails to deserialize, the iterator is finished, as the
+/// position of any following elements is unknown.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::{XbfPrimitive, XbfPrimitiveMetadata, XbfReader, XbfVec, XbfVecMetadata, XbfVecReader};
+///
+/// let metadata = XbfVecMetadata::new(XbfPrimitiveMetadata::U32);
+/// let vec = XbfVec::new(metadata.clone(), (0..1000u32).map(XbfPrimitive::U32)).unwrap();
+///
+/// let mut data = vec![];
+/// vec.serialize_vec_type(&mut data).unwrap();
+///
+/// let elements = XbfVecReader::new(XbfReader::new(data.as_slice()), metadata).unwrap();
+/// assert_eq!(elements.len(), 1000);
+///
+/// let mut sum = 0;
+/// for element in elements {
+///     sum += element.unwrap().as_u32().unwrap();
+/// }
+/// assert_eq!(sum, 499500);
+/// ```
+#[derive(Debug)]
+pub struct XbfVecReader<R> {
+    reader: XbfReader<R>,
+    metadata: XbfVecMetadata,
+    len: u64,
+    index: u64,
+    failed: bool,
+}
+
+impl<R: Read> XbfVecReader<R> {
+    /// Creates a reader for the elements of a vector described by `metadata`, reading the length
+    /// of the vector.
+    ///
+    /// The limits in the [`XbfReader`]'s options apply to the vector and each of its elements.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`XbfError`] if the length could not be read or exceeds a limit.
+    pub fn new(mut reader: XbfReader<R>, metadata: XbfVecMetadata) -> Result<Self, XbfError> {
+        let len = reader
+            .nested(|reader| {
+                let len = reader.read_vec_len()?;
+                reader.charge_elements(metadata.inner_type.fixed_size(), len)?;
+                Ok(len)
+            })
+            .map_err(|e| e.in_root(&metadata.inner_type))?;
+        Ok(Self {
+            reader,
+            metadata,
+            len,
+            index: 0,
+            failed: false,
+        })
+    }
+
+    /// Returns the metadata of the vector.
+    pub fn metadata(&self) -> &XbfVecMetadata {
+        &self.metadata
+    }
+
+    /// Returns the length of the vector.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns `true` if the vector has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of elements which have not been read yet.
+    pub fn remaining(&self) -> u64 {
+        if self.failed {
+            0
+        } else {
+            self.len - self.index
+        }
+    }
+
+    /// Consumes this reader, returning the underlying [`XbfReader`].
+    ///
+    /// Unless every element has been read, the underlying reader is left part way through the
+    /// vector.
+    pub fn into_inner(self) -> XbfReader<R> {
+        self.reader
+    }
+}
+
+impl<R: Read> Iterator for XbfVecReader<R> {
+    type Item = Result<XbfType, XbfError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining() == 0 {
+            return None;
+        }
+        let index = self.index;
+        let inner_type = &self.metadata.inner_type;
+        let result = self
+            .reader
+            .nested(|reader| XbfType::read_base_type(inner_type, reader))
+            .map_err(|e| e.at_index(index).in_root(inner_type));
+        self.index += 1;
+        self.failed = result.is_err();
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = usize::try_from(self.remaining()).unwrap_or(usize::MAX);
+        // an error ends the iterator early
+        (0, Some(remaining))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        DeserializeOptions, XbfErrorKind, XbfLimit, XbfPrimitive, XbfPrimitiveMetadata, XbfVec,
+    };
+
+    #[test]
+    fn vec_reader_works() {
+        let metadata = XbfVecMetadata::new(XbfPrimitiveMetadata::String);
+        let vec = XbfVec::new(
+            metadata.clone(),
+            ["Eragon", "Arya"].map(|x| XbfPrimitive::String(x.to_string())),
+        )
+        .unwrap();
+        let mut data = vec![];
+        vec.serialize_vec_type(&mut data).unwrap();
+        data.push(42);
+
+        let mut elements = XbfVecReader::new(XbfReader::new(data.as_slice()), metadata).unwrap();
+        assert_eq!(elements.remaining(), 2);
+        assert_eq!(elements.next().unwrap().unwrap(), vec[0]);
+        assert_eq!(elements.next().unwrap().unwrap(), vec[1]);
+        assert!(elements.next().is_none());
+
+        assert_eq!(elements.into_inner().into_inner(), [42]);
+    }
+
+    #[test]
+    fn vec_reader_stops_after_error() {
+        let metadata = XbfVecMetadata::new(XbfPrimitiveMetadata::String);
+        let mut data = vec![];
+        data.extend_from_slice(&2u64.to_le_bytes());
+        data.extend_from_slice(&2u64.to_le_bytes());
+        data.extend_from_slice(&[0xff, 0xff]);
+
+        let mut elements = XbfVecReader::new(XbfReader::new(data.as_slice()), metadata).unwrap();
+        let err = elements.next().unwrap().unwrap_err();
+        assert!(matches!(err.kind(), XbfErrorKind::InvalidUtf8));
+        assert_eq!(err.path(), "String[0]");
+        assert!(elements.next().is_none());
+    }
+
+    #[test]
+    fn vec_reader_respects_limits() {
+        let metadata = XbfVecMetadata::new(XbfVecMetadata::new(XbfPrimitiveMetadata::U8));
+        let data = [1u64.to_le_bytes(), 1u64.to_le_bytes()].concat();
+        let options = DeserializeOptions::new().max_depth(1);
+
+        let reader = XbfReader::with_options(data.as_slice(), options);
+        let mut elements = XbfVecReader::new(reader, metadata).unwrap();
+        let err = elements.next().unwrap().unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            XbfErrorKind::LimitExceeded {
+                limit: XbfLimit::Depth,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,346 @@
+use crate::{XbfMetadata, XbfType, XbfVecMetadata};
+use byteorder::{LittleEndian, WriteBytesExt};
+use std::{
+    error::Error,
+    fmt::Display,
+    io::{self, Seek, SeekFrom, Write},
+};
+
+/// A writer which serializes a vector one element at a time, without needing every element in
+/// memory at once.
+///
+/// The vector's metadata is written when the writer is created, followed by the vector itself
+/// as elements are written. As the length of a vector comes before its elements, it must either
+/// be known up front with [`XbfVecWriter::new`], or the underlying writer must be seekable so the
+/// length can be written once every element has been, with [`XbfVecWriter::new_seekable`].
+///
+/// [`XbfVecWriter::finish`] must be called once every element has been written, otherwise the
+/// vector may be left with the wrong length.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::{XbfPrimitive, XbfPrimitiveMetadata, XbfReader, XbfVecMetadata, XbfVecWriter};
+/// use std::io::Cursor;
+///
+/// let metadata = XbfVecMetadata::new(XbfPrimitiveMetadata::U32);
+/// let mut writer = XbfVecWriter::new_seekable(Cursor::new(vec![]), metadata).unwrap();
+///
+/// for x in 0..1000u32 {
+///     writer.write_element(&XbfPrimitive::U32(x).into()).unwrap();
+/// }
+/// let data = writer.finish().unwrap().into_inner();
+///
+/// let mut reader = XbfReader::new(data.as_slice());
+/// let metadata = reader.read_metadata().unwrap();
+/// let value = reader.read_value(&metadata).unwrap();
+///
+/// assert_eq!(value.as_vec().unwrap().len(), 1000);
+/// ```
+pub struct XbfVecWriter<W: Write> {
+    writer: W,
+    metadata: XbfVecMetadata,
+    len: VecWriterLen<W>,
+    written: u64,
+}
+
+enum VecWriterLen<W> {
+    Known(u64),
+    BackPatched {
+        position: u64,
+        patch: fn(&mut W, u64, u64) -> io::Result<()>,
+    },
+}
+
+impl<W: Write> XbfVecWriter<W> {
+    /// Creates a writer for a vector of exactly `len` elements, writing the metadata and length
+    /// of the vector.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::Error`] if writing to the underlying writer fails.
+    pub fn new(mut writer: W, metadata: XbfVecMetadata, len: u64) -> io::Result<Self> {
+        metadata.serialize_vec_metadata(&mut writer)?;
+        writer.write_u64::<LittleEndian>(len)?;
+        Ok(Self {
+            writer,
+            metadata,
+            len: VecWriterLen::Known(len),
+            written: 0,
+        })
+    }
+
+    /// Returns the metadata of the vector being written.
+    pub fn metadata(&self) -> &XbfVecMetadata {
+        &self.metadata
+    }
+
+    /// Returns the number of elements written so far.
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Writes the next element of the vector.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`VecWriterError`] if the element is not the type of the vector's elements, if
+    /// the vector's length was given up front and that many elements have already been written,
+    /// or if writing to the underlying writer fails. Nothing is written if the element is
+    /// rejected.
+    pub fn write_element(&mut self, element: &XbfType) -> Result<(), VecWriterError> {
+        let actual = XbfMetadata::from(element);
+        if actual != *self.metadata.inner_type {
+            return Err(VecWriterError::ElementMismatch {
+                index: self.written,
+                expected: (*self.metadata.inner_type).clone(),
+                actual,
+            });
+        }
+        if let VecWriterLen::Known(len) = self.len {
+            if self.written == len {
+                return Err(VecWriterError::LengthMismatch {
+                    expected: len,
+                    actual: len + 1,
+                });
+            }
+        }
+        element.serialize_base_type(&mut self.writer)?;
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Finishes writing the vector, returning the underlying writer.
+    ///
+    /// If the writer was created with [`XbfVecWriter::new_seekable`], this writes the length of
+    /// the vector and seeks back to the end of it.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`VecWriterError`] if the vector's length was given up front and fewer elements
+    /// were written, or if writing to the underlying writer fails.
+    pub fn finish(mut self) -> Result<W, VecWriterError> {
+        match self.len {
+            VecWriterLen::Known(len) if self.written != len => {
+                return Err(VecWriterError::LengthMismatch {
+                    expected: len,
+                    actual: self.written,
+                })
+            }
+            VecWriterLen::Known(_) => {}
+            VecWriterLen::BackPatched { position, patch } => {
+                patch(&mut self.writer, position, self.written)?;
+            }
+        }
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write + Seek> XbfVecWriter<W> {
+    /// Creates a writer for a vector whose length is not known up front, writing the metadata of
+    /// the vector. The length is written when [`XbfVecWriter::finish`] is called.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::Error`] if writing to or seeking the underlying writer fails.
+    pub fn new_seekable(mut writer: W, metadata: XbfVecMetadata) -> io::Result<Self> {
+        metadata.serialize_vec_metadata(&mut writer)?;
+        let position = writer.stream_position()?;
+        writer.write_u64::<LittleEndian>(0)?;
+        Ok(Self {
+            writer,
+            metadata,
+            len: VecWriterLen::BackPatched {
+                position,
+                patch: Self::patch_len,
+            },
+            written: 0,
+        })
+    }
+
+    fn patch_len(writer: &mut W, position: u64, len: u64) -> io::Result<()> {
+        let end = writer.stream_position()?;
+        writer.seek(SeekFrom::Start(position))?;
+        writer.write_u64::<LittleEndian>(len)?;
+        writer.seek(SeekFrom::Start(end))?;
+        Ok(())
+    }
+}
+
+/// Error type for [`XbfVecWriter`].
+#[derive(Debug)]
+pub enum VecWriterError {
+    /// An element was not the type of the vector's elements.
+    ElementMismatch {
+        index: u64,
+        expected: XbfMetadata,
+        actual: XbfMetadata,
+    },
+    /// The number of elements written did not match the length given up front.
+    LengthMismatch { expected: u64, actual: u64 },
+    /// Writing to the underlying writer failed.
+    Io(io::Error),
+}
+
+impl Display for VecWriterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VecWriterError::ElementMismatch {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Element {index} is of type {actual:?}, expected {expected:?}"
+            ),
+            VecWriterError::LengthMismatch { expected, actual } => write!(
+                f,
+                "Vec was declared with length {expected}, but {actual} elements were written"
+            ),
+            VecWriterError::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Error for VecWriterError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            VecWriterError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for VecWriterError {
+    fn from(value: io::Error) -> Self {
+        VecWriterError::Io(value)
+    }
+}
+
+/// Converts to an [`io::Error`], keeping only the message of errors other than
+/// [`VecWriterError::Io`], as the metadata in them is not `Send` without the `xbf_threadsafe`
+/// feature.
+impl From<VecWriterError> for io::Error {
+    fn from(value: VecWriterError) -> Self {
+        match value {
+            VecWriterError::Io(e) => e,
+            other => io::Error::new(io::ErrorKind::InvalidInput, other.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{XbfPrimitive, XbfPrimitiveMetadata, XbfTypeUpcast, XbfVec};
+    use std::io::Cursor;
+
+    fn elements() -> Vec<XbfType> {
+        ["Eragon", "Arya", "Murtagh"]
+            .map(|x| XbfPrimitive::String(x.to_string()).into())
+            .to_vec()
+    }
+
+    fn expected() -> Vec<u8> {
+        let vec = XbfVec::new(
+            XbfVecMetadata::new(XbfPrimitiveMetadata::String),
+            elements(),
+        )
+        .unwrap()
+        .into_base_type();
+        let mut expected = vec![];
+        XbfMetadata::from(&vec)
+            .serialize_base_metadata(&mut expected)
+            .unwrap();
+        vec.serialize_base_type(&mut expected).unwrap();
+        expected
+    }
+
+    #[test]
+    fn known_len_works() {
+        let metadata = XbfVecMetadata::new(XbfPrimitiveMetadata::String);
+        let mut writer = XbfVecWriter::new(vec![], metadata, 3).unwrap();
+        for element in elements() {
+            writer.write_element(&element).unwrap();
+        }
+        assert_eq!(writer.finish().unwrap(), expected());
+    }
+
+    #[test]
+    fn seekable_works() {
+        let metadata = XbfVecMetadata::new(XbfPrimitiveMetadata::String);
+        let mut writer = XbfVecWriter::new_seekable(Cursor::new(vec![]), metadata).unwrap();
+        for element in elements() {
+            writer.write_element(&element).unwrap();
+        }
+        let mut cursor = writer.finish().unwrap();
+        assert_eq!(cursor.position(), expected().len() as u64);
+
+        // writing after the vector carries on from the end of it
+        cursor.write_all(&[42]).unwrap();
+        let mut expected = expected();
+        expected.push(42);
+        assert_eq!(cursor.into_inner(), expected);
+    }
+
+    #[test]
+    fn wrong_element_type_fails() {
+        let metadata = XbfVecMetadata::new(XbfPrimitiveMetadata::String);
+        let mut writer = XbfVecWriter::new(vec![], metadata, 1).unwrap();
+        let written = writer.get_ref().len();
+
+        let err = writer
+            .write_element(&XbfPrimitive::U8(42).into())
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            VecWriterError::ElementMismatch { index: 0, .. }
+        ));
+        assert_eq!(writer.get_ref().len(), written);
+    }
+
+    #[test]
+    fn into_io_error_works() {
+        let metadata = XbfVecMetadata::new(XbfPrimitiveMetadata::String);
+        let mut writer = XbfVecWriter::new(vec![], metadata, 1).unwrap();
+        let err = writer
+            .write_element(&XbfPrimitive::U8(42).into())
+            .unwrap_err();
+        let message = err.to_string();
+
+        let err = io::Error::from(err);
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(err.to_string(), message);
+    }
+
+    #[test]
+    fn wrong_len_fails() {
+        let metadata = XbfVecMetadata::new(XbfPrimitiveMetadata::String);
+        let mut writer = XbfVecWriter::new(vec![], metadata.clone(), 2).unwrap();
+        writer.write_element(&elements()[0]).unwrap();
+        let err = writer.finish().unwrap_err();
+        assert!(matches!(
+            err,
+            VecWriterError::LengthMismatch {
+                expected: 2,
+                actual: 1
+            }
+        ));
+
+        let mut writer = XbfVecWriter::new(vec![], metadata, 1).unwrap();
+        writer.write_element(&elements()[0]).unwrap();
+        let err = writer.write_element(&elements()[1]).unwrap_err();
+        assert!(matches!(
+            err,
+            VecWriterError::LengthMismatch {
+                expected: 1,
+                actual: 2
+            }
+        ));
+    }
+}
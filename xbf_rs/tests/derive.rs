@@ -0,0 +1,123 @@
+use indexmap::indexmap;
+use std::io::Cursor;
+use xbf_rs::{
+    prelude::*, FromXbfError, XbfMetadata, XbfPrimitive, XbfPrimitiveMetadata, XbfStruct,
+    XbfStructMetadata, XbfType, XbfVec, XbfVecMetadata,
+};
+use xbf_rs_derive::Xbf;
+
+#[derive(Debug, Clone, PartialEq, Xbf)]
+struct DragonRider {
+    #[xbf(rename = "Name")]
+    name: String,
+    #[xbf(rename = "Age")]
+    age: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Xbf)]
+#[xbf(rename = "Dragon")]
+struct NativeDragon {
+    name: String,
+    riders: Vec<DragonRider>,
+    scales: Vec<u8>,
+}
+
+fn eragon() -> DragonRider {
+    DragonRider {
+        name: "Eragon".to_string(),
+        age: 16,
+    }
+}
+
+#[test]
+fn derived_metadata_works() {
+    let expected = XbfStructMetadata::new(
+        "DragonRider",
+        indexmap! {
+            "Name" => XbfPrimitiveMetadata::String.into(),
+            "Age" => XbfPrimitiveMetadata::U16.into(),
+        },
+    );
+
+    assert_eq!(
+        DragonRider::xbf_metadata(),
+        XbfMetadata::Struct(expected.clone())
+    );
+
+    let expected_dragon = XbfStructMetadata::new(
+        "Dragon",
+        indexmap! {
+            "name" => XbfPrimitiveMetadata::String.into(),
+            "riders" => XbfVecMetadata::new(expected).into(),
+            "scales" => XbfPrimitiveMetadata::Bytes.into(),
+        },
+    );
+
+    assert_eq!(NativeDragon::xbf_metadata(), expected_dragon.into());
+}
+
+#[test]
+fn derived_conversion_works() {
+    let rider = eragon();
+    let s = XbfStruct::from(rider.clone());
+
+    assert_eq!(
+        s.get("Name"),
+        Some(&XbfPrimitive::String("Eragon".to_string()).into())
+    );
+    assert_eq!(s.get("Age"), Some(&XbfPrimitive::U16(16).into()));
+    assert_eq!(rider.to_xbf_type(), XbfType::Struct(s.clone()));
+    assert_eq!(DragonRider::try_from(&s), Ok(rider));
+}
+
+#[test]
+fn derived_nested_round_trip_works() {
+    let dragon = NativeDragon {
+        name: "Saphira".to_string(),
+        riders: vec![eragon()],
+        scales: vec![1, 2, 3],
+    };
+
+    let value = dragon.to_xbf_type();
+    let mut writer = vec![];
+    XbfMetadata::from(&value)
+        .serialize_base_metadata(&mut writer)
+        .unwrap();
+    value.serialize_base_type(&mut writer).unwrap();
+
+    let mut reader = Cursor::new(writer);
+    let metadata = XbfMetadata::deserialize_base_metadata(&mut reader).unwrap();
+    let deserialized = XbfType::deserialize_base_type(&metadata, &mut reader).unwrap();
+
+    assert_eq!(NativeDragon::from_xbf(&deserialized), Ok(dragon));
+}
+
+#[test]
+fn derived_metadata_is_shared() {
+    let riders = XbfVec::new(
+        XbfVecMetadata::new(DragonRider::xbf_metadata()),
+        [eragon(), eragon()].map(XbfStruct::from),
+    );
+
+    assert!(riders.is_ok());
+}
+
+#[test]
+fn derived_try_from_fails_on_missing_field() {
+    let s = XbfStruct::new(
+        XbfStructMetadata::new(
+            "DragonRider",
+            indexmap! {"Name" => XbfPrimitiveMetadata::String.into()},
+        ),
+        [XbfPrimitive::String("Eragon".to_string()).into()],
+    )
+    .unwrap();
+
+    assert_eq!(
+        DragonRider::try_from(&s),
+        Err(FromXbfError::MissingField {
+            struct_name: "DragonRider".into(),
+            field_name: "Age".into(),
+        })
+    );
+}
@@ -0,0 +1,231 @@
+//! Derive macros for [`xbf_rs`](https://docs.rs/xbf_rs).
+//!
+//! This crate is not intended to be used directly, enable the `derive` feature of `xbf_rs` and
+//! use `xbf_rs::Xbf` instead.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Fields, LitStr};
+
+/// Derives the XBF conversions for a struct with named fields.
+///
+/// The following are generated for the struct:
+///
+/// - `xbf_rs::NativeToXbfType`, whose metadata is an `XbfStructMetadata` created once per thread
+///   and shared between every converted value.
+/// - `xbf_rs::FromXbf`, which extracts the struct's fields from an `XbfStruct` by name.
+/// - `From<T> for xbf_rs::XbfStruct`.
+/// - `TryFrom<&xbf_rs::XbfStruct> for T`.
+///
+/// Every field must implement both `NativeToXbfType` and `FromXbf`.
+///
+/// # Attributes
+///
+/// - `#[xbf(rename = "name")]` on the struct sets the name used in its metadata, which defaults to
+///   the name of the Rust struct.
+/// - `#[xbf(rename = "name")]` on a field sets the name used for the field in the metadata, which
+///   defaults to the name of the Rust field.
+///
+/// # Examples
+///
+/// ```rust
+/// use xbf_rs::{Xbf, XbfStruct};
+///
+/// #[derive(Debug, PartialEq, Xbf)]
+/// #[xbf(rename = "DragonRider")]
+/// struct Rider {
+///     #[xbf(rename = "Name")]
+///     name: String,
+///     #[xbf(rename = "Age")]
+///     age: u16,
+/// }
+///
+/// let eragon = Rider {
+///     name: "Eragon".to_string(),
+///     age: 16,
+/// };
+///
+/// let xbf_struct = XbfStruct::from(eragon);
+/// assert_eq!(xbf_struct.get_metadata().name(), "DragonRider");
+/// assert!(xbf_struct.get_metadata().get_field_type("Name").is_some());
+///
+/// let rider = Rider::try_from(&xbf_struct).unwrap();
+/// assert_eq!(rider.name, "Eragon");
+/// assert_eq!(rider.age, 16);
+/// ```
+#[proc_macro_derive(Xbf, attributes(xbf))]
+pub fn derive_xbf(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "#[derive(Xbf)] does not support generic structs",
+        ));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new(
+                    input.ident.span(),
+                    "#[derive(Xbf)] only supports structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "#[derive(Xbf)] only supports structs",
+            ))
+        }
+    };
+
+    let ident = &input.ident;
+    let struct_name = rename(&input.attrs)?.unwrap_or_else(|| ident.to_string());
+
+    let field_idents = fields
+        .iter()
+        .map(|f| f.ident.as_ref().expect("named fields have identifiers"))
+        .collect::<Vec<_>>();
+    let field_types = fields.iter().map(|f| &f.ty).collect::<Vec<_>>();
+    let field_names = fields
+        .iter()
+        .zip(&field_idents)
+        .map(|(f, ident)| Ok(rename(&f.attrs)?.unwrap_or_else(|| ident.to_string())))
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    for (i, (field, name)) in fields.iter().zip(&field_names).enumerate() {
+        if field_names[..i].contains(name) {
+            return Err(syn::Error::new(
+                field.span(),
+                format!("duplicate xbf field name `{name}`"),
+            ));
+        }
+    }
+
+    Ok(quote! {
+        const _: () = {
+            fn __xbf_struct_metadata() -> ::xbf_rs::XbfStructMetadata {
+                ::std::thread_local! {
+                    static METADATA: ::xbf_rs::XbfStructMetadata = ::xbf_rs::XbfStructMetadata::new(
+                        #struct_name,
+                        ::xbf_rs::__private::IndexMap::<&str, ::xbf_rs::XbfMetadata>::from([
+                            #(
+                                (
+                                    #field_names,
+                                    <#field_types as ::xbf_rs::NativeToXbfType>::xbf_metadata(),
+                                ),
+                            )*
+                        ]),
+                    );
+                }
+
+                METADATA.with(::std::clone::Clone::clone)
+            }
+
+            #[automatically_derived]
+            impl ::xbf_rs::NativeToXbfType for #ident {
+                fn xbf_metadata() -> ::xbf_rs::XbfMetadata {
+                    ::xbf_rs::XbfMetadata::Struct(__xbf_struct_metadata())
+                }
+
+                fn into_xbf_type(self) -> ::xbf_rs::XbfType {
+                    ::xbf_rs::XbfType::Struct(::xbf_rs::XbfStruct::from(self))
+                }
+
+                fn to_xbf_type(&self) -> ::xbf_rs::XbfType {
+                    ::xbf_rs::XbfType::Struct(::xbf_rs::XbfStruct::new_unchecked(
+                        __xbf_struct_metadata(),
+                        [
+                            #(
+                                <#field_types as ::xbf_rs::NativeToXbfType>::to_xbf_type(
+                                    &self.#field_idents
+                                ),
+                            )*
+                        ],
+                    ))
+                }
+            }
+
+            #[automatically_derived]
+            impl ::xbf_rs::FromXbf for #ident {
+                fn from_xbf(
+                    value: &::xbf_rs::XbfType,
+                ) -> ::std::result::Result<Self, ::xbf_rs::FromXbfError> {
+                    match value {
+                        ::xbf_rs::XbfType::Struct(x) => {
+                            <Self as ::std::convert::TryFrom<&::xbf_rs::XbfStruct>>::try_from(x)
+                        }
+                        other => ::std::result::Result::Err(
+                            ::xbf_rs::FromXbfError::TypeMismatch {
+                                expected: ::std::concat!("struct ", #struct_name).into(),
+                                actual: other.into(),
+                            },
+                        ),
+                    }
+                }
+            }
+
+            #[automatically_derived]
+            impl ::xbf_rs::XbfVecElement for #ident {}
+
+            #[automatically_derived]
+            impl ::std::convert::From<#ident> for ::xbf_rs::XbfStruct {
+                fn from(value: #ident) -> Self {
+                    ::xbf_rs::XbfStruct::new_unchecked(
+                        __xbf_struct_metadata(),
+                        [
+                            #(
+                                <#field_types as ::xbf_rs::NativeToXbfType>::into_xbf_type(
+                                    value.#field_idents
+                                ),
+                            )*
+                        ],
+                    )
+                }
+            }
+
+            #[automatically_derived]
+            impl ::std::convert::TryFrom<&::xbf_rs::XbfStruct> for #ident {
+                type Error = ::xbf_rs::FromXbfError;
+
+                fn try_from(
+                    value: &::xbf_rs::XbfStruct,
+                ) -> ::std::result::Result<Self, Self::Error> {
+                    ::std::result::Result::Ok(Self {
+                        #(
+                            #field_idents: <#field_types as ::xbf_rs::FromXbf>::from_xbf_field(
+                                value,
+                                #field_names,
+                            )?,
+                        )*
+                    })
+                }
+            }
+        };
+    })
+}
+
+/// Returns the value of a `#[xbf(rename = "...")]` attribute, if one is present.
+fn rename(attrs: &[syn::Attribute]) -> syn::Result<Option<String>> {
+    let mut name = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("xbf")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                name = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else {
+                Err(meta.error("unsupported xbf attribute, expected `rename`"))
+            }
+        })?;
+    }
+    Ok(name)
+}